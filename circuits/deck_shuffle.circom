@@ -70,6 +70,17 @@ template DeckShuffle(nCards) {
         high_bits[i].in <== high_weights[i];
     }
 
+    // ...and below the modulus' high half (p >> 128), so the split can't
+    // be of weight + p instead of the weight. An honest weight fails this
+    // only if its top 126 bits equal the modulus', with probability 2^-126.
+    component high_canonical[nCards];
+    for (var i = 0; i < nCards; i++) {
+        high_canonical[i] = LessThan(126);
+        high_canonical[i].in[0] <== high_weights[i];
+        high_canonical[i].in[1] <== 64323764613183177041862057485226039389;
+        high_canonical[i].out === 1;
+    }
+
     // --- 3. Sorting verification ---
     // Verify trunc_weights[i] <= trunc_weights[i+1] for all consecutive pairs.
     // Since truncation preserves order with overwhelming probability for 25 elements
@@ -211,7 +211,9 @@ template GameSim(nCards) {
     coin.inputs[0] <== combined_seed;
     coin.inputs[1] <== nCards; // 25
 
-    component coin_bits = Num2Bits(254);
+    // Strict: Num2Bits(254) would also accept the bits of coin + p,
+    // letting the prover pick either LSB
+    component coin_bits = Num2Bits_strict();
     coin_bits.in <== coin.out;
     signal coin_lsb <== coin_bits.out[0]; // 0 or 1
 
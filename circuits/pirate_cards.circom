@@ -10,13 +10,16 @@ include "game_sim.circom";
 /// outcome (winner) is correctly computed from the deterministic
 /// deck shuffle and game simulation.
 ///
-/// Public inputs (6):
+/// Public inputs (9):
 ///   seed_commit1  - Poseidon(seed1), committed before game
 ///   seed_commit2  - Poseidon(seed2), committed before game
 ///   seed1         - revealed seed from player 1
 ///   seed2         - revealed seed from player 2
 ///   session_id    - unique game identifier
 ///   winner        - 1 (player 1 wins) or 2 (player 2 wins)
+///   beacon        - randomness beacon value reduced to the field, or 0
+///   deck_size     - number of cards in the ruleset (25)
+///   suits         - number of non-black-spot suits in the ruleset (3)
 ///
 /// Private inputs:
 ///   deck[25]          - the shuffled card order (permutation of 0..24)
@@ -35,12 +38,19 @@ template PirateCards() {
     signal input seed2;
     signal input session_id;
     signal input winner;
+    signal input beacon;
+    signal input deck_size;
+    signal input suits;
 
     // Private inputs
     signal input deck[25];
     signal input trunc_weights[25];
     signal input high_weights[25];
 
+    // --- 0. Bind the ruleset this circuit implements ---
+    deck_size === 25;
+    suits === 3;
+
     // --- 1. Verify seed commitments ---
     component hash1 = Poseidon(1);
     hash1.inputs[0] <== seed1;
@@ -51,10 +61,11 @@ template PirateCards() {
     hash2.out === seed_commit2;
 
     // --- 2. Compute combined seed ---
-    component cs = Poseidon(3);
+    component cs = Poseidon(4);
     cs.inputs[0] <== seed1;
     cs.inputs[1] <== seed2;
     cs.inputs[2] <== session_id;
+    cs.inputs[3] <== beacon;
 
     // --- 3. Verify deck shuffle ---
     component shuffle = DeckShuffle(25);
@@ -76,4 +87,4 @@ template PirateCards() {
     game.winner === winner;
 }
 
-component main {public [seed_commit1, seed_commit2, seed1, seed2, session_id, winner]} = PirateCards();
+component main {public [seed_commit1, seed_commit2, seed1, seed2, session_id, winner, beacon, deck_size, suits]} = PirateCards();
@@ -0,0 +1,157 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contractevent, contractimpl, contracttype, xdr::ToXdr, Address,
+    BytesN, Env, Vec,
+};
+
+use pirate_vk::VerificationKey;
+
+/// Most deployments `list_deployments` returns per call.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+#[contracttype]
+enum DataKey {
+    Admin,
+    WasmHash,
+    Count,
+    Deployment(u32),
+    Index(Address),
+}
+
+/// A pirate-cards instance this factory deployed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deployment {
+    pub contract: Address,
+    pub operator: Address,
+    pub admin: Address,
+    pub salt: BytesN<32>, // as supplied by the operator
+    pub wasm_hash: BytesN<32>,
+    pub ledger: u32,
+}
+
+#[contractevent]
+pub struct InstanceDeployed {
+    #[topic]
+    pub operator: Address,
+    pub contract: Address,
+    pub index: u32,
+}
+
+/// Deploys configured pirate-cards instances, e.g. one per league season,
+/// in a single transaction and keeps an on-chain list of them. Addresses
+/// are derived from the operator and their salt, so they can be known
+/// before deployment and no one can take another operator's address.
+#[contract]
+pub struct Factory;
+
+#[contractimpl]
+impl Factory {
+    /// Deploy: the admin who may change the pirate-cards build, and the
+    /// uploaded Wasm hash of that build.
+    pub fn __constructor(env: Env, admin: Address, wasm_hash: BytesN<32>) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::WasmHash, &wasm_hash);
+    }
+
+    /// Admin: deploy later instances from another uploaded build. Existing
+    /// instances are unaffected.
+    pub fn set_wasm_hash(env: Env, wasm_hash: BytesN<32>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::WasmHash, &wasm_hash);
+    }
+
+    /// Deploy a pirate-cards instance for `operator`, constructed with
+    /// `admin`, `ohloss` and optionally the classic variant's key. Returns
+    /// the new contract's address.
+    pub fn deploy(
+        env: Env,
+        operator: Address,
+        salt: BytesN<32>,
+        admin: Address,
+        ohloss: Address,
+        vk: Option<VerificationKey>,
+    ) -> Address {
+        operator.require_auth();
+        let wasm_hash = get_wasm_hash(&env);
+        let contract = env
+            .deployer()
+            .with_current_contract(operator_salt(&env, &operator, &salt))
+            .deploy_v2(wasm_hash.clone(), (admin.clone(), ohloss, vk));
+
+        let index: u32 = env.storage().instance().get(&DataKey::Count).unwrap_or(0);
+        let deployment = Deployment {
+            contract: contract.clone(),
+            operator: operator.clone(),
+            admin,
+            salt,
+            wasm_hash,
+            ledger: env.ledger().sequence(),
+        };
+        let ttl = env.storage().max_ttl();
+        let key = DataKey::Deployment(index);
+        env.storage().persistent().set(&key, &deployment);
+        env.storage().persistent().extend_ttl(&key, ttl, ttl);
+        let key = DataKey::Index(contract.clone());
+        env.storage().persistent().set(&key, &index);
+        env.storage().persistent().extend_ttl(&key, ttl, ttl);
+        env.storage().instance().set(&DataKey::Count, &(index + 1));
+
+        InstanceDeployed { operator, contract: contract.clone(), index }.publish(&env);
+        contract
+    }
+
+    /// The address `deploy` will give `operator`'s instance for `salt`.
+    pub fn deployed_address(env: Env, operator: Address, salt: BytesN<32>) -> Address {
+        env.deployer()
+            .with_current_contract(operator_salt(&env, &operator, &salt))
+            .deployed_address()
+    }
+
+    /// Query the Wasm hash new instances are deployed from.
+    pub fn get_wasm_hash(env: Env) -> BytesN<32> {
+        get_wasm_hash(&env)
+    }
+
+    /// How many instances this factory has deployed.
+    pub fn deployment_count(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Count).unwrap_or(0)
+    }
+
+    /// Deployments with index >= `cursor` in deployment order, at most
+    /// `limit` (capped at 100).
+    pub fn list_deployments(env: Env, cursor: u32, limit: u32) -> Vec<Deployment> {
+        let count = Self::deployment_count(env.clone());
+        let end = count.min(cursor.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        let mut deployments = Vec::new(&env);
+        for index in cursor..end {
+            if let Some(deployment) = env.storage().persistent().get(&DataKey::Deployment(index)) {
+                deployments.push_back(deployment);
+            }
+        }
+        deployments
+    }
+
+    /// The record for `contract`, if this factory deployed it.
+    pub fn get_deployment(env: Env, contract: Address) -> Option<Deployment> {
+        let index: u32 = env.storage().persistent().get(&DataKey::Index(contract))?;
+        env.storage().persistent().get(&DataKey::Deployment(index))
+    }
+}
+
+fn get_wasm_hash(env: &Env) -> BytesN<32> {
+    env.storage().instance().get(&DataKey::WasmHash).unwrap()
+}
+
+/// Namespace the operator's salt by their address, so salts chosen by
+/// different operators can never collide.
+fn operator_salt(env: &Env, operator: &Address, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = operator.clone().to_xdr(env);
+    preimage.extend_from_array(&salt.to_array());
+    env.crypto().sha256(&preimage).to_bytes()
+}
@@ -0,0 +1,78 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env};
+
+use crate::{Factory, FactoryClient};
+
+/// A minimal contract with a three-argument `__constructor` returning
+/// void, standing in for the pirate-cards build (this host cannot compile
+/// to Wasm).
+const STUB_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+    // custom "contractenvmetav0": interface version 25, not pre-release
+    0x00, 0x1e, 0x11, b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm',
+    b'e', b't', b'a', b'v', b'0', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x19, 0x00, 0x00,
+    0x00, 0x00,
+    // type: (i64, i64, i64) -> i64
+    0x01, 0x08, 0x01, 0x60, 0x03, 0x7e, 0x7e, 0x7e, 0x01, 0x7e,
+    // function 0 has type 0
+    0x03, 0x02, 0x01, 0x00,
+    // export "__constructor"
+    0x07, 0x11, 0x01, 0x0d, b'_', b'_', b'c', b'o', b'n', b's', b't', b'r', b'u', b'c', b't',
+    b'o', b'r', 0x00, 0x00,
+    // body: i64.const 2 (void)
+    0x0a, 0x06, 0x01, 0x04, 0x00, 0x42, 0x02, 0x0b,
+];
+
+fn setup() -> (Env, FactoryClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let wasm_hash = env.deployer().upload_contract_wasm(Bytes::from_slice(&env, STUB_WASM));
+    let admin = Address::generate(&env);
+    let factory_id = env.register(Factory, (&admin, &wasm_hash));
+    let client = FactoryClient::new(&env, &factory_id);
+    (env, client, admin)
+}
+
+#[test]
+fn test_deploy_records_instances() {
+    let (env, client, _) = setup();
+    let operator = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let ohloss = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[1u8; 32]);
+
+    let predicted = client.deployed_address(&operator, &salt);
+    let contract = client.deploy(&operator, &salt, &admin, &ohloss, &None);
+    assert_eq!(contract, predicted);
+
+    let deployment = client.get_deployment(&contract).unwrap();
+    assert_eq!((deployment.operator, deployment.admin, deployment.salt), (operator, admin.clone(), salt.clone()));
+    assert_eq!(deployment.wasm_hash, client.get_wasm_hash());
+    assert_eq!(client.deployment_count(), 1);
+    assert_eq!(client.get_deployment(&Address::generate(&env)), None);
+
+    // Salts are namespaced per operator
+    let other = Address::generate(&env);
+    let second = client.deploy(&other, &salt, &admin, &ohloss, &None);
+    assert_ne!(second, contract);
+    let listed = client.list_deployments(&1, &10);
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed.get(0).unwrap().contract, second);
+}
+
+#[test]
+fn test_set_wasm_hash_applies_to_later_deployments() {
+    let (env, client, _) = setup();
+    let operator = Address::generate(&env);
+    let first = client.deploy(&operator, &BytesN::from_array(&env, &[1u8; 32]), &operator, &operator, &None);
+
+    let mut wasm = Bytes::from_slice(&env, STUB_WASM);
+    wasm.extend_from_array(&[0x00, 0x02, 0x01, b'x']); // distinct build, same code
+    let wasm_hash = env.deployer().upload_contract_wasm(wasm);
+    client.set_wasm_hash(&wasm_hash);
+    let second = client.deploy(&operator, &BytesN::from_array(&env, &[2u8; 32]), &operator, &operator, &None);
+
+    assert_ne!(client.get_deployment(&first).unwrap().wasm_hash, wasm_hash);
+    assert_eq!(client.get_deployment(&second).unwrap().wasm_hash, wasm_hash);
+}
@@ -0,0 +1,24 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, BytesN, Env};
+
+#[contracttype]
+enum DataKey {
+    Round(u32),
+}
+
+/// Randomness beacon stub: values are published per round (ledger sequence)
+/// by anyone, for local testing only.
+#[contract]
+pub struct MockBeacon;
+
+#[contractimpl]
+impl MockBeacon {
+    pub fn publish(env: Env, round: u32, value: BytesN<32>) {
+        env.storage().persistent().set(&DataKey::Round(round), &value);
+    }
+
+    pub fn randomness(env: Env, round: u32) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::Round(round))
+    }
+}
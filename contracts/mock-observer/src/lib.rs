@@ -0,0 +1,41 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Symbol, Vec};
+
+#[contracttype]
+enum DataKey {
+    Calls(u32),
+}
+
+/// Game observer stub: records every callback it receives per session, for
+/// local testing only.
+#[contract]
+pub struct MockObserver;
+
+#[contractimpl]
+impl MockObserver {
+    pub fn on_joined(env: Env, session_id: u32) {
+        record(&env, session_id, Symbol::new(&env, "joined"));
+    }
+
+    pub fn on_revealed(env: Env, session_id: u32) {
+        record(&env, session_id, Symbol::new(&env, "revealed"));
+    }
+
+    pub fn on_settled(env: Env, session_id: u32, _winner: Option<Address>) {
+        record(&env, session_id, Symbol::new(&env, "settled"));
+    }
+
+    pub fn calls(env: Env, session_id: u32) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Calls(session_id))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+fn record(env: &Env, session_id: u32, call: Symbol) {
+    let mut calls = MockObserver::calls(env.clone(), session_id);
+    calls.push_back(call);
+    env.storage().persistent().set(&DataKey::Calls(session_id), &calls);
+}
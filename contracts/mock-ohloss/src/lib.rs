@@ -1,6 +1,24 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, Env, log};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env, Symbol};
+
+#[contracttype]
+enum DataKey {
+    Capabilities,
+    ProtocolVersion,
+    Failing,
+    LastReport(u32),
+    Rating(Address),
+}
+
+/// Result detail accepted by `end_game_scored`; mirrors pirate-cards' type.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameScore {
+    pub outcome: u32,
+    pub tiebroken: bool,
+    pub variant: u32,
+}
 
 #[contract]
 pub struct MockOhloss;
@@ -14,6 +32,7 @@ impl MockOhloss {
         player1: Address,
         player2: Address,
     ) {
+        fail_if_set(&env);
         log!(
             &env,
             "mock start_game: session={}, p1={}, p2={}",
@@ -30,5 +49,77 @@ impl MockOhloss {
             session_id,
             player1_won
         );
+        record(&env, session_id, "end_game");
     }
+
+    /// Test hook: make `start_game` and every result report panic while set.
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage().instance().set(&DataKey::Failing, &failing);
+    }
+
+    /// Test hook: choose which protocol version this mock claims. Version 0
+    /// stands in for a build from before the call existed, which traps.
+    pub fn set_protocol_version(env: Env, version: u32) {
+        env.storage().instance().set(&DataKey::ProtocolVersion, &version);
+    }
+
+    pub fn protocol_version(env: Env) -> u32 {
+        let version = env.storage().instance().get(&DataKey::ProtocolVersion).unwrap_or(2);
+        if version == 0 {
+            panic!("protocol_version not supported");
+        }
+        version
+    }
+
+    /// Test hook: choose which optional calls this mock advertises.
+    pub fn set_capabilities(env: Env, capabilities: u32) {
+        env.storage().instance().set(&DataKey::Capabilities, &capabilities);
+    }
+
+    pub fn capabilities(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Capabilities).unwrap_or(0)
+    }
+
+    pub fn end_game_scored(env: Env, session_id: u32, _player1_won: bool, _score: GameScore) {
+        record(&env, session_id, "end_game_scored");
+    }
+
+    pub fn report_draw(env: Env, session_id: u32) {
+        record(&env, session_id, "report_draw");
+    }
+
+    pub fn report_forfeit(env: Env, session_id: u32, _forfeiter: Address) {
+        record(&env, session_id, "report_forfeit");
+    }
+
+    pub fn correct_result(env: Env, session_id: u32, _outcome: u32) {
+        record(&env, session_id, "correct_result");
+    }
+
+    /// Test hook: set the rating `rating` reports for `player`.
+    pub fn set_rating(env: Env, player: Address, rating: u32) {
+        env.storage().persistent().set(&DataKey::Rating(player), &rating);
+    }
+
+    pub fn rating(env: Env, player: Address) -> u32 {
+        env.storage().persistent().get(&DataKey::Rating(player)).unwrap_or(0)
+    }
+
+    /// Name of the last call that reported `session_id`.
+    pub fn last_report(env: Env, session_id: u32) -> Option<Symbol> {
+        env.storage().persistent().get(&DataKey::LastReport(session_id))
+    }
+}
+
+fn fail_if_set(env: &Env) {
+    if env.storage().instance().get(&DataKey::Failing).unwrap_or(false) {
+        panic!("mock ohloss unavailable");
+    }
+}
+
+fn record(env: &Env, session_id: u32, call: &str) {
+    fail_if_set(env);
+    env.storage()
+        .persistent()
+        .set(&DataKey::LastReport(session_id), &Symbol::new(env, call));
 }
@@ -0,0 +1,45 @@
+#![no_std]
+
+use soroban_sdk::{
+    auth::Context, contract, contracterror, contractimpl, contracttype, crypto::Hash, BytesN, Env,
+    Vec,
+};
+
+#[contracttype]
+enum DataKey {
+    Key,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum WalletError {
+    BadSignature = 1,
+}
+
+/// Smart-wallet stub: a custom account that authorizes any call signed with
+/// the key it was deployed with, standing in for a passkey wallet in local
+/// testing only.
+#[contract]
+pub struct MockWallet;
+
+#[contractimpl]
+impl MockWallet {
+    pub fn __constructor(env: Env, key: BytesN<32>) {
+        env.storage().instance().set(&DataKey::Key, &key);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn __check_auth(
+        env: Env,
+        _signature_payload: Hash<32>,
+        signature: BytesN<32>,
+        _auth_contexts: Vec<Context>,
+    ) -> Result<(), WalletError> {
+        let key: BytesN<32> = env.storage().instance().get(&DataKey::Key).unwrap();
+        if signature != key {
+            return Err(WalletError::BadSignature);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Bytes, Env,
+    String,
+};
+
+/// Shortest and longest display names, in bytes.
+pub const MIN_NAME_LEN: u32 = 3;
+pub const MAX_NAME_LEN: u32 = 16;
+
+/// Largest avatar reference (e.g. an IPFS CID or image hash), in bytes.
+pub const MAX_AVATAR_LEN: u32 = 64;
+
+/// Ledgers a registration lasts before it must be renewed (~90 days).
+/// Lapsed names are free for anyone to claim.
+pub const NAME_TERM: u32 = 1_555_200;
+
+/// Ledgers an address must hold a name before switching to another
+/// (~7 days), so one account cannot sweep through names.
+pub const RENAME_COOLDOWN: u32 = 120_960;
+
+#[contracttype]
+enum DataKey {
+    Name(String),
+    Profile(Address),
+}
+
+/// Who holds a name, and until when.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameRecord {
+    pub owner: Address,
+    pub expires_at: u32,
+}
+
+/// An address's display name and avatar.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub avatar: Bytes,
+    pub registered_at: u32,
+    pub expires_at: u32,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum RegistryError {
+    InvalidName = 1,
+    NameTaken = 2,
+    RenameCooldown = 3,
+    NoName = 4,
+    AvatarTooLong = 5,
+}
+
+#[contractevent]
+pub struct NameRegistered {
+    #[topic]
+    pub owner: Address,
+    pub name: String,
+    pub expires_at: u32,
+}
+
+#[contractevent]
+pub struct NameReleased {
+    #[topic]
+    pub owner: Address,
+    pub name: String,
+}
+
+/// Display names for player addresses. Names are 3-16 bytes of lowercase
+/// letters, digits and underscores starting with a letter, so every name
+/// has one spelling and none can pass for an address. Each address holds
+/// at most one name, may only switch names after `RENAME_COOLDOWN`, and
+/// must renew within `NAME_TERM` or lose it to the next claimant.
+#[contract]
+pub struct NameRegistry;
+
+#[contractimpl]
+impl NameRegistry {
+    /// Claim `name` for `owner`, replacing any name they already hold, and
+    /// set their avatar. Re-registering one's own name renews it. Returns
+    /// the ledger the registration lapses after.
+    pub fn register(env: Env, owner: Address, name: String, avatar: Bytes) -> Result<u32, RegistryError> {
+        owner.require_auth();
+        check_name(&name)?;
+        check_avatar(&avatar)?;
+
+        let now = env.ledger().sequence();
+        if let Some(record) = live_record(&env, &name) {
+            if record.owner != owner {
+                return Err(RegistryError::NameTaken);
+            }
+        }
+
+        let mut registered_at = now;
+        if let Some(profile) = get_profile(&env, &owner) {
+            if profile.name == name {
+                registered_at = profile.registered_at;
+            } else {
+                if profile.expires_at >= now && now < profile.registered_at + RENAME_COOLDOWN {
+                    return Err(RegistryError::RenameCooldown);
+                }
+                drop_name(&env, &owner, &profile.name);
+            }
+        }
+
+        let expires_at = now + NAME_TERM;
+        set_record(&env, &name, &NameRecord { owner: owner.clone(), expires_at });
+        set_profile(&env, &owner, &Profile { name: name.clone(), avatar, registered_at, expires_at });
+        NameRegistered { owner, name, expires_at }.publish(&env);
+        Ok(expires_at)
+    }
+
+    /// Extend the owner's current registration by a full term.
+    pub fn renew(env: Env, owner: Address) -> Result<u32, RegistryError> {
+        owner.require_auth();
+        let mut profile = live_profile(&env, &owner).ok_or(RegistryError::NoName)?;
+        profile.expires_at = env.ledger().sequence() + NAME_TERM;
+        set_record(&env, &profile.name, &NameRecord { owner: owner.clone(), expires_at: profile.expires_at });
+        set_profile(&env, &owner, &profile);
+        Ok(profile.expires_at)
+    }
+
+    /// Change the owner's avatar without touching their name.
+    pub fn set_avatar(env: Env, owner: Address, avatar: Bytes) -> Result<(), RegistryError> {
+        owner.require_auth();
+        check_avatar(&avatar)?;
+        let mut profile = live_profile(&env, &owner).ok_or(RegistryError::NoName)?;
+        profile.avatar = avatar;
+        set_profile(&env, &owner, &profile);
+        Ok(())
+    }
+
+    /// Give up the owner's name; it is free to claim immediately. The
+    /// rename cooldown still runs, so releasing is no shortcut to a new
+    /// name.
+    pub fn release(env: Env, owner: Address) -> Result<(), RegistryError> {
+        owner.require_auth();
+        let profile = live_profile(&env, &owner).ok_or(RegistryError::NoName)?;
+        drop_name(&env, &owner, &profile.name);
+        NameReleased { owner, name: profile.name }.publish(&env);
+        Ok(())
+    }
+
+    /// The player's display name, if they hold one that has not lapsed.
+    pub fn name_of(env: Env, player: Address) -> Option<String> {
+        live_profile(&env, &player).map(|profile| profile.name)
+    }
+
+    /// Who currently holds `name`.
+    pub fn owner_of(env: Env, name: String) -> Option<Address> {
+        live_record(&env, &name).map(|record| record.owner)
+    }
+
+    /// The player's profile, if they hold a name that has not lapsed.
+    pub fn get_profile(env: Env, player: Address) -> Option<Profile> {
+        live_profile(&env, &player)
+    }
+}
+
+fn check_name(name: &String) -> Result<(), RegistryError> {
+    let len = name.len();
+    if !(MIN_NAME_LEN..=MAX_NAME_LEN).contains(&len) {
+        return Err(RegistryError::InvalidName);
+    }
+    let mut buf = [0u8; MAX_NAME_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    name.copy_into_slice(bytes);
+    if !bytes[0].is_ascii_lowercase() {
+        return Err(RegistryError::InvalidName);
+    }
+    if !bytes
+        .iter()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || *b == b'_')
+    {
+        return Err(RegistryError::InvalidName);
+    }
+    Ok(())
+}
+
+fn check_avatar(avatar: &Bytes) -> Result<(), RegistryError> {
+    if avatar.len() > MAX_AVATAR_LEN {
+        return Err(RegistryError::AvatarTooLong);
+    }
+    Ok(())
+}
+
+fn get_profile(env: &Env, owner: &Address) -> Option<Profile> {
+    env.storage().persistent().get(&DataKey::Profile(owner.clone()))
+}
+
+/// A profile whose name has not lapsed and was not since claimed by
+/// someone else.
+fn live_profile(env: &Env, owner: &Address) -> Option<Profile> {
+    let profile = get_profile(env, owner)?;
+    match live_record(env, &profile.name) {
+        Some(record) if record.owner == *owner => Some(profile),
+        _ => None,
+    }
+}
+
+fn live_record(env: &Env, name: &String) -> Option<NameRecord> {
+    let record: NameRecord = env.storage().persistent().get(&DataKey::Name(name.clone()))?;
+    if record.expires_at < env.ledger().sequence() {
+        return None;
+    }
+    Some(record)
+}
+
+/// Remove `name`'s record if `owner` still holds it.
+fn drop_name(env: &Env, owner: &Address, name: &String) {
+    let key = DataKey::Name(name.clone());
+    let record: Option<NameRecord> = env.storage().persistent().get(&key);
+    if record.is_some_and(|record| record.owner == *owner) {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+fn set_record(env: &Env, name: &String, record: &NameRecord) {
+    let key = DataKey::Name(name.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, record);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+fn set_profile(env: &Env, owner: &Address, profile: &Profile) {
+    let key = DataKey::Profile(owner.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, profile);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
@@ -0,0 +1,95 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Bytes, Env, String};
+
+use crate::{NameRegistry, NameRegistryClient, RegistryError, NAME_TERM, RENAME_COOLDOWN};
+
+fn setup() -> (Env, NameRegistryClient<'static>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(NameRegistry, ());
+    let client = NameRegistryClient::new(&env, &contract_id);
+    (env, client)
+}
+
+fn name(env: &Env, s: &str) -> String {
+    String::from_str(env, s)
+}
+
+#[test]
+fn test_register_and_resolve() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let avatar = Bytes::from_array(&env, &[7u8; 32]);
+
+    client.register(&alice, &name(&env, "blackbeard"), &avatar);
+    assert_eq!(client.name_of(&alice), Some(name(&env, "blackbeard")));
+    assert_eq!(client.owner_of(&name(&env, "blackbeard")), Some(alice.clone()));
+    assert_eq!(client.get_profile(&alice).unwrap().avatar, avatar);
+    assert_eq!(client.name_of(&Address::generate(&env)), None);
+}
+
+#[test]
+fn test_name_rules() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let none = Bytes::new(&env);
+    for bad in ["ab", "Blackbeard", "1pirate", "black beard", "seventeen_letters"] {
+        assert_eq!(
+            client.try_register(&alice, &name(&env, bad), &none),
+            Err(Ok(RegistryError::InvalidName))
+        );
+    }
+    assert_eq!(
+        client.try_register(&alice, &name(&env, "anne_bonny"), &Bytes::from_array(&env, &[0u8; 65])),
+        Err(Ok(RegistryError::AvatarTooLong))
+    );
+}
+
+#[test]
+fn test_anti_squatting() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let none = Bytes::new(&env);
+    client.register(&alice, &name(&env, "calico_jack"), &none);
+
+    // Taken names stay taken, and holders cannot hop between names
+    assert_eq!(
+        client.try_register(&bob, &name(&env, "calico_jack"), &none),
+        Err(Ok(RegistryError::NameTaken))
+    );
+    assert_eq!(
+        client.try_register(&alice, &name(&env, "anne_bonny"), &none),
+        Err(Ok(RegistryError::RenameCooldown))
+    );
+    client.release(&alice);
+    assert_eq!(
+        client.try_register(&alice, &name(&env, "anne_bonny"), &none),
+        Err(Ok(RegistryError::RenameCooldown))
+    );
+    client.register(&bob, &name(&env, "calico_jack"), &none);
+    assert_eq!(client.name_of(&alice), None);
+
+    env.ledger().with_mut(|l| l.sequence_number += RENAME_COOLDOWN);
+    client.register(&alice, &name(&env, "anne_bonny"), &none);
+    assert_eq!(client.name_of(&alice), Some(name(&env, "anne_bonny")));
+}
+
+#[test]
+fn test_lapsed_names_can_be_claimed() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let none = Bytes::new(&env);
+    client.register(&alice, &name(&env, "flint"), &none);
+
+    env.ledger().with_mut(|l| l.sequence_number += NAME_TERM);
+    client.renew(&alice);
+    env.ledger().with_mut(|l| l.sequence_number += NAME_TERM + 1);
+    assert_eq!(client.name_of(&alice), None);
+    assert_eq!(client.try_renew(&alice), Err(Ok(RegistryError::NoName)));
+
+    client.register(&bob, &name(&env, "flint"), &none);
+    assert_eq!(client.owner_of(&name(&env, "flint")), Some(bob));
+}
@@ -0,0 +1,325 @@
+use soroban_sdk::{token, Address, BytesN, Env, IntoVal, Vec, I256};
+
+use crate::events;
+use crate::storage;
+use crate::types::{bump_seq, EscrowedBounty, EscrowedStake, PendingCompensation, Sponsorship, TreasuryAccount};
+
+/// Rake used until the admin sets one, in basis points.
+pub const DEFAULT_RAKE_BPS: u32 = 250;
+
+/// Hard ceiling on the configurable rake, in basis points.
+pub const MAX_RAKE_BPS: u32 = 1_000;
+
+/// Share of the rake waived on a rematch of an acknowledged result, in
+/// basis points.
+pub const REMATCH_RAKE_DISCOUNT_BPS: u32 = 5_000;
+
+/// Most sponsorships a single session may hold.
+pub const MAX_SPONSORSHIPS: u32 = 16;
+
+/// Delay between proposing and paying an insurance compensation.
+pub const COMPENSATION_DELAY_LEDGERS: u32 = 17_280; // ~1 day
+
+/// Share of collected rake set aside for the insurance fund, in basis points.
+pub const INSURANCE_SHARE_BPS: i128 = 2_000;
+
+// Helpers that publish events about a session number them on `seq`, its
+// counter: `event_seq` of the game copy the caller stores afterwards.
+
+/// Pull the creator's settlement bounty into the contract. The record lives
+/// in persistent storage so it can still be refunded after the temporary
+/// game entry has been evicted.
+pub fn deposit_bounty(env: &Env, session_id: u32, funder: &Address, token: &Address, amount: i128) {
+    token::Client::new(env, token).transfer(funder, env.current_contract_address(), &amount);
+    storage::set_bounty(
+        env,
+        session_id,
+        &EscrowedBounty {
+            funder: funder.clone(),
+            token: token.clone(),
+            amount,
+        },
+    );
+}
+
+/// Pay the escrowed bounty (if any) to the account that settled the game.
+pub fn pay_bounty(env: &Env, session_id: u32, seq: &mut u32, settler: &Address) {
+    if let Some(bounty) = storage::take_bounty(env, session_id) {
+        token::Client::new(env, &bounty.token).transfer(
+            &env.current_contract_address(),
+            settler,
+            &bounty.amount,
+        );
+        events::emit_bounty_paid(env, session_id, bump_seq(seq), settler, &bounty);
+    }
+}
+
+/// Return the escrowed bounty (if any) to whoever funded it.
+pub fn refund_bounty(env: &Env, session_id: u32, seq: &mut u32) -> Option<EscrowedBounty> {
+    let bounty = storage::take_bounty(env, session_id)?;
+    token::Client::new(env, &bounty.token).transfer(
+        &env.current_contract_address(),
+        &bounty.funder,
+        &bounty.amount,
+    );
+    events::emit_bounty_refunded(env, session_id, bump_seq(seq), &bounty);
+    Some(bounty)
+}
+
+/// Pull one player's stake into the contract, opening the session's stake
+/// record on the first deposit.
+pub fn deposit_stake(env: &Env, session_id: u32, seq: &mut u32, from: &Address, token: &Address, amount: i128) {
+    token::Client::new(env, token).transfer(from, env.current_contract_address(), &amount);
+    let mut stake = storage::get_stake(env, session_id).unwrap_or(EscrowedStake {
+        token: token.clone(),
+        amounts: Vec::new(env),
+        depositors: Vec::new(env),
+    });
+    stake.amounts.push_back(amount);
+    stake.depositors.push_back(from.clone());
+    storage::set_stake(env, session_id, &stake);
+    events::emit_stake_deposited(env, session_id, bump_seq(seq), from, token, amount);
+}
+
+/// Rake owed on a pot at `rake_bps`, rounded down.
+fn rake_of(pot: i128, rake_bps: u32) -> i128 {
+    pot * rake_bps as i128 / 10_000
+}
+
+/// Book rake to the treasury, less the insurance fund's slice and the
+/// referring affiliate's share of what remains.
+fn collect_rake(env: &Env, session_id: u32, seq: &mut u32, token: &Address, rake: i128, referrer: &Option<BytesN<32>>) {
+    if rake > 0 {
+        let insurance = rake * INSURANCE_SHARE_BPS / 10_000;
+        let mut treasury_cut = rake - insurance;
+        if let Some(code) = referrer {
+            if let Some(affiliate) = storage::get_affiliate(env, code).filter(|a| a.share_bps > 0) {
+                let share = treasury_cut * affiliate.share_bps as i128 / 10_000;
+                let balance = storage::get_affiliate_balance(env, code, token) + share;
+                storage::set_affiliate_balance(env, code, token, balance);
+                treasury_cut -= share;
+                events::emit_affiliate_credited(env, session_id, bump_seq(seq), code, token, share);
+            }
+        }
+        let mut account = storage::get_treasury_account(env, token);
+        account.accrued += treasury_cut;
+        storage::set_treasury_account(env, token, &account);
+        storage::set_insurance_balance(env, token, storage::get_insurance_balance(env, token) + insurance);
+        events::emit_rake_collected(env, session_id, bump_seq(seq), token, rake, insurance);
+    }
+}
+
+/// Pay the whole pot minus rake to the winner. Rounding in the rake is in
+/// the winner's favour.
+pub fn pay_winner(env: &Env, session_id: u32, seq: &mut u32, winner: &Address, rake_bps: u32, referrer: &Option<BytesN<32>>) {
+    let Some(stake) = storage::take_stake(env, session_id) else {
+        return;
+    };
+    let pot: i128 = stake.amounts.iter().sum();
+    let rake = rake_of(pot, rake_bps);
+    let payout = pot - rake;
+
+    token::Client::new(env, &stake.token).transfer(&env.current_contract_address(), winner, &payout);
+    events::emit_stake_paid(env, session_id, bump_seq(seq), winner, &stake.token, payout);
+    collect_rake(env, session_id, seq, &stake.token, rake, referrer);
+    record_volume(env, &stake.token, pot, rake);
+}
+
+/// Return the pot minus rake to the depositors in proportion to their
+/// stakes, so handicap odds carry through a draw. Shares round down and the
+/// dust goes to the treasury with the rake.
+pub fn split_draw(env: &Env, session_id: u32, seq: &mut u32, rake_bps: u32, referrer: &Option<BytesN<32>>) {
+    let Some(stake) = storage::take_stake(env, session_id) else {
+        return;
+    };
+    let pot: i128 = stake.amounts.iter().sum();
+    let net = pot - rake_of(pot, rake_bps);
+    let mut rake = pot;
+
+    let client = token::Client::new(env, &stake.token);
+    for (player, amount) in stake.depositors.iter().zip(stake.amounts.iter()) {
+        let share = if pot == 0 { 0 } else { share_of(env, amount, net, pot) };
+        rake -= share;
+        client.transfer(&env.current_contract_address(), &player, &share);
+        events::emit_stake_paid(env, session_id, bump_seq(seq), &player, &stake.token, share);
+    }
+    collect_rake(env, session_id, seq, &stake.token, rake, referrer);
+    record_volume(env, &stake.token, pot, rake);
+}
+
+/// `amount * net / pot` rounded down, taken in 256 bits since the product
+/// of two large stakes overflows an i128. The result never exceeds
+/// `amount`.
+fn share_of(env: &Env, amount: i128, net: i128, pot: i128) -> i128 {
+    let wide = |v: i128| I256::from_i128(env, v);
+    wide(amount).mul(&wide(net)).div(&wide(pot)).to_i128().unwrap()
+}
+
+fn record_volume(env: &Env, token: &Address, pot: i128, rake: i128) {
+    let mut volume = storage::get_token_volume(env, token);
+    volume.games += 1;
+    volume.staked += pot;
+    volume.rake += rake;
+    storage::set_token_volume(env, token, &volume);
+}
+
+/// Escrow a challenger's dispute bond.
+pub fn deposit_bond(env: &Env, challenger: &Address, token: &Address, bond: i128) {
+    token::Client::new(env, token).transfer(challenger, env.current_contract_address(), &bond);
+}
+
+/// Pay out a dispute bond: back to the challenger, or slashed to the
+/// other player.
+pub fn release_bond(env: &Env, to: &Address, token: &Address, bond: i128) {
+    token::Client::new(env, token).transfer(&env.current_contract_address(), to, &bond);
+}
+
+/// Return the variant's report bonds once the breaker they tripped is
+/// reset.
+pub fn refund_report_bonds(env: &Env, variant: u32) {
+    for bond in storage::take_report_bonds(env, variant).iter() {
+        release_bond(env, &bond.reporter, &bond.token, bond.amount);
+    }
+}
+
+/// Book the variant's report bonds to the treasury once a valid proof
+/// shows the reports did not point at a broken key.
+pub fn forfeit_report_bonds(env: &Env, variant: u32) {
+    for bond in storage::take_report_bonds(env, variant).iter() {
+        let mut account = storage::get_treasury_account(env, &bond.token);
+        account.accrued += bond.amount;
+        storage::set_treasury_account(env, &bond.token, &account);
+    }
+}
+
+/// Return every deposited stake in full, with no rake.
+pub fn refund_stakes(env: &Env, session_id: u32, seq: &mut u32) {
+    let Some(stake) = storage::take_stake(env, session_id) else {
+        return;
+    };
+    let client = token::Client::new(env, &stake.token);
+    for (player, amount) in stake.depositors.iter().zip(stake.amounts.iter()) {
+        client.transfer(&env.current_contract_address(), &player, &amount);
+        events::emit_stake_refunded(env, session_id, bump_seq(seq), &player, &stake.token, amount);
+    }
+}
+
+/// Return one depositor's stake, leaving the rest escrowed.
+pub fn refund_stake(env: &Env, session_id: u32, seq: &mut u32, from: &Address) {
+    let Some(mut stake) = storage::get_stake(env, session_id) else {
+        return;
+    };
+    let Some(index) = stake.depositors.first_index_of(from) else {
+        return;
+    };
+    stake.depositors.remove(index);
+    let amount = stake.amounts.get_unchecked(index);
+    stake.amounts.remove(index);
+    token::Client::new(env, &stake.token).transfer(&env.current_contract_address(), from, &amount);
+    storage::set_stake(env, session_id, &stake);
+    events::emit_stake_refunded(env, session_id, bump_seq(seq), from, &stake.token, amount);
+}
+
+/// Pay a matured compensation out of the insurance fund.
+pub fn pay_compensation(env: &Env, session_id: u32, seq: &mut u32, compensation: &PendingCompensation) {
+    let balance = storage::get_insurance_balance(env, &compensation.token);
+    storage::set_insurance_balance(env, &compensation.token, balance - compensation.amount);
+    token::Client::new(env, &compensation.token).transfer(
+        &env.current_contract_address(),
+        &compensation.to,
+        &compensation.amount,
+    );
+    events::emit_compensation_paid(env, session_id, bump_seq(seq), compensation);
+}
+
+/// Send withdrawable rake to `to` and record it against the token's account.
+pub fn withdraw_treasury(env: &Env, token: &Address, to: &Address, amount: i128) -> TreasuryAccount {
+    let mut account = storage::get_treasury_account(env, token);
+    account.withdrawn += amount;
+    storage::set_treasury_account(env, token, &account);
+    token::Client::new(env, token).transfer(&env.current_contract_address(), to, &amount);
+    events::emit_treasury_withdrawal(env, token, to, amount, &account);
+    account
+}
+
+/// Pay an affiliate's whole claimable balance in `token` to its payout
+/// address.
+pub fn claim_affiliate(env: &Env, code: &BytesN<32>, payout: &Address, token: &Address) -> i128 {
+    let amount = storage::get_affiliate_balance(env, code, token);
+    storage::set_affiliate_balance(env, code, token, 0);
+    token::Client::new(env, token).transfer(&env.current_contract_address(), payout, &amount);
+    events::emit_affiliate_claimed(env, code, payout, token, amount);
+    amount
+}
+
+/// Pull a sponsor's contribution to a session's prize into the contract.
+pub fn sponsor_pot(env: &Env, session_id: u32, seq: &mut u32, sponsor: &Address, token: &Address, amount: i128) {
+    token::Client::new(env, token).transfer(sponsor, env.current_contract_address(), &amount);
+    record_sponsorship(env, session_id, seq, sponsor, token, amount);
+}
+
+/// Pull a freeroll sponsor's prize using the allowance they granted this
+/// contract. The sponsor still signs for this session, token and amount, so
+/// nobody else can spend the allowance on a game of their own.
+pub fn pull_sponsorship(env: &Env, session_id: u32, seq: &mut u32, sponsor: &Address, token: &Address, amount: i128) {
+    sponsor.require_auth_for_args((session_id, token.clone(), amount).into_val(env));
+    let contract = env.current_contract_address();
+    token::Client::new(env, token).transfer_from(&contract, sponsor, &contract, &amount);
+    record_sponsorship(env, session_id, seq, sponsor, token, amount);
+}
+
+fn record_sponsorship(env: &Env, session_id: u32, seq: &mut u32, sponsor: &Address, token: &Address, amount: i128) {
+    let sponsorship = Sponsorship {
+        sponsor: sponsor.clone(),
+        token: token.clone(),
+        amount,
+    };
+    let mut sponsorships = storage::get_sponsorships(env, session_id);
+    sponsorships.push_back(sponsorship.clone());
+    storage::set_sponsorships(env, session_id, &sponsorships);
+    events::emit_pot_sponsored(env, session_id, bump_seq(seq), &sponsorship);
+}
+
+/// Pay every sponsorship to the winner. Sponsored prizes carry no rake.
+pub fn pay_sponsorships(env: &Env, session_id: u32, seq: &mut u32, winner: &Address) {
+    for sponsorship in storage::take_sponsorships(env, session_id).iter() {
+        token::Client::new(env, &sponsorship.token).transfer(
+            &env.current_contract_address(),
+            winner,
+            &sponsorship.amount,
+        );
+        events::emit_sponsorship_paid(env, session_id, bump_seq(seq), winner, &sponsorship.token, sponsorship.amount);
+    }
+}
+
+/// Split every sponsorship evenly between both players on a draw; an odd
+/// unit goes back to its sponsor.
+pub fn split_sponsorships(env: &Env, session_id: u32, seq: &mut u32, player1: &Address, player2: &Address) {
+    for sponsorship in storage::take_sponsorships(env, session_id).iter() {
+        let client = token::Client::new(env, &sponsorship.token);
+        let share = sponsorship.amount / 2;
+        let dust = sponsorship.amount - share * 2;
+        for player in [player1, player2] {
+            client.transfer(&env.current_contract_address(), player, &share);
+            events::emit_sponsorship_paid(env, session_id, bump_seq(seq), player, &sponsorship.token, share);
+        }
+        if dust > 0 {
+            client.transfer(&env.current_contract_address(), &sponsorship.sponsor, &dust);
+            events::emit_sponsorship_refunded(env, session_id, bump_seq(seq), &sponsorship.sponsor, &sponsorship.token, dust);
+        }
+    }
+}
+
+/// Return every sponsorship to its sponsor.
+pub fn refund_sponsorships(env: &Env, session_id: u32, seq: &mut u32) -> u32 {
+    let sponsorships = storage::take_sponsorships(env, session_id);
+    for sponsorship in sponsorships.iter() {
+        token::Client::new(env, &sponsorship.token).transfer(
+            &env.current_contract_address(),
+            &sponsorship.sponsor,
+            &sponsorship.amount,
+        );
+        events::emit_sponsorship_refunded(env, session_id, bump_seq(seq), &sponsorship.sponsor, &sponsorship.token, sponsorship.amount);
+    }
+    sponsorships.len()
+}
@@ -1,57 +1,940 @@
-use soroban_sdk::{contractevent, Address, Env};
+use soroban_pvp_game::PvpResult;
+use soroban_sdk::{contractevent, Address, BytesN, Env, Symbol, Vec};
+
+use crate::types::{Affiliate, Council, Dispute, EscrowedBounty, Game, Operation, PendingCompensation, Phase, SessionRange, Sponsorship, TimingConfig, TreasuryAccount, Variant, Winner};
+
+// Events about a session carry `seq`, numbered 1, 2, .. per session in
+// emission order so consumers can order them however RPC delivers them.
+// Events for a session whose game entry has expired carry 0.
 
 #[contractevent]
 pub struct GameCreated {
     pub session_id: u32,
+    pub seq: u32,
     pub player1: Address,
+    pub wager_token: Option<Address>,
+    pub wager_amount: i128,
+    pub opponent_stake: i128, // player2's wager; differs from wager_amount only at odds
+    pub memo: BytesN<32>,
+    pub metadata: BytesN<32>, // player1's
+    pub terms: BytesN<32>,    // side-conditions a joiner must co-sign; zero for none
+    pub starts_at: u32,       // first ledger seeds may be revealed; zero for none
 }
 
 #[contractevent]
 pub struct GameJoined {
     pub session_id: u32,
+    pub seq: u32,
     pub player2: Address,
+    pub metadata: BytesN<32>, // player2's
+}
+
+#[contractevent]
+pub struct JoinRequested {
+    pub session_id: u32,
+    pub seq: u32,
+    pub joiner: Address,
+}
+
+#[contractevent]
+pub struct JoinDeclined {
+    pub session_id: u32,
+    pub seq: u32,
+    pub joiner: Address,
 }
 
 #[contractevent]
 pub struct SeedRevealed {
     pub session_id: u32,
+    pub seq: u32,
     pub player: Address,
 }
 
+/// A game's final result, whether proven, timed out or forfeited. Both
+/// commitments and seeds ride along so the deal can be audited from the
+/// event stream after the game entry lapses; a seed never revealed is zero.
 #[contractevent]
 pub struct GameSettled {
     pub session_id: u32,
-    pub winner: Address,
+    pub seq: u32,
+    pub winner: Option<Address>, // None for a draw
+    pub outcome: Winner,
+    pub memo: BytesN<32>,
+    pub seed_commit1: BytesN<32>,
+    pub seed_commit2: BytesN<32>,
+    pub seed1: BytesN<32>,
+    pub seed2: BytesN<32>,
+}
+
+/// A spectator followed a live game; `followers` is the new count.
+#[contractevent]
+pub struct GameFollowed {
+    pub session_id: u32,
+    pub seq: u32,
+    pub follower: Address,
+    pub followers: u32,
+}
+
+/// Player2 co-signed the game's side-conditions on taking the seat.
+#[contractevent]
+pub struct TermsAgreed {
+    pub session_id: u32,
+    pub seq: u32,
+    pub terms: BytesN<32>,
+}
+
+/// The loser of a decided game accepted the result.
+#[contractevent]
+pub struct ResultAcknowledged {
+    pub session_id: u32,
+    pub seq: u32,
+    pub player: Address,
+}
+
+/// A game ended with nothing to report: withdrawn before anyone joined,
+/// or timed out with neither seed in. Carries the same commitments and
+/// seeds as GameSettled, zero where missing.
+#[contractevent]
+pub struct GameCancelled {
+    pub session_id: u32,
+    pub seq: u32,
+    pub seed_commit1: BytesN<32>,
+    pub seed_commit2: BytesN<32>,
+    pub seed1: BytesN<32>,
+    pub seed2: BytesN<32>,
+}
+
+/// Nudge: a live game's next deadline or its entry's expiry is within the
+/// configured warning window.
+#[contractevent]
+pub struct GameExpiringSoon {
+    pub session_id: u32,
+    pub seq: u32,
+    pub phase: Phase,
+    pub ledgers_left: u32, // as counted by get_time_remaining
+}
+
+#[contractevent]
+pub struct BountyPaid {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct BountyRefunded {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StakeDeposited {
+    pub session_id: u32,
+    pub seq: u32,
+    pub from: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StakePaid {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StakeRefunded {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct PotSponsored {
+    pub session_id: u32,
+    pub seq: u32,
+    pub sponsor: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct SponsorshipPaid {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct SponsorshipRefunded {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct RakeCollected {
+    pub session_id: u32,
+    pub seq: u32,
+    pub token: Address,
+    pub amount: i128,
+    pub insurance: i128, // portion of `amount` set aside for the insurance fund
+}
+
+#[contractevent]
+pub struct CompensationProposed {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub eta: u32,
+}
+
+#[contractevent]
+pub struct CompensationPaid {
+    pub session_id: u32,
+    pub seq: u32,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct TreasuryWithdrawal {
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub accrued: i128,   // lifetime totals after this withdrawal
+    pub withdrawn: i128,
+}
+
+#[contractevent]
+pub struct AffiliateRegistered {
+    pub code: BytesN<32>,
+    pub payout: Address,
+    pub share_bps: u32,
+}
+
+#[contractevent]
+pub struct AffiliateRemoved {
+    pub code: BytesN<32>,
+}
+
+#[contractevent]
+pub struct AffiliateCredited {
+    pub session_id: u32,
+    pub seq: u32,
+    pub code: BytesN<32>,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct AffiliateClaimed {
+    pub code: BytesN<32>,
+    pub payout: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct OhlossUpdated {
+    pub ohloss: Address,
+    pub version: u32,
+}
+
+#[contractevent]
+pub struct ResultSinkAllowed {
+    pub sink: Address,
+    pub version: u32,
+}
+
+#[contractevent]
+pub struct ResultSinkRemoved {
+    pub sink: Address,
+}
+
+#[contractevent]
+pub struct AdminRenounced {
+    pub admin: Address,
+}
+
+#[contractevent]
+pub struct VkFrozen {}
+
+#[contractevent]
+pub struct DisputeFlagged {
+    pub session_id: u32,
+    pub seq: u32,
+    pub challenger: Address,
+    pub token: Address,
+    pub bond: i128,
+}
+
+#[contractevent]
+pub struct DisputeResolved {
+    pub session_id: u32,
+    pub seq: u32,
+    pub ruling: Winner,
+    pub upheld: bool,     // the original result stood
+    pub bond_to: Address, // challenger if overturned, opponent if upheld
+}
+
+#[contractevent]
+pub struct PayoutReleased {
+    pub session_id: u32,
+    pub seq: u32,
+}
+
+#[contractevent]
+pub struct ArbiterUpdated {
+    pub arbiter: Address,
+}
+
+#[contractevent]
+pub struct NameRegistryUpdated {
+    pub registry: Address,
+}
+
+#[contractevent]
+pub struct Secp256k1KeyBound {
+    pub player: Address,
+    pub key: BytesN<65>,
 }
 
-pub fn emit_game_created(env: &Env, session_id: u32, player1: &Address) {
+/// Audit-log entry for a privileged call; `seq` increases by one per entry.
+#[contractevent(topics = ["admin_action"])]
+pub struct AdminAudit {
+    pub seq: u32,
+    pub kind: Symbol,
+    pub actor: Address,
+    pub details_hash: BytesN<32>,
+}
+
+/// Alert: a variant's settlements halted after repeated invalid proofs.
+#[contractevent]
+pub struct BreakerTripped {
+    #[topic]
+    pub variant: u32,
+    pub failures: u32,
+}
+
+#[contractevent]
+pub struct BreakerReset {
+    #[topic]
+    pub variant: u32,
+}
+
+#[contractevent]
+pub struct PauseUpdated {
+    #[topic]
+    pub op: Operation,
+    pub paused: bool,
+}
+
+#[contractevent]
+pub struct CouncilUpdated {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[contractevent]
+pub struct ProposalCreated {
+    pub proposal_id: u32,
+    pub proposer: Address,
+    pub eta: u32,
+}
+
+#[contractevent]
+pub struct ProposalApproved {
+    pub proposal_id: u32,
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct ProposalExecuted {
+    pub proposal_id: u32,
+}
+
+#[contractevent]
+pub struct ProposalCancelled {
+    pub proposal_id: u32,
+    pub canceller: Address,
+}
+
+#[contractevent]
+pub struct TimelockUpdated {
+    pub delay: u32,
+}
+
+#[contractevent]
+pub struct SchemaMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+#[contractevent]
+pub struct TreasuryUpdated {
+    pub treasury: Address,
+}
+
+#[contractevent]
+pub struct RakeUpdated {
+    pub old_bps: u32,
+    pub new_bps: u32,
+}
+
+#[contractevent]
+pub struct TimingConfigUpdated {
+    pub game_ttl: u32,
+    pub join_window: u32,
+    pub reveal_window: u32,
+}
+
+#[contractevent]
+pub struct VariantRegistered {
+    pub variant_id: u32,
+    pub deck_size: u32,
+    pub suits: u32,
+    pub rounds: u32,
+    pub vk_id: u32,
+}
+
+#[contractevent]
+pub struct SessionRangeAssigned {
+    pub orchestrator: Address,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[contractevent]
+pub struct SessionRangeRemoved {
+    pub orchestrator: Address,
+}
+
+#[contractevent]
+pub struct BeaconUpdated {
+    pub beacon: Address,
+}
+
+pub fn emit_game_created(env: &Env, session_id: u32, seq: u32, game: &Game) {
     GameCreated {
         session_id,
-        player1: player1.clone(),
+        seq,
+        player1: game.player1.clone(),
+        wager_token: game.wager_token.clone(),
+        wager_amount: game.wager_amount,
+        opponent_stake: game.opponent_stake,
+        memo: game.memo.clone(),
+        metadata: game.metadata1.clone(),
+        terms: game.terms.clone(),
+        starts_at: game.starts_at,
     }
     .publish(env);
 }
 
-pub fn emit_game_joined(env: &Env, session_id: u32, player2: &Address) {
+pub fn emit_game_joined(env: &Env, session_id: u32, seq: u32, game: &Game) {
     GameJoined {
         session_id,
-        player2: player2.clone(),
+        seq,
+        player2: game.player2.clone(),
+        metadata: game.metadata2.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_join_requested(env: &Env, session_id: u32, seq: u32, joiner: &Address) {
+    JoinRequested {
+        session_id,
+        seq,
+        joiner: joiner.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_join_declined(env: &Env, session_id: u32, seq: u32, joiner: &Address) {
+    JoinDeclined {
+        session_id,
+        seq,
+        joiner: joiner.clone(),
     }
     .publish(env);
 }
 
-pub fn emit_seed_revealed(env: &Env, session_id: u32, player: &Address) {
+pub fn emit_seed_revealed(env: &Env, session_id: u32, seq: u32, player: &Address) {
     SeedRevealed {
         session_id,
+        seq,
         player: player.clone(),
     }
     .publish(env);
 }
 
-pub fn emit_game_settled(env: &Env, session_id: u32, winner: &Address) {
+pub fn emit_game_settled(env: &Env, session_id: u32, seq: u32, game: &Game) {
     GameSettled {
         session_id,
-        winner: winner.clone(),
+        seq,
+        winner: game.winner_address.clone(),
+        outcome: game.winner,
+        memo: game.memo.clone(),
+        seed_commit1: game.seed_commit1.clone(),
+        seed_commit2: game.seed_commit2.clone(),
+        seed1: game.seed1.clone(),
+        seed2: game.seed2.clone(),
+    }
+    .publish(env);
+}
+
+/// The `soroban-pvp-game` result event for a decided or re-decided session.
+pub fn emit_pvp_result(
+    env: &Env,
+    session_id: u32,
+    player1: &Address,
+    player2: &Address,
+    winner: Winner,
+    winner_address: &Option<Address>,
+) {
+    PvpResult {
+        session_id,
+        player1: player1.clone(),
+        player2: player2.clone(),
+        winner: winner_address.clone(),
+        draw: winner == Winner::Draw,
+    }
+    .publish(env);
+}
+
+pub fn emit_game_followed(env: &Env, session_id: u32, seq: u32, follower: &Address, followers: u32) {
+    GameFollowed {
+        session_id,
+        seq,
+        follower: follower.clone(),
+        followers,
+    }
+    .publish(env);
+}
+
+pub fn emit_terms_agreed(env: &Env, session_id: u32, seq: u32, terms: &BytesN<32>) {
+    TermsAgreed {
+        session_id,
+        seq,
+        terms: terms.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_result_acknowledged(env: &Env, session_id: u32, seq: u32, player: &Address) {
+    ResultAcknowledged {
+        session_id,
+        seq,
+        player: player.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_game_cancelled(env: &Env, session_id: u32, seq: u32, game: &Game) {
+    GameCancelled {
+        session_id,
+        seq,
+        seed_commit1: game.seed_commit1.clone(),
+        seed_commit2: game.seed_commit2.clone(),
+        seed1: game.seed1.clone(),
+        seed2: game.seed2.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_game_expiring_soon(env: &Env, session_id: u32, seq: u32, phase: Phase, ledgers_left: u32) {
+    GameExpiringSoon {
+        session_id,
+        seq,
+        phase,
+        ledgers_left,
+    }
+    .publish(env);
+}
+
+pub fn emit_bounty_paid(env: &Env, session_id: u32, seq: u32, to: &Address, bounty: &EscrowedBounty) {
+    BountyPaid {
+        session_id,
+        seq,
+        to: to.clone(),
+        token: bounty.token.clone(),
+        amount: bounty.amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_bounty_refunded(env: &Env, session_id: u32, seq: u32, bounty: &EscrowedBounty) {
+    BountyRefunded {
+        session_id,
+        seq,
+        to: bounty.funder.clone(),
+        token: bounty.token.clone(),
+        amount: bounty.amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_stake_deposited(env: &Env, session_id: u32, seq: u32, from: &Address, token: &Address, amount: i128) {
+    StakeDeposited {
+        session_id,
+        seq,
+        from: from.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_stake_paid(env: &Env, session_id: u32, seq: u32, to: &Address, token: &Address, amount: i128) {
+    StakePaid {
+        session_id,
+        seq,
+        to: to.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_stake_refunded(env: &Env, session_id: u32, seq: u32, to: &Address, token: &Address, amount: i128) {
+    StakeRefunded {
+        session_id,
+        seq,
+        to: to.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_pot_sponsored(env: &Env, session_id: u32, seq: u32, sponsorship: &Sponsorship) {
+    PotSponsored {
+        session_id,
+        seq,
+        sponsor: sponsorship.sponsor.clone(),
+        token: sponsorship.token.clone(),
+        amount: sponsorship.amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_sponsorship_paid(env: &Env, session_id: u32, seq: u32, to: &Address, token: &Address, amount: i128) {
+    SponsorshipPaid {
+        session_id,
+        seq,
+        to: to.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_sponsorship_refunded(env: &Env, session_id: u32, seq: u32, to: &Address, token: &Address, amount: i128) {
+    SponsorshipRefunded {
+        session_id,
+        seq,
+        to: to.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_rake_collected(env: &Env, session_id: u32, seq: u32, token: &Address, amount: i128, insurance: i128) {
+    RakeCollected {
+        session_id,
+        seq,
+        token: token.clone(),
+        amount,
+        insurance,
+    }
+    .publish(env);
+}
+
+pub fn emit_compensation_proposed(env: &Env, session_id: u32, seq: u32, compensation: &PendingCompensation) {
+    CompensationProposed {
+        session_id,
+        seq,
+        to: compensation.to.clone(),
+        token: compensation.token.clone(),
+        amount: compensation.amount,
+        eta: compensation.eta,
+    }
+    .publish(env);
+}
+
+pub fn emit_compensation_paid(env: &Env, session_id: u32, seq: u32, compensation: &PendingCompensation) {
+    CompensationPaid {
+        session_id,
+        seq,
+        to: compensation.to.clone(),
+        token: compensation.token.clone(),
+        amount: compensation.amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_treasury_withdrawal(
+    env: &Env,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+    account: &TreasuryAccount,
+) {
+    TreasuryWithdrawal {
+        token: token.clone(),
+        to: to.clone(),
+        amount,
+        accrued: account.accrued,
+        withdrawn: account.withdrawn,
+    }
+    .publish(env);
+}
+
+pub fn emit_treasury_updated(env: &Env, treasury: &Address) {
+    TreasuryUpdated {
+        treasury: treasury.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_affiliate_registered(env: &Env, code: &BytesN<32>, affiliate: &Affiliate) {
+    AffiliateRegistered {
+        code: code.clone(),
+        payout: affiliate.payout.clone(),
+        share_bps: affiliate.share_bps,
+    }
+    .publish(env);
+}
+
+pub fn emit_affiliate_removed(env: &Env, code: &BytesN<32>) {
+    AffiliateRemoved { code: code.clone() }.publish(env);
+}
+
+pub fn emit_affiliate_credited(env: &Env, session_id: u32, seq: u32, code: &BytesN<32>, token: &Address, amount: i128) {
+    AffiliateCredited {
+        session_id,
+        seq,
+        code: code.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_affiliate_claimed(env: &Env, code: &BytesN<32>, payout: &Address, token: &Address, amount: i128) {
+    AffiliateClaimed {
+        code: code.clone(),
+        payout: payout.clone(),
+        token: token.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_ohloss_updated(env: &Env, ohloss: &Address, version: u32) {
+    OhlossUpdated {
+        ohloss: ohloss.clone(),
+        version,
+    }
+    .publish(env);
+}
+
+pub fn emit_result_sink_allowed(env: &Env, sink: &Address, version: u32) {
+    ResultSinkAllowed {
+        sink: sink.clone(),
+        version,
+    }
+    .publish(env);
+}
+
+pub fn emit_session_range_assigned(env: &Env, range: &SessionRange) {
+    SessionRangeAssigned {
+        orchestrator: range.orchestrator.clone(),
+        start: range.start,
+        end: range.end,
+    }
+    .publish(env);
+}
+
+pub fn emit_session_range_removed(env: &Env, orchestrator: &Address) {
+    SessionRangeRemoved { orchestrator: orchestrator.clone() }.publish(env);
+}
+
+pub fn emit_result_sink_removed(env: &Env, sink: &Address) {
+    ResultSinkRemoved { sink: sink.clone() }.publish(env);
+}
+
+pub fn emit_admin_renounced(env: &Env, admin: &Address) {
+    AdminRenounced {
+        admin: admin.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_vk_frozen(env: &Env) {
+    VkFrozen {}.publish(env);
+}
+
+pub fn emit_breaker_tripped(env: &Env, variant: u32, failures: u32) {
+    BreakerTripped { variant, failures }.publish(env);
+}
+
+pub fn emit_breaker_reset(env: &Env, variant: u32) {
+    BreakerReset { variant }.publish(env);
+}
+
+pub fn emit_admin_audit(env: &Env, seq: u32, kind: Symbol, actor: &Address, details_hash: &BytesN<32>) {
+    AdminAudit {
+        seq,
+        kind,
+        actor: actor.clone(),
+        details_hash: details_hash.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_secp256k1_key_bound(env: &Env, player: &Address, key: &BytesN<65>) {
+    Secp256k1KeyBound {
+        player: player.clone(),
+        key: key.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_dispute_flagged(env: &Env, session_id: u32, seq: u32, dispute: &Dispute) {
+    DisputeFlagged {
+        session_id,
+        seq,
+        challenger: dispute.challenger.clone(),
+        token: dispute.token.clone(),
+        bond: dispute.bond,
+    }
+    .publish(env);
+}
+
+pub fn emit_dispute_resolved(env: &Env, session_id: u32, seq: u32, ruling: Winner, upheld: bool, bond_to: &Address) {
+    DisputeResolved {
+        session_id,
+        seq,
+        ruling,
+        upheld,
+        bond_to: bond_to.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_payout_released(env: &Env, session_id: u32, seq: u32) {
+    PayoutReleased { session_id, seq }.publish(env);
+}
+
+pub fn emit_arbiter_updated(env: &Env, arbiter: &Address) {
+    ArbiterUpdated {
+        arbiter: arbiter.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_name_registry_updated(env: &Env, registry: &Address) {
+    NameRegistryUpdated {
+        registry: registry.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_pause_updated(env: &Env, op: Operation, paused: bool) {
+    PauseUpdated { op, paused }.publish(env);
+}
+
+pub fn emit_council_updated(env: &Env, council: &Council) {
+    CouncilUpdated {
+        signers: council.signers.clone(),
+        threshold: council.threshold,
+    }
+    .publish(env);
+}
+
+pub fn emit_proposal_created(env: &Env, proposal_id: u32, proposer: &Address, eta: u32) {
+    ProposalCreated {
+        proposal_id,
+        proposer: proposer.clone(),
+        eta,
+    }
+    .publish(env);
+}
+
+pub fn emit_proposal_cancelled(env: &Env, proposal_id: u32, canceller: &Address) {
+    ProposalCancelled {
+        proposal_id,
+        canceller: canceller.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_timelock_updated(env: &Env, delay: u32) {
+    TimelockUpdated { delay }.publish(env);
+}
+
+pub fn emit_schema_migrated(env: &Env, from_version: u32, to_version: u32) {
+    SchemaMigrated { from_version, to_version }.publish(env);
+}
+
+pub fn emit_proposal_approved(env: &Env, proposal_id: u32, signer: &Address) {
+    ProposalApproved {
+        proposal_id,
+        signer: signer.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_proposal_executed(env: &Env, proposal_id: u32) {
+    ProposalExecuted { proposal_id }.publish(env);
+}
+
+pub fn emit_rake_updated(env: &Env, old_bps: u32, new_bps: u32) {
+    RakeUpdated { old_bps, new_bps }.publish(env);
+}
+
+pub fn emit_timing_config_updated(env: &Env, config: &TimingConfig) {
+    TimingConfigUpdated {
+        game_ttl: config.game_ttl,
+        join_window: config.join_window,
+        reveal_window: config.reveal_window,
+    }
+    .publish(env);
+}
+
+pub fn emit_beacon_updated(env: &Env, beacon: &Address) {
+    BeaconUpdated {
+        beacon: beacon.clone(),
+    }
+    .publish(env);
+}
+
+pub fn emit_variant_registered(env: &Env, variant_id: u32, variant: &Variant) {
+    VariantRegistered {
+        variant_id,
+        deck_size: variant.deck_size,
+        suits: variant.suits,
+        rounds: variant.rounds,
+        vk_id: variant.vk_id,
     }
     .publish(env);
 }
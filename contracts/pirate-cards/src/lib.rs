@@ -1,27 +1,39 @@
 #![no_std]
 
+mod escrow;
 mod events;
+mod poseidon;
 mod storage;
 pub mod types;
 mod verifier;
 
 #[cfg(test)]
 mod test;
+#[cfg(test)]
+mod test_bench;
 
+use soroban_pvp_game::{GameInfo, GameResult, GameStatus, PvpGame};
 use soroban_sdk::{
-    contract, contractclient, contracterror, contractimpl, Address, BytesN, Env,
+    address_payload::AddressPayload, contract, contractclient, contracterror, contractimpl, crypto::Hash,
+    xdr::ToXdr, Address, Bytes, BytesN, Env, Executable, IntoVal, Map, String, Symbol, Val, Vec, U256,
 };
 
-use types::{Game, Groth16Proof, PublicInputs, VerificationKey};
-
-// Game phases
-const PHASE_CREATED: u32 = 0;
-const PHASE_JOINED: u32 = 1;
-const PHASE_REVEALED: u32 = 2;
-const PHASE_SETTLED: u32 = 3;
+use types::{
+    bump_seq, AdminAction, AdminProposal, Affiliate, AggregateEntry, Attestation, BatchSettlement,
+    BondTerms, CommitScheme, Config, Council, CreationWindow, DeckOrder, Dispute, EscrowedBounty,
+    EscrowedStake, Followers, Game, GameOptions, GameScore, GameStats, GameSummary, Groth16Proof,
+    JoinOptions, JoinRequest, LobbyEntry, LogEntry, MatchFilter, Operation, OutcomePayload,
+    PendingCompensation, PendingPayout, Phase, PhaseCounts, PublicInputs, RateLimit, ReportBond,
+    SeedCommitment, SeedOpening, SessionPage, SessionRange, SessionRanges, SettlementReceipt,
+    Sponsorship, TimeInfo, TimingConfig, TokenVolume, Tombstone, TreasuryAccount, Variant,
+    VariantConfig, VerificationKey, VkInfo, VkProvenance, Winner, PUBLIC_INPUT_COUNT,
+};
 
 /// Ohloss protocol interface. The `#[contractclient]` macro generates
 /// `OhlossClient` for cross-contract calls to start_game / end_game.
+/// Everything after `end_game` is optional: a deployment advertises what it
+/// accepts through `capabilities`, and older ones without it get plain
+/// win/lose reports.
 #[contractclient(name = "OhlossClient")]
 pub trait Ohloss {
     fn start_game(
@@ -33,6 +45,54 @@ pub trait Ohloss {
     );
 
     fn end_game(env: Env, session_id: u32, player1_won: bool);
+
+    fn protocol_version(env: Env) -> u32;
+
+    fn capabilities(env: Env) -> u32; // protocol version 2 and later
+
+    fn end_game_scored(env: Env, session_id: u32, player1_won: bool, score: GameScore);
+
+    fn report_draw(env: Env, session_id: u32);
+
+    fn report_forfeit(env: Env, session_id: u32, forfeiter: Address);
+
+    fn rating(env: Env, player: Address) -> u32;
+
+    fn correct_result(env: Env, session_id: u32, outcome: Winner);
+}
+
+/// Ohloss protocol versions this contract can drive.
+pub const MIN_OHLOSS_VERSION: u32 = 1;
+pub const MAX_OHLOSS_VERSION: u32 = 2;
+
+/// `capabilities` bits.
+pub const OHLOSS_CAP_DRAWS: u32 = 1 << 0;
+pub const OHLOSS_CAP_FORFEITS: u32 = 1 << 1;
+pub const OHLOSS_CAP_SCORES: u32 = 1 << 2;
+pub const OHLOSS_CAP_RATINGS: u32 = 1 << 3;
+pub const OHLOSS_CAP_CORRECTIONS: u32 = 1 << 4;
+
+/// Randomness beacon interface. A beacon publishes one 32-byte value per
+/// round, where a round is a ledger sequence number.
+#[contractclient(name = "BeaconClient")]
+pub trait Beacon {
+    fn randomness(env: Env, round: u32) -> Option<BytesN<32>>;
+}
+
+/// Display-name registry interface (see the name-registry contract).
+/// Lookups are best-effort: a failing registry only leaves names unset.
+#[contractclient(name = "DisplayNamesClient")]
+pub trait DisplayNames {
+    fn name_of(env: Env, player: Address) -> Option<String>;
+}
+
+/// Per-game observer interface, e.g. a tournament controller. Callbacks
+/// are best-effort: a failing observer cannot block the game.
+#[contractclient(name = "ObserverClient")]
+pub trait Observer {
+    fn on_joined(env: Env, session_id: u32);
+    fn on_revealed(env: Env, session_id: u32);
+    fn on_settled(env: Env, session_id: u32, winner: Option<Address>);
 }
 
 #[contracterror]
@@ -51,6 +111,71 @@ pub enum Error {
     NoVk = 10,
     PublicInputMismatch = 11,
     SelfPlay = 12,
+    InvalidConfig = 13,
+    JoinWindowClosed = 14,
+    RevealWindowClosed = 15,
+    InputCountMismatch = 16,
+    VkIcLengthMismatch = 17,
+    DegenerateProofPoint = 18,
+    InvalidAmount = 19,
+    NoBounty = 20,
+    SessionIdConsumed = 21,
+    CommitmentMismatch = 22,
+    NoBeacon = 23,
+    BeaconNotReady = 24,
+    UnknownVariant = 25,
+    InsufficientInsurance = 26,
+    TimelockActive = 27,
+    InsufficientTreasury = 28,
+    SponsorLimit = 29,
+    UnknownAffiliate = 30,
+    NothingToClaim = 31,
+    IncompatibleOhloss = 32,
+    ReportFailed = 33,
+    UnknownResultSink = 34,
+    BatchTooLarge = 35,
+    AdminRenounced = 36,
+    VkFrozen = 37,
+    CouncilRequired = 38,
+    NotCouncilMember = 39,
+    AlreadyApproved = 40,
+    ProposalNotFound = 41,
+    NotEnoughApprovals = 42,
+    TimelockRequired = 43,
+    NotAdmin = 44,
+    Paused = 45,
+    BreakerTripped = 46,
+    InvalidSignature = 47,
+    DisputeWindowClosed = 48,
+    DisputeOpen = 49,
+    NoDispute = 50,
+    NotArbiter = 51,
+    RateLimited = 52,
+    PairCooldown = 53,
+    VkRequired = 54,
+    VkHashMismatch = 55,
+    AlreadyInitialized = 56,
+    NotAContract = 57,
+    SchemaMismatch = 58,
+    DuplicateCommitment = 59,
+    NoJoinRequest = 60,
+    JoinRequestLimit = 61,
+    NoOpenGame = 62,
+    RegisterFailed = 63,
+    ZeroCommitment = 64,     // all-zero commitments and seeds mean "unset" in storage
+    ZeroSeed = 65,
+    ReservedSessionId = 66,
+    NotInitialized = 67,     // no admin or Ohloss stored: the constructor never ran
+    NoDelegate = 68,         // the player registered no reveal delegate
+    RematchUnavailable = 69, // not an acknowledged game of the creator's, or already rematched
+    TermsMismatch = 70,      // the joiner did not co-sign the game's terms
+    NotStarted = 71,         // a scheduled game's start ledger has not been reached
+    OddsMismatch = 72,       // the joiner did not co-sign a handicap game's stake
+    RevealWindowOpen = 73,   // a timeout claimed before the reveal deadline passed
+    TokenNotAllowed = 74,    // a sponsorship in a token neither the game nor the allowlist accepts
+    AlreadyReported = 75,    // this proof was already reported against the session
+    KeysNotCovered = 76,     // an aggregate batch mixes keys the aggregate key does not cover
+    DeckNotDerivable = 77,   // seeds, beacon or deck size the variant's circuit can't lay out
 }
 
 #[contract]
@@ -60,218 +185,3700 @@ fn zero32(env: &Env) -> BytesN<32> {
     BytesN::from_array(env, &[0u8; 32])
 }
 
-#[contractimpl]
-impl PirateCardsContract {
-    /// Deploy: store admin and Ohloss contract address.
-    pub fn __constructor(env: Env, admin: Address, ohloss: Address) {
-        storage::set_admin(&env, &admin);
-        storage::set_ohloss(&env, &ohloss);
+/// Encode a u32 as a 32-byte big-endian field element.
+fn u32_field(env: &Env, value: u32) -> BytesN<32> {
+    let mut bytes = [0u8; 32];
+    bytes[28..32].copy_from_slice(&value.to_be_bytes());
+    BytesN::from_array(env, &bytes)
+}
+
+/// Reduce a beacon value to a BN254 scalar field element by clearing its top
+/// three bits (2^253 < r), so it can be passed as a public input.
+fn beacon_field(env: &Env, value: &BytesN<32>) -> BytesN<32> {
+    let mut bytes = value.to_array();
+    bytes[0] &= 0x1f;
+    BytesN::from_array(env, &bytes)
+}
+
+/// Pick a winner for a drawn no-draws game. The PRNG is seeded from both
+/// revealed seeds, so anyone can recompute the result and neither player
+/// could steer it after the other revealed.
+fn tiebreak_winner(env: &Env, seed1: &BytesN<32>, seed2: &BytesN<32>) -> Winner {
+    let mut preimage = Bytes::from_array(env, &seed1.to_array());
+    preimage.append(&seed2.clone().into());
+    env.prng().seed(env.crypto().sha256(&preimage).into());
+    if env.prng().gen_range::<u64>(0..=1) == 0 {
+        Winner::Player1
+    } else {
+        Winner::Player2
     }
+}
 
-    /// Admin: set the Groth16 verification key.
-    pub fn set_vk(env: Env, vk: VerificationKey) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
-        storage::set_vk(&env, &vk);
-        Ok(())
+/// Authorize a privileged call. Fails for good once the admin has
+/// renounced.
+fn require_admin(env: &Env) -> Result<Address, Error> {
+    if storage::is_admin_renounced(env) {
+        return Err(Error::AdminRenounced);
     }
+    let admin = storage::get_admin(env).ok_or(Error::NotInitialized)?;
+    admin.require_auth();
+    storage::extend_instance(env, false);
+    Ok(admin)
+}
 
-    /// P1 creates an open game. Anyone can join via join_game.
-    pub fn create_game(
-        env: Env,
-        session_id: u32,
-        player1: Address,
-        seed_commit1: BytesN<32>,
-    ) -> Result<(), Error> {
-        if storage::has_game(&env, session_id) {
-            return Err(Error::GameAlreadyExists);
-        }
+/// Fail a direct call to an operation the council has taken over.
+fn require_no_council(env: &Env) -> Result<(), Error> {
+    match storage::get_council(env) {
+        Some(_) => Err(Error::CouncilRequired),
+        None => Ok(()),
+    }
+}
 
-        player1.require_auth();
+/// Append a privileged call to the audit log under the next sequence
+/// number, so consumers can detect gaps. Arguments are logged as the hash
+/// of their XDR.
+fn audit(env: &Env, kind: &str, actor: &Address, details: Bytes) {
+    let seq = storage::next_audit_seq(env);
+    let details_hash = env.crypto().sha256(&details).to_bytes();
+    events::emit_admin_audit(env, seq, Symbol::new(env, kind), actor, &details_hash);
+}
 
-        let z = zero32(&env);
-        let game = Game {
-            player1: player1.clone(),
-            player2: player1.clone(), // sentinel — no player2 yet
-            seed_commit1,
-            seed_commit2: z.clone(),
-            seed1: z.clone(),
-            seed2: z,
-            phase: PHASE_CREATED,
-            winner: 0,
-        };
-        storage::set_game(&env, session_id, &game);
-        events::emit_game_created(&env, session_id, &player1);
+/// Append `action` by `actor` to the session's activity log.
+fn log_action(env: &Env, session_id: u32, actor: &Address, action: &str) {
+    let entry = LogEntry { actor: actor.clone(), action: Symbol::new(env, action), ledger: env.ledger().sequence() };
+    storage::append_game_log(env, session_id, &entry, MAX_LOG_ENTRIES);
+}
 
-        Ok(())
+/// Check that `signature` over `digest` recovers to `player`'s bound
+/// secp256k1 key.
+fn check_secp256k1(
+    env: &Env,
+    player: &Address,
+    digest: &Hash<32>,
+    signature: &BytesN<64>,
+    recovery_id: u32,
+) -> Result<(), Error> {
+    let key = storage::get_secp256k1_key(env, player).ok_or(Error::InvalidSignature)?;
+    if recovery_id > 3 {
+        return Err(Error::InvalidSignature);
     }
+    if env.crypto().secp256k1_recover(digest, signature, recovery_id) != key {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(())
+}
 
-    /// Any player can join an open game by providing the session ID.
-    /// Sets player2, commits their seed, and registers the game with Ohloss.
-    pub fn join_game(
-        env: Env,
-        session_id: u32,
-        player2: Address,
-        seed_commit2: BytesN<32>,
-    ) -> Result<(), Error> {
-        let mut game = storage::get_game(&env, session_id)
-            .ok_or(Error::GameNotFound)?;
-        if game.phase != PHASE_CREATED {
-            return Err(Error::InvalidState);
-        }
-        if player2 == game.player1 {
-            return Err(Error::SelfPlay);
-        }
+/// Check that `signature` over `digest` is by the ed25519 key behind
+/// `player`'s Stellar account. Contract addresses have no such key; a
+/// signature that does not verify traps in the host.
+fn check_ed25519(env: &Env, player: &Address, digest: &Hash<32>, signature: &BytesN<64>) -> Result<(), Error> {
+    let Some(AddressPayload::AccountIdPublicKeyEd25519(key)) = player.to_payload() else {
+        return Err(Error::InvalidSignature);
+    };
+    env.crypto().ed25519_verify(&key, &digest.to_bytes().into(), signature);
+    Ok(())
+}
 
-        player2.require_auth();
+/// The game an agreed `outcome` may settle, and the `OutcomePayload`
+/// digest both players must have signed for it.
+fn signed_outcome(env: &Env, session_id: u32, outcome: Winner) -> Result<(Game, Hash<32>), Error> {
+    require_unpaused(env, Operation::Settle)?;
+    let game = storage::get_game(env, session_id)
+        .ok_or(Error::GameNotFound)?;
+    if game.phase != Phase::Joined && game.phase != Phase::Revealed {
+        return Err(Error::InvalidState);
+    }
+    match outcome {
+        Winner::Player1 | Winner::Player2 => {}
+        Winner::Draw if !game.no_draws => {}
+        _ => return Err(Error::InvalidWinner),
+    }
+
+    let digest = OutcomePayload {
+        contract: env.current_contract_address(),
+        session_id,
+        outcome,
+    }
+    .digest(env);
+    Ok((game, digest))
+}
 
-        game.player2 = player2.clone();
-        game.seed_commit2 = seed_commit2;
-        game.phase = PHASE_JOINED;
+/// Count a game creation against `creator`'s rate-limit window.
+fn check_rate_limit(env: &Env, creator: &Address) -> Result<(), Error> {
+    let limit = storage::get_rate_limit(env);
+    if limit.max_creations == 0 {
+        return Ok(());
+    }
+    let now = env.ledger().sequence();
+    let mut window = match storage::get_creation_window(env, creator) {
+        Some(window) if now < window.start + limit.window => window,
+        _ => CreationWindow { start: now, count: 0 },
+    };
+    if window.count >= limit.max_creations {
+        return Err(Error::RateLimited);
+    }
+    window.count += 1;
+    let ttl = (window.start + limit.window).saturating_sub(now).max(1);
+    storage::set_creation_window(env, creator, &window, ttl);
+    Ok(())
+}
 
-        // Both players now known — register with Ohloss
-        let ohloss_addr = storage::get_ohloss(&env);
-        let ohloss = OhlossClient::new(&env, &ohloss_addr);
-        ohloss.start_game(
-            &env.current_contract_address(),
-            &session_id,
-            &game.player1,
-            &player2,
-        );
+fn require_unpaused(env: &Env, op: Operation) -> Result<(), Error> {
+    if storage::is_paused(env, op) {
+        return Err(Error::Paused);
+    }
+    Ok(())
+}
 
-        storage::set_game(&env, session_id, &game);
-        events::emit_game_joined(&env, session_id, &player2);
+/// Consecutive invalid proofs on one variant that halt its settlements.
+pub const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
 
-        Ok(())
+/// Fail a direct call to an operation that must wait out the timelock.
+fn require_no_timelock(env: &Env) -> Result<(), Error> {
+    if storage::get_timelock_delay(env) > 0 {
+        return Err(Error::TimelockRequired);
     }
+    Ok(())
+}
 
-    /// Either player reveals their seed. Both must reveal before settlement.
-    pub fn reveal_seed(
-        env: Env,
-        session_id: u32,
-        player: Address,
-        seed: BytesN<32>,
-    ) -> Result<(), Error> {
-        let mut game = storage::get_game(&env, session_id)
-            .ok_or(Error::GameNotFound)?;
+/// Who may propose and approve queued operations: the council, or the
+/// admin alone while there is none.
+fn approvers(env: &Env) -> Result<Council, Error> {
+    if let Some(council) = storage::get_council(env) {
+        return Ok(council);
+    }
+    if storage::is_admin_renounced(env) {
+        return Err(Error::AdminRenounced);
+    }
+    Ok(Council {
+        signers: Vec::from_array(env, [storage::get_admin(env).ok_or(Error::NotInitialized)?]),
+        threshold: 1,
+    })
+}
 
-        // Must be joined but not yet fully revealed
-        if game.phase < PHASE_JOINED || game.phase >= PHASE_REVEALED {
-            return Err(Error::InvalidState);
-        }
+/// Renouncing the admin role also closes the queue, council or not, to
+/// everything but treasury withdrawals.
+fn require_action_allowed(env: &Env, action: &AdminAction) -> Result<(), Error> {
+    if storage::is_admin_renounced(env) && !matches!(action, AdminAction::WithdrawTreasury(..)) {
+        return Err(Error::AdminRenounced);
+    }
+    Ok(())
+}
 
-        player.require_auth();
+fn require_approver(env: &Env, who: &Address) -> Result<Council, Error> {
+    let approvers = approvers(env)?;
+    if !approvers.signers.contains(who) {
+        return Err(if storage::get_council(env).is_some() {
+            Error::NotCouncilMember
+        } else {
+            Error::NotAdmin
+        });
+    }
+    who.require_auth();
+    Ok(approvers)
+}
 
-        let z = zero32(&env);
+/// Longest timelock the queue may be configured with (~30 days).
+pub const MAX_TIMELOCK_DELAY: u32 = 518_400;
+
+/// Most signers a council may have.
+pub const MAX_COUNCIL_SIZE: u32 = 10;
+
+/// Smallest dispute bond, in basis points of the session's escrowed pot.
+pub const MIN_DISPUTE_BOND_BPS: i128 = 500;
+
+/// How long after the dispute window the arbiter has to rule (~7 days)
+/// before the held payout can be released on the original result.
+pub const ARBITRATION_WINDOW: u32 = 120_960;
+
+/// Most open games one `join_any` call considers, oldest first.
+pub const MAX_QUICK_PLAY_SCAN: u32 = 20;
+
+/// Public inputs of the aggregation circuit: the batch commitment.
+pub const AGGREGATE_INPUT_COUNT: u32 = 1;
+
+/// Most sessions one aggregated proof may settle; bounded by the ledger
+/// entries a transaction may write.
+pub const MAX_AGGREGATE_BATCH: u32 = 20;
+
+/// Storage layout this build expects. Bump it with every change to stored
+/// types or keys, and add the step that rewrites older data to
+/// `migrate_step`.
+pub const SCHEMA_VERSION: u32 = 6;
+
+/// Shape of the published events. Bump it with every change to an event's
+/// topics or data fields, so indexers know to update their decoders; the
+/// event snapshot test records each version under `test_snapshots/events`.
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
 
-        if player == game.player1 {
-            if game.seed1 != z {
-                return Err(Error::AlreadyRevealed);
+/// Session ids from here up are reserved for orchestrators (tournament or
+/// factory contracts) the admin assigns ranges to, so ids they pick never
+/// collide with ones users choose.
+pub const RESERVED_SESSION_START: u32 = 0xF000_0000;
+
+/// Most join requests a `require_approval` game holds at once.
+pub const MAX_JOIN_REQUESTS: u32 = 8;
+
+/// Most followers `get_followers` lists; later ones are only counted.
+pub const MAX_LISTED_FOLLOWERS: u32 = 20;
+
+/// Most entries a game's activity log keeps; older ones are dropped.
+pub const MAX_LOG_ENTRIES: u32 = 32;
+
+/// Most games `settle_batch` settles per call; four proofs plus the
+/// shared pairings already use most of a transaction's CPU budget.
+pub const MAX_SETTLE_BATCH: u32 = 4;
+
+/// Rewrite storage laid out for `from_version` into the next version's
+/// layout.
+fn migrate_step(env: &Env, from_version: u32) {
+    // 0 -> 1: keys move to persistent storage and constructors leave an
+    // initialized flag; apply both now rather than on first use.
+    if from_version == 0 {
+        for variant_id in storage::get_variant_ids(env).iter() {
+            if let Some(variant) = storage::get_variant(env, variant_id) {
+                storage::get_vk(env, variant.vk_id);
             }
-            game.seed1 = seed;
-        } else if player == game.player2 {
-            if game.seed2 != z {
-                return Err(Error::AlreadyRevealed);
+        }
+        storage::set_initialized(env);
+    }
+    // 1 -> 2: open games carry a LobbyEntry for quick-play filtering.
+    if from_version == 1 {
+        for session_id in storage::take_legacy_open_games(env).iter() {
+            if let Some(game) = storage::get_game(env, session_id) {
+                storage::add_open_game(env, session_id, &lobby_entry(&game));
+            }
+        }
+    }
+    // 2 -> 3: the settlement and open-games indexes move to radix pages,
+    // and lobby entries to one temporary entry per game.
+    if from_version == 2 {
+        for session_id in storage::take_legacy_lobby(env).iter() {
+            if let Some(game) = storage::get_game(env, session_id) {
+                storage::add_open_game(env, session_id, &lobby_entry(&game));
+            }
+        }
+        for session_id in storage::take_legacy_pending_settlement(env).iter() {
+            storage::add_pending_settlement(env, session_id);
+        }
+    }
+    // 3 -> 4: player indexes move to radix pages. Nothing lists the
+    // players, so each one's is folded in when it is next touched.
+    // 4 -> 5: lobby entries drop the creator's rating.
+    if from_version == 4 {
+        let mut cursor = Some(0);
+        while let Some(page) = cursor {
+            let open = storage::list_open_games(env, page, storage::MAX_PAGE_SIZE);
+            cursor = open.next;
+            for session_id in open.ids.iter() {
+                match storage::get_game(env, session_id) {
+                    Some(game) => storage::add_open_game(env, session_id, &lobby_entry(&game)),
+                    None => storage::remove_open_game(env, session_id),
+                }
+            }
+        }
+    }
+    // 5 -> 6: games, join requests, stakes and receipts gained fields
+    // (odds, terms, metadata, scheduling, rematches, event numbering, the
+    // variant's key id) and decode from their schema 2 layout on read.
+    // Rewrite the live games the indexes reach; the rest upgrade when next
+    // read.
+    if from_version == 5 {
+        for list in [storage::list_open_games, storage::list_pending_settlement] {
+            let mut cursor = Some(0);
+            while let Some(page) = cursor {
+                let sessions = list(env, page, storage::MAX_PAGE_SIZE);
+                cursor = sessions.next;
+                for session_id in sessions.ids.iter() {
+                    rewrite_session(env, session_id);
+                }
             }
-            game.seed2 = seed;
-        } else {
-            return Err(Error::NotPlayer);
         }
+    }
+}
+
+/// Store a session's game, join requests and stake back in the current
+/// layout.
+fn rewrite_session(env: &Env, session_id: u32) {
+    let Some(mut game) = storage::get_game(env, session_id) else {
+        return;
+    };
+    storage::set_game(env, session_id, &mut game);
+    let requests = storage::get_join_requests(env, session_id);
+    if !requests.is_empty() {
+        storage::set_join_requests(env, session_id, &requests);
+    }
+    if let Some(stake) = storage::get_stake(env, session_id) {
+        storage::set_stake(env, session_id, &stake);
+    }
+}
 
-        // If both seeds revealed, advance phase
-        if game.seed1 != z && game.seed2 != z {
-            game.phase = PHASE_REVEALED;
+fn check_council(council: &Council) -> Result<(), Error> {
+    let size = council.signers.len();
+    if size > MAX_COUNCIL_SIZE || council.threshold == 0 || council.threshold > size {
+        return Err(Error::InvalidConfig);
+    }
+    for (i, signer) in council.signers.iter().enumerate() {
+        if council.signers.first_index_of(&signer) != Some(i as u32) {
+            return Err(Error::InvalidConfig);
         }
+    }
+    Ok(())
+}
 
-        let p = player.clone();
-        storage::set_game(&env, session_id, &game);
-        events::emit_seed_revealed(&env, session_id, &p);
+/// Keys and variants are locked by a freeze or by renouncing the admin.
+fn vk_locked(env: &Env) -> bool {
+    storage::is_vk_frozen(env) || storage::is_admin_renounced(env)
+}
 
-        Ok(())
+fn apply_register_vk(env: &Env, vk_id: u32, vk: &VerificationKey) -> Result<(), Error> {
+    if vk_locked(env) {
+        return Err(Error::VkFrozen);
+    }
+    if vk.ic.len() != PUBLIC_INPUT_COUNT + 1 {
+        return Err(Error::VkIcLengthMismatch);
     }
+    storage::set_vk(env, vk_id, vk);
+    storage::remove_vk_hash(env, vk_id);
+    storage::remove_vk_provenance(env, vk_id);
+    Ok(())
+}
 
-    /// Anyone can settle by submitting a valid ZK proof.
-    /// The proof determines the winner based on the revealed seeds.
-    /// Calls ohloss.end_game() to report the result.
-    pub fn settle_game(
-        env: Env,
-        session_id: u32,
-        proof: Groth16Proof,
-        pub_inputs: PublicInputs,
-    ) -> Result<Address, Error> {
-        let game = storage::get_game(&env, session_id)
-            .ok_or(Error::GameNotFound)?;
+fn apply_set_vk_provenance(env: &Env, vk_id: u32, provenance: &VkProvenance) -> Result<(), Error> {
+    if vk_locked(env) {
+        return Err(Error::VkFrozen);
+    }
+    if storage::get_vk(env, vk_id).is_none() && !storage::has_vk_hash(env, vk_id) {
+        return Err(Error::NoVk);
+    }
+    storage::set_vk_provenance(env, vk_id, provenance);
+    Ok(())
+}
+
+fn apply_set_aggregate_vk(env: &Env, vk: &VerificationKey, vk_ids: &Vec<u32>) -> Result<(), Error> {
+    if vk_locked(env) {
+        return Err(Error::VkFrozen);
+    }
+    if vk.ic.len() != AGGREGATE_INPUT_COUNT + 1 {
+        return Err(Error::VkIcLengthMismatch);
+    }
+    storage::set_aggregate_vk(env, vk);
+    storage::set_aggregate_vk_ids(env, vk_ids);
+    Ok(())
+}
+
+/// Hash of the key `vk_id` proofs verify under, stored or hash-only.
+fn vk_hash(env: &Env, vk_id: u32) -> Result<BytesN<32>, Error> {
+    match storage::get_vk(env, vk_id) {
+        Some(vk) => Ok(vk.hash(env)),
+        None => storage::get_vk_hash(env, vk_id).ok_or(Error::NoVk),
+    }
+}
+
+/// Each entry's key id and key hash, failing for a batch that mixes keys
+/// the aggregate key was not set to cover.
+fn batch_keys(env: &Env, entries: &Vec<AggregateEntry>) -> Result<Vec<(u32, BytesN<32>)>, Error> {
+    let covered = storage::get_aggregate_vk_ids(env);
+    let mut hashes: Map<u32, BytesN<32>> = Map::new(env);
+    let mut keys = Vec::new(env);
+    for entry in entries.iter() {
+        let vk_id = storage::get_game(env, entry.session_id)
+            .ok_or(Error::GameNotFound)?
+            .vk_id;
+        let hash = match hashes.get(vk_id) {
+            Some(hash) => hash,
+            None => vk_hash(env, vk_id)?,
+        };
+        hashes.set(vk_id, hash.clone());
+        keys.push_back((vk_id, hash));
+    }
+    if hashes.len() > 1 && hashes.keys().iter().any(|vk_id| !covered.contains(vk_id)) {
+        return Err(Error::KeysNotCovered);
+    }
+    Ok(keys)
+}
 
-        if game.phase < PHASE_REVEALED {
-            return Err(Error::SeedsNotRevealed);
+/// Commitment to an aggregated batch: sha256 over, for each entry in
+/// order, its key id (4 bytes, big-endian), its key hash and its public
+/// inputs in circuit order (32 bytes each), with the top three bits
+/// cleared so it is a BN254 scalar. The aggregation circuit exposes it as
+/// its only public input.
+fn batch_commitment(env: &Env, entries: &Vec<AggregateEntry>, keys: &Vec<(u32, BytesN<32>)>) -> BytesN<32> {
+    let mut preimage = Bytes::new(env);
+    for (entry, (vk_id, hash)) in entries.iter().zip(keys.iter()) {
+        preimage.extend_from_array(&vk_id.to_be_bytes());
+        preimage.extend_from_array(&hash.to_array());
+        for input in entry.pub_inputs.to_array() {
+            preimage.extend_from_array(&input.to_array());
         }
-        if game.phase >= PHASE_SETTLED {
-            return Err(Error::GameAlreadySettled);
+    }
+    let mut commitment = env.crypto().sha256(&preimage).to_array();
+    commitment[0] &= 0x1f;
+    BytesN::from_array(env, &commitment)
+}
+
+fn apply_register_vk_hash(env: &Env, vk_id: u32, hash: &BytesN<32>) -> Result<(), Error> {
+    if vk_locked(env) {
+        return Err(Error::VkFrozen);
+    }
+    storage::set_vk_hash(env, vk_id, hash);
+    storage::remove_vk(env, vk_id);
+    storage::remove_vk_provenance(env, vk_id);
+    Ok(())
+}
+
+/// The key to verify `vk_id` proofs with: the stored key, or for hash-only
+/// keys the caller's, once it matches the stored hash.
+fn resolve_vk(env: &Env, vk_id: u32, supplied: Option<&VerificationKey>) -> Result<VerificationKey, Error> {
+    if let Some(vk) = storage::get_vk(env, vk_id) {
+        return Ok(vk);
+    }
+    let hash = storage::get_vk_hash(env, vk_id).ok_or(Error::NoVk)?;
+    let vk = supplied.ok_or(Error::VkRequired)?;
+    if vk.hash(env) != hash {
+        return Err(Error::VkHashMismatch);
+    }
+    Ok(vk.clone())
+}
+
+fn apply_withdraw_treasury(
+    env: &Env,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<TreasuryAccount, Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+    let account = storage::get_treasury_account(env, token);
+    if amount > account.accrued - account.withdrawn {
+        return Err(Error::InsufficientTreasury);
+    }
+    Ok(escrow::withdraw_treasury(env, token, to, amount))
+}
+
+fn apply_action(env: &Env, action: &AdminAction) -> Result<(), Error> {
+    require_action_allowed(env, action)?;
+    match action {
+        AdminAction::RegisterVk(vk_id, vk) => apply_register_vk(env, *vk_id, vk),
+        AdminAction::RegisterVkHash(vk_id, hash) => apply_register_vk_hash(env, *vk_id, hash),
+        AdminAction::Upgrade(wasm_hash) => {
+            env.deployer().update_current_contract_wasm(wasm_hash.clone());
+            Ok(())
         }
-        if !storage::has_vk(&env) {
-            return Err(Error::NoVk);
+        AdminAction::WithdrawTreasury(token, to, amount) => {
+            apply_withdraw_treasury(env, token, to, *amount).map(|_| ())
         }
-
-        // Verify public inputs match on-chain state
-        if pub_inputs.seed_commit1 != game.seed_commit1
-            || pub_inputs.seed_commit2 != game.seed_commit2
-            || pub_inputs.seed1 != game.seed1
-            || pub_inputs.seed2 != game.seed2
-        {
-            return Err(Error::PublicInputMismatch);
+        AdminAction::SetCouncil(council) => {
+            check_council(council)?;
+            storage::set_council(env, council);
+            events::emit_council_updated(env, council);
+            Ok(())
+        }
+        AdminAction::SetOhloss(ohloss) => apply_set_ohloss(env, ohloss).map(|_| ()),
+        AdminAction::SetRakeBps(rake_bps) => apply_set_rake_bps(env, *rake_bps),
+        AdminAction::SetTimelockDelay(delay) => apply_set_timelock_delay(env, *delay),
+        AdminAction::SetAggregateVk(vk, vk_ids) => apply_set_aggregate_vk(env, vk, vk_ids),
+        AdminAction::SetVkProvenance(vk_id, provenance) => apply_set_vk_provenance(env, *vk_id, provenance),
+        AdminAction::SetNameRegistry(registry) => {
+            apply_set_name_registry(env, registry);
+            Ok(())
         }
+    }
+}
 
-        // session_id: u32 → 32-byte big-endian field element
-        let mut sid_bytes = [0u8; 32];
-        sid_bytes[28..32].copy_from_slice(&session_id.to_be_bytes());
-        if pub_inputs.session_id != BytesN::from_array(&env, &sid_bytes) {
-            return Err(Error::PublicInputMismatch);
+fn apply_set_ohloss(env: &Env, ohloss: &Address) -> Result<u32, Error> {
+    let version = negotiate_ohloss(env, ohloss)?;
+    storage::set_ohloss(env, ohloss);
+    storage::set_ohloss_version(env, version);
+    events::emit_ohloss_updated(env, ohloss, version);
+    Ok(version)
+}
+
+fn apply_set_name_registry(env: &Env, registry: &Address) {
+    storage::set_name_registry(env, registry);
+    events::emit_name_registry_updated(env, registry);
+}
+
+fn apply_set_rake_bps(env: &Env, rake_bps: u32) -> Result<(), Error> {
+    if rake_bps > escrow::MAX_RAKE_BPS {
+        return Err(Error::InvalidConfig);
+    }
+    let old = storage::get_rake_bps(env);
+    storage::set_rake_bps(env, rake_bps);
+    events::emit_rake_updated(env, old, rake_bps);
+    Ok(())
+}
+
+fn apply_set_timelock_delay(env: &Env, delay: u32) -> Result<(), Error> {
+    if delay > MAX_TIMELOCK_DELAY {
+        return Err(Error::InvalidConfig);
+    }
+    storage::set_timelock_delay(env, delay);
+    events::emit_timelock_updated(env, delay);
+    Ok(())
+}
+
+/// Ask `ohloss` for its protocol version and reject unsupported ones. A
+/// target that cannot answer predates the call and speaks version 1.
+fn negotiate_ohloss(env: &Env, ohloss: &Address) -> Result<u32, Error> {
+    let version = match OhlossClient::new(env, ohloss).try_protocol_version() {
+        Ok(Ok(version)) => version,
+        _ => 1,
+    };
+    if !(MIN_OHLOSS_VERSION..=MAX_OHLOSS_VERSION).contains(&version) {
+        return Err(Error::IncompatibleOhloss);
+    }
+    Ok(version)
+}
+
+/// The Ohloss a game reports to and its protocol version: the one it
+/// registered with at join, else the game's result sink if it names one,
+/// else the default. Fails once the game's sink has been removed from the
+/// allowlist.
+fn result_sink(env: &Env, game: &Game) -> Result<(Address, u32), Error> {
+    if let Some(ohloss) = &game.ohloss {
+        if game.result_sink.is_some() && storage::get_result_sink(env, ohloss).is_none() {
+            return Err(Error::UnknownResultSink);
         }
+        return Ok((ohloss.clone(), game.ohloss_version));
+    }
+    match &game.result_sink {
+        Some(sink) => storage::get_result_sink(env, sink)
+            .map(|version| (sink.clone(), version))
+            .ok_or(Error::UnknownResultSink),
+        None => storage::get_ohloss(env)
+            .map(|ohloss| (ohloss, storage::get_ohloss_version(env)))
+            .ok_or(Error::NotInitialized),
+    }
+}
 
-        // Winner must be 1 (player1) or 2 (player2)
-        let mut w1_bytes = [0u8; 32];
-        w1_bytes[31] = 1;
-        let mut w2_bytes = [0u8; 32];
-        w2_bytes[31] = 2;
+/// `player`'s rating from the Ohloss `game` reports to, or 0 when that
+/// deployment keeps no ratings.
+fn player_rating(env: &Env, game: &Game, player: &Address) -> u32 {
+    let Ok((sink, version)) = result_sink(env, game) else {
+        return 0;
+    };
+    if version < 2 {
+        return 0;
+    }
+    let ohloss = OhlossClient::new(env, &sink);
+    match ohloss.try_capabilities() {
+        Ok(Ok(caps)) if caps & OHLOSS_CAP_RATINGS != 0 => {}
+        _ => return 0,
+    }
+    match ohloss.try_rating(player) {
+        Ok(Ok(rating)) => rating,
+        _ => 0,
+    }
+}
 
-        let player1_won = if pub_inputs.winner == BytesN::from_array(&env, &w1_bytes) {
-            true
-        } else if pub_inputs.winner == BytesN::from_array(&env, &w2_bytes) {
-            false
-        } else {
-            return Err(Error::InvalidWinner);
-        };
+fn lobby_entry(game: &Game) -> LobbyEntry {
+    let (token, stake) = match (game.escrow_wager, &game.wager_token) {
+        (true, Some(token)) => (Some(token.clone()), game.opponent_stake),
+        _ => (None, 0),
+    };
+    LobbyEntry {
+        creator: game.player1.clone(),
+        token,
+        stake,
+        variant: game.variant,
+        approval: game.require_approval,
+    }
+}
 
-        // Verify ZK proof (expensive — last)
-        let vk = storage::get_vk(&env);
-        if !verifier::verify_groth16(&env, &proof, &vk, &pub_inputs) {
-            return Err(Error::InvalidProof);
+/// Report a concluded game to Ohloss using the richest call the deployment
+/// supports. Draws go unreported to deployments that cannot take them.
+/// Failures are swallowed so reporting can never block settlement; returns
+/// whether Ohloss accepted the report.
+fn report_result(env: &Env, session_id: u32, game: &Game) -> bool {
+    if game.unrated {
+        return true; // nothing to report
+    }
+    let Ok((sink, version)) = result_sink(env, game) else {
+        return false;
+    };
+    let ohloss = OhlossClient::new(env, &sink);
+    let caps = if version >= 2 {
+        match ohloss.try_capabilities() {
+            Ok(Ok(caps)) => caps,
+            _ => return false,
         }
+    } else {
+        0
+    };
+    let player1_won = game.winner == Winner::Player1;
 
-        let winner_addr = if player1_won {
-            game.player1.clone()
-        } else {
-            game.player2.clone()
-        };
+    match game.winner {
+        Winner::Player1 | Winner::Player2 => {
+            if game.forfeit && caps & OHLOSS_CAP_FORFEITS != 0 {
+                let forfeiter = if player1_won { &game.player2 } else { &game.player1 };
+                ohloss.try_report_forfeit(&session_id, forfeiter).is_ok()
+            } else if caps & OHLOSS_CAP_SCORES != 0 {
+                let score = GameScore {
+                    outcome: game.winner,
+                    tiebroken: game.tiebroken,
+                    variant: game.variant,
+                };
+                ohloss.try_end_game_scored(&session_id, &player1_won, &score).is_ok()
+            } else {
+                ohloss.try_end_game(&session_id, &player1_won).is_ok()
+            }
+        }
+        _ => {
+            if caps & OHLOSS_CAP_DRAWS != 0 {
+                ohloss.try_report_draw(&session_id).is_ok()
+            } else {
+                true // nothing this deployment can record
+            }
+        }
+    }
+}
 
-        // Update game state
-        let mut settled = game;
-        settled.winner = if player1_won { 1 } else { 2 };
-        settled.phase = PHASE_SETTLED;
-        storage::set_game(&env, session_id, &settled);
+/// Tell the game's Ohloss that a dispute overturned the result it was
+/// sent. Deployments that cannot take corrections keep the original.
+fn report_correction(env: &Env, session_id: u32, game: &Game) -> bool {
+    if game.unrated {
+        return true;
+    }
+    let Ok((sink, version)) = result_sink(env, game) else {
+        return false;
+    };
+    if version < 2 {
+        return true;
+    }
+    let ohloss = OhlossClient::new(env, &sink);
+    match ohloss.try_capabilities() {
+        Ok(Ok(caps)) if caps & OHLOSS_CAP_CORRECTIONS == 0 => true,
+        Ok(Ok(_)) => ohloss.try_correct_result(&session_id, &game.winner).is_ok(),
+        _ => false,
+    }
+}
 
-        // Report result to Ohloss
-        let ohloss_addr = storage::get_ohloss(&env);
-        let ohloss = OhlossClient::new(&env, &ohloss_addr);
-        ohloss.end_game(&session_id, &player1_won);
+/// The player's registered display name, if a registry is configured and
+/// answers.
+fn display_name(env: &Env, player: &Address) -> Option<String> {
+    let registry = storage::get_name_registry(env)?;
+    match DisplayNamesClient::new(env, &registry).try_name_of(player) {
+        Ok(Ok(name)) => name,
+        _ => None,
+    }
+}
 
-        events::emit_game_settled(&env, session_id, &winner_addr);
+/// Verify a settlement and conclude the game on the proven outcome.
+fn settle(
+    env: &Env,
+    session_id: u32,
+    proof: &Groth16Proof,
+    pub_inputs: &PublicInputs,
+    settler: &Address,
+    vk: Option<&VerificationKey>,
+) -> Result<Option<Address>, Error> {
+    require_unpaused(env, Operation::Settle)?;
+    let (game, outcome, valid) = check_settlement(env, session_id, proof, pub_inputs, vk)?;
+    if !valid {
+        return Err(Error::InvalidProof);
+    }
+    Ok(finish_settlement(env, session_id, game, outcome, proof, settler))
+}
 
-        Ok(winner_addr)
+/// Conclude a game whose settlement proof has verified.
+fn finish_settlement(
+    env: &Env,
+    session_id: u32,
+    game: Game,
+    outcome: Winner,
+    proof: &Groth16Proof,
+    settler: &Address,
+) -> Option<Address> {
+    if storage::get_proof_failures(env, game.variant) > 0 {
+        storage::set_proof_failures(env, game.variant, 0);
+        escrow::forfeit_report_bonds(env, game.variant);
     }
 
-    /// Query game state.
-    pub fn get_game(env: Env, session_id: u32) -> Option<Game> {
-        storage::get_game(&env, session_id)
+    // No-draws games resolve a proven draw by tiebreak
+    let tiebroken = outcome == Winner::Draw && game.no_draws;
+    let winner = if tiebroken {
+        tiebreak_winner(env, &game.seed1, &game.seed2)
+    } else {
+        outcome
+    };
+    let mut game = game;
+    game.tiebroken = tiebroken;
+    log_action(env, session_id, settler, "settle");
+    conclude(env, session_id, game, winner, Some(settler), proof_hash(env, proof))
+}
+
+/// SHA-256 of the proof's points, as recorded in settlement receipts.
+fn proof_hash(env: &Env, proof: &Groth16Proof) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &proof.pi_a.to_array());
+    preimage.extend_from_array(&proof.pi_b.to_array());
+    preimage.extend_from_array(&proof.pi_c.to_array());
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+/// Count a verified-bad proof against its variant, tripping the breaker
+/// at the threshold. Returns the new failure count.
+fn record_proof_failure(env: &Env, variant: u32) -> u32 {
+    let failures = storage::get_proof_failures(env, variant) + 1;
+    storage::set_proof_failures(env, variant, failures);
+    let threshold = storage::get_breaker_threshold(env);
+    if threshold > 0 && failures >= threshold {
+        storage::set_breaker_tripped(env, variant, true);
+        events::emit_breaker_tripped(env, variant, failures);
+    }
+    failures
+}
+
+/// Check a settlement against on-chain state and verify its proof. Returns
+/// the game, the proven outcome and whether the proof verified. `vk` is
+/// only consulted for hash-only keys.
+fn check_settlement(
+    env: &Env,
+    session_id: u32,
+    proof: &Groth16Proof,
+    pub_inputs: &PublicInputs,
+    vk: Option<&VerificationKey>,
+) -> Result<(Game, Winner, bool), Error> {
+    let (game, outcome, _, vk) = prepare_settlement(env, session_id, pub_inputs, vk)?;
+    let valid = verifier::verify_groth16(env, proof, &vk, &pub_inputs.to_array())?;
+    Ok((game, outcome, valid))
+}
+
+/// Every settlement check short of the pairing: returns the game, the
+/// claimed outcome and the key (with its id) the proof must verify under.
+fn prepare_settlement(
+    env: &Env,
+    session_id: u32,
+    pub_inputs: &PublicInputs,
+    vk: Option<&VerificationKey>,
+) -> Result<(Game, Winner, u32, VerificationKey), Error> {
+    let (game, outcome, vk_id) = check_public_inputs(env, session_id, pub_inputs)?;
+
+    // A VK stored by an older build may not match this circuit's input
+    // count.
+    let vk = resolve_vk(env, vk_id, vk)?;
+    if vk.ic.len() != PUBLIC_INPUT_COUNT + 1 {
+        return Err(Error::VkIcLengthMismatch);
+    }
+    Ok((game, outcome, vk_id, vk))
+}
+
+/// Check a settlement's public inputs against on-chain state. Returns the
+/// game, the claimed outcome and the key id its variant had at creation.
+fn check_public_inputs(
+    env: &Env,
+    session_id: u32,
+    pub_inputs: &PublicInputs,
+) -> Result<(Game, Winner, u32), Error> {
+    let game = storage::get_game(env, session_id)
+        .ok_or(Error::GameNotFound)?;
+
+    match game.phase {
+        Phase::Created | Phase::Joined => return Err(Error::SeedsNotRevealed),
+        Phase::Revealed => {}
+        Phase::Settled => return Err(Error::GameAlreadySettled),
+        Phase::Cancelled => return Err(Error::InvalidState),
+    }
+    let variant = storage::get_variant(env, game.variant)
+        .ok_or(Error::UnknownVariant)?;
+    if storage::is_breaker_tripped(env, game.variant) {
+        return Err(Error::BreakerTripped);
+    }
+
+    // Verify public inputs match on-chain state. Salted commitments were
+    // already opened at reveal; the circuit's seed_commit inputs are then
+    // Poseidon(seed) and fully determined by the seeds checked here.
+    // DualHash games bind the circuit to the inner Poseidon commitments
+    // whose keccak layer was checked at reveal.
+    if pub_inputs.seed1 != game.seed1 || pub_inputs.seed2 != game.seed2 {
+        return Err(Error::PublicInputMismatch);
+    }
+    let circuit_commits = match game.commit_scheme {
+        CommitScheme::Poseidon => Some((&game.seed_commit1, &game.seed_commit2)),
+        CommitScheme::DualHash => Some((&game.inner_commit1, &game.inner_commit2)),
+        CommitScheme::Salted => None,
+    };
+    if let Some((c1, c2)) = circuit_commits {
+        if pub_inputs.seed_commit1 != *c1 || pub_inputs.seed_commit2 != *c2 {
+            return Err(Error::PublicInputMismatch);
+        }
+    }
+
+    // Beacon games mix in the value for the round after the final reveal,
+    // which neither player could know when revealing.
+    let beacon_value = match &game.beacon {
+        Some(beacon) => {
+            let value = BeaconClient::new(env, beacon)
+                .randomness(&game.beacon_round)
+                .ok_or(Error::BeaconNotReady)?;
+            beacon_field(env, &value)
+        }
+        None => zero32(env),
+    };
+    if pub_inputs.beacon != beacon_value {
+        return Err(Error::PublicInputMismatch);
+    }
+
+    if pub_inputs.session_id != u32_field(env, session_id) {
+        return Err(Error::PublicInputMismatch);
+    }
+
+    // The proof must have been produced under this game's ruleset
+    if pub_inputs.deck_size != u32_field(env, variant.deck_size)
+        || pub_inputs.suits != u32_field(env, variant.suits)
+    {
+        return Err(Error::PublicInputMismatch);
+    }
+
+    // The proof must name player1, player2 or a draw
+    let outcome = match Winner::from_field(&pub_inputs.winner) {
+        Some(w @ (Winner::Player1 | Winner::Player2 | Winner::Draw)) => w,
+        _ => return Err(Error::InvalidWinner),
+    };
+    let vk_id = game.vk_id;
+    Ok((game, outcome, vk_id))
+}
+
+/// Record a final outcome (Player1, Player2 or Draw) and move the money:
+/// winner-takes-pot or an even split of stakes, the bounty to `settler` (or
+/// back to its funder when there is none), and the Ohloss report.
+/// `proof_hash` goes into the settlement receipt; zero when no proof.
+fn conclude(
+    env: &Env,
+    session_id: u32,
+    mut game: Game,
+    winner: Winner,
+    settler: Option<&Address>,
+    proof_hash: BytesN<32>,
+) -> Option<Address> {
+    let now = env.ledger().sequence();
+    let winner_addr = match winner {
+        Winner::Player1 => Some(game.player1.clone()),
+        Winner::Player2 => Some(game.player2.clone()),
+        _ => None,
+    };
+
+    game.winner = winner;
+    game.winner_address = winner_addr.clone();
+    storage::move_phase(env, session_id, Some(game.phase), Phase::Settled);
+    game.phase = Phase::Settled;
+    game.result_reported = report_result(env, session_id, &game);
+    storage::extend_instance(env, false);
+    drop_from_indexes(env, session_id);
+    storage::remove_reveal_delegates(env, session_id);
+    record_stats(env, &game);
+    storage::set_receipt(
+        env,
+        session_id,
+        &SettlementReceipt {
+            player1: game.player1.clone(),
+            player2: game.player2.clone(),
+            winner,
+            winner_address: winner_addr.clone(),
+            seed_commit1: game.seed_commit1.clone(),
+            seed_commit2: game.seed_commit2.clone(),
+            seed1: game.seed1.clone(),
+            seed2: game.seed2.clone(),
+            proof_hash,
+            settled_at: now,
+            variant: game.variant,
+            terms: game.terms.clone(),
+        },
+    );
+    let attestation = Attestation::compute(
+        env,
+        &env.current_contract_address(),
+        session_id,
+        &game.player1,
+        &game.player2,
+        winner,
+        now,
+    );
+    storage::set_attestation(env, session_id, &attestation.to_bytes());
+    storage::set_tombstone(
+        env,
+        session_id,
+        &Tombstone {
+            winner,
+            winner_address: winner_addr.clone(),
+            settled_at: now,
+        },
+    );
+
+    // Staked results are held for the dispute window, if one is set
+    let window = storage::get_dispute_window(env);
+    let payout = PendingPayout {
+        release_at: now + window,
+        player1: game.player1.clone(),
+        player2: game.player2.clone(),
+        winner,
+        rake_bps: game.rake_bps,
+        referrer: game.referrer.clone(),
+    };
+    if window > 0 && storage::has_stake(env, session_id) {
+        storage::set_pending_payout(env, session_id, &payout);
+        storage::hold_escrow(env, session_id);
+    } else {
+        pay_out(env, session_id, &mut game.event_seq, &payout);
+    }
+    match settler {
+        Some(settler) => escrow::pay_bounty(env, session_id, &mut game.event_seq, settler),
+        None => {
+            escrow::refund_bounty(env, session_id, &mut game.event_seq);
+        }
+    }
+    let seq = game.next_event_seq();
+    storage::set_game(env, session_id, &mut game);
+    events::emit_game_settled(env, session_id, seq, &game);
+    events::emit_pvp_result(env, session_id, &game.player1, &game.player2, winner, &winner_addr);
+    if let Some(observer) = &game.observer {
+        let _ = ObserverClient::new(env, observer).try_on_settled(&session_id, &winner_addr);
+    }
+
+    winner_addr
+}
+
+/// Tell a game's observer, if any, that both seeds are revealed.
+fn notify_revealed(env: &Env, session_id: u32, game: &Game) {
+    if let (Phase::Revealed, Some(observer)) = (game.phase, &game.observer) {
+        let _ = ObserverClient::new(env, observer).try_on_revealed(&session_id);
+    }
+}
+
+/// Ledgers left on `game`'s clocks as of now; see `get_time_remaining`.
+fn time_remaining(env: &Env, game: &Game) -> TimeInfo {
+    let now = env.ledger().sequence();
+    let left = |deadline: u32, open: bool| if open { (deadline + 1).saturating_sub(now) } else { 0 };
+    TimeInfo {
+        ledger: now,
+        phase: game.phase,
+        join_ledgers: left(game.join_deadline, game.phase == Phase::Created),
+        reveal_ledgers: left(game.reveal_deadline, game.phase == Phase::Joined),
+        ttl_ledgers: game.expires_at.saturating_sub(now),
+    }
+}
+
+/// Publish `GameExpiringSoon` if a live game's nearest clock is inside the
+/// warning window. The event is numbered on `game`, which the caller
+/// stores afterwards.
+fn warn_if_expiring(env: &Env, session_id: u32, game: &mut Game) {
+    let window = storage::get_expiry_warning(env);
+    if window == 0 || matches!(game.phase, Phase::Settled | Phase::Cancelled) {
+        return;
+    }
+    let time = time_remaining(env, game);
+    let nearest = [time.join_ledgers, time.reveal_ledgers, time.ttl_ledgers]
+        .into_iter()
+        .filter(|&left| left > 0)
+        .min();
+    if let Some(left) = nearest.filter(|&left| left <= window) {
+        let seq = game.next_event_seq();
+        events::emit_game_expiring_soon(env, session_id, seq, game.phase, left);
+    }
+}
+
+/// Pay a settled session's stakes and sponsorships for its outcome.
+fn pay_out(env: &Env, session_id: u32, seq: &mut u32, payout: &PendingPayout) {
+    match payout.winner {
+        Winner::Player1 | Winner::Player2 => {
+            let winner = if payout.winner == Winner::Player1 {
+                &payout.player1
+            } else {
+                &payout.player2
+            };
+            escrow::pay_winner(env, session_id, seq, winner, payout.rake_bps, &payout.referrer);
+            escrow::pay_sponsorships(env, session_id, seq, winner);
+        }
+        _ => {
+            escrow::split_draw(env, session_id, seq, payout.rake_bps, &payout.referrer);
+            escrow::split_sponsorships(env, session_id, seq, &payout.player1, &payout.player2);
+        }
+    }
+}
+
+/// Close a dispute with a final outcome. The challenger's bond is returned
+/// if the ruling overturns the result and slashed to the opponent if it
+/// stands; either way the held payout is released on the ruling.
+fn resolve_dispute(env: &Env, session_id: u32, ruling: Winner) -> Result<(), Error> {
+    if !matches!(ruling, Winner::Player1 | Winner::Player2 | Winner::Draw) {
+        return Err(Error::InvalidWinner);
+    }
+    let dispute = storage::get_dispute(env, session_id).ok_or(Error::NoDispute)?;
+    let mut payout = storage::get_pending_payout(env, session_id).ok_or(Error::InvalidState)?;
+
+    let upheld = ruling == payout.winner;
+    let bond_to = if upheld {
+        if dispute.challenger == payout.player1 {
+            payout.player2.clone()
+        } else {
+            payout.player1.clone()
+        }
+    } else {
+        dispute.challenger.clone()
+    };
+    if !upheld {
+        payout.winner = ruling;
+        record_ruling(env, session_id, &payout);
+    }
+
+    storage::remove_dispute(env, session_id);
+    storage::remove_pending_payout(env, session_id);
+    escrow::release_bond(env, &bond_to, &dispute.token, dispute.bond);
+    storage::with_event_seq(env, session_id, |seq| {
+        pay_out(env, session_id, seq, &payout);
+        events::emit_dispute_resolved(env, session_id, bump_seq(seq), ruling, upheld, &bond_to);
+    });
+    Ok(())
+}
+
+/// Rewrite a session's recorded outcome after a dispute overturned it.
+fn record_ruling(env: &Env, session_id: u32, payout: &PendingPayout) {
+    let winner_addr = match payout.winner {
+        Winner::Player1 => Some(payout.player1.clone()),
+        Winner::Player2 => Some(payout.player2.clone()),
+        _ => None,
+    };
+    events::emit_pvp_result(env, session_id, &payout.player1, &payout.player2, payout.winner, &winner_addr);
+    if let Some(mut game) = storage::get_game(env, session_id) {
+        game.winner = payout.winner;
+        game.winner_address = winner_addr.clone();
+        // A result Ohloss never received goes out corrected on the retry
+        if game.result_reported && !report_correction(env, session_id, &game) {
+            game.result_reported = false;
+            storage::set_correction_pending(env, session_id, true);
+        }
+        storage::set_game(env, session_id, &mut game);
+    }
+    if let Some(mut tombstone) = storage::get_tombstone(env, session_id) {
+        tombstone.winner = payout.winner;
+        tombstone.winner_address = winner_addr.clone();
+        storage::set_tombstone(env, session_id, &tombstone);
+    }
+    if let Some(mut receipt) = storage::get_receipt(env, session_id) {
+        receipt.winner = payout.winner;
+        receipt.winner_address = winner_addr;
+        let attestation = Attestation::compute(
+            env,
+            &env.current_contract_address(),
+            session_id,
+            &payout.player1,
+            &payout.player2,
+            payout.winner,
+            receipt.settled_at,
+        );
+        storage::set_attestation(env, session_id, &attestation.to_bytes());
+        storage::set_receipt(env, session_id, &receipt);
+    }
+}
+
+/// Count a concluded game in the deployment-wide and per-variant stats.
+fn record_stats(env: &Env, game: &Game) {
+    for variant in [None, Some(game.variant)] {
+        let mut stats = storage::get_stats(env, variant);
+        stats.settled += 1;
+        stats.draws += (game.winner == Winner::Draw) as u32;
+        stats.forfeits += game.forfeit as u32;
+        stats.tiebroken += game.tiebroken as u32;
+        storage::set_stats(env, variant, &stats);
+    }
+}
+
+/// The stake a joiner must co-sign: player2's at handicap odds, zero when
+/// both players stake the same.
+fn odds_stake(game: &Game) -> i128 {
+    if game.opponent_stake == game.wager_amount {
+        0
+    } else {
+        game.opponent_stake
+    }
+}
+
+/// Whether `player2` may take the open seat of `game` with `seed_commit2`.
+fn check_joinable(
+    env: &Env,
+    session_id: u32,
+    game: &Game,
+    player2: &Address,
+    seed_commit2: &BytesN<32>,
+    terms: &BytesN<32>,
+    stake: i128,
+) -> Result<(), Error> {
+    if game.phase != Phase::Created {
+        return Err(Error::InvalidState);
+    }
+    if *player2 == game.player1 {
+        return Err(Error::SelfPlay);
+    }
+    // Player 1's reveal delegate could hold back their reveal and win on
+    // the clock
+    if storage::get_reveal_delegate(env, session_id, &game.player1).as_ref() == Some(player2) {
+        return Err(Error::InvalidConfig);
+    }
+    if env.ledger().sequence() > game.join_deadline {
+        return Err(Error::JoinWindowClosed);
+    }
+    // Mirroring the creator's commitment would make the combined seed
+    // predictable to the joiner
+    if *seed_commit2 == game.seed_commit1 {
+        return Err(Error::DuplicateCommitment);
+    }
+    if *terms != game.terms {
+        return Err(Error::TermsMismatch);
+    }
+    if stake != odds_stake(game) {
+        return Err(Error::OddsMismatch);
+    }
+    // A rematch seat is the previous opponent's
+    if let Some(previous) = game.rematch_of {
+        let receipt = storage::get_receipt(env, previous).ok_or(Error::InvalidState)?;
+        if *player2 != receipt.player1 && *player2 != receipt.player2 {
+            return Err(Error::NotPlayer);
+        }
+    }
+    Ok(())
+}
+
+/// Ids in the reserved space can only be used inside an assigned range,
+/// with its orchestrator's authorization.
+fn check_session_id(env: &Env, session_id: u32) -> Result<(), Error> {
+    if session_id < RESERVED_SESSION_START {
+        return Ok(());
+    }
+    let range = storage::get_session_ranges(env)
+        .iter()
+        .find(|range| range.start <= session_id && session_id <= range.end)
+        .ok_or(Error::ReservedSessionId)?;
+    range.orchestrator.require_auth_for_args((session_id,).into_val(env));
+    Ok(())
+}
+
+/// True while the pair cooldown bars `player2` from another game against
+/// the creator of `game`.
+fn pair_cooling_down(env: &Env, game: &Game, player2: &Address) -> bool {
+    let cooldown = storage::get_pair_cooldown(env);
+    match storage::get_last_pair_game(env, &game.player1, player2) {
+        Some(last) if cooldown > 0 => env.ledger().sequence() < last + cooldown,
+        _ => false,
+    }
+}
+
+/// Remove `joiner`'s pending request and refund their stake.
+fn drop_join_request(env: &Env, session_id: u32, mut game: Game, joiner: &Address) -> Result<(), Error> {
+    if game.phase != Phase::Created {
+        return Err(Error::InvalidState);
+    }
+    let mut requests = storage::get_join_requests(env, session_id);
+    let index = requests
+        .iter()
+        .position(|r| r.joiner == *joiner)
+        .ok_or(Error::NoJoinRequest)?;
+    requests.remove(index as u32);
+    storage::set_join_requests(env, session_id, &requests);
+    storage::remove_reveal_delegate(env, session_id, joiner);
+    escrow::refund_stake(env, session_id, &mut game.event_seq, joiner);
+    events::emit_join_declined(env, session_id, game.next_event_seq(), joiner);
+    warn_if_expiring(env, session_id, &mut game);
+    storage::set_game(env, session_id, &mut game);
+    Ok(())
+}
+
+/// `join_game` for `player2`, or `join_game_with` when `options` are
+/// given. Joiners awaiting approval keep their delegate and metadata until
+/// they are seated or turned away.
+fn join(
+    env: &Env,
+    session_id: u32,
+    player2: &Address,
+    seed_commit2: BytesN<32>,
+    options: Option<JoinOptions>,
+) -> Result<(), Error> {
+    require_unpaused(env, Operation::Join)?;
+    if seed_commit2 == zero32(env) {
+        return Err(Error::ZeroCommitment);
+    }
+    let mut game = storage::get_game(env, session_id)
+        .ok_or(Error::GameNotFound)?;
+    let terms = options.as_ref().map_or(zero32(env), |o| o.terms.clone());
+    let stake = options.as_ref().map_or(0, |o| o.stake);
+    check_joinable(env, session_id, &game, player2, &seed_commit2, &terms, stake)?;
+    let delegate = options.as_ref().and_then(|o| o.reveal_delegate.clone());
+    if delegate.as_ref().is_some_and(|d| d == player2 || *d == game.player1) {
+        return Err(Error::InvalidConfig);
+    }
+
+    let args: Vec<Val> = match &options {
+        Some(options) => (session_id, seed_commit2.clone(), options.clone()).into_val(env),
+        None => (session_id, seed_commit2.clone()).into_val(env),
+    };
+    player2.require_auth_for_args(args);
+    if let Some(delegate) = &delegate {
+        storage::set_reveal_delegate(env, session_id, player2, delegate);
+    }
+    let metadata = options.map_or(zero32(env), |o| o.metadata);
+
+    if game.require_approval {
+        let mut requests = storage::get_join_requests(env, session_id);
+        if requests.iter().any(|r| r.joiner == *player2) {
+            return Err(Error::InvalidState);
+        }
+        if requests.len() >= MAX_JOIN_REQUESTS {
+            return Err(Error::JoinRequestLimit);
+        }
+        requests.push_back(JoinRequest { joiner: player2.clone(), seed_commit: seed_commit2, metadata });
+        storage::set_join_requests(env, session_id, &requests);
+        if let (true, Some(token)) = (game.escrow_wager, &game.wager_token) {
+            escrow::deposit_stake(env, session_id, &mut game.event_seq, player2, token, game.opponent_stake);
+        }
+        log_action(env, session_id, player2, "request_join");
+        events::emit_join_requested(env, session_id, game.next_event_seq(), player2);
+        warn_if_expiring(env, session_id, &mut game);
+        storage::set_game(env, session_id, &mut game);
+        return Ok(());
+    }
+
+    game.seed_commit2 = seed_commit2;
+    game.metadata2 = metadata;
+    if let (true, Some(token)) = (game.escrow_wager, &game.wager_token) {
+        escrow::deposit_stake(env, session_id, &mut game.event_seq, player2, token, game.opponent_stake);
+    }
+    seat_player2(env, session_id, &mut game, player2)?;
+    Ok(())
+}
+
+/// `reveal_seed` for `player`, authorized by their reveal delegate when
+/// `delegated`.
+fn reveal(
+    env: &Env,
+    session_id: u32,
+    player: &Address,
+    seed: BytesN<32>,
+    opening: SeedOpening,
+    delegated: bool,
+) -> Result<(), Error> {
+    require_unpaused(env, Operation::Reveal)?;
+    let mut game = storage::get_game(env, session_id)
+        .ok_or(Error::GameNotFound)?;
+
+    // Must be joined but not yet fully revealed
+    if game.phase < Phase::Joined || game.phase >= Phase::Revealed {
+        return Err(Error::InvalidState);
+    }
+    if env.ledger().sequence() > game.reveal_deadline {
+        return Err(Error::RevealWindowClosed);
+    }
+    if env.ledger().sequence() < game.starts_at {
+        return Err(Error::NotStarted);
+    }
+
+    // Logged against whoever authorized the reveal
+    let actor = if delegated {
+        let delegate = storage::get_reveal_delegate(env, session_id, player).ok_or(Error::NoDelegate)?;
+        delegate.require_auth_for_args((session_id, player.clone(), seed.clone()).into_val(env));
+        delegate
+    } else {
+        player.require_auth_for_args((session_id, seed.clone()).into_val(env));
+        player.clone()
+    };
+
+    let z = zero32(env);
+    if seed == z {
+        return Err(Error::ZeroSeed);
+    }
+
+    if *player == game.player1 {
+        if game.seed1 != z {
+            return Err(Error::AlreadyRevealed);
+        }
+        if seed == game.seed2 {
+            return Err(Error::DuplicateCommitment);
+        }
+        game.inner_commit1 =
+            check_opening(env, game.commit_scheme, session_id, player, &game.seed_commit1, &seed, &opening)?;
+        game.seed1 = seed;
+    } else if *player == game.player2 {
+        if game.seed2 != z {
+            return Err(Error::AlreadyRevealed);
+        }
+        if seed == game.seed1 {
+            return Err(Error::DuplicateCommitment);
+        }
+        game.inner_commit2 =
+            check_opening(env, game.commit_scheme, session_id, player, &game.seed_commit2, &seed, &opening)?;
+        game.seed2 = seed;
+    } else {
+        return Err(Error::NotPlayer);
+    }
+
+    // If both seeds revealed, advance phase
+    if game.seed1 != z && game.seed2 != z {
+        game.phase = Phase::Revealed;
+        storage::move_phase(env, session_id, Some(Phase::Joined), Phase::Revealed);
+        if game.beacon.is_some() {
+            game.beacon_round = env.ledger().sequence() + 1;
+        }
+        storage::add_pending_settlement(env, session_id);
+    }
+
+    log_action(env, session_id, &actor, if delegated { "reveal_by_delegate" } else { "reveal" });
+    events::emit_seed_revealed(env, session_id, game.next_event_seq(), player);
+    warn_if_expiring(env, session_id, &mut game);
+    storage::set_game(env, session_id, &mut game);
+    notify_revealed(env, session_id, &game);
+
+    Ok(())
+}
+
+/// Give the open seat to `player2`, whose commitment is already in
+/// `seed_commit2`, and register the game with Ohloss.
+fn seat_player2(env: &Env, session_id: u32, game: &mut Game, player2: &Address) -> Result<(), Error> {
+    let timing = storage::get_timing_config(env);
+    game.player2 = player2.clone();
+    storage::move_phase(env, session_id, Some(Phase::Created), Phase::Joined);
+    game.phase = Phase::Joined;
+    // A scheduled game's reveal window opens at its start
+    game.reveal_deadline = env.ledger().sequence().max(game.starts_at) + timing.reveal_window;
+
+    // Both players now known — register rated games with Ohloss
+    if !game.unrated {
+        if pair_cooling_down(env, game, player2) {
+            return Err(Error::PairCooldown);
+        }
+        let cooldown = storage::get_pair_cooldown(env);
+        if cooldown > 0 {
+            storage::set_last_pair_game(env, &game.player1, player2, cooldown);
+        }
+
+        let (ohloss_addr, version) = result_sink(env, game)?;
+        let ohloss = OhlossClient::new(env, &ohloss_addr);
+        // A typed error rather than a trap, so the whole join (stake and
+        // commitment included) reverts and the client can simply retry
+        let registered = ohloss.try_start_game(
+            &env.current_contract_address(),
+            &session_id,
+            &game.player1,
+            player2,
+        );
+        if !matches!(registered, Ok(Ok(()))) {
+            return Err(Error::RegisterFailed);
+        }
+        game.ohloss = Some(ohloss_addr);
+        game.ohloss_version = version;
+    }
+
+    storage::remove_open_game(env, session_id);
+    storage::add_player_game(env, player2, session_id);
+    storage::set_session_joiner(env, session_id, player2);
+    log_action(env, session_id, player2, "join");
+    events::emit_game_joined(env, session_id, game.next_event_seq(), game);
+    if game.terms != zero32(env) {
+        events::emit_terms_agreed(env, session_id, game.next_event_seq(), &game.terms);
+    }
+    warn_if_expiring(env, session_id, game);
+    storage::set_game(env, session_id, game);
+    if let Some(observer) = &game.observer {
+        let _ = ObserverClient::new(env, observer).try_on_joined(&session_id);
+    }
+    Ok(())
+}
+
+/// Take an evicted session out of the phase counts, reading the phase it
+/// lapsed in from what outlives the game: revealed games await settlement,
+/// joined ones have a joiner on record and open ones are only in their
+/// creator's list. Sessions that finished are in none of these.
+fn uncount_evicted(env: &Env, session_id: u32) {
+    let listed = |player: Address| storage::is_player_game(env, &player, session_id);
+    let phase = if storage::is_pending_settlement(env, session_id) {
+        Phase::Revealed
+    } else if storage::get_session_joiner(env, session_id).is_some() {
+        Phase::Joined
+    } else if storage::get_session_creator(env, session_id).is_some_and(listed) {
+        Phase::Created
+    } else {
+        return;
+    };
+    storage::uncount_phase(env, session_id, phase);
+}
+
+/// Remove a finished or evicted session from every index.
+fn drop_from_indexes(env: &Env, session_id: u32) {
+    storage::remove_pending_settlement(env, session_id);
+    storage::remove_open_game(env, session_id);
+    if let Some(creator) = storage::get_session_creator(env, session_id) {
+        storage::remove_player_game(env, &creator, session_id);
+    }
+    if let Some(joiner) = storage::get_session_joiner(env, session_id) {
+        storage::remove_player_game(env, &joiner, session_id);
+        storage::remove_session_joiner(env, session_id);
+    }
+}
+
+/// True once a session can no longer be settled: its entry has expired, or
+/// it missed its join or reveal deadline.
+fn is_stranded(env: &Env, session_id: u32) -> bool {
+    match storage::get_game(env, session_id) {
+        Some(game) => {
+            let now = env.ledger().sequence();
+            match game.phase {
+                Phase::Created => now > game.join_deadline,
+                Phase::Joined => now > game.reveal_deadline,
+                _ => false,
+            }
+        }
+        None => true,
+    }
+}
+
+/// Check a revealed seed against the player's commitment under the game's
+/// scheme. Poseidon commitments can only be checked in-circuit, so only the
+/// opening kind is validated for them here.
+///
+/// Returns the inner Poseidon commitment for DualHash openings (zero for
+/// every other scheme), to be matched against the proof's public inputs.
+fn check_opening(
+    env: &Env,
+    scheme: CommitScheme,
+    session_id: u32,
+    player: &Address,
+    commit: &BytesN<32>,
+    seed: &BytesN<32>,
+    opening: &SeedOpening,
+) -> Result<BytesN<32>, Error> {
+    let ok = match (scheme, opening) {
+        (CommitScheme::Poseidon, SeedOpening::Plain) => true,
+        (CommitScheme::Salted, SeedOpening::Salted(salt)) => {
+            let expected = SeedCommitment::compute(
+                env,
+                &env.current_contract_address(),
+                session_id,
+                player,
+                seed,
+                salt,
+            );
+            expected.to_bytes() == *commit
+        }
+        (CommitScheme::DualHash, SeedOpening::DualHash(inner)) => {
+            if env.crypto().keccak256(&inner.clone().into()).to_bytes() != *commit {
+                return Err(Error::CommitmentMismatch);
+            }
+            return Ok(inner.clone());
+        }
+        _ => false,
+    };
+    if ok {
+        Ok(zero32(env))
+    } else {
+        Err(Error::CommitmentMismatch)
+    }
+}
+
+#[contractimpl]
+impl PirateCardsContract {
+    /// Deploy: store admin and Ohloss contract address. Fails unless the
+    /// Ohloss is a deployed contract, distinct from the admin and this
+    /// contract, that speaks a supported protocol version. A persistent
+    /// flag keeps initialization from ever running twice, whatever later
+    /// upgrades add. Passing the classic variant's key makes the deployment
+    /// settleable from its first ledger; it is validated as `set_vk` would.
+    pub fn __constructor(
+        env: Env,
+        admin: Address,
+        ohloss: Address,
+        vk: Option<VerificationKey>,
+    ) -> Result<(), Error> {
+        if storage::is_initialized(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+        let this = env.current_contract_address();
+        if admin == ohloss || admin == this || ohloss == this {
+            return Err(Error::InvalidConfig);
+        }
+        if !matches!(ohloss.executable(), Some(Executable::Wasm(_))) {
+            return Err(Error::NotAContract);
+        }
+
+        storage::set_admin(&env, &admin);
+        let version = negotiate_ohloss(&env, &ohloss)?;
+        storage::set_ohloss(&env, &ohloss);
+        storage::set_ohloss_version(&env, version);
+        if let Some(vk) = vk {
+            apply_register_vk(&env, 0, &vk)?;
+        }
+        storage::set_initialized(&env);
+        storage::set_schema_version(&env, SCHEMA_VERSION);
+        storage::extend_instance(&env, true);
+        Ok(())
+    }
+
+    /// Admin: bring storage written by an older build up to this build's
+    /// layout. Call it right after `upgrade`, passing the version reported
+    /// by `get_schema_version` beforehand; each intermediate step runs in
+    /// order. Returns the new version. (Not `__migrate`: the host reserves
+    /// the `__` prefix for calls it makes itself.)
+    pub fn migrate(env: Env, from_version: u32) -> Result<u32, Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "migrate", &admin, (&from_version,).to_xdr(&env));
+        if from_version != storage::get_schema_version(&env) {
+            return Err(Error::SchemaMismatch);
+        }
+        if from_version >= SCHEMA_VERSION {
+            return Err(Error::InvalidState);
+        }
+
+        for version in from_version..SCHEMA_VERSION {
+            migrate_step(&env, version);
+        }
+        storage::set_schema_version(&env, SCHEMA_VERSION);
+        events::emit_schema_migrated(&env, from_version, SCHEMA_VERSION);
+        Ok(SCHEMA_VERSION)
+    }
+
+    /// Anyone can extend the instance entry, which holds the admin, Ohloss
+    /// and configuration, to the maximum TTL so a dormant deployment's
+    /// settings are not archived out from under its persistent data.
+    /// Game activity extends it too. Returns the new expiry ledger.
+    pub fn extend_instance(env: Env) -> u32 {
+        storage::extend_instance(&env, true)
+    }
+
+    /// Query the ledger at which the instance entry lapses unless extended.
+    pub fn get_instance_expiry(env: Env) -> u32 {
+        storage::get_instance_expiry(&env)
+    }
+
+    /// Query the storage layout version this deployment is at.
+    pub fn get_schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
+    /// Query the event schema this build publishes.
+    pub fn get_event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    /// Whether the constructor has run (false only for builds predating
+    /// the flag).
+    pub fn is_initialized(env: Env) -> bool {
+        storage::is_initialized(&env)
+    }
+
+    /// Admin: point the contract at another Ohloss deployment. Games
+    /// already joined report to the new one when they conclude.
+    pub fn set_ohloss(env: Env, ohloss: Address) -> Result<u32, Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_ohloss", &admin, (&ohloss,).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_ohloss(&env, &ohloss)
+    }
+
+    /// Admin: allow games to report to `sink`, an Ohloss deployment other
+    /// than the default (e.g. one per league). Returns its protocol version.
+    pub fn allow_result_sink(env: Env, sink: Address) -> Result<u32, Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "allow_result_sink", &admin, (&sink,).to_xdr(&env));
+        let version = negotiate_ohloss(&env, &sink)?;
+        storage::set_result_sink(&env, &sink, version);
+        events::emit_result_sink_allowed(&env, &sink, version);
+        Ok(version)
+    }
+
+    /// Admin: stop new games from naming `sink`. Games already using it
+    /// can no longer be joined, and their reports fail until it is allowed
+    /// again.
+    pub fn remove_result_sink(env: Env, sink: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "remove_result_sink", &admin, (&sink,).to_xdr(&env));
+        if storage::get_result_sink(&env, &sink).is_none() {
+            return Err(Error::UnknownResultSink);
+        }
+        storage::remove_result_sink(&env, &sink);
+        events::emit_result_sink_removed(&env, &sink);
+        Ok(())
+    }
+
+    /// Admin: accept pot sponsorships in `token` of at least `min_amount`.
+    /// Staked games still only take sponsorships in their wager token.
+    pub fn allow_sponsor_token(env: Env, token: Address, min_amount: i128) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "allow_sponsor_token", &admin, (&token, min_amount).to_xdr(&env));
+        if min_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        storage::set_sponsor_token(&env, &token, min_amount);
+        Ok(())
+    }
+
+    /// Admin: stop accepting new sponsorships in `token`. Sponsorships
+    /// already escrowed are still paid out or refunded.
+    pub fn remove_sponsor_token(env: Env, token: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "remove_sponsor_token", &admin, (&token,).to_xdr(&env));
+        if storage::get_sponsor_token(&env, &token).is_none() {
+            return Err(Error::TokenNotAllowed);
+        }
+        storage::remove_sponsor_token(&env, &token);
+        Ok(())
+    }
+
+    /// The minimum sponsorship accepted in `token`, if it is allowlisted.
+    pub fn get_sponsor_token(env: Env, token: Address) -> Option<i128> {
+        storage::get_sponsor_token(&env, &token)
+    }
+
+    /// Admin: give up the admin role for good. Every admin-only call fails
+    /// afterwards, including VK, Ohloss, rake and compensation changes, and
+    /// so does every queued operation but a treasury withdrawal, even under
+    /// a council. The treasury role is unaffected.
+    pub fn renounce_admin(env: Env) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "renounce_admin", &admin, ().to_xdr(&env));
+        storage::set_admin_renounced(&env);
+        events::emit_admin_renounced(&env, &admin);
+        Ok(())
+    }
+
+    /// Admin: permanently lock the verification keys and variant registry
+    /// while keeping the rest of the admin role.
+    pub fn freeze_vk(env: Env) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "freeze_vk", &admin, ().to_xdr(&env));
+        storage::set_vk_frozen(&env);
+        events::emit_vk_frozen(&env);
+        Ok(())
+    }
+
+    /// Admin: pause or resume one operation. Each switch is independent,
+    /// so new games can be stopped while existing ones finish.
+    pub fn set_paused(env: Env, op: Operation, paused: bool) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_paused", &admin, (&op, &paused).to_xdr(&env));
+        storage::set_paused(&env, op, paused);
+        events::emit_pause_updated(&env, op, paused);
+        Ok(())
+    }
+
+    /// Query whether an operation is paused.
+    pub fn is_paused(env: Env, op: Operation) -> bool {
+        storage::is_paused(&env, op)
+    }
+
+    /// Admin: set how many consecutive invalid proofs halt a variant's
+    /// settlements. Zero disables the breaker.
+    pub fn set_breaker_threshold(env: Env, threshold: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_breaker_threshold", &admin, (&threshold,).to_xdr(&env));
+        storage::set_breaker_threshold(&env, threshold);
+        Ok(())
+    }
+
+    /// Admin: set the bond an invalid-proof report must post.
+    pub fn set_report_bond(env: Env, terms: BondTerms) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_report_bond", &admin, (&terms,).to_xdr(&env));
+        if terms.amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        storage::set_report_bond_terms(&env, &terms);
+        Ok(())
+    }
+
+    /// Query the bond an invalid-proof report must post, if reports are
+    /// open.
+    pub fn get_report_bond(env: Env) -> Option<BondTerms> {
+        storage::get_report_bond_terms(&env)
+    }
+
+    /// Admin: resume settlements on a variant halted by the breaker, clear
+    /// its failure count and return the bonds of the reports behind it.
+    pub fn reset_breaker(env: Env, variant: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "reset_breaker", &admin, (&variant,).to_xdr(&env));
+        storage::set_proof_failures(&env, variant, 0);
+        storage::set_breaker_tripped(&env, variant, false);
+        escrow::refund_report_bonds(&env, variant);
+        events::emit_breaker_reset(&env, variant);
+        Ok(())
+    }
+
+    /// Query the sequence number of the latest audit-log entry.
+    pub fn get_audit_seq(env: Env) -> u32 {
+        storage::get_audit_seq(&env)
+    }
+
+    /// Query the breaker threshold.
+    pub fn get_breaker_threshold(env: Env) -> u32 {
+        storage::get_breaker_threshold(&env)
+    }
+
+    /// Query a variant's consecutive invalid proofs and whether its
+    /// breaker has tripped.
+    pub fn get_breaker(env: Env, variant: u32) -> (u32, bool) {
+        (
+            storage::get_proof_failures(&env, variant),
+            storage::is_breaker_tripped(&env, variant),
+        )
+    }
+
+    /// Query whether the admin role has been renounced.
+    pub fn is_admin_renounced(env: Env) -> bool {
+        storage::is_admin_renounced(&env)
+    }
+
+    /// Query whether verification keys and variants are frozen, either by
+    /// `freeze_vk` or by renouncing the admin.
+    pub fn is_vk_frozen(env: Env) -> bool {
+        vk_locked(&env)
+    }
+
+    /// Query the protocol version negotiated with Ohloss.
+    pub fn get_ohloss_version(env: Env) -> u32 {
+        storage::get_ohloss_version(&env)
+    }
+
+    /// Admin: set the Groth16 verification key of the classic circuit.
+    pub fn set_vk(env: Env, vk: VerificationKey) -> Result<(), Error> {
+        Self::register_vk(env, 0, vk)
+    }
+
+    /// Admin: store a Groth16 verification key under `vk_id` for variants
+    /// to reference. The IC vector must have one entry per public input plus one.
+    pub fn register_vk(env: Env, vk_id: u32, vk: VerificationKey) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "register_vk", &admin, (&vk_id, &vk).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_register_vk(&env, vk_id, &vk)
+    }
+
+    /// Admin: store only the hash of `vk_id`'s key; settlers then pass the
+    /// full key to `settle_game_with_vk`. Replaces any stored key.
+    pub fn register_vk_hash(env: Env, vk_id: u32, hash: BytesN<32>) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "register_vk_hash", &admin, (&vk_id, &hash).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_register_vk_hash(&env, vk_id, &hash)
+    }
+
+    /// Query the hash of a hash-only verification key.
+    pub fn get_vk_hash(env: Env, vk_id: u32) -> Option<BytesN<32>> {
+        storage::get_vk_hash(&env, vk_id)
+    }
+
+    /// Admin: record where `vk_id`'s current key came from. Replacing the
+    /// key drops it, so it always describes the key in force. Goes through
+    /// the council and timelock like the key itself.
+    pub fn set_vk_provenance(env: Env, vk_id: u32, provenance: VkProvenance) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_vk_provenance", &admin, (&vk_id, &provenance).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_vk_provenance(&env, vk_id, &provenance)
+    }
+
+    /// Query the key a variant settles under: its id and hash.
+    pub fn get_vk_info(env: Env, variant_id: u32) -> Option<VkInfo> {
+        let vk_id = storage::get_variant(&env, variant_id)?.vk_id;
+        let vk_hash = match storage::get_vk(&env, vk_id) {
+            Some(vk) => vk.hash(&env),
+            None => storage::get_vk_hash(&env, vk_id)?,
+        };
+        Some(VkInfo { vk_id, vk_hash })
+    }
+
+    /// Query where `vk_id`'s key came from. `None` until recorded, and
+    /// again once the key is replaced.
+    pub fn get_vk_provenance(env: Env, vk_id: u32) -> Option<VkProvenance> {
+        storage::get_vk_provenance(&env, vk_id)
+    }
+
+    /// Admin: replace the contract code. Goes through the council once one
+    /// is configured.
+    pub fn upgrade(env: Env, wasm_hash: BytesN<32>) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "upgrade", &admin, (&wasm_hash,).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_action(&env, &AdminAction::Upgrade(wasm_hash))
+    }
+
+    /// Admin: hand VK changes, upgrades and treasury withdrawals to an
+    /// M-of-N council. Only possible once; afterwards the council changes
+    /// itself through a `SetCouncil` proposal.
+    pub fn set_council(env: Env, council: Council) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_council", &admin, (&council,).to_xdr(&env));
+        require_no_council(&env)?;
+        check_council(&council)?;
+        storage::set_council(&env, &council);
+        events::emit_council_updated(&env, &council);
+        Ok(())
+    }
+
+    /// Admin: set the delay queued operations wait before executing. Only
+    /// direct while no delay is set; after that, change it through a
+    /// `SetTimelockDelay` proposal.
+    pub fn set_timelock_delay(env: Env, delay: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_timelock_delay", &admin, (&delay,).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_timelock_delay(&env, delay)
+    }
+
+    /// Queue a sensitive operation, proposed by a council member (or the
+    /// admin while there is no council). The proposer's approval is
+    /// counted and the operation becomes executable after the timelock
+    /// delay. Returns the proposal id.
+    pub fn propose(env: Env, proposer: Address, action: AdminAction) -> Result<u32, Error> {
+        require_approver(&env, &proposer)?;
+        require_action_allowed(&env, &action)?;
+        audit(&env, "propose", &proposer, (&action,).to_xdr(&env));
+
+        let proposal_id = storage::next_proposal_id(&env);
+        let proposal = AdminProposal {
+            action,
+            approvals: Vec::from_array(&env, [proposer.clone()]),
+            eta: env.ledger().sequence() + storage::get_timelock_delay(&env),
+        };
+        storage::set_proposal(&env, proposal_id, &proposal);
+        events::emit_proposal_created(&env, proposal_id, &proposer, proposal.eta);
+        Ok(proposal_id)
+    }
+
+    /// Council member: approve a pending proposal. Returns the approval count.
+    pub fn approve(env: Env, signer: Address, proposal_id: u32) -> Result<u32, Error> {
+        let mut proposal = storage::get_proposal(&env, proposal_id)
+            .ok_or(Error::ProposalNotFound)?;
+        if proposal.approvals.contains(&signer) {
+            return Err(Error::AlreadyApproved);
+        }
+        require_approver(&env, &signer)?;
+        audit(&env, "approve", &signer, (&proposal_id,).to_xdr(&env));
+
+        proposal.approvals.push_back(signer.clone());
+        storage::set_proposal(&env, proposal_id, &proposal);
+        events::emit_proposal_approved(&env, proposal_id, &signer);
+        Ok(proposal.approvals.len())
+    }
+
+    /// Anyone can execute a proposal once its timelock has passed and at
+    /// least `threshold` current approvers have approved it.
+    pub fn execute(env: Env, proposal_id: u32) -> Result<(), Error> {
+        let approvers = approvers(&env)?;
+        let proposal = storage::get_proposal(&env, proposal_id)
+            .ok_or(Error::ProposalNotFound)?;
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|signer| approvers.signers.contains(signer))
+            .count() as u32;
+        if approvals < approvers.threshold {
+            return Err(Error::NotEnoughApprovals);
+        }
+        if env.ledger().sequence() < proposal.eta {
+            return Err(Error::TimelockActive);
+        }
+
+        // Logged under the proposer; the approvals are in earlier entries
+        let proposer = proposal.approvals.get_unchecked(0);
+        audit(&env, "execute", &proposer, (&proposal_id, &proposal.action).to_xdr(&env));
+        storage::remove_proposal(&env, proposal_id);
+        apply_action(&env, &proposal.action)?;
+        events::emit_proposal_executed(&env, proposal_id);
+        Ok(())
+    }
+
+    /// Council member (or the admin while there is no council): drop a
+    /// queued operation before it executes.
+    pub fn cancel_proposal(env: Env, canceller: Address, proposal_id: u32) -> Result<(), Error> {
+        if storage::get_proposal(&env, proposal_id).is_none() {
+            return Err(Error::ProposalNotFound);
+        }
+        require_approver(&env, &canceller)?;
+        audit(&env, "cancel_proposal", &canceller, (&proposal_id,).to_xdr(&env));
+        storage::remove_proposal(&env, proposal_id);
+        events::emit_proposal_cancelled(&env, proposal_id, &canceller);
+        Ok(())
+    }
+
+    /// Query the delay queued operations wait before executing.
+    pub fn get_timelock_delay(env: Env) -> u32 {
+        storage::get_timelock_delay(&env)
+    }
+
+    /// Query the council, if one is configured.
+    pub fn get_council(env: Env) -> Option<Council> {
+        storage::get_council(&env)
+    }
+
+    /// Query a pending proposal.
+    pub fn get_proposal(env: Env, proposal_id: u32) -> Option<AdminProposal> {
+        storage::get_proposal(&env, proposal_id)
+    }
+
+    /// Admin: register or replace a ruleset variant. Games keep the variant
+    /// id they were created with and settle under its current definition.
+    pub fn register_variant(env: Env, variant_id: u32, variant: Variant) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "register_variant", &admin, (&variant_id, &variant).to_xdr(&env));
+        if vk_locked(&env) {
+            return Err(Error::VkFrozen);
+        }
+
+        if variant.deck_size == 0
+            || variant.suits == 0
+            || variant.rounds == 0
+            || variant.rounds > variant.deck_size / 2
+        {
+            return Err(Error::InvalidConfig);
+        }
+
+        storage::set_variant(&env, variant_id, &variant);
+        events::emit_variant_registered(&env, variant_id, &variant);
+        Ok(())
+    }
+
+    /// Admin: record how `variant_id`'s circuit lays out the deck. Locked
+    /// with the keys, since it describes the circuit behind one.
+    pub fn set_variant_deck_order(env: Env, variant_id: u32, order: DeckOrder) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_variant_deck_order", &admin, (&variant_id, &order).to_xdr(&env));
+        if vk_locked(&env) {
+            return Err(Error::VkFrozen);
+        }
+        if storage::get_variant(&env, variant_id).is_none() {
+            return Err(Error::UnknownVariant);
+        }
+        storage::set_variant_deck_order(&env, variant_id, order);
+        Ok(())
+    }
+
+    /// Query how `variant_id`'s circuit lays out the deck.
+    pub fn get_variant_deck_order(env: Env, variant_id: u32) -> DeckOrder {
+        storage::get_variant_deck_order(&env, variant_id)
+    }
+
+    /// Admin: replace the timing configuration used for new games and TTLs.
+    pub fn set_timing_config(env: Env, config: TimingConfig) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_timing_config", &admin, (&config,).to_xdr(&env));
+
+        if config.game_ttl == 0
+            || config.game_ttl > env.storage().max_ttl()
+            || config.join_window == 0
+            || config.join_window > config.game_ttl
+            || config.reveal_window == 0
+            || config.reveal_window > config.game_ttl
+        {
+            return Err(Error::InvalidConfig);
+        }
+
+        storage::set_timing_config(&env, &config);
+        events::emit_timing_config_updated(&env, &config);
+        Ok(())
+    }
+
+    /// Admin: set the rake taken from stake pots, capped at MAX_RAKE_BPS.
+    /// Each game keeps the rate in force when it was created.
+    pub fn set_rake_bps(env: Env, rake_bps: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_rake_bps", &admin, (&rake_bps,).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_rake_bps(&env, rake_bps)
+    }
+
+    /// Admin: hand the treasury role to another account.
+    pub fn set_treasury(env: Env, treasury: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_treasury", &admin, (&treasury,).to_xdr(&env));
+        storage::set_treasury(&env, &treasury);
+        events::emit_treasury_updated(&env, &treasury);
+        Ok(())
+    }
+
+    /// Admin: register an affiliate code, or rotate an existing code's
+    /// payout address or rate. Balances already earned stay with the code.
+    pub fn register_affiliate(
+        env: Env,
+        code: BytesN<32>,
+        payout: Address,
+        share_bps: u32,
+    ) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "register_affiliate", &admin, (&code, &payout, &share_bps).to_xdr(&env));
+
+        if share_bps > 10_000 {
+            return Err(Error::InvalidConfig);
+        }
+        let affiliate = Affiliate { payout, share_bps };
+        storage::set_affiliate(&env, &code, &affiliate);
+        events::emit_affiliate_registered(&env, &code, &affiliate);
+        Ok(())
+    }
+
+    /// Admin: retire an affiliate code. Open games referred by it stop
+    /// earning; the code's payout address can still claim what it earned.
+    pub fn remove_affiliate(env: Env, code: BytesN<32>) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "remove_affiliate", &admin, (&code,).to_xdr(&env));
+
+        let affiliate = storage::get_affiliate(&env, &code)
+            .ok_or(Error::UnknownAffiliate)?;
+        // Keep the payout address on record so earned balances stay claimable
+        storage::set_affiliate(&env, &code, &Affiliate { share_bps: 0, ..affiliate });
+        events::emit_affiliate_removed(&env, &code);
+        Ok(())
+    }
+
+    /// Affiliate: claim everything the code has earned in `token`. Paid to
+    /// the code's current payout address, which must authorize.
+    pub fn claim_affiliate(env: Env, code: BytesN<32>, token: Address) -> Result<i128, Error> {
+        let affiliate = storage::get_affiliate(&env, &code)
+            .ok_or(Error::UnknownAffiliate)?;
+        affiliate.payout.require_auth();
+        require_unpaused(&env, Operation::Withdraw)?;
+
+        if storage::get_affiliate_balance(&env, &code, &token) <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        Ok(escrow::claim_affiliate(&env, &code, &affiliate.payout, &token))
+    }
+
+    /// Treasury: withdraw collected rake in `token`. Insurance funds are
+    /// accounted separately and cannot be withdrawn here.
+    pub fn withdraw_treasury(
+        env: Env,
+        token: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<TreasuryAccount, Error> {
+        let treasury = storage::get_treasury(&env).ok_or(Error::NotInitialized)?;
+        treasury.require_auth();
+        require_no_council(&env)?;
+        require_unpaused(&env, Operation::Withdraw)?;
+        audit(&env, "withdraw_treasury", &treasury, (&token, &to, &amount).to_xdr(&env));
+
+        apply_withdraw_treasury(&env, &token, &to, amount)
+    }
+
+    /// Admin: set the randomness beacon that new games may opt into.
+    /// Games keep the beacon they were created with.
+    pub fn set_beacon(env: Env, beacon: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_beacon", &admin, (&beacon,).to_xdr(&env));
+        storage::set_beacon(&env, &beacon);
+        events::emit_beacon_updated(&env, &beacon);
+        Ok(())
+    }
+
+    /// Admin: queue an insurance payout for a session whose funds were
+    /// stranded or voided. Paid in the session's stake token by `compensate`
+    /// once COMPENSATION_DELAY_LEDGERS have passed; re-proposing resets the
+    /// delay.
+    pub fn propose_compensation(
+        env: Env,
+        session_id: u32,
+        to: Address,
+        amount: i128,
+    ) -> Result<PendingCompensation, Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "propose_compensation", &admin, (&session_id, &to, &amount).to_xdr(&env));
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        let token = storage::get_stake(&env, session_id)
+            .map(|stake| stake.token)
+            .or_else(|| storage::get_game(&env, session_id).and_then(|game| game.wager_token))
+            .ok_or(Error::InvalidState)?;
+
+        let compensation = PendingCompensation {
+            to,
+            token,
+            amount,
+            eta: env.ledger().sequence() + escrow::COMPENSATION_DELAY_LEDGERS,
+        };
+        storage::set_compensation(&env, session_id, &compensation);
+        storage::with_event_seq(&env, session_id, |seq| {
+            events::emit_compensation_proposed(&env, session_id, bump_seq(seq), &compensation)
+        });
+        Ok(compensation)
+    }
+
+    /// Admin: pay a queued compensation from the insurance fund. `to` and
+    /// `amount` must match the proposal.
+    pub fn compensate(env: Env, session_id: u32, to: Address, amount: i128) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "compensate", &admin, (&session_id, &to, &amount).to_xdr(&env));
+        require_unpaused(&env, Operation::Withdraw)?;
+
+        let compensation = storage::get_compensation(&env, session_id)
+            .ok_or(Error::InvalidState)?;
+        if compensation.to != to || compensation.amount != amount {
+            return Err(Error::InvalidState);
+        }
+        if env.ledger().sequence() < compensation.eta {
+            return Err(Error::TimelockActive);
+        }
+        if storage::get_insurance_balance(&env, &compensation.token) < amount {
+            return Err(Error::InsufficientInsurance);
+        }
+
+        storage::remove_compensation(&env, session_id);
+        storage::with_event_seq(&env, session_id, |seq| {
+            escrow::pay_compensation(&env, session_id, seq, &compensation)
+        });
+        Ok(())
+    }
+
+    /// P1 creates an open game. Anyone can join via join_game.
+    /// An optional bounty is escrowed from P1 and paid to whoever settles.
+    pub fn create_game(
+        env: Env,
+        session_id: u32,
+        player1: Address,
+        seed_commit1: BytesN<32>,
+        options: GameOptions,
+    ) -> Result<(), Error> {
+        require_unpaused(&env, Operation::Create)?;
+        if storage::has_game(&env, session_id) {
+            return Err(Error::GameAlreadyExists);
+        }
+        if storage::get_tombstone(&env, session_id).is_some() {
+            return Err(Error::GameAlreadySettled);
+        }
+        if storage::get_session_creator(&env, session_id).is_some() {
+            return Err(Error::SessionIdConsumed);
+        }
+        if seed_commit1 == zero32(&env) {
+            return Err(Error::ZeroCommitment);
+        }
+        check_session_id(&env, session_id)?;
+        let bounty_ok = match options.bounty_token {
+            Some(_) => options.bounty_amount > 0,
+            None => options.bounty_amount == 0,
+        };
+        let wager_ok = match options.wager_token {
+            Some(_) => options.wager_amount > 0 && options.opponent_stake >= 0,
+            None => options.wager_amount == 0 && options.opponent_stake == 0,
+        };
+        let sponsor_ok = match (&options.sponsor, &options.sponsor_token) {
+            (Some(_), Some(_)) => options.sponsor_amount > 0 && !options.escrow_wager,
+            (None, None) => options.sponsor_amount == 0,
+            _ => false,
+        };
+        if !bounty_ok || !wager_ok || !sponsor_ok
+            || (options.escrow_wager && options.wager_token.is_none())
+        {
+            return Err(Error::InvalidAmount);
+        }
+        let vk_id = storage::get_variant(&env, options.variant)
+            .ok_or(Error::UnknownVariant)?
+            .vk_id;
+        if let Some(sink) = &options.result_sink {
+            if storage::get_result_sink(&env, sink).is_none() {
+                return Err(Error::UnknownResultSink);
+            }
+        }
+        if let Some(code) = &options.referrer {
+            match storage::get_affiliate(&env, code) {
+                Some(affiliate) if affiliate.share_bps > 0 => {}
+                _ => return Err(Error::UnknownAffiliate),
+            }
+        }
+        if options.reveal_delegate.as_ref() == Some(&player1) {
+            return Err(Error::InvalidConfig);
+        }
+        // A start the game entry would not live to see can never be played
+        let now = env.ledger().sequence();
+        let horizon = now + storage::get_timing_config(&env).game_ttl;
+        if options.starts_at != 0 && (options.starts_at <= now || options.starts_at > horizon) {
+            return Err(Error::InvalidConfig);
+        }
+        let mut rake_bps = storage::get_rake_bps(&env);
+        if let Some(previous) = options.rematch_of {
+            let acknowledged = storage::get_game(&env, previous)
+                .is_some_and(|g| g.result_acknowledged && (g.player1 == player1 || g.player2 == player1));
+            // A rematch that can no longer be played does not use up the slot
+            let taken = storage::get_rematch(&env, previous).is_some_and(|rematch| !is_stranded(&env, rematch));
+            if !acknowledged || taken {
+                return Err(Error::RematchUnavailable);
+            }
+            rake_bps = rake_bps * (10_000 - escrow::REMATCH_RAKE_DISCOUNT_BPS) / 10_000;
+        }
+        let beacon = if options.use_beacon {
+            Some(storage::get_beacon(&env).ok_or(Error::NoBeacon)?)
+        } else {
+            None
+        };
+
+        // Player auth is scoped to the session and what the player commits to,
+        // so a relayer cannot replay it against another game or other terms
+        player1.require_auth_for_args((session_id, seed_commit1.clone(), options.clone()).into_val(&env));
+        check_rate_limit(&env, &player1)?;
+
+        let timing = storage::get_timing_config(&env);
+        let z = zero32(&env);
+        let mut game = Game {
+            player1: player1.clone(),
+            player2: player1.clone(), // sentinel — no player2 yet
+            seed_commit1,
+            seed_commit2: z.clone(),
+            seed1: z.clone(),
+            seed2: z.clone(),
+            inner_commit1: z.clone(),
+            inner_commit2: z.clone(),
+            commit_scheme: options.commit_scheme,
+            phase: Phase::Created,
+            winner: Winner::None,
+            winner_address: None,
+            expires_at: 0, // stamped by set_game
+            join_deadline: env.ledger().sequence() + timing.join_window,
+            reveal_deadline: 0,
+            beacon,
+            beacon_round: 0,
+            no_draws: options.no_draws,
+            tiebroken: false,
+            wager_token: options.wager_token,
+            wager_amount: options.wager_amount,
+            opponent_stake: match options.opponent_stake {
+                0 => options.wager_amount,
+                stake => stake,
+            },
+            memo: options.memo,
+            variant: options.variant,
+            vk_id,
+            escrow_wager: options.escrow_wager,
+            rake_bps,
+            freeroll: options.sponsor.is_some(),
+            referrer: options.referrer,
+            observer: options.observer,
+            forfeit: false,
+            result_reported: false,
+            result_sink: options.result_sink,
+            unrated: options.unrated,
+            require_approval: options.require_approval,
+            ohloss: None,
+            ohloss_version: 0,
+            event_seq: 0,
+            metadata1: options.metadata,
+            metadata2: z,
+            result_acknowledged: false,
+            rematch_of: options.rematch_of,
+            terms: options.terms.clone(),
+            starts_at: options.starts_at,
+        };
+        storage::set_session_creator(&env, session_id, &player1);
+        storage::extend_instance(&env, false);
+        storage::move_phase(&env, session_id, None, Phase::Created);
+        storage::add_open_game(&env, session_id, &lobby_entry(&game));
+        storage::add_player_game(&env, &player1, session_id);
+        if let Some(delegate) = &options.reveal_delegate {
+            storage::set_reveal_delegate(&env, session_id, &player1, delegate);
+        }
+        if let Some(previous) = options.rematch_of {
+            storage::set_rematch(&env, previous, session_id);
+        }
+        if let Some(token) = &options.bounty_token {
+            escrow::deposit_bounty(&env, session_id, &player1, token, options.bounty_amount);
+        }
+        if let (true, Some(token)) = (game.escrow_wager, &game.wager_token) {
+            escrow::deposit_stake(&env, session_id, &mut game.event_seq, &player1, token, game.wager_amount);
+        }
+        if let (Some(sponsor), Some(token)) = (&options.sponsor, &options.sponsor_token) {
+            escrow::pull_sponsorship(&env, session_id, &mut game.event_seq, sponsor, token, options.sponsor_amount);
+        }
+        log_action(&env, session_id, &player1, "create");
+        events::emit_game_created(&env, session_id, game.next_event_seq(), &game);
+        storage::set_game(&env, session_id, &mut game);
+
+        Ok(())
+    }
+
+    /// P1 withdraws a game nobody has joined yet. Refunds any bounty.
+    pub fn cancel_game(env: Env, session_id: u32) -> Result<(), Error> {
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Created {
+            return Err(Error::InvalidState);
+        }
+
+        game.player1.require_auth_for_args((session_id,).into_val(&env));
+
+        game.phase = Phase::Cancelled;
+        storage::move_phase(&env, session_id, Some(Phase::Created), Phase::Cancelled);
+        drop_from_indexes(&env, session_id);
+        storage::take_join_requests(&env, session_id);
+        storage::remove_reveal_delegates(&env, session_id);
+        if let Some(previous) = game.rematch_of {
+            storage::remove_rematch(&env, previous);
+        }
+        escrow::refund_bounty(&env, session_id, &mut game.event_seq);
+        escrow::refund_stakes(&env, session_id, &mut game.event_seq);
+        escrow::refund_sponsorships(&env, session_id, &mut game.event_seq);
+        log_action(&env, session_id, &game.player1, "cancel");
+        events::emit_game_cancelled(&env, session_id, game.next_event_seq(), &game);
+        storage::set_game(&env, session_id, &mut game);
+
+        Ok(())
+    }
+
+    /// Anyone can return an escrowed bounty to its funder once the game can
+    /// no longer be settled: its entry has expired, or it missed its join or
+    /// reveal deadline.
+    pub fn refund_bounty(env: Env, session_id: u32) -> Result<EscrowedBounty, Error> {
+        if storage::get_bounty(&env, session_id).is_none() {
+            return Err(Error::NoBounty);
+        }
+
+        if !is_stranded(&env, session_id) {
+            return Err(Error::InvalidState);
+        }
+
+        storage::with_event_seq(&env, session_id, |seq| escrow::refund_bounty(&env, session_id, seq))
+            .ok_or(Error::NoBounty)
+    }
+
+    /// Anyone can add to a game's prize before both seeds are revealed, in
+    /// an allowlisted token and at least its minimum; a staked game only
+    /// takes its wager token. The winner takes every sponsorship; a draw
+    /// splits them and a cancelled or stranded game refunds them.
+    pub fn sponsor_pot(
+        env: Env,
+        session_id: u32,
+        sponsor: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Created && game.phase != Phase::Joined {
+            return Err(Error::InvalidState);
+        }
+        if game.wager_token.as_ref().is_some_and(|wager| *wager != token) {
+            return Err(Error::TokenNotAllowed);
+        }
+        let min_amount = storage::get_sponsor_token(&env, &token)
+            .ok_or(Error::TokenNotAllowed)?;
+        if amount < min_amount {
+            return Err(Error::InvalidAmount);
+        }
+        if storage::get_sponsorships(&env, session_id).len() >= escrow::MAX_SPONSORSHIPS {
+            return Err(Error::SponsorLimit);
+        }
+
+        sponsor.require_auth();
+        escrow::sponsor_pot(&env, session_id, &mut game.event_seq, &sponsor, &token, amount);
+        warn_if_expiring(&env, session_id, &mut game);
+        storage::set_game(&env, session_id, &mut game);
+        Ok(())
+    }
+
+    /// Anyone can return sponsorships once the game can no longer be
+    /// settled. Returns how many were refunded.
+    pub fn refund_sponsors(env: Env, session_id: u32) -> Result<u32, Error> {
+        if !is_stranded(&env, session_id) {
+            return Err(Error::InvalidState);
+        }
+        Ok(storage::with_event_seq(&env, session_id, |seq| {
+            escrow::refund_sponsorships(&env, session_id, seq)
+        }))
+    }
+
+    /// Any player can join an open game by providing the session ID.
+    /// Sets player2, commits their seed, and registers the game with Ohloss.
+    ///
+    /// In games created with `require_approval` this only lodges a request
+    /// (escrowing any stake) and the seat stays open until the creator picks
+    /// one of up to `MAX_JOIN_REQUESTS` requesters with `approve_join`.
+    pub fn join_game(
+        env: Env,
+        session_id: u32,
+        player2: Address,
+        seed_commit2: BytesN<32>,
+    ) -> Result<(), Error> {
+        join(&env, session_id, &player2, seed_commit2, None)
+    }
+
+    /// `join_game` with extras: a reveal delegate that may call
+    /// `delegate_reveal` for player2 (e.g. a custodial game server) and
+    /// nothing else, and player2's metadata commitment, stored on the game
+    /// and published in `GameJoined`.
+    pub fn join_game_with(
+        env: Env,
+        session_id: u32,
+        player2: Address,
+        seed_commit2: BytesN<32>,
+        options: JoinOptions,
+    ) -> Result<(), Error> {
+        join(&env, session_id, &player2, seed_commit2, Some(options))
+    }
+
+    /// Quick play: join the oldest open game matching `filter` that
+    /// `player` can take straight away, escrowing its stake if it has one,
+    /// and return its session id. Games that need the creator's approval,
+    /// or that `player` could not join for any other reason, are passed
+    /// over. Only the oldest `MAX_QUICK_PLAY_SCAN` open games are looked
+    /// at; `gc_indexes` keeps lapsed ones from crowding the front.
+    pub fn join_any(
+        env: Env,
+        player: Address,
+        seed_commit: BytesN<32>,
+        filter: MatchFilter,
+    ) -> Result<u32, Error> {
+        require_unpaused(&env, Operation::Join)?;
+        if seed_commit == zero32(&env) {
+            return Err(Error::ZeroCommitment);
+        }
+        player.require_auth_for_args((seed_commit.clone(), filter.clone()).into_val(&env));
+
+        let mut cursor = Some(0);
+        let mut scanned = 0;
+        while let Some(page) = cursor {
+            if scanned >= MAX_QUICK_PLAY_SCAN {
+                break;
+            }
+            let open = storage::list_open_games(&env, page, MAX_QUICK_PLAY_SCAN - scanned);
+            cursor = open.next;
+            scanned += open.ids.len();
+            for session_id in open.ids.iter() {
+                let Some(entry) = storage::get_lobby_entry(&env, session_id) else {
+                    continue;
+                };
+                if entry.approval || entry.creator == player || !filter.matches(&entry) {
+                    continue;
+                }
+                let Some(mut game) = storage::get_game(&env, session_id) else {
+                    continue;
+                };
+                // The stake band `player` signed covers whatever odds it admits
+                if check_joinable(&env, session_id, &game, &player, &seed_commit, &zero32(&env), odds_stake(&game)).is_err()
+                    || (!game.unrated && pair_cooling_down(&env, &game, &player))
+                    || (filter.bands_rating() && !filter.rating_matches(player_rating(&env, &game, &game.player1)))
+                {
+                    continue;
+                }
+                game.seed_commit2 = seed_commit;
+                if let (true, Some(token)) = (game.escrow_wager, &game.wager_token) {
+                    escrow::deposit_stake(&env, session_id, &mut game.event_seq, &player, token, game.opponent_stake);
+                }
+                seat_player2(&env, session_id, &mut game, &player)?;
+                return Ok(session_id);
+            }
+        }
+        Err(Error::NoOpenGame)
+    }
+
+    /// P1 picks `joiner` from the join requests; they take the seat as
+    /// though they had joined directly and every other requester is
+    /// refunded.
+    pub fn approve_join(env: Env, session_id: u32, joiner: Address) -> Result<(), Error> {
+        require_unpaused(&env, Operation::Join)?;
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Created {
+            return Err(Error::InvalidState);
+        }
+        if env.ledger().sequence() > game.join_deadline {
+            return Err(Error::JoinWindowClosed);
+        }
+        let requests = storage::get_join_requests(&env, session_id);
+        let chosen = requests
+            .iter()
+            .find(|r| r.joiner == joiner)
+            .ok_or(Error::NoJoinRequest)?;
+
+        game.player1.require_auth_for_args((session_id, joiner.clone()).into_val(&env));
+
+        game.seed_commit2 = chosen.seed_commit;
+        game.metadata2 = chosen.metadata;
+        storage::take_join_requests(&env, session_id);
+        for request in requests.iter().filter(|r| r.joiner != joiner) {
+            storage::remove_reveal_delegate(&env, session_id, &request.joiner);
+            escrow::refund_stake(&env, session_id, &mut game.event_seq, &request.joiner);
+            events::emit_join_declined(&env, session_id, game.next_event_seq(), &request.joiner);
+        }
+        seat_player2(&env, session_id, &mut game, &joiner)?;
+        Ok(())
+    }
+
+    /// P1 turns down `joiner`'s request, refunding their stake.
+    pub fn decline_join(env: Env, session_id: u32, joiner: Address) -> Result<(), Error> {
+        let game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        game.player1.require_auth_for_args((session_id, joiner.clone()).into_val(&env));
+        log_action(&env, session_id, &game.player1, "decline_join");
+        drop_join_request(&env, session_id, game, &joiner)
+    }
+
+    /// A requester withdraws their own join request and stake.
+    pub fn withdraw_join(env: Env, session_id: u32, joiner: Address) -> Result<(), Error> {
+        let game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        joiner.require_auth_for_args((session_id,).into_val(&env));
+        log_action(&env, session_id, &joiner, "withdraw_join");
+        drop_join_request(&env, session_id, game, &joiner)
+    }
+
+    /// Query the requests waiting on a `require_approval` game, oldest first.
+    pub fn get_join_requests(env: Env, session_id: u32) -> Vec<JoinRequest> {
+        storage::get_join_requests(&env, session_id)
+    }
+
+    /// Either player reveals their seed. Both must reveal before settlement.
+    /// `opening` must match the game's commit scheme; salted commitments
+    /// are verified here rather than at proof time.
+    pub fn reveal_seed(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        seed: BytesN<32>,
+        opening: SeedOpening,
+    ) -> Result<(), Error> {
+        reveal(&env, session_id, &player, seed, opening, false)
+    }
+
+    /// The reveal delegate `player` registered at creation or join reveals
+    /// their seed; it authorizes this and nothing else. Fails with
+    /// `NoDelegate` if `player` registered none.
+    pub fn delegate_reveal(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        seed: BytesN<32>,
+        opening: SeedOpening,
+    ) -> Result<(), Error> {
+        reveal(&env, session_id, &player, seed, opening, true)
+    }
+
+    /// Both players reveal in a single transaction (e.g. when they share a
+    /// frontend session). Requires auth from both and moves the game
+    /// straight to Phase::Revealed.
+    pub fn reveal_both(
+        env: Env,
+        session_id: u32,
+        seed1: BytesN<32>,
+        seed2: BytesN<32>,
+        opening1: SeedOpening,
+        opening2: SeedOpening,
+    ) -> Result<(), Error> {
+        require_unpaused(&env, Operation::Reveal)?;
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+
+        if game.phase != Phase::Joined {
+            return Err(Error::InvalidState);
+        }
+        if env.ledger().sequence() > game.reveal_deadline {
+            return Err(Error::RevealWindowClosed);
+        }
+        if env.ledger().sequence() < game.starts_at {
+            return Err(Error::NotStarted);
+        }
+
+        game.player1.require_auth_for_args((session_id, seed1.clone()).into_val(&env));
+        game.player2.require_auth_for_args((session_id, seed2.clone()).into_val(&env));
+
+        let z = zero32(&env);
+        if game.seed1 != z || game.seed2 != z {
+            return Err(Error::AlreadyRevealed);
+        }
+        if seed1 == z || seed2 == z {
+            return Err(Error::ZeroSeed);
+        }
+        if seed1 == seed2 {
+            return Err(Error::DuplicateCommitment);
+        }
+        game.inner_commit1 =
+            check_opening(&env, game.commit_scheme, session_id, &game.player1, &game.seed_commit1, &seed1, &opening1)?;
+        game.inner_commit2 =
+            check_opening(&env, game.commit_scheme, session_id, &game.player2, &game.seed_commit2, &seed2, &opening2)?;
+
+        game.seed1 = seed1;
+        game.seed2 = seed2;
+        game.phase = Phase::Revealed;
+        storage::move_phase(&env, session_id, Some(Phase::Joined), Phase::Revealed);
+        if game.beacon.is_some() {
+            game.beacon_round = env.ledger().sequence() + 1;
+        }
+        storage::add_pending_settlement(&env, session_id);
+
+        log_action(&env, session_id, &game.player1, "reveal");
+        log_action(&env, session_id, &game.player2, "reveal");
+        events::emit_seed_revealed(&env, session_id, game.next_event_seq(), &game.player1);
+        events::emit_seed_revealed(&env, session_id, game.next_event_seq(), &game.player2);
+        warn_if_expiring(&env, session_id, &mut game);
+        storage::set_game(&env, session_id, &mut game);
+        notify_revealed(&env, session_id, &game);
+
+        Ok(())
+    }
+
+    /// Anyone can settle by submitting a valid ZK proof.
+    /// The proof determines the winner based on the revealed seeds.
+    /// Calls ohloss.end_game() to report the result and pays any bounty
+    /// to `settler`. Returns the winner's address, or None for a draw.
+    pub fn settle_game(
+        env: Env,
+        session_id: u32,
+        proof: Groth16Proof,
+        pub_inputs: PublicInputs,
+        settler: Address,
+    ) -> Result<Option<Address>, Error> {
+        settle(&env, session_id, &proof, &pub_inputs, &settler, None)
+    }
+
+    /// Settle up to 4 revealed games in one call, e.g. from a keeper.
+    /// Proofs sharing a verification key are checked together in one
+    /// multi-pairing; if that fails, each is checked alone so only the bad
+    /// ones are skipped (and counted toward the breaker). That fallback
+    /// costs a full verification per proof, so a batch holding a bad proof
+    /// may need resubmitting in smaller pieces. Malformed proofs, entries
+    /// that fail any other settlement check, repeat a session or need a
+    /// supplied key are skipped without counting. Results line up with
+    /// `settlements`: true where the game settled.
+    pub fn settle_batch(
+        env: Env,
+        settlements: Vec<BatchSettlement>,
+        settler: Address,
+    ) -> Result<Vec<bool>, Error> {
+        if settlements.len() > MAX_SETTLE_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+        require_unpaused(&env, Operation::Settle)?;
+
+        // Group the entries that pass every check except the pairing by key
+        let mut settled = Vec::new(&env);
+        let mut seen = Map::new(&env);
+        let mut keys: Map<u32, VerificationKey> = Map::new(&env);
+        let mut groups: Map<u32, Vec<u32>> = Map::new(&env);
+        let mut prepared: Map<u32, (Game, Winner)> = Map::new(&env);
+        for (i, entry) in settlements.iter().enumerate() {
+            settled.push_back(false);
+            if seen.contains_key(entry.session_id) {
+                continue;
+            }
+            seen.set(entry.session_id, ());
+            let Ok((game, outcome, vk_id, vk)) = prepare_settlement(&env, entry.session_id, &entry.pub_inputs, None)
+            else {
+                continue;
+            };
+            if verifier::check_proof(&entry.proof, &vk, &entry.pub_inputs.to_array()).is_err() {
+                continue;
+            }
+            let mut group = groups.get(vk_id).unwrap_or(Vec::new(&env));
+            group.push_back(i as u32);
+            groups.set(vk_id, group);
+            keys.set(vk_id, vk);
+            prepared.set(i as u32, (game, outcome));
+        }
+
+        for (vk_id, group) in groups.iter() {
+            let vk = keys.get_unchecked(vk_id);
+            let mut proofs = Vec::new(&env);
+            let mut inputs = Vec::new(&env);
+            for i in group.iter() {
+                let entry = settlements.get_unchecked(i);
+                proofs.push_back(entry.proof);
+                inputs.push_back(entry.pub_inputs);
+            }
+            // Every proof passed its shape checks; should the batch still
+            // fail, verify each alone rather than failing the group
+            let valid = verifier::verify_groth16_batch(&env, &vk, &proofs, &inputs).ok();
+            for (j, i) in group.iter().enumerate() {
+                let entry = settlements.get_unchecked(i);
+                let (game, outcome) = prepared.get_unchecked(i);
+                let verified = match &valid {
+                    Some(valid) => Ok(valid.get_unchecked(j as u32)),
+                    None => verifier::verify_groth16(&env, &entry.proof, &vk, &entry.pub_inputs.to_array()),
+                };
+                match verified {
+                    Ok(true) => {
+                        finish_settlement(&env, entry.session_id, game, outcome, &entry.proof, &settler);
+                        settled.set(i, true);
+                    }
+                    Ok(false) => {
+                        record_proof_failure(&env, game.variant);
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+        Ok(settled)
+    }
+
+    /// Settle many revealed games with one aggregated proof attesting to
+    /// all of their outcomes. Each entry carries the inputs its own proof
+    /// would have; every entry must pass the usual settlement checks, and
+    /// the proof must verify under the aggregate key against the batch
+    /// commitment (see `get_batch_commitment`). All entries settle or none
+    /// do. Returns the winner addresses in entry order.
+    pub fn settle_aggregate(
+        env: Env,
+        entries: Vec<AggregateEntry>,
+        proof: Groth16Proof,
+        settler: Address,
+    ) -> Result<Vec<Option<Address>>, Error> {
+        if entries.is_empty() {
+            return Err(Error::InvalidState);
+        }
+        if entries.len() > MAX_AGGREGATE_BATCH {
+            return Err(Error::BatchTooLarge);
+        }
+        require_unpaused(&env, Operation::Settle)?;
+        let vk = storage::get_aggregate_vk(&env).ok_or(Error::NoVk)?;
+
+        let mut seen = Map::new(&env);
+        let mut prepared = Vec::new(&env);
+        for entry in entries.iter() {
+            if seen.contains_key(entry.session_id) {
+                return Err(Error::InvalidState);
+            }
+            seen.set(entry.session_id, ());
+            let (game, outcome, _) = check_public_inputs(&env, entry.session_id, &entry.pub_inputs)?;
+            prepared.push_back((game, outcome));
+        }
+
+        let keys = batch_keys(&env, &entries)?;
+        let commitment = batch_commitment(&env, &entries, &keys);
+        if !verifier::verify_groth16(&env, &proof, &vk, &[commitment])? {
+            return Err(Error::InvalidProof);
+        }
+
+        let mut winners = Vec::new(&env);
+        for (entry, (game, outcome)) in entries.iter().zip(prepared.iter()) {
+            winners.push_back(finish_settlement(&env, entry.session_id, game, outcome, &proof, &settler));
+        }
+        Ok(winners)
+    }
+
+    /// The commitment an aggregated proof over `entries` must attest to.
+    pub fn get_batch_commitment(env: Env, entries: Vec<AggregateEntry>) -> Result<BytesN<32>, Error> {
+        let keys = batch_keys(&env, &entries)?;
+        Ok(batch_commitment(&env, &entries, &keys))
+    }
+
+    /// Admin: set the key aggregated batch proofs verify under. Its IC
+    /// vector has two entries: the circuit's only input is the batch
+    /// commitment. `vk_ids` are the keys whose proofs the aggregation
+    /// circuit checks; a batch may mix only those, while a batch under a
+    /// single key is always accepted.
+    pub fn set_aggregate_vk(env: Env, vk: VerificationKey, vk_ids: Vec<u32>) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_aggregate_vk", &admin, (&vk, &vk_ids).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_aggregate_vk(&env, &vk, &vk_ids)
+    }
+
+    /// Like `settle_game`, for variants whose key is stored only as a
+    /// hash: the settler supplies the full key, which must match it.
+    pub fn settle_game_with_vk(
+        env: Env,
+        session_id: u32,
+        proof: Groth16Proof,
+        pub_inputs: PublicInputs,
+        settler: Address,
+        vk: VerificationKey,
+    ) -> Result<Option<Address>, Error> {
+        settle(&env, session_id, &proof, &pub_inputs, &settler, Some(&vk))
+    }
+
+    /// Like `settle_game`, but a session that is already settled returns
+    /// its recorded winner instead of `GameAlreadySettled`, so keepers and
+    /// wallets can resubmit safely. The bounty is only ever paid once.
+    pub fn settle_or_get(
+        env: Env,
+        session_id: u32,
+        proof: Groth16Proof,
+        pub_inputs: PublicInputs,
+        settler: Address,
+    ) -> Result<Option<Address>, Error> {
+        if let Some(tombstone) = storage::get_tombstone(&env, session_id) {
+            return Ok(tombstone.winner_address);
+        }
+        Self::settle_game(env, session_id, proof, pub_inputs, settler)
+    }
+
+    /// Anyone can record a proof that `settle_game` rejected as invalid.
+    /// A failed settlement rolls back its own writes, so the failure is
+    /// counted here: the submission is re-checked in full and counts only
+    /// if its public inputs match the game and the proof does not verify.
+    /// Each proof counts once per session, and the reporter posts the
+    /// admin's report bond: it is returned when the breaker trips and is
+    /// reset, and goes to the treasury if a valid proof clears the count
+    /// first. Once a variant reaches the breaker threshold its settlements
+    /// halt until the admin resets it. `vk` is needed only for hash-only
+    /// keys. Returns the variant's failure count.
+    pub fn report_invalid_proof(
+        env: Env,
+        session_id: u32,
+        reporter: Address,
+        proof: Groth16Proof,
+        pub_inputs: PublicInputs,
+        vk: Option<VerificationKey>,
+    ) -> Result<u32, Error> {
+        let terms = storage::get_report_bond_terms(&env).ok_or(Error::InvalidConfig)?;
+        if storage::get_breaker_threshold(&env) == 0 {
+            return Err(Error::InvalidConfig);
+        }
+        let (game, _, valid) = check_settlement(&env, session_id, &proof, &pub_inputs, vk.as_ref())?;
+        if valid {
+            return Err(Error::InvalidState);
+        }
+        let proof_hash = proof_hash(&env, &proof);
+        if storage::has_proof_report(&env, session_id, &proof_hash) {
+            return Err(Error::AlreadyReported);
+        }
+
+        reporter.require_auth_for_args((session_id, proof_hash.clone()).into_val(&env));
+        escrow::deposit_bond(&env, &reporter, &terms.token, terms.amount);
+        storage::set_proof_report(&env, session_id, &proof_hash);
+        let bond = ReportBond { reporter, token: terms.token, amount: terms.amount };
+        storage::add_report_bond(&env, game.variant, &bond);
+        Ok(record_proof_failure(&env, game.variant))
+    }
+
+    /// Both players agree to end a joined game as a draw, without a proof.
+    /// Stakes are split as for a proven draw and any bounty is refunded.
+    pub fn agree_draw(env: Env, session_id: u32) -> Result<(), Error> {
+        let game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if (game.phase != Phase::Joined && game.phase != Phase::Revealed) || game.no_draws {
+            return Err(Error::InvalidState);
+        }
+
+        game.player1.require_auth_for_args((session_id,).into_val(&env));
+        game.player2.require_auth_for_args((session_id,).into_val(&env));
+        require_unpaused(&env, Operation::Settle)?;
+
+        log_action(&env, session_id, &game.player1, "agree_draw");
+        log_action(&env, session_id, &game.player2, "agree_draw");
+        conclude(&env, session_id, game, Winner::Draw, None, zero32(&env));
+        Ok(())
+    }
+
+    /// Player: bind the secp256k1 key (65-byte uncompressed SEC1) used to
+    /// sign agreed outcomes, so EVM-style keys can settle without a
+    /// Stellar signature per game. Rebinding replaces the old key.
+    pub fn bind_secp256k1_key(env: Env, player: Address, key: BytesN<65>) -> Result<(), Error> {
+        player.require_auth_for_args((key.clone(),).into_val(&env));
+        storage::set_secp256k1_key(&env, &player, &key);
+        events::emit_secp256k1_key_bound(&env, &player, &key);
+        Ok(())
+    }
+
+    /// Anyone can settle a joined game without a proof by submitting both
+    /// players' ed25519 signatures, made with their Stellar account keys,
+    /// over the same `OutcomePayload` digest. The signed outcome stands as
+    /// if proven; a draw in a no-draws game is rejected. The receipt has no
+    /// proof, so its `proof_hash` is zero.
+    pub fn settle_signed(
+        env: Env,
+        session_id: u32,
+        outcome: Winner,
+        signature1: BytesN<64>,
+        signature2: BytesN<64>,
+    ) -> Result<Option<Address>, Error> {
+        let (game, digest) = signed_outcome(&env, session_id, outcome)?;
+        check_ed25519(&env, &game.player1, &digest, &signature1)?;
+        check_ed25519(&env, &game.player2, &digest, &signature2)?;
+        log_action(&env, session_id, &game.player1, "settle_signed");
+        log_action(&env, session_id, &game.player2, "settle_signed");
+
+        Ok(conclude(&env, session_id, game, outcome, None, zero32(&env)))
+    }
+
+    /// `settle_signed` for players whose keys are secp256k1: each signature
+    /// over the `OutcomePayload` digest must recover to the key the player
+    /// bound with `bind_secp256k1_key`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle_signed_secp256k1(
+        env: Env,
+        session_id: u32,
+        outcome: Winner,
+        signature1: BytesN<64>,
+        recovery_id1: u32,
+        signature2: BytesN<64>,
+        recovery_id2: u32,
+    ) -> Result<Option<Address>, Error> {
+        let (game, digest) = signed_outcome(&env, session_id, outcome)?;
+        check_secp256k1(&env, &game.player1, &digest, &signature1, recovery_id1)?;
+        check_secp256k1(&env, &game.player2, &digest, &signature2, recovery_id2)?;
+        log_action(&env, session_id, &game.player1, "settle_signed");
+        log_action(&env, session_id, &game.player2, "settle_signed");
+
+        Ok(conclude(&env, session_id, game, outcome, None, zero32(&env)))
+    }
+
+    /// Query the secp256k1 key a player has bound.
+    pub fn get_secp256k1_key(env: Env, player: Address) -> Option<BytesN<65>> {
+        storage::get_secp256k1_key(&env, &player)
+    }
+
+    /// A player concedes a joined game; the opponent wins without a proof.
+    /// Stakes and sponsorships go to the opponent and any bounty is refunded.
+    pub fn forfeit(env: Env, session_id: u32, player: Address) -> Result<Address, Error> {
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Joined && game.phase != Phase::Revealed {
+            return Err(Error::InvalidState);
+        }
+        let (winner, winner_addr) = if player == game.player1 {
+            (Winner::Player2, game.player2.clone())
+        } else if player == game.player2 {
+            (Winner::Player1, game.player1.clone())
+        } else {
+            return Err(Error::NotPlayer);
+        };
+
+        player.require_auth_for_args((session_id,).into_val(&env));
+        require_unpaused(&env, Operation::Settle)?;
+
+        game.forfeit = true;
+        log_action(&env, session_id, &player, "forfeit");
+        conclude(&env, session_id, game, winner, None, zero32(&env));
+        Ok(winner_addr)
+    }
+
+    /// Anyone can end a joined game whose reveal deadline passed before
+    /// both seeds were in. A player who revealed wins by forfeit; if
+    /// neither did the game is cancelled and every stake, sponsorship and
+    /// bounty refunded in full, with nothing reported to Ohloss. Returns
+    /// the winner, or None when cancelled.
+    pub fn claim_timeout(env: Env, session_id: u32) -> Result<Option<Address>, Error> {
+        require_unpaused(&env, Operation::Settle)?;
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Joined {
+            return Err(Error::InvalidState);
+        }
+        if env.ledger().sequence() <= game.reveal_deadline {
+            return Err(Error::RevealWindowOpen);
+        }
+
+        let z = zero32(&env);
+        let winner = match (game.seed1 != z, game.seed2 != z) {
+            (true, _) => Winner::Player1,
+            (_, true) => Winner::Player2,
+            _ => {
+                game.phase = Phase::Cancelled;
+                storage::move_phase(&env, session_id, Some(Phase::Joined), Phase::Cancelled);
+                drop_from_indexes(&env, session_id);
+                storage::remove_reveal_delegates(&env, session_id);
+                if let Some(previous) = game.rematch_of {
+                    storage::remove_rematch(&env, previous);
+                }
+                escrow::refund_bounty(&env, session_id, &mut game.event_seq);
+                escrow::refund_stakes(&env, session_id, &mut game.event_seq);
+                escrow::refund_sponsorships(&env, session_id, &mut game.event_seq);
+                log_action(&env, session_id, &game.player1, "timeout");
+                log_action(&env, session_id, &game.player2, "timeout");
+                events::emit_game_cancelled(&env, session_id, game.next_event_seq(), &game);
+                storage::set_game(&env, session_id, &mut game);
+                return Ok(None);
+            }
+        };
+        // Logged against the player who let the clock run out
+        let missed = if winner == Winner::Player1 { game.player2.clone() } else { game.player1.clone() };
+        game.forfeit = true;
+        log_action(&env, session_id, &missed, "timeout");
+        Ok(conclude(&env, session_id, game, winner, None, z))
+    }
+
+    /// The loser of a decided game accepts the result, telling leagues it
+    /// is not contested. It forgoes any dispute and lets either player
+    /// open a rematch against the same opponent at a discounted rake
+    /// (`GameOptions::rematch_of`).
+    pub fn acknowledge_result(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        let loser = match game.winner {
+            Winner::Player1 if game.phase == Phase::Settled => &game.player2,
+            Winner::Player2 if game.phase == Phase::Settled => &game.player1,
+            _ => return Err(Error::InvalidState),
+        };
+        if player != *loser {
+            return Err(Error::NotPlayer);
+        }
+        if game.result_acknowledged {
+            return Err(Error::InvalidState);
+        }
+        if storage::get_dispute(&env, session_id).is_some() {
+            return Err(Error::DisputeOpen);
+        }
+
+        player.require_auth_for_args((session_id,).into_val(&env));
+
+        game.result_acknowledged = true;
+        let seq = game.next_event_seq();
+        storage::set_game(&env, session_id, &mut game);
+        log_action(&env, session_id, &player, "acknowledge_result");
+        events::emit_result_acknowledged(&env, session_id, seq, &player);
+        Ok(())
+    }
+
+    /// Admin: cap how many games one address may create per window of
+    /// ledgers. A zero `max_creations` lifts the cap.
+    pub fn set_rate_limit(env: Env, limit: RateLimit) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_rate_limit", &admin, (&limit,).to_xdr(&env));
+        if limit.max_creations > 0 && limit.window == 0 {
+            return Err(Error::InvalidConfig);
+        }
+        storage::set_rate_limit(&env, &limit);
+        Ok(())
+    }
+
+    /// Admin: set how many ledgers must pass before the same two addresses
+    /// can start another rated game, to deter win-trading. Zero disables
+    /// it; unrated games are never limited.
+    pub fn set_pair_cooldown(env: Env, cooldown: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_pair_cooldown", &admin, (&cooldown,).to_xdr(&env));
+        storage::set_pair_cooldown(&env, cooldown);
+        Ok(())
+    }
+
+    /// Admin: set how many ledgers ahead of a game's next deadline or
+    /// expiry touching it publishes `GameExpiringSoon`, so bots and UIs
+    /// can prompt players in time. Zero turns the warnings off.
+    pub fn set_expiry_warning(env: Env, window: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_expiry_warning", &admin, (&window,).to_xdr(&env));
+        storage::set_expiry_warning(&env, window);
+        Ok(())
+    }
+
+    /// Admin: reserve session ids `start..=end` for `orchestrator`,
+    /// replacing any range it already holds. The range must lie in the
+    /// reserved space and not overlap another orchestrator's.
+    pub fn assign_session_range(env: Env, orchestrator: Address, start: u32, end: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "assign_session_range", &admin, (&orchestrator, start, end).to_xdr(&env));
+        if start < RESERVED_SESSION_START || start > end {
+            return Err(Error::InvalidConfig);
+        }
+        let mut ranges = Vec::new(&env);
+        for range in storage::get_session_ranges(&env).iter() {
+            if range.orchestrator == orchestrator {
+                continue;
+            }
+            if range.start <= end && start <= range.end {
+                return Err(Error::InvalidConfig);
+            }
+            ranges.push_back(range);
+        }
+        let range = SessionRange { orchestrator, start, end };
+        ranges.push_back(range.clone());
+        storage::set_session_ranges(&env, &ranges);
+        events::emit_session_range_assigned(&env, &range);
+        Ok(())
+    }
+
+    /// Admin: release `orchestrator`'s range. Games already created under
+    /// it are unaffected.
+    pub fn remove_session_range(env: Env, orchestrator: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "remove_session_range", &admin, (&orchestrator,).to_xdr(&env));
+        let mut ranges = storage::get_session_ranges(&env);
+        let index = ranges
+            .iter()
+            .position(|range| range.orchestrator == orchestrator)
+            .ok_or(Error::InvalidState)?;
+        ranges.remove(index as u32);
+        storage::set_session_ranges(&env, &ranges);
+        events::emit_session_range_removed(&env, &orchestrator);
+        Ok(())
+    }
+
+    /// Query how session ids are split between users and orchestrators.
+    pub fn get_session_ranges(env: Env) -> SessionRanges {
+        SessionRanges {
+            reserved_start: RESERVED_SESSION_START,
+            ranges: storage::get_session_ranges(&env),
+        }
+    }
+
+    /// Query the game-creation rate limit.
+    pub fn get_rate_limit(env: Env) -> RateLimit {
+        storage::get_rate_limit(&env)
+    }
+
+    /// Admin: set how many ledgers after settlement a staked result can be
+    /// disputed. Payouts are held until the window passes; zero pays out
+    /// at settlement.
+    pub fn set_dispute_window(env: Env, window: u32) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_dispute_window", &admin, (&window,).to_xdr(&env));
+        if window > MAX_TIMELOCK_DELAY {
+            return Err(Error::InvalidConfig);
+        }
+        storage::set_dispute_window(&env, window);
+        Ok(())
+    }
+
+    /// Admin: appoint the arbiter who rules on disputes.
+    pub fn set_arbiter(env: Env, arbiter: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_arbiter", &admin, (&arbiter,).to_xdr(&env));
+        storage::set_arbiter(&env, &arbiter);
+        events::emit_arbiter_updated(&env, &arbiter);
+        Ok(())
+    }
+
+    /// A player challenges a settled result while its payout is held,
+    /// posting `bond` in the session's stake token, at least
+    /// MIN_DISPUTE_BOND_BPS of the pot. Payouts stay frozen until the
+    /// arbiter or both players resolve it, or the arbiter lets
+    /// ARBITRATION_WINDOW pass without a ruling.
+    pub fn flag_dispute(env: Env, session_id: u32, challenger: Address, bond: i128) -> Result<(), Error> {
+        let payout = storage::get_pending_payout(&env, session_id)
+            .ok_or(Error::DisputeWindowClosed)?;
+        if env.ledger().sequence() >= payout.release_at {
+            return Err(Error::DisputeWindowClosed);
+        }
+        if storage::get_dispute(&env, session_id).is_some() {
+            return Err(Error::DisputeOpen);
+        }
+        if challenger != payout.player1 && challenger != payout.player2 {
+            return Err(Error::NotPlayer);
+        }
+        // The loser gave up the right to contest by acknowledging
+        let acknowledged = storage::get_game(&env, session_id)
+            .is_some_and(|g| g.result_acknowledged && g.winner_address.as_ref() != Some(&challenger));
+        if acknowledged {
+            return Err(Error::InvalidState);
+        }
+        let stake = storage::get_stake(&env, session_id)
+            .ok_or(Error::InvalidState)?;
+        let pot: i128 = stake.amounts.iter().sum();
+        if bond <= 0 || bond < pot * MIN_DISPUTE_BOND_BPS / 10_000 {
+            return Err(Error::InvalidAmount);
+        }
+        let token = stake.token;
+
+        challenger.require_auth_for_args((session_id, bond).into_val(&env));
+        escrow::deposit_bond(&env, &challenger, &token, bond);
+        let dispute = Dispute {
+            challenger,
+            token,
+            bond,
+        };
+        storage::set_dispute(&env, session_id, &dispute);
+        log_action(&env, session_id, &dispute.challenger, "flag_dispute");
+        storage::with_event_seq(&env, session_id, |seq| {
+            events::emit_dispute_flagged(&env, session_id, bump_seq(seq), &dispute)
+        });
+        Ok(())
+    }
+
+    /// Arbiter: rule on a dispute with the session's final outcome.
+    pub fn arbitrate(env: Env, session_id: u32, ruling: Winner) -> Result<(), Error> {
+        let arbiter = storage::get_arbiter(&env).ok_or(Error::NotArbiter)?;
+        arbiter.require_auth();
+        resolve_dispute(&env, session_id, ruling)?;
+        log_action(&env, session_id, &arbiter, "arbitrate");
+        Ok(())
+    }
+
+    /// Both players settle a dispute between themselves.
+    pub fn settle_dispute(env: Env, session_id: u32, ruling: Winner) -> Result<(), Error> {
+        let payout = storage::get_pending_payout(&env, session_id)
+            .ok_or(Error::NoDispute)?;
+        payout.player1.require_auth_for_args((session_id, ruling).into_val(&env));
+        payout.player2.require_auth_for_args((session_id, ruling).into_val(&env));
+        resolve_dispute(&env, session_id, ruling)?;
+        log_action(&env, session_id, &payout.player1, "settle_dispute");
+        log_action(&env, session_id, &payout.player2, "settle_dispute");
+        Ok(())
+    }
+
+    /// Anyone can release a held payout once its dispute window has passed
+    /// without a challenge, or on the original result once a challenge
+    /// has gone ARBITRATION_WINDOW past it without a ruling. The
+    /// challenger's bond is then returned.
+    pub fn release_payout(env: Env, session_id: u32) -> Result<(), Error> {
+        let payout = storage::get_pending_payout(&env, session_id)
+            .ok_or(Error::InvalidState)?;
+        if let Some(dispute) = storage::get_dispute(&env, session_id) {
+            if env.ledger().sequence() < payout.release_at + ARBITRATION_WINDOW {
+                return Err(Error::DisputeOpen);
+            }
+            storage::remove_dispute(&env, session_id);
+            storage::remove_pending_payout(&env, session_id);
+            escrow::release_bond(&env, &dispute.challenger, &dispute.token, dispute.bond);
+            storage::with_event_seq(&env, session_id, |seq| {
+                pay_out(&env, session_id, seq, &payout);
+                let seq = bump_seq(seq);
+                events::emit_dispute_resolved(&env, session_id, seq, payout.winner, true, &dispute.challenger);
+            });
+            return Ok(());
+        }
+        if env.ledger().sequence() < payout.release_at {
+            return Err(Error::TimelockActive);
+        }
+
+        storage::remove_pending_payout(&env, session_id);
+        storage::with_event_seq(&env, session_id, |seq| {
+            pay_out(&env, session_id, seq, &payout);
+            events::emit_payout_released(&env, session_id, bump_seq(seq));
+        });
+        Ok(())
+    }
+
+    /// Query a session's held payout.
+    pub fn get_pending_payout(env: Env, session_id: u32) -> Option<PendingPayout> {
+        storage::get_pending_payout(&env, session_id)
+    }
+
+    /// Query a session's open dispute.
+    pub fn get_dispute(env: Env, session_id: u32) -> Option<Dispute> {
+        storage::get_dispute(&env, session_id)
+    }
+
+    /// Anyone can resend a settled game's result to Ohloss if the report
+    /// made at settlement, or the correction sent after a dispute
+    /// overturned it, failed.
+    pub fn report_settlement(env: Env, session_id: u32) -> Result<(), Error> {
+        let mut game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase != Phase::Settled || game.result_reported {
+            return Err(Error::InvalidState);
+        }
+        let correction = storage::is_correction_pending(&env, session_id);
+        let reported = if correction {
+            report_correction(&env, session_id, &game)
+        } else {
+            report_result(&env, session_id, &game)
+        };
+        if !reported {
+            return Err(Error::ReportFailed);
+        }
+        if correction {
+            storage::set_correction_pending(&env, session_id, false);
+        }
+
+        game.result_reported = true;
+        storage::set_game(&env, session_id, &mut game);
+        Ok(())
+    }
+
+    /// Query the rake accounting for `token`.
+    pub fn get_treasury_account(env: Env, token: Address) -> TreasuryAccount {
+        storage::get_treasury_account(&env, &token)
+    }
+
+    /// Query the insurance fund balance held in `token`.
+    pub fn get_insurance_balance(env: Env, token: Address) -> i128 {
+        storage::get_insurance_balance(&env, &token)
+    }
+
+    /// Query the compensation queued for a session, if any.
+    pub fn get_compensation(env: Env, session_id: u32) -> Option<PendingCompensation> {
+        storage::get_compensation(&env, session_id)
+    }
+
+    /// Query the sponsorships held for a session.
+    pub fn get_sponsorships(env: Env, session_id: u32) -> Vec<Sponsorship> {
+        storage::get_sponsorships(&env, session_id)
+    }
+
+    /// Query the settlement receipt of a concluded session. Kept in
+    /// persistent storage after the game entry is evicted.
+    pub fn get_settlement_receipt(env: Env, session_id: u32) -> Option<SettlementReceipt> {
+        storage::get_receipt(&env, session_id)
+    }
+
+    /// Query the canonical result message written at settlement, for
+    /// bridges and oracles relaying results to other chains.
+    pub fn get_attestation(env: Env, session_id: u32) -> Option<Bytes> {
+        storage::get_attestation(&env, session_id)
+    }
+
+    /// Query deployment-wide settlement counters.
+    pub fn get_stats(env: Env) -> GameStats {
+        storage::get_stats(&env, None)
+    }
+
+    /// Query how many games are in each phase, e.g. for a dashboard
+    /// watching the backlog of revealed games awaiting a proof.
+    pub fn get_phase_counts(env: Env) -> PhaseCounts {
+        storage::get_phase_counts(&env)
+    }
+
+    /// Query settlement counters for one variant.
+    pub fn get_variant_stats(env: Env, variant: u32) -> GameStats {
+        storage::get_stats(&env, Some(variant))
+    }
+
+    /// Query the escrowed stake volume settled in `token`.
+    pub fn get_token_volume(env: Env, token: Address) -> TokenVolume {
+        storage::get_token_volume(&env, &token)
+    }
+
+    /// Query a registered affiliate code.
+    pub fn get_affiliate(env: Env, code: BytesN<32>) -> Option<Affiliate> {
+        storage::get_affiliate(&env, &code)
+    }
+
+    /// Query an affiliate code's claimable balance in `token`.
+    pub fn get_affiliate_balance(env: Env, code: BytesN<32>, token: Address) -> i128 {
+        storage::get_affiliate_balance(&env, &code, &token)
+    }
+
+    /// Query the stakes escrowed for a session, if any.
+    pub fn get_stake(env: Env, session_id: u32) -> Option<EscrowedStake> {
+        storage::get_stake(&env, session_id)
+    }
+
+    /// Query the rake applied to newly created games, in basis points.
+    pub fn get_rake_bps(env: Env) -> u32 {
+        storage::get_rake_bps(&env)
+    }
+
+    /// Query a ruleset variant.
+    pub fn get_variant(env: Env, variant_id: u32) -> Option<Variant> {
+        storage::get_variant(&env, variant_id)
+    }
+
+    /// Query the deck a game's seeds give under `variant`, as its circuit
+    /// lays it out. `DeckOrder::PoseidonWeights` variants take
+    /// `pirate_engine::deal` of the Poseidon(combined, k) stream, where
+    /// combined is Poseidon(seed1, seed2, session_id, beacon) and `beacon`
+    /// is the value the proof takes (zero without one); seeds or a beacon
+    /// outside the scalar field, or a deck other than the classic 25, fail
+    /// with DeckNotDerivable. `DeckOrder::KeccakShuffle` variants take the
+    /// Fisher–Yates shuffle of `pirate_engine::derive_deck`, which depends
+    /// on the seeds alone.
+    pub fn derive_deck(
+        env: Env,
+        seed1: BytesN<32>,
+        seed2: BytesN<32>,
+        session_id: u32,
+        beacon: BytesN<32>,
+        variant: u32,
+    ) -> Result<Vec<u32>, Error> {
+        let variant_config = storage::get_variant(&env, variant).ok_or(Error::UnknownVariant)?;
+        let deck_size = variant_config.deck_size as usize;
+        let mut deck = [0u32; pirate_engine::MAX_DERIVED_DECK];
+        match storage::get_variant_deck_order(&env, variant) {
+            DeckOrder::PoseidonWeights => {
+                let scalars = [&seed1, &seed2, &beacon].iter().all(|x| poseidon::is_scalar(x));
+                if !scalars || deck_size != pirate_engine::DECK_SIZE {
+                    return Err(Error::DeckNotDerivable);
+                }
+                let field = |x: &BytesN<32>| U256::from_be_bytes(&env, &x.clone().into());
+                let combined = poseidon::Poseidon::width5(&env).hash(&[
+                    field(&seed1),
+                    field(&seed2),
+                    U256::from_u32(&env, session_id),
+                    field(&beacon),
+                ]);
+                let weight = poseidon::Poseidon::width3(&env);
+                let stream = pirate_engine::Prng::new(|k: u32| {
+                    let mut block = [0u8; 32];
+                    weight.hash(&[combined.clone(), U256::from_u32(&env, k)]).to_be_bytes().copy_into_slice(&mut block);
+                    block
+                });
+                for (slot, card) in deck.iter_mut().zip(pirate_engine::deal(stream).deck) {
+                    *slot = card as u32;
+                }
+            }
+            DeckOrder::KeccakShuffle => {
+                if deck_size > pirate_engine::MAX_DERIVED_DECK {
+                    return Err(Error::InvalidConfig);
+                }
+                let keccak = |data: &[u8]| env.crypto().keccak256(&Bytes::from_slice(&env, data)).to_array();
+                pirate_engine::derive_deck(&seed1.to_array(), &seed2.to_array(), variant, &mut deck[..deck_size], keccak);
+            }
+        }
+        let mut cards = Vec::new(&env);
+        for card in &deck[..deck_size] {
+            cards.push_back(*card);
+        }
+        Ok(cards)
+    }
+
+    /// Query game state.
+    pub fn get_game(env: Env, session_id: u32) -> Option<Game> {
+        storage::get_game(&env, session_id)
+    }
+
+    /// Query several games in one call, e.g. to refresh a lobby. Results
+    /// line up with `session_ids`; at most 100 ids per call.
+    pub fn get_games(env: Env, session_ids: Vec<u32>) -> Result<Vec<Option<Game>>, Error> {
+        if session_ids.len() > storage::MAX_PAGE_SIZE {
+            return Err(Error::BatchTooLarge);
+        }
+        let mut games = Vec::new(&env);
+        for session_id in session_ids.iter() {
+            games.push_back(storage::get_game(&env, session_id));
+        }
+        Ok(games)
+    }
+
+    /// Query only the phase of a game, for cheap polling.
+    pub fn get_phase(env: Env, session_id: u32) -> Option<Phase> {
+        storage::get_game(&env, session_id).map(|game| game.phase)
+    }
+
+    /// List revealed games awaiting a settlement proof.
+    /// Returns session ids >= `cursor` in ascending order, at most `limit`
+    /// (capped at 100), and the cursor to pass for the next page.
+    pub fn list_pending_settlement(env: Env, cursor: u32, limit: u32) -> SessionPage {
+        storage::list_pending_settlement(&env, cursor, limit)
+    }
+
+    /// Anyone can prune index entries left behind by games whose temporary
+    /// entry was evicted, and open-game entries past their join deadline.
+    /// Returns how many of `session_ids` were pruned.
+    pub fn gc_indexes(env: Env, session_ids: Vec<u32>) -> Result<u32, Error> {
+        if session_ids.len() > storage::MAX_PAGE_SIZE {
+            return Err(Error::BatchTooLarge);
+        }
+        let now = env.ledger().sequence();
+        let mut pruned = 0;
+        for session_id in session_ids.iter() {
+            match storage::get_game(&env, session_id) {
+                None => {
+                    uncount_evicted(&env, session_id);
+                    drop_from_indexes(&env, session_id)
+                }
+                Some(game) if game.phase == Phase::Created && now > game.join_deadline => {
+                    storage::remove_open_game(&env, session_id)
+                }
+                Some(_) => continue,
+            }
+            pruned += 1;
+        }
+        Ok(pruned)
+    }
+
+    /// List games waiting for a second player. Paginated like
+    /// `list_pending_settlement`.
+    pub fn list_open_games(env: Env, cursor: u32, limit: u32) -> SessionPage {
+        storage::list_open_games(&env, cursor, limit)
+    }
+
+    /// List games `player` has created or joined. Paginated like
+    /// `list_pending_settlement`.
+    pub fn list_player_games(env: Env, player: Address, cursor: u32, limit: u32) -> SessionPage {
+        storage::list_player_games(&env, &player, cursor, limit)
+    }
+
+    /// Query who created a session id. Outlives the game entry itself, so
+    /// off-chain systems can attribute an id even after eviction.
+    pub fn get_session_creator(env: Env, session_id: u32) -> Option<Address> {
+        storage::get_session_creator(&env, session_id)
+    }
+
+    /// Check a salted seed commitment as `reveal_seed` would for this
+    /// contract. Lets clients confirm their encoding before committing.
+    pub fn verify_commitment(
+        env: Env,
+        session_id: u32,
+        player: Address,
+        seed: BytesN<32>,
+        salt: BytesN<32>,
+        commitment: BytesN<32>,
+    ) -> bool {
+        let expected = SeedCommitment::compute(
+            &env,
+            &env.current_contract_address(),
+            session_id,
+            &player,
+            &seed,
+            &salt,
+        );
+        expected.to_bytes() == commitment
+    }
+
+    /// Query the bounty escrowed for a session, if any.
+    pub fn get_bounty(env: Env, session_id: u32) -> Option<EscrowedBounty> {
+        storage::get_bounty(&env, session_id)
+    }
+
+    /// Query the winning address of a settled game. Falls back to the
+    /// tombstone once the game entry has been evicted.
+    pub fn get_winner(env: Env, session_id: u32) -> Option<Address> {
+        match storage::get_game(&env, session_id) {
+            Some(game) => game.winner_address,
+            None => storage::get_tombstone(&env, session_id).and_then(|t| t.winner_address),
+        }
+    }
+
+    /// Query the persistent settlement record for a session, if settled.
+    pub fn get_tombstone(env: Env, session_id: u32) -> Option<Tombstone> {
+        storage::get_tombstone(&env, session_id)
+    }
+
+    /// Query the active timing configuration.
+    pub fn get_timing_config(env: Env) -> TimingConfig {
+        storage::get_timing_config(&env)
+    }
+
+    /// Query the whole deployment configuration at once.
+    pub fn get_config(env: Env) -> Result<Config, Error> {
+        let council = storage::get_council(&env);
+        let mut paused = Vec::new(&env);
+        for op in Operation::ALL {
+            if storage::is_paused(&env, op) {
+                paused.push_back(op);
+            }
+        }
+        let mut variants = Vec::new(&env);
+        for variant_id in storage::get_variant_ids(&env).iter() {
+            let Some(variant) = storage::get_variant(&env, variant_id) else {
+                continue;
+            };
+            let vk_hash = match storage::get_vk(&env, variant.vk_id) {
+                Some(vk) => Some(vk.hash(&env)),
+                None => storage::get_vk_hash(&env, variant.vk_id),
+            };
+            variants.push_back(VariantConfig {
+                variant_id,
+                variant,
+                vk_hash,
+                breaker_tripped: storage::is_breaker_tripped(&env, variant_id),
+            });
+        }
+
+        Ok(Config {
+            admin: storage::get_admin(&env).ok_or(Error::NotInitialized)?,
+            admin_renounced: storage::is_admin_renounced(&env),
+            treasury: storage::get_treasury(&env).ok_or(Error::NotInitialized)?,
+            ohloss: storage::get_ohloss(&env).ok_or(Error::NotInitialized)?,
+            ohloss_version: storage::get_ohloss_version(&env),
+            beacon: storage::get_beacon(&env),
+            arbiter: storage::get_arbiter(&env),
+            name_registry: storage::get_name_registry(&env),
+            council_threshold: council.as_ref().map_or(0, |c| c.threshold),
+            council: council.map_or(Vec::new(&env), |c| c.signers),
+            timelock_delay: storage::get_timelock_delay(&env),
+            rake_bps: storage::get_rake_bps(&env),
+            timing: storage::get_timing_config(&env),
+            rate_limit: storage::get_rate_limit(&env),
+            pair_cooldown: storage::get_pair_cooldown(&env),
+            expiry_warning: storage::get_expiry_warning(&env),
+            dispute_window: storage::get_dispute_window(&env),
+            breaker_threshold: storage::get_breaker_threshold(&env),
+            paused,
+            vk_frozen: vk_locked(&env),
+            variants,
+        })
+    }
+
+    /// Query the lobby-facing fields of a game, including its expiry ledger.
+    /// Player names are resolved when a name registry is configured.
+    pub fn get_game_summary(env: Env, session_id: u32) -> Option<GameSummary> {
+        storage::get_game(&env, session_id).map(|game| GameSummary {
+            player1_name: display_name(&env, &game.player1),
+            player2_name: display_name(&env, &game.player2),
+            player1: game.player1,
+            player2: game.player2,
+            phase: game.phase,
+            winner: game.winner,
+            expires_at: game.expires_at,
+            followers: storage::get_followers(&env, session_id).count,
+        })
+    }
+
+    /// A spectator follows a live game, so lobbies can rank games by
+    /// interest. Each address counts once; following again changes
+    /// nothing. Returns the follower count.
+    pub fn follow_game(env: Env, session_id: u32, follower: Address) -> Result<u32, Error> {
+        let game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        if game.phase >= Phase::Settled {
+            return Err(Error::InvalidState);
+        }
+        follower.require_auth_for_args((session_id,).into_val(&env));
+
+        match storage::add_follower(&env, session_id, &follower, MAX_LISTED_FOLLOWERS) {
+            Some(followers) => {
+                log_action(&env, session_id, &follower, "follow");
+                storage::with_event_seq(&env, session_id, |seq| {
+                    events::emit_game_followed(&env, session_id, bump_seq(seq), &follower, followers.count)
+                });
+                Ok(followers.count)
+            }
+            None => Ok(storage::get_followers(&env, session_id).count),
+        }
+    }
+
+    /// Query a game's followers: the count and the first
+    /// `MAX_LISTED_FOLLOWERS` addresses.
+    pub fn get_followers(env: Env, session_id: u32) -> Followers {
+        storage::get_followers(&env, session_id)
+    }
+
+    /// Query a game's activity log, oldest first: who called which
+    /// entrypoint at which ledger, for the last `MAX_LOG_ENTRIES` actions.
+    /// It outlives the game by up to one game TTL.
+    pub fn get_game_log(env: Env, session_id: u32) -> Vec<LogEntry> {
+        storage::get_game_log(&env, session_id)
+    }
+
+    /// Admin: point `get_game_summary` at a display-name registry.
+    pub fn set_name_registry(env: Env, registry: Address) -> Result<(), Error> {
+        let admin = require_admin(&env)?;
+        audit(&env, "set_name_registry", &admin, (&registry,).to_xdr(&env));
+        require_no_council(&env)?;
+        require_no_timelock(&env)?;
+        apply_set_name_registry(&env, &registry);
+        Ok(())
+    }
+
+    /// Whether the game's temporary entry has passed its expiry ledger. Once
+    /// the entry has lapsed the session is known only from its persistent
+    /// creator record or tombstone, which then report it expired.
+    pub fn is_expired(env: Env, session_id: u32) -> Result<bool, Error> {
+        match storage::get_game(&env, session_id) {
+            Some(game) => Ok(env.ledger().sequence() > game.expires_at),
+            None if storage::get_session_creator(&env, session_id).is_some()
+                || storage::get_tombstone(&env, session_id).is_some() =>
+            {
+                Ok(true)
+            }
+            None => Err(Error::GameNotFound),
+        }
+    }
+
+    /// Ledgers left until the game's join and reveal deadlines and its
+    /// entry's expiry.
+    pub fn get_time_remaining(env: Env, session_id: u32) -> Result<TimeInfo, Error> {
+        let game = storage::get_game(&env, session_id)
+            .ok_or(Error::GameNotFound)?;
+        Ok(time_remaining(&env, &game))
+    }
+}
+
+/// The `soroban-pvp-game` interface, for leaderboards and tournaments that
+/// integrate games generically. Results come from the tombstone, so they
+/// outlive the game entry; each result also publishes `PvpResult`.
+#[contractimpl]
+impl PvpGame for PirateCardsContract {
+    fn game_info(env: Env, session_id: u32) -> Option<GameInfo> {
+        let game = storage::get_game(&env, session_id)?;
+        let status = match game.phase {
+            Phase::Created => GameStatus::Open,
+            Phase::Joined | Phase::Revealed => GameStatus::InProgress,
+            Phase::Settled => GameStatus::Finished,
+            Phase::Cancelled => GameStatus::Cancelled,
+        };
+        let player2 = (game.phase != Phase::Created && game.phase != Phase::Cancelled).then_some(game.player2);
+        Some(GameInfo { player1: game.player1, player2, status })
+    }
+
+    fn get_result(env: Env, session_id: u32) -> Option<GameResult> {
+        storage::get_tombstone(&env, session_id).map(|tombstone| GameResult {
+            draw: tombstone.winner == Winner::Draw,
+            winner: tombstone.winner_address,
+            finished_at: tombstone.settled_at,
+        })
     }
 }
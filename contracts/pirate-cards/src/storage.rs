@@ -1,34 +1,399 @@
-use soroban_sdk::{contracttype, Address, Env};
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, BytesN, Env, FromVal, Map, Symbol, TryFromVal, Val, Vec};
 
-use crate::types::{Game, VerificationKey};
+use crate::escrow::DEFAULT_RAKE_BPS;
+use crate::types::{
+    AdminProposal, Affiliate, BondTerms, Council, CreationWindow, DeckOrder, Dispute,
+    EscrowedBounty, EscrowedStake, EscrowedStakeV2, Followers, Game, GameStats, GameV2, JoinRequest,
+    JoinRequestV2, LobbyEntry, LogEntry, Operation, PendingCompensation, PendingPayout, Phase,
+    PhaseCounts, RateLimit, ReportBond, SessionPage, SessionRange, SettlementReceipt,
+    SettlementReceiptV2, Sponsorship, TimingConfig, TokenVolume, Tombstone, TreasuryAccount,
+    Variant, VerificationKey, VkProvenance,
+};
 
-const GAME_TTL_LEDGERS: u32 = 535_680; // ~30 days at 5s/ledger
+// Defaults used until the admin stores a TimingConfig
+const DEFAULT_GAME_TTL_LEDGERS: u32 = 535_680; // ~30 days at 5s/ledger
+const DEFAULT_JOIN_WINDOW_LEDGERS: u32 = 120_960; // ~7 days
+const DEFAULT_REVEAL_WINDOW_LEDGERS: u32 = 17_280; // ~1 day
+
+/// Variant id of the original 25-card ruleset.
+pub const CLASSIC_VARIANT: u32 = 0;
+
+/// Upper bound on entries returned by a single list query.
+pub const MAX_PAGE_SIZE: u32 = 100;
 
 #[contracttype]
 pub enum DataKey {
     Admin,
+    AdminRenounced,
+    Initialized,
+    SchemaVersion,
+    InstanceExpiry,
+    VkFrozen,
+    Paused(Operation),
+    AuditSeq,
+    BreakerThreshold,
+    ProofFailures(u32),   // variant id
+    BreakerTripped(u32),  // variant id
+    ReportBondTerms,
+    ReportBonds(u32),     // variant id
+    ProofReport(u32, BytesN<32>), // session id, proof hash
+    Council,
+    NextProposalId,
+    TimelockDelay,
+    Proposal(u32),
     OhlossAddress,
-    Vk,
+    OhlossVersion,
+    ResultSink(Address),
+    SponsorToken(Address),
+    Vk(u32),
+    VkHash(u32),
+    VkProvenance(u32),
+    AggregateVk,
+    AggregateVkIds,
+    Variant(u32),
+    VariantIds,
+    VariantDeckOrder(u32),
+    TimingConfig,
+    Beacon,
+    RakeBps,
+    PendingSettlement,    // schema 2 settlement index
+    OpenGames,            // schema 1-2 open-games index
+    PlayerGames(Address), // schema 2-3 player index
+    IndexNode(Index, u32, u32), // depth, id prefix
+    LobbyEntry(u32),
     Game(u32),
+    Bounty(u32),
+    Stake(u32),
+    Sponsors(u32),
+    Treasury,
+    TreasuryAccount(Address),
+    Insurance(Address),
+    Affiliate(BytesN<32>),
+    AffiliateBalance(BytesN<32>, Address),
+    Secp256k1Key(Address),
+    Compensation(u32),
+    SessionCreator(u32),
+    SessionJoiner(u32),
+    JoinRequests(u32),
+    RevealDelegates(u32), // player -> delegate
+    Rematch(u32),         // acknowledged session -> its rematch
+    Followers(u32),
+    Following(u32, Address),
+    GameLog(u32),
+    Tombstone(u32),
+    Receipt(u32),
+    Attestation(u32),
+    DisputeWindow,
+    RateLimit,
+    PairCooldown,
+    ExpiryWarning,
+    LastPairGame(Address, Address), // lower address first
+    Creations(Address),
+    Arbiter,
+    NameRegistry,
+    PendingPayout(u32),
+    Dispute(u32),
+    CorrectionPending(u32),
+    Stats,
+    VariantStats(u32),
+    TokenVolume(Address),
+    SessionRanges,
+    PhaseCounts,          // schema 2 unsharded counts
+    PhaseCountShard(u32),
 }
 
 // --- Admin ---
 
-pub fn get_admin(env: &Env) -> Address {
-    env.storage().instance().get(&DataKey::Admin).unwrap()
+/// None only on an instance deployed without running the constructor.
+pub fn get_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Admin)
 }
 
 pub fn set_admin(env: &Env, admin: &Address) {
     env.storage().instance().set(&DataKey::Admin, admin);
 }
 
+/// Set once by the constructor. Persistent so that no upgrade which
+/// reshapes instance storage can make the contract look uninitialized.
+pub fn is_initialized(env: &Env) -> bool {
+    env.storage().persistent().has(&DataKey::Initialized)
+}
+
+pub fn set_initialized(env: &Env) {
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&DataKey::Initialized, &true);
+    env.storage().persistent().extend_ttl(&DataKey::Initialized, ttl, ttl);
+}
+
+// --- Instance TTL ---
+
+/// Ledger at which the instance entry (admin, Ohloss, configuration and
+/// the contract code with it) lapses, as stamped by the last extension.
+/// Kept in persistent storage so every call does not carry it with the
+/// instance.
+pub fn get_instance_expiry(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::InstanceExpiry)
+        .unwrap_or(0)
+}
+
+/// Extend the instance to the maximum TTL: always when `force`, otherwise
+/// only once less than half of it remains, so routine calls rarely pay
+/// the fee. Returns the expiry ledger.
+pub fn extend_instance(env: &Env, force: bool) -> u32 {
+    let ttl = env.storage().max_ttl();
+    let now = env.ledger().sequence();
+    let expiry = get_instance_expiry(env);
+    if !force && expiry.saturating_sub(now) >= ttl / 2 {
+        return expiry;
+    }
+    env.storage().instance().extend_ttl(ttl, ttl);
+    env.storage().persistent().set(&DataKey::InstanceExpiry, &(now + ttl));
+    env.storage().persistent().extend_ttl(&DataKey::InstanceExpiry, ttl, ttl);
+    now + ttl
+}
+
+/// Layout version of this deployment's storage; 0 for builds that
+/// predate versioning.
+pub fn get_schema_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SchemaVersion)
+        .unwrap_or(0)
+}
+
+pub fn set_schema_version(env: &Env, version: u32) {
+    env.storage().instance().set(&DataKey::SchemaVersion, &version);
+}
+
+pub fn is_admin_renounced(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::AdminRenounced)
+}
+
+pub fn set_admin_renounced(env: &Env) {
+    env.storage().instance().set(&DataKey::AdminRenounced, &true);
+}
+
+pub fn is_vk_frozen(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::VkFrozen)
+}
+
+pub fn set_vk_frozen(env: &Env) {
+    env.storage().instance().set(&DataKey::VkFrozen, &true);
+}
+
+pub fn is_paused(env: &Env, op: Operation) -> bool {
+    env.storage().instance().has(&DataKey::Paused(op))
+}
+
+pub fn set_paused(env: &Env, op: Operation, paused: bool) {
+    if paused {
+        env.storage().instance().set(&DataKey::Paused(op), &true);
+    } else {
+        env.storage().instance().remove(&DataKey::Paused(op));
+    }
+}
+
+pub fn get_breaker_threshold(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::BreakerThreshold)
+        .unwrap_or(crate::DEFAULT_BREAKER_THRESHOLD)
+}
+
+pub fn set_breaker_threshold(env: &Env, threshold: u32) {
+    env.storage().instance().set(&DataKey::BreakerThreshold, &threshold);
+}
+
+/// Consecutive invalid proofs recorded against a variant.
+pub fn get_proof_failures(env: &Env, variant: u32) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ProofFailures(variant))
+        .unwrap_or(0)
+}
+
+pub fn set_proof_failures(env: &Env, variant: u32, failures: u32) {
+    if failures == 0 {
+        env.storage().instance().remove(&DataKey::ProofFailures(variant));
+    } else {
+        env.storage().instance().set(&DataKey::ProofFailures(variant), &failures);
+    }
+}
+
+pub fn is_breaker_tripped(env: &Env, variant: u32) -> bool {
+    env.storage().instance().has(&DataKey::BreakerTripped(variant))
+}
+
+pub fn set_breaker_tripped(env: &Env, variant: u32, tripped: bool) {
+    if tripped {
+        env.storage().instance().set(&DataKey::BreakerTripped(variant), &true);
+    } else {
+        env.storage().instance().remove(&DataKey::BreakerTripped(variant));
+    }
+}
+
+pub fn get_report_bond_terms(env: &Env) -> Option<BondTerms> {
+    env.storage().instance().get(&DataKey::ReportBondTerms)
+}
+
+pub fn set_report_bond_terms(env: &Env, terms: &BondTerms) {
+    env.storage().instance().set(&DataKey::ReportBondTerms, terms);
+}
+
+/// Whether this proof has already been reported against the session.
+pub fn has_proof_report(env: &Env, session_id: u32, proof_hash: &BytesN<32>) -> bool {
+    env.storage()
+        .temporary()
+        .has(&DataKey::ProofReport(session_id, proof_hash.clone()))
+}
+
+pub fn set_proof_report(env: &Env, session_id: u32, proof_hash: &BytesN<32>) {
+    let key = DataKey::ProofReport(session_id, proof_hash.clone());
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().temporary().set(&key, &true);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn add_report_bond(env: &Env, variant: u32, bond: &ReportBond) {
+    let key = DataKey::ReportBonds(variant);
+    let mut bonds: Vec<ReportBond> = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+    bonds.push_back(bond.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &bonds);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Remove and return every report bond held against a variant.
+pub fn take_report_bonds(env: &Env, variant: u32) -> Vec<ReportBond> {
+    let key = DataKey::ReportBonds(variant);
+    let bonds = env.storage().persistent().get(&key).unwrap_or(Vec::new(env));
+    env.storage().persistent().remove(&key);
+    bonds
+}
+
+/// Allocate the next audit-log sequence number, starting at 1.
+pub fn next_audit_seq(env: &Env) -> u32 {
+    let seq: u32 = env.storage().instance().get(&DataKey::AuditSeq).unwrap_or(0) + 1;
+    env.storage().instance().set(&DataKey::AuditSeq, &seq);
+    seq
+}
+
+pub fn get_audit_seq(env: &Env) -> u32 {
+    env.storage().instance().get(&DataKey::AuditSeq).unwrap_or(0)
+}
+
+// --- Council ---
+
+pub fn get_council(env: &Env) -> Option<Council> {
+    env.storage().instance().get(&DataKey::Council)
+}
+
+pub fn set_council(env: &Env, council: &Council) {
+    env.storage().instance().set(&DataKey::Council, council);
+}
+
+/// Ledgers a queued admin operation waits before it can execute.
+pub fn get_timelock_delay(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TimelockDelay)
+        .unwrap_or(0)
+}
+
+pub fn set_timelock_delay(env: &Env, delay: u32) {
+    env.storage().instance().set(&DataKey::TimelockDelay, &delay);
+}
+
+/// Allocate the next proposal id.
+pub fn next_proposal_id(env: &Env) -> u32 {
+    let id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextProposalId)
+        .unwrap_or(0);
+    env.storage().instance().set(&DataKey::NextProposalId, &(id + 1));
+    id
+}
+
+pub fn get_proposal(env: &Env, proposal_id: u32) -> Option<AdminProposal> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Proposal(proposal_id))
+}
+
+pub fn set_proposal(env: &Env, proposal_id: u32, proposal: &AdminProposal) {
+    let key = DataKey::Proposal(proposal_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, proposal);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_proposal(env: &Env, proposal_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Proposal(proposal_id));
+}
+
 // --- Ohloss ---
 
-pub fn get_ohloss(env: &Env) -> Address {
+pub fn get_ohloss(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::OhlossAddress)
+}
+
+/// Protocol version negotiated with the configured Ohloss; 0 without one.
+pub fn get_ohloss_version(env: &Env) -> u32 {
     env.storage()
         .instance()
-        .get(&DataKey::OhlossAddress)
-        .unwrap()
+        .get(&DataKey::OhlossVersion)
+        .unwrap_or(0)
+}
+
+pub fn set_ohloss_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::OhlossVersion, &version);
+}
+
+/// Negotiated protocol version of an allowlisted result sink.
+pub fn get_result_sink(env: &Env, sink: &Address) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ResultSink(sink.clone()))
+}
+
+pub fn set_result_sink(env: &Env, sink: &Address, version: u32) {
+    let key = DataKey::ResultSink(sink.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &version);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_result_sink(env: &Env, sink: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::ResultSink(sink.clone()));
+}
+
+/// Minimum pot sponsorship in an allowlisted token.
+pub fn get_sponsor_token(env: &Env, token: &Address) -> Option<i128> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SponsorToken(token.clone()))
+}
+
+pub fn set_sponsor_token(env: &Env, token: &Address, min_amount: i128) {
+    let key = DataKey::SponsorToken(token.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &min_amount);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_sponsor_token(env: &Env, token: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::SponsorToken(token.clone()));
 }
 
 pub fn set_ohloss(env: &Env, ohloss: &Address) {
@@ -39,32 +404,253 @@ pub fn set_ohloss(env: &Env, ohloss: &Address) {
 
 // --- Verification Key ---
 
-pub fn get_vk(env: &Env) -> VerificationKey {
-    env.storage().instance().get(&DataKey::Vk).unwrap()
+// Keys live in persistent storage so calls that never verify a proof do
+// not load them with the instance. Builds before that kept them in
+// instance storage; such keys are moved over on first read.
+
+pub fn get_vk(env: &Env, vk_id: u32) -> Option<VerificationKey> {
+    let key = DataKey::Vk(vk_id);
+    if let Some(vk) = env.storage().persistent().get(&key) {
+        let ttl = env.storage().max_ttl();
+        env.storage().persistent().extend_ttl(&key, ttl / 2, ttl);
+        return Some(vk);
+    }
+    let vk: VerificationKey = env.storage().instance().get(&key)?;
+    env.storage().instance().remove(&key);
+    set_vk(env, vk_id, &vk);
+    Some(vk)
+}
+
+pub fn set_vk(env: &Env, vk_id: u32, vk: &VerificationKey) {
+    let key = DataKey::Vk(vk_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, vk);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_vk(env: &Env, vk_id: u32) {
+    let key = DataKey::Vk(vk_id);
+    env.storage().persistent().remove(&key);
+    env.storage().instance().remove(&key);
+}
+
+/// Hash of a key that settlers supply with each proof instead of it
+/// being stored.
+pub fn get_vk_hash(env: &Env, vk_id: u32) -> Option<BytesN<32>> {
+    env.storage().persistent().get(&DataKey::VkHash(vk_id))
+}
+
+pub fn set_vk_hash(env: &Env, vk_id: u32, hash: &BytesN<32>) {
+    let key = DataKey::VkHash(vk_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, hash);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn has_vk_hash(env: &Env, vk_id: u32) -> bool {
+    env.storage().persistent().has(&DataKey::VkHash(vk_id))
+}
+
+pub fn remove_vk_hash(env: &Env, vk_id: u32) {
+    env.storage().persistent().remove(&DataKey::VkHash(vk_id));
+}
+
+pub fn get_vk_provenance(env: &Env, vk_id: u32) -> Option<VkProvenance> {
+    env.storage().persistent().get(&DataKey::VkProvenance(vk_id))
+}
+
+pub fn set_vk_provenance(env: &Env, vk_id: u32, provenance: &VkProvenance) {
+    let key = DataKey::VkProvenance(vk_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, provenance);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_vk_provenance(env: &Env, vk_id: u32) {
+    env.storage().persistent().remove(&DataKey::VkProvenance(vk_id));
+}
+
+/// Key for proofs that attest to a whole batch of sessions.
+pub fn get_aggregate_vk(env: &Env) -> Option<VerificationKey> {
+    let vk = env.storage().persistent().get(&DataKey::AggregateVk);
+    if vk.is_some() {
+        let ttl = env.storage().max_ttl();
+        env.storage().persistent().extend_ttl(&DataKey::AggregateVk, ttl / 2, ttl);
+    }
+    vk
+}
+
+pub fn set_aggregate_vk(env: &Env, vk: &VerificationKey) {
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&DataKey::AggregateVk, vk);
+    env.storage().persistent().extend_ttl(&DataKey::AggregateVk, ttl, ttl);
+}
+
+/// Key ids an aggregate batch may mix.
+pub fn get_aggregate_vk_ids(env: &Env) -> Vec<u32> {
+    let ids = env.storage().persistent().get(&DataKey::AggregateVkIds);
+    if ids.is_some() {
+        let ttl = env.storage().max_ttl();
+        env.storage().persistent().extend_ttl(&DataKey::AggregateVkIds, ttl / 2, ttl);
+    }
+    ids.unwrap_or(Vec::new(env))
 }
 
-pub fn set_vk(env: &Env, vk: &VerificationKey) {
-    env.storage().instance().set(&DataKey::Vk, vk);
+pub fn set_aggregate_vk_ids(env: &Env, vk_ids: &Vec<u32>) {
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&DataKey::AggregateVkIds, vk_ids);
+    env.storage().persistent().extend_ttl(&DataKey::AggregateVkIds, ttl, ttl);
 }
 
-pub fn has_vk(env: &Env) -> bool {
-    env.storage().instance().has(&DataKey::Vk)
+// --- Variants ---
+
+/// Registered variant, falling back to the built-in classic ruleset for
+/// id 0 until the admin overrides it.
+pub fn get_variant(env: &Env, variant_id: u32) -> Option<Variant> {
+    let stored = env.storage().instance().get(&DataKey::Variant(variant_id));
+    if stored.is_none() && variant_id == CLASSIC_VARIANT {
+        return Some(Variant {
+            deck_size: 25,
+            suits: 3,
+            rounds: 12,
+            vk_id: 0,
+        });
+    }
+    stored
+}
+
+pub fn set_variant(env: &Env, variant_id: u32, variant: &Variant) {
+    env.storage()
+        .instance()
+        .set(&DataKey::Variant(variant_id), variant);
+    let mut ids = get_variant_ids(env);
+    if !ids.contains(variant_id) {
+        ids.push_back(variant_id);
+        env.storage().instance().set(&DataKey::VariantIds, &ids);
+    }
+}
+
+/// Ids of variants registered since this list was introduced, plus the
+/// classic ruleset.
+pub fn get_variant_ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .instance()
+        .get(&DataKey::VariantIds)
+        .unwrap_or(Vec::from_array(env, [CLASSIC_VARIANT]))
+}
+
+/// How `variant_id`'s circuit lays out the deck; Poseidon weights until
+/// the admin records otherwise.
+pub fn get_variant_deck_order(env: &Env, variant_id: u32) -> DeckOrder {
+    env.storage()
+        .instance()
+        .get(&DataKey::VariantDeckOrder(variant_id))
+        .unwrap_or(DeckOrder::PoseidonWeights)
+}
+
+pub fn set_variant_deck_order(env: &Env, variant_id: u32, order: DeckOrder) {
+    env.storage()
+        .instance()
+        .set(&DataKey::VariantDeckOrder(variant_id), &order);
+}
+
+// --- Timing Config ---
+
+pub fn get_timing_config(env: &Env) -> TimingConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::TimingConfig)
+        .unwrap_or(TimingConfig {
+            game_ttl: DEFAULT_GAME_TTL_LEDGERS,
+            join_window: DEFAULT_JOIN_WINDOW_LEDGERS,
+            reveal_window: DEFAULT_REVEAL_WINDOW_LEDGERS,
+        })
+}
+
+pub fn set_timing_config(env: &Env, config: &TimingConfig) {
+    env.storage().instance().set(&DataKey::TimingConfig, config);
+}
+
+// --- Rake ---
+
+pub fn get_rake_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::RakeBps)
+        .unwrap_or(DEFAULT_RAKE_BPS)
+}
+
+pub fn set_rake_bps(env: &Env, rake_bps: u32) {
+    env.storage().instance().set(&DataKey::RakeBps, &rake_bps);
+}
+
+// --- Randomness Beacon ---
+
+pub fn get_beacon(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Beacon)
+}
+
+pub fn set_beacon(env: &Env, beacon: &Address) {
+    env.storage().instance().set(&DataKey::Beacon, beacon);
 }
 
 // --- Games ---
 
+/// Decode a stored value as the current layout, or when it lacks `marker`,
+/// a field its schema 2 layout `L` did not have, as that layout brought up
+/// to date by `upgrade`.
+fn decode<T, L>(env: &Env, val: Val, marker: Symbol, upgrade: impl FnOnce(L) -> T) -> T
+where
+    T: TryFromVal<Env, Val>,
+    L: TryFromVal<Env, Val>,
+{
+    if Map::<Symbol, Val>::from_val(env, &val).contains_key(marker) {
+        T::from_val(env, &val)
+    } else {
+        upgrade(L::from_val(env, &val))
+    }
+}
+
 pub fn get_game(env: &Env, session_id: u32) -> Option<Game> {
-    env.storage()
-        .temporary()
-        .get(&DataKey::Game(session_id))
+    let val = env.storage().temporary().get(&DataKey::Game(session_id))?;
+    Some(decode(env, val, symbol_short!("starts_at"), |legacy: GameV2| {
+        let vk_id = get_variant(env, legacy.variant).map_or(0, |variant| variant.vk_id);
+        legacy.upgrade(vk_id)
+    }))
 }
 
-pub fn set_game(env: &Env, session_id: u32, game: &Game) {
+/// Write a game, extending its TTL only once less than half of `game_ttl`
+/// remains so most transitions pay no extension fee. The expiry ledger is
+/// stamped into `game.expires_at`, which both tells clients how long the
+/// entry will live and saves reading its TTL back.
+pub fn set_game(env: &Env, session_id: u32, game: &mut Game) {
     let key = DataKey::Game(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    let now = env.ledger().sequence();
+    let refresh = game.expires_at.saturating_sub(now) < ttl / 2;
+    if refresh {
+        game.expires_at = now + ttl;
+    }
     env.storage().temporary().set(&key, game);
-    env.storage()
-        .temporary()
-        .extend_ttl(&key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+    if refresh {
+        env.storage().temporary().extend_ttl(&key, ttl, ttl);
+    }
+}
+
+/// Number events about `session_id` for a caller not holding its game:
+/// `f` gets the stored game's event counter, and the game is written back
+/// once afterwards if `f` advanced it. `f` must not store the game itself.
+/// Events about an evicted game number from 0.
+pub fn with_event_seq<T>(env: &Env, session_id: u32, f: impl FnOnce(&mut u32) -> T) -> T {
+    let mut game = get_game(env, session_id);
+    let start = game.as_ref().map_or(0, |game| game.event_seq);
+    let mut seq = start;
+    let result = f(&mut seq);
+    if let Some(game) = game.as_mut().filter(|_| seq != start) {
+        game.event_seq = seq;
+        set_game(env, session_id, game);
+    }
+    result
 }
 
 pub fn has_game(env: &Env, session_id: u32) -> bool {
@@ -72,3 +658,937 @@ pub fn has_game(env: &Env, session_id: u32) -> bool {
         .temporary()
         .has(&DataKey::Game(session_id))
 }
+
+// --- Session indexes ---
+// Each index is a four-level radix tree of session ids in persistent
+// storage, so clients can discover games without an indexer and no entry
+// grows past 256 ids. A node at depth d holds, sorted, the distinct values
+// of `id >> (24 - 8d)` among the ids sharing its top 8d bits; leaves
+// (depth 3) hold the ids themselves. Inserting and removing touch only the
+// leaf unless it fills from or drains to empty. Every list query takes a
+// `(cursor, limit)` pair and returns ids >= `cursor` in ascending order, at
+// most `limit` (capped at MAX_PAGE_SIZE), plus where to resume; ids
+// inserted behind a cursor are not revisited.
+
+const INDEX_LEAF_DEPTH: u32 = 3;
+/// Nodes one list query reads before handing back a resume cursor.
+const INDEX_SCAN_READS: u32 = 16;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum Index {
+    PendingSettlement,
+    OpenGames,
+    PlayerGames(Address),
+}
+
+/// The node at `depth` on the path to `id`.
+fn node_key(index: &Index, depth: u32, id: u32) -> DataKey {
+    let prefix = (id as u64 >> (32 - 8 * depth)) as u32;
+    DataKey::IndexNode(index.clone(), depth, prefix)
+}
+
+/// What the node at `depth` records for `id`.
+fn node_child(depth: u32, id: u32) -> u32 {
+    id >> (24 - 8 * depth)
+}
+
+/// The first id after the subtree at `depth` holding `id`, if any.
+fn past_subtree(depth: u32, id: u32) -> Option<u32> {
+    let shift = 32 - 8 * depth;
+    let next = ((id as u64 >> shift) + 1) << shift;
+    u32::try_from(next).ok()
+}
+
+fn get_node(env: &Env, key: &DataKey) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Nodes outlive any game they point at, so they take the longest TTL and
+/// are pruned through removal and `gc_indexes` instead.
+fn set_node(env: &Env, key: &DataKey, node: &Vec<u32>) {
+    if node.is_empty() {
+        env.storage().persistent().remove(key);
+        return;
+    }
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(key, node);
+    env.storage().persistent().extend_ttl(key, ttl, ttl);
+}
+
+fn index_insert(env: &Env, index: &Index, session_id: u32) {
+    for depth in (0..=INDEX_LEAF_DEPTH).rev() {
+        let key = node_key(index, depth, session_id);
+        let mut node = get_node(env, &key);
+        let Err(pos) = node.binary_search(node_child(depth, session_id)) else {
+            return;
+        };
+        node.insert(pos, node_child(depth, session_id));
+        set_node(env, &key, &node);
+        if node.len() > 1 {
+            return;
+        }
+    }
+}
+
+fn index_remove(env: &Env, index: &Index, session_id: u32) {
+    for depth in (0..=INDEX_LEAF_DEPTH).rev() {
+        let key = node_key(index, depth, session_id);
+        let mut node = get_node(env, &key);
+        let Ok(pos) = node.binary_search(node_child(depth, session_id)) else {
+            return;
+        };
+        node.remove(pos);
+        set_node(env, &key, &node);
+        if !node.is_empty() {
+            return;
+        }
+    }
+}
+
+fn index_contains(env: &Env, index: &Index, session_id: u32) -> bool {
+    let leaf = node_key(index, INDEX_LEAF_DEPTH, session_id);
+    get_node(env, &leaf).binary_search(session_id).is_ok()
+}
+
+/// Walk down from the root to the first leaf holding ids >= `cursor`,
+/// collecting from each leaf in turn.
+fn index_list(env: &Env, index: &Index, cursor: u32, limit: u32) -> SessionPage {
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let mut ids = Vec::new(env);
+    let mut from = Some(cursor);
+    let mut reads = 0;
+    'walk: while let Some(start) = from {
+        if ids.len() >= limit || reads >= INDEX_SCAN_READS {
+            break;
+        }
+        let mut at = start;
+        for depth in 0..=INDEX_LEAF_DEPTH {
+            let node = get_node(env, &node_key(index, depth, at));
+            reads += 1;
+            let want = node_child(depth, at);
+            let pos = match node.binary_search(want) {
+                Ok(pos) | Err(pos) => pos,
+            };
+            if depth == INDEX_LEAF_DEPTH {
+                for id in node.slice(pos..).iter() {
+                    if ids.len() >= limit {
+                        from = Some(id);
+                        break 'walk;
+                    }
+                    ids.push_back(id);
+                }
+                from = past_subtree(depth, at);
+                continue 'walk;
+            }
+            match node.get(pos) {
+                Some(child) if child == want => {}
+                Some(child) => at = child << (24 - 8 * depth),
+                None => {
+                    from = if depth == 0 { None } else { past_subtree(depth, at) };
+                    continue 'walk;
+                }
+            }
+        }
+    }
+    SessionPage { ids, next: from }
+}
+
+// Games in Phase::Revealed, awaiting a settlement proof.
+
+pub fn add_pending_settlement(env: &Env, session_id: u32) {
+    index_insert(env, &Index::PendingSettlement, session_id);
+}
+
+pub fn remove_pending_settlement(env: &Env, session_id: u32) {
+    index_remove(env, &Index::PendingSettlement, session_id);
+}
+
+pub fn is_pending_settlement(env: &Env, session_id: u32) -> bool {
+    index_contains(env, &Index::PendingSettlement, session_id)
+}
+
+pub fn list_pending_settlement(env: &Env, cursor: u32, limit: u32) -> SessionPage {
+    index_list(env, &Index::PendingSettlement, cursor, limit)
+}
+
+// Games in Phase::Created, waiting for a second player.
+
+// Each carries a LobbyEntry beside the index so quick play can filter
+// without loading games. The entry lives in temporary storage alongside
+// the game it describes.
+
+pub fn get_lobby_entry(env: &Env, session_id: u32) -> Option<LobbyEntry> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::LobbyEntry(session_id))
+}
+
+pub fn add_open_game(env: &Env, session_id: u32, entry: &LobbyEntry) {
+    let key = DataKey::LobbyEntry(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().temporary().set(&key, entry);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+    index_insert(env, &Index::OpenGames, session_id);
+}
+
+pub fn remove_open_game(env: &Env, session_id: u32) {
+    env.storage().temporary().remove(&DataKey::LobbyEntry(session_id));
+    index_remove(env, &Index::OpenGames, session_id);
+}
+
+pub fn list_open_games(env: &Env, cursor: u32, limit: u32) -> SessionPage {
+    index_list(env, &Index::OpenGames, cursor, limit)
+}
+
+/// Remove and return the open-games index as schema 1 stored it, a plain
+/// sorted Vec of session ids.
+pub fn take_legacy_open_games(env: &Env) -> Vec<u32> {
+    let index = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OpenGames)
+        .unwrap_or(Vec::new(env));
+    env.storage().persistent().remove(&DataKey::OpenGames);
+    index
+}
+
+/// Remove and return the ids of the open-games index as schema 2 stored
+/// it, a single map to each game's LobbyEntry.
+pub fn take_legacy_lobby(env: &Env) -> Vec<u32> {
+    let lobby: Map<u32, LobbyEntry> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OpenGames)
+        .unwrap_or(Map::new(env));
+    env.storage().persistent().remove(&DataKey::OpenGames);
+    lobby.keys()
+}
+
+/// Remove and return the settlement index as schema 2 stored it, a plain
+/// sorted Vec of session ids.
+pub fn take_legacy_pending_settlement(env: &Env) -> Vec<u32> {
+    let index = env
+        .storage()
+        .persistent()
+        .get(&DataKey::PendingSettlement)
+        .unwrap_or(Vec::new(env));
+    env.storage().persistent().remove(&DataKey::PendingSettlement);
+    index
+}
+
+// Games a player has created or joined.
+
+/// Fold a schema 2 player index, a plain sorted Vec under the player's key,
+/// into the paged one the first time the player's index is touched; there
+/// is no list of players to migrate them all at once.
+fn upgrade_player_games(env: &Env, player: &Address) {
+    let key = DataKey::PlayerGames(player.clone());
+    let Some(legacy) = env.storage().persistent().get::<_, Vec<u32>>(&key) else {
+        return;
+    };
+    env.storage().persistent().remove(&key);
+    for session_id in legacy.iter() {
+        index_insert(env, &Index::PlayerGames(player.clone()), session_id);
+    }
+}
+
+pub fn add_player_game(env: &Env, player: &Address, session_id: u32) {
+    upgrade_player_games(env, player);
+    index_insert(env, &Index::PlayerGames(player.clone()), session_id);
+}
+
+pub fn remove_player_game(env: &Env, player: &Address, session_id: u32) {
+    upgrade_player_games(env, player);
+    index_remove(env, &Index::PlayerGames(player.clone()), session_id);
+}
+
+pub fn is_player_game(env: &Env, player: &Address, session_id: u32) -> bool {
+    index_contains(env, &Index::PlayerGames(player.clone()), session_id)
+        || env
+            .storage()
+            .persistent()
+            .get::<_, Vec<u32>>(&DataKey::PlayerGames(player.clone()))
+            .is_some_and(|legacy| legacy.binary_search(session_id).is_ok())
+}
+
+pub fn list_player_games(env: &Env, player: &Address, cursor: u32, limit: u32) -> SessionPage {
+    upgrade_player_games(env, player);
+    index_list(env, &Index::PlayerGames(player.clone()), cursor, limit)
+}
+
+// --- Bounties ---
+
+pub fn get_bounty(env: &Env, session_id: u32) -> Option<EscrowedBounty> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Bounty(session_id))
+}
+
+pub fn set_bounty(env: &Env, session_id: u32, bounty: &EscrowedBounty) {
+    let key = DataKey::Bounty(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, bounty);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Remove and return the bounty for a session, if one is escrowed.
+pub fn take_bounty(env: &Env, session_id: u32) -> Option<EscrowedBounty> {
+    let bounty = get_bounty(env, session_id)?;
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Bounty(session_id));
+    Some(bounty)
+}
+
+// --- Stakes ---
+
+pub fn has_stake(env: &Env, session_id: u32) -> bool {
+    env.storage().persistent().has(&DataKey::Stake(session_id))
+}
+
+pub fn get_stake(env: &Env, session_id: u32) -> Option<EscrowedStake> {
+    let val = env.storage().persistent().get(&DataKey::Stake(session_id))?;
+    Some(decode(env, val, symbol_short!("amounts"), EscrowedStakeV2::upgrade))
+}
+
+pub fn set_stake(env: &Env, session_id: u32, stake: &EscrowedStake) {
+    let key = DataKey::Stake(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, stake);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Remove and return the stakes for a session, if any are escrowed.
+pub fn take_stake(env: &Env, session_id: u32) -> Option<EscrowedStake> {
+    let stake = get_stake(env, session_id)?;
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Stake(session_id));
+    Some(stake)
+}
+
+/// Keep a session's escrow alive while its payout is held for disputes.
+pub fn hold_escrow(env: &Env, session_id: u32) {
+    let ttl = env.storage().max_ttl();
+    for key in [DataKey::Stake(session_id), DataKey::Sponsors(session_id)] {
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().extend_ttl(&key, ttl, ttl);
+        }
+    }
+}
+
+// --- Rate limiting ---
+
+pub fn get_rate_limit(env: &Env) -> RateLimit {
+    env.storage()
+        .instance()
+        .get(&DataKey::RateLimit)
+        .unwrap_or_default()
+}
+
+pub fn set_rate_limit(env: &Env, limit: &RateLimit) {
+    env.storage().instance().set(&DataKey::RateLimit, limit);
+}
+
+pub fn get_creation_window(env: &Env, creator: &Address) -> Option<CreationWindow> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Creations(creator.clone()))
+}
+
+/// Kept only as long as the window it counts.
+pub fn set_creation_window(env: &Env, creator: &Address, window: &CreationWindow, ttl: u32) {
+    let key = DataKey::Creations(creator.clone());
+    env.storage().temporary().set(&key, window);
+    env.storage().temporary().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Pair cooldown ---
+
+/// Ledgers before the same two addresses may start another rated game.
+pub fn get_pair_cooldown(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::PairCooldown)
+        .unwrap_or(0)
+}
+
+pub fn set_pair_cooldown(env: &Env, cooldown: u32) {
+    env.storage().instance().set(&DataKey::PairCooldown, &cooldown);
+}
+
+// --- Expiry warnings ---
+
+/// Ledgers before a game's next deadline or expiry within which touching
+/// it publishes `GameExpiringSoon`. Zero disables the warnings.
+pub fn get_expiry_warning(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::ExpiryWarning)
+        .unwrap_or(0)
+}
+
+pub fn set_expiry_warning(env: &Env, window: u32) {
+    env.storage().instance().set(&DataKey::ExpiryWarning, &window);
+}
+
+fn pair_key(a: &Address, b: &Address) -> DataKey {
+    if a < b {
+        DataKey::LastPairGame(a.clone(), b.clone())
+    } else {
+        DataKey::LastPairGame(b.clone(), a.clone())
+    }
+}
+
+/// Ledger at which the pair last started a rated game, if recently.
+pub fn get_last_pair_game(env: &Env, a: &Address, b: &Address) -> Option<u32> {
+    env.storage().temporary().get(&pair_key(a, b))
+}
+
+/// Kept only for the cooldown it enforces.
+pub fn set_last_pair_game(env: &Env, a: &Address, b: &Address, cooldown: u32) {
+    let key = pair_key(a, b);
+    env.storage().temporary().set(&key, &env.ledger().sequence());
+    env.storage().temporary().extend_ttl(&key, cooldown, cooldown);
+}
+
+// --- Disputes ---
+
+/// Ledgers after settlement during which a staked result can be disputed.
+/// Zero pays out at settlement.
+pub fn get_dispute_window(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::DisputeWindow)
+        .unwrap_or(0)
+}
+
+pub fn set_dispute_window(env: &Env, window: u32) {
+    env.storage().instance().set(&DataKey::DisputeWindow, &window);
+}
+
+pub fn get_arbiter(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Arbiter)
+}
+
+pub fn set_arbiter(env: &Env, arbiter: &Address) {
+    env.storage().instance().set(&DataKey::Arbiter, arbiter);
+}
+
+pub fn get_name_registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::NameRegistry)
+}
+
+pub fn set_name_registry(env: &Env, registry: &Address) {
+    env.storage().instance().set(&DataKey::NameRegistry, registry);
+}
+
+pub fn get_pending_payout(env: &Env, session_id: u32) -> Option<PendingPayout> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PendingPayout(session_id))
+}
+
+pub fn set_pending_payout(env: &Env, session_id: u32, payout: &PendingPayout) {
+    let key = DataKey::PendingPayout(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, payout);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_pending_payout(env: &Env, session_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PendingPayout(session_id));
+}
+
+pub fn get_dispute(env: &Env, session_id: u32) -> Option<Dispute> {
+    env.storage().persistent().get(&DataKey::Dispute(session_id))
+}
+
+pub fn set_dispute(env: &Env, session_id: u32, dispute: &Dispute) {
+    let key = DataKey::Dispute(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, dispute);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Whether an overturned result still has to reach Ohloss.
+pub fn is_correction_pending(env: &Env, session_id: u32) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::CorrectionPending(session_id))
+}
+
+pub fn set_correction_pending(env: &Env, session_id: u32, pending: bool) {
+    let key = DataKey::CorrectionPending(session_id);
+    if pending {
+        let ttl = get_timing_config(env).game_ttl;
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, ttl, ttl);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+pub fn remove_dispute(env: &Env, session_id: u32) {
+    env.storage().persistent().remove(&DataKey::Dispute(session_id));
+}
+
+// --- Sponsorships ---
+
+pub fn get_sponsorships(env: &Env, session_id: u32) -> Vec<Sponsorship> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Sponsors(session_id))
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_sponsorships(env: &Env, session_id: u32, sponsorships: &Vec<Sponsorship>) {
+    let key = DataKey::Sponsors(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, sponsorships);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Remove and return every sponsorship for a session.
+pub fn take_sponsorships(env: &Env, session_id: u32) -> Vec<Sponsorship> {
+    let sponsorships = get_sponsorships(env, session_id);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Sponsors(session_id));
+    sponsorships
+}
+
+// --- Reserved session ranges ---
+
+pub fn get_session_ranges(env: &Env) -> Vec<SessionRange> {
+    env.storage()
+        .instance()
+        .get(&DataKey::SessionRanges)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_session_ranges(env: &Env, ranges: &Vec<SessionRange>) {
+    env.storage().instance().set(&DataKey::SessionRanges, ranges);
+}
+
+// --- Join requests ---
+
+pub fn get_join_requests(env: &Env, session_id: u32) -> Vec<JoinRequest> {
+    let stored: Vec<Val> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::JoinRequests(session_id))
+        .unwrap_or(Vec::new(env));
+    let mut requests = Vec::new(env);
+    for val in stored.iter() {
+        requests.push_back(decode(env, val, symbol_short!("metadata"), JoinRequestV2::upgrade));
+    }
+    requests
+}
+
+pub fn set_join_requests(env: &Env, session_id: u32, requests: &Vec<JoinRequest>) {
+    let key = DataKey::JoinRequests(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, requests);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Remove and return every join request for a session.
+pub fn take_join_requests(env: &Env, session_id: u32) -> Vec<JoinRequest> {
+    let requests = get_join_requests(env, session_id);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::JoinRequests(session_id));
+    requests
+}
+
+// --- Reveal delegates ---
+
+/// The address `player` allowed to reveal their seed, if any.
+pub fn get_reveal_delegate(env: &Env, session_id: u32, player: &Address) -> Option<Address> {
+    let delegates: Map<Address, Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::RevealDelegates(session_id))?;
+    delegates.get(player.clone())
+}
+
+pub fn set_reveal_delegate(env: &Env, session_id: u32, player: &Address, delegate: &Address) {
+    let key = DataKey::RevealDelegates(session_id);
+    let mut delegates: Map<Address, Address> = env.storage().persistent().get(&key).unwrap_or(Map::new(env));
+    delegates.set(player.clone(), delegate.clone());
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, &delegates);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_reveal_delegate(env: &Env, session_id: u32, player: &Address) {
+    let key = DataKey::RevealDelegates(session_id);
+    let Some(mut delegates) = env.storage().persistent().get::<_, Map<Address, Address>>(&key) else {
+        return;
+    };
+    delegates.remove(player.clone());
+    if delegates.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &delegates);
+    }
+}
+
+/// Forget every delegate for a session once it can no longer be revealed.
+pub fn remove_reveal_delegates(env: &Env, session_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::RevealDelegates(session_id));
+}
+
+// --- Rematches ---
+
+/// The session created as the rematch of `session_id`, if any.
+pub fn get_rematch(env: &Env, session_id: u32) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Rematch(session_id))
+}
+
+pub fn set_rematch(env: &Env, session_id: u32, rematch: u32) {
+    let key = DataKey::Rematch(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, &rematch);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Free `session_id` for another rematch once its rematch is cancelled.
+pub fn remove_rematch(env: &Env, session_id: u32) {
+    env.storage().persistent().remove(&DataKey::Rematch(session_id));
+}
+
+// --- Followers ---
+
+pub fn get_followers(env: &Env, session_id: u32) -> Followers {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Followers(session_id))
+        .unwrap_or(Followers { count: 0, listed: Vec::new(env) })
+}
+
+/// Count `follower`, listing them while there is room, and return the
+/// updated followers; None if they already follow. Both entries live as
+/// long as a game entry.
+pub fn add_follower(env: &Env, session_id: u32, follower: &Address, max_listed: u32) -> Option<Followers> {
+    let following = DataKey::Following(session_id, follower.clone());
+    if env.storage().persistent().has(&following) {
+        return None;
+    }
+    let mut followers = get_followers(env, session_id);
+    followers.count += 1;
+    if followers.listed.len() < max_listed {
+        followers.listed.push_back(follower.clone());
+    }
+    let key = DataKey::Followers(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&following, &());
+    env.storage().persistent().extend_ttl(&following, ttl, ttl);
+    env.storage().persistent().set(&key, &followers);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+    Some(followers)
+}
+
+// --- Activity log ---
+
+pub fn get_game_log(env: &Env, session_id: u32) -> Vec<LogEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::GameLog(session_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Append `entry`, dropping the oldest entries beyond `max_entries`. The
+/// log lives as long as a game entry from its latest append, so it is
+/// still there for a while after the game itself is gone.
+pub fn append_game_log(env: &Env, session_id: u32, entry: &LogEntry, max_entries: u32) {
+    let mut log = get_game_log(env, session_id);
+    log.push_back(entry.clone());
+    while log.len() > max_entries {
+        log.pop_front();
+    }
+    let key = DataKey::GameLog(session_id);
+    let ttl = get_timing_config(env).game_ttl;
+    env.storage().persistent().set(&key, &log);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Treasury ---
+
+/// Account allowed to withdraw rake; the admin until one is set.
+pub fn get_treasury(env: &Env) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Treasury)
+        .or_else(|| get_admin(env))
+}
+
+pub fn set_treasury(env: &Env, treasury: &Address) {
+    env.storage().instance().set(&DataKey::Treasury, treasury);
+}
+
+pub fn get_treasury_account(env: &Env, token: &Address) -> TreasuryAccount {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TreasuryAccount(token.clone()))
+        .unwrap_or(TreasuryAccount {
+            accrued: 0,
+            withdrawn: 0,
+        })
+}
+
+pub fn set_treasury_account(env: &Env, token: &Address, account: &TreasuryAccount) {
+    let key = DataKey::TreasuryAccount(token.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, account);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Affiliates ---
+
+pub fn get_affiliate(env: &Env, code: &BytesN<32>) -> Option<Affiliate> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Affiliate(code.clone()))
+}
+
+pub fn set_affiliate(env: &Env, code: &BytesN<32>, affiliate: &Affiliate) {
+    let key = DataKey::Affiliate(code.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, affiliate);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Uncompressed secp256k1 key a player signs agreed outcomes with.
+pub fn get_secp256k1_key(env: &Env, player: &Address) -> Option<BytesN<65>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Secp256k1Key(player.clone()))
+}
+
+pub fn set_secp256k1_key(env: &Env, player: &Address, key: &BytesN<65>) {
+    let key_entry = DataKey::Secp256k1Key(player.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key_entry, key);
+    env.storage().persistent().extend_ttl(&key_entry, ttl, ttl);
+}
+
+/// Claimable balance owed to an affiliate code in `token`.
+pub fn get_affiliate_balance(env: &Env, code: &BytesN<32>, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AffiliateBalance(code.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_affiliate_balance(env: &Env, code: &BytesN<32>, token: &Address, balance: i128) {
+    let key = DataKey::AffiliateBalance(code.clone(), token.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &balance);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Insurance ---
+
+pub fn get_insurance_balance(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Insurance(token.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_insurance_balance(env: &Env, token: &Address, balance: i128) {
+    let key = DataKey::Insurance(token.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &balance);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn get_compensation(env: &Env, session_id: u32) -> Option<PendingCompensation> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Compensation(session_id))
+}
+
+pub fn set_compensation(env: &Env, session_id: u32, compensation: &PendingCompensation) {
+    let key = DataKey::Compensation(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, compensation);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_compensation(env: &Env, session_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Compensation(session_id));
+}
+
+// --- Session id consumption ---
+// Persistent marker written at creation so a session id is never reused
+// after its temporary Game entry is evicted.
+
+pub fn get_session_creator(env: &Env, session_id: u32) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SessionCreator(session_id))
+}
+
+pub fn set_session_creator(env: &Env, session_id: u32, creator: &Address) {
+    let key = DataKey::SessionCreator(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, creator);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Who joined a session, kept until the session leaves the indexes so
+/// `gc_indexes` can clean up the joiner's player-games entry.
+pub fn get_session_joiner(env: &Env, session_id: u32) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SessionJoiner(session_id))
+}
+
+pub fn set_session_joiner(env: &Env, session_id: u32, joiner: &Address) {
+    let key = DataKey::SessionJoiner(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, joiner);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn remove_session_joiner(env: &Env, session_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::SessionJoiner(session_id));
+}
+
+// --- Settlement receipts ---
+
+pub fn get_receipt(env: &Env, session_id: u32) -> Option<SettlementReceipt> {
+    let val = env.storage().persistent().get(&DataKey::Receipt(session_id))?;
+    Some(decode(env, val, symbol_short!("terms"), SettlementReceiptV2::upgrade))
+}
+
+pub fn set_receipt(env: &Env, session_id: u32, receipt: &SettlementReceipt) {
+    let key = DataKey::Receipt(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, receipt);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+/// Canonical result message written at settlement; see `types::Attestation`.
+pub fn get_attestation(env: &Env, session_id: u32) -> Option<Bytes> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Attestation(session_id))
+}
+
+pub fn set_attestation(env: &Env, session_id: u32, message: &Bytes) {
+    let key = DataKey::Attestation(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, message);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Statistics ---
+
+/// Deployment-wide counters, or one variant's when `variant` is set.
+pub fn get_stats(env: &Env, variant: Option<u32>) -> GameStats {
+    let key = match variant {
+        Some(id) => DataKey::VariantStats(id),
+        None => DataKey::Stats,
+    };
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+/// Shards the phase counts are spread over by session id, so games moving
+/// at the same time rarely write the same entry. Every transition of one
+/// session lands in the same shard.
+const PHASE_COUNT_SHARDS: u32 = 8;
+
+fn update_phase_shard(env: &Env, session_id: u32, update: impl FnOnce(&mut PhaseCounts)) {
+    let key = DataKey::PhaseCountShard(session_id % PHASE_COUNT_SHARDS);
+    let mut counts: PhaseCounts = env.storage().persistent().get(&key).unwrap_or_default();
+    update(&mut counts);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &counts);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn get_phase_counts(env: &Env) -> PhaseCounts {
+    let mut total = PhaseCounts::default();
+    for shard in 0..PHASE_COUNT_SHARDS {
+        if let Some(counts) = env.storage().persistent().get(&DataKey::PhaseCountShard(shard)) {
+            total.add(&counts);
+        }
+    }
+    total
+}
+
+/// Count `session_id` leaving `from` (None when just created) for `to`.
+pub fn move_phase(env: &Env, session_id: u32, from: Option<Phase>, to: Phase) {
+    update_phase_shard(env, session_id, |counts| {
+        if let Some(from) = from {
+            let slot = counts.slot(from);
+            *slot = slot.saturating_sub(1);
+        }
+        *counts.slot(to) += 1;
+    });
+}
+
+/// Stop counting `session_id` in `phase`, once its entry has expired there.
+pub fn uncount_phase(env: &Env, session_id: u32, phase: Phase) {
+    update_phase_shard(env, session_id, |counts| {
+        let slot = counts.slot(phase);
+        *slot = slot.saturating_sub(1);
+    });
+}
+
+pub fn set_stats(env: &Env, variant: Option<u32>, stats: &GameStats) {
+    let key = match variant {
+        Some(id) => DataKey::VariantStats(id),
+        None => DataKey::Stats,
+    };
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, stats);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+pub fn get_token_volume(env: &Env, token: &Address) -> TokenVolume {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenVolume(token.clone()))
+        .unwrap_or_default()
+}
+
+pub fn set_token_volume(env: &Env, token: &Address, volume: &TokenVolume) {
+    let key = DataKey::TokenVolume(token.clone());
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, volume);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+// --- Tombstones ---
+
+pub fn get_tombstone(env: &Env, session_id: u32) -> Option<Tombstone> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Tombstone(session_id))
+}
+
+pub fn set_tombstone(env: &Env, session_id: u32, tombstone: &Tombstone) {
+    let key = DataKey::Tombstone(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, tombstone);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
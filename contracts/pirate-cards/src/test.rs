@@ -1,11 +1,32 @@
 #![cfg(test)]
+extern crate mock_beacon;
+extern crate mock_observer;
 extern crate mock_ohloss;
+extern crate mock_wallet;
+extern crate name_registry;
+extern crate std;
 
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+use soroban_sdk::{
+    address_payload::AddressPayload,
+    crypto::bn254::{Bn254G1Affine, Fr},
+    testutils::{Address as _, AuthorizedFunction, Events as _, Ledger, MockAuth, MockAuthInvoke},
+    token::{StellarAssetClient, TokenClient},
+    vec, xdr, xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+
+use soroban_pvp_game::{GameInfo, GameResult, GameStatus, PvpGameClient, PvpResult};
 
 use crate::{
-    types::Game, Error, PirateCardsContract, PirateCardsContractClient, PHASE_CREATED,
-    PHASE_JOINED, PHASE_REVEALED,
+    storage,
+    types::{
+        AdminAction, AggregateEntry, BatchSettlement, BondTerms, CommitScheme, Council, DeckOrder,
+        Game, GameOptions, Groth16Proof, JoinOptions, LogEntry, MatchFilter, Operation,
+        OutcomePayload, Phase, PhaseCounts, PublicInputs, RateLimit, SeedCommitment, SeedOpening,
+        SessionPage, TimingConfig, Variant, VerificationKey, VkProvenance, Winner,
+        PUBLIC_INPUT_COUNT,
+    },
+    verifier, Error, PirateCardsContract, PirateCardsContractClient, OHLOSS_CAP_CORRECTIONS,
+    OHLOSS_CAP_DRAWS, OHLOSS_CAP_FORFEITS, OHLOSS_CAP_RATINGS, OHLOSS_CAP_SCORES,
 };
 
 fn setup_env() -> (
@@ -26,7 +47,7 @@ fn setup_env() -> (
     let player2 = Address::generate(&env);
 
     let contract_id =
-        env.register(PirateCardsContract, (&admin, &ohloss_id));
+        env.register(PirateCardsContract, (&admin, &ohloss_id, &None::<VerificationKey>));
     let client = PirateCardsContractClient::new(&env, &contract_id);
 
     (env, contract_id, client, admin, ohloss_id, player1, player2)
@@ -38,19 +59,181 @@ fn fake_commit(env: &Env, val: u8) -> BytesN<32> {
     BytesN::from_array(env, &arr)
 }
 
+fn u32_field(env: &Env, value: u32) -> BytesN<32> {
+    let mut arr = [0u8; 32];
+    arr[28..32].copy_from_slice(&value.to_be_bytes());
+    BytesN::from_array(env, &arr)
+}
+
+fn zero_proof(env: &Env) -> Groth16Proof {
+    Groth16Proof {
+        pi_a: BytesN::from_array(env, &[0u8; 64]),
+        pi_b: BytesN::from_array(env, &[0u8; 128]),
+        pi_c: BytesN::from_array(env, &[0u8; 64]),
+    }
+}
+
+/// Placeholder VK with `n_ic` IC entries; points are never valid curve points.
+fn dummy_vk(env: &Env, n_ic: u32) -> VerificationKey {
+    let mut ic = Vec::new(env);
+    for i in 0..n_ic {
+        ic.push_back(BytesN::from_array(env, &[i as u8 + 1; 64]));
+    }
+    VerificationKey {
+        alpha_g1: BytesN::from_array(env, &[1u8; 64]),
+        beta_g2: BytesN::from_array(env, &[2u8; 128]),
+        gamma_g2: BytesN::from_array(env, &[3u8; 128]),
+        delta_g2: BytesN::from_array(env, &[4u8; 128]),
+        ic,
+        negated_b: false,
+    }
+}
+
+// BN254 generators in the host encoding (G2: X_c1 || X_c0 || Y_c1 || Y_c0).
+const G2_GEN: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+const G2_GEN_NEG: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed275dc4a288d1afb3cbb1ac09187524c7db36395df7be3b99e673b13a075a65ec1d9befcd05a5323e6da4d435f3b617cdb3af83285c2df711ef39c01571827f9d";
+
+// On the twist (x = 1) but outside the prime-order G2 subgroup.
+const G2_OFF_SUBGROUP: &str = "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010d1271953ed9ea0836846e70a1934187998c7f790cb4d7511b7f8da82de048a42869111d5381f072f8e2728fdb825a51aadd70e52c9830e9ab4b871c0531f1bb";
+
+fn hex_bytes<const N: usize>(env: &Env, hex: &str) -> BytesN<N> {
+    let mut arr = [0u8; N];
+    for (i, byte) in arr.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+    }
+    BytesN::from_array(env, &arr)
+}
+
+fn g1_gen(env: &Env) -> BytesN<64> {
+    let mut arr = [0u8; 64];
+    arr[31] = 1;
+    arr[63] = 2;
+    BytesN::from_array(env, &arr)
+}
+
+/// A VK that accepts any public inputs: alpha = G1, beta = gamma = delta = G2
+/// and every IC point is the identity, so vk_x is always the identity.
+fn synthetic_vk(env: &Env, negated_b: bool) -> VerificationKey {
+    let mut ic = Vec::new(env);
+    for _ in 0..=PUBLIC_INPUT_COUNT {
+        ic.push_back(BytesN::from_array(env, &[0u8; 64]));
+    }
+    VerificationKey {
+        alpha_g1: g1_gen(env),
+        beta_g2: hex_bytes(env, G2_GEN),
+        gamma_g2: hex_bytes(env, G2_GEN),
+        delta_g2: hex_bytes(env, G2_GEN),
+        ic,
+        negated_b,
+    }
+}
+
+/// A proof satisfying `synthetic_vk`: e(2·G1, G2) == e(G1, G2) · e(G1, G2).
+fn synthetic_proof(env: &Env, negated_b: bool) -> Groth16Proof {
+    let g1 = Bn254G1Affine::from_bytes(g1_gen(env));
+    let two_g1 = env.crypto().bn254().g1_add(&g1, &g1);
+    Groth16Proof {
+        pi_a: two_g1.to_bytes(),
+        pi_b: hex_bytes(env, if negated_b { G2_GEN_NEG } else { G2_GEN }),
+        pi_c: g1_gen(env),
+    }
+}
+
+/// Create, join and reveal session 1, returning public inputs that match
+/// the on-chain state with player1 as the claimed winner.
+fn revealed_game(
+    env: &Env,
+    client: &PirateCardsContractClient,
+    p1: &Address,
+    p2: &Address,
+) -> PublicInputs {
+    let commit1 = fake_commit(env, 0xAA);
+    let commit2 = fake_commit(env, 0xBB);
+    let seed1 = fake_commit(env, 0x11);
+    let seed2 = fake_commit(env, 0x22);
+
+    client.create_game(&1u32, p1, &commit1, &no_options(env));
+    client.join_game(&1u32, p2, &commit2);
+    client.reveal_seed(&1u32, p1, &seed1, &SeedOpening::Plain);
+    client.reveal_seed(&1u32, p2, &seed2, &SeedOpening::Plain);
+
+    PublicInputs {
+        seed_commit1: commit1,
+        seed_commit2: commit2,
+        seed1,
+        seed2,
+        session_id: u32_field(env, 1),
+        winner: fake_commit(env, 1),
+        beacon: fake_commit(env, 0),
+        deck_size: u32_field(env, 25),
+        suits: u32_field(env, 3),
+    }
+}
+
+/// Register a Stellar asset and mint `amount` to each holder.
+fn create_token(env: &Env, holders: &[&Address], amount: i128) -> (Address, TokenClient<'static>) {
+    let issuer = Address::generate(env);
+    let token = env.register_stellar_asset_contract_v2(issuer).address();
+    let admin_client = StellarAssetClient::new(env, &token);
+    for holder in holders {
+        admin_client.mint(holder, &amount);
+    }
+    (token.clone(), TokenClient::new(env, &token))
+}
+
+fn no_options(env: &Env) -> GameOptions {
+    GameOptions {
+        bounty_token: None,
+        bounty_amount: 0,
+        commit_scheme: CommitScheme::Poseidon,
+        use_beacon: false,
+        no_draws: false,
+        wager_token: None,
+        wager_amount: 0,
+        opponent_stake: 0,
+        memo: BytesN::from_array(env, &[0u8; 32]),
+        variant: 0,
+        escrow_wager: false,
+        sponsor: None,
+        sponsor_token: None,
+        sponsor_amount: 0,
+        referrer: None,
+        observer: None,
+        result_sink: None,
+        unrated: false,
+        require_approval: false,
+        reveal_delegate: None,
+        metadata: BytesN::from_array(env, &[0u8; 32]),
+        rematch_of: None,
+        terms: BytesN::from_array(env, &[0u8; 32]),
+        starts_at: 0,
+    }
+}
+
+fn any_match() -> MatchFilter {
+    MatchFilter {
+        token: None,
+        min_stake: 0,
+        max_stake: i128::MAX,
+        variant: None,
+        min_rating: 0,
+        max_rating: u32::MAX,
+    }
+}
+
 #[test]
 fn test_create_game() {
     let (env, _, client, _, _, p1, _) = setup_env();
 
     let commit1 = fake_commit(&env, 0xAA);
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
 
     let game: Game = client.get_game(&1u32).unwrap();
     assert_eq!(game.player1, p1);
     assert_eq!(game.player2, p1); // sentinel: player2 == player1 until join
     assert_eq!(game.seed_commit1, commit1);
-    assert_eq!(game.phase, PHASE_CREATED);
-    assert_eq!(game.winner, 0);
+    assert_eq!(game.phase, Phase::Created);
+    assert_eq!(game.winner, Winner::None);
 }
 
 #[test]
@@ -59,7 +242,7 @@ fn test_self_play_rejected() {
     let commit1 = fake_commit(&env, 0xAA);
     let commit2 = fake_commit(&env, 0xBB);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     let result = client.try_join_game(&1u32, &p1, &commit2);
     assert_eq!(result.err().unwrap().unwrap(), Error::SelfPlay);
 }
@@ -69,8 +252,8 @@ fn test_duplicate_session_rejected() {
     let (env, _, client, _, _, p1, _) = setup_env();
     let commit1 = fake_commit(&env, 0xAA);
 
-    client.create_game(&1u32, &p1, &commit1);
-    let result = client.try_create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
+    let result = client.try_create_game(&1u32, &p1, &commit1, &no_options(&env));
     assert_eq!(result.err().unwrap().unwrap(), Error::GameAlreadyExists);
 }
 
@@ -80,13 +263,13 @@ fn test_join_game() {
     let commit1 = fake_commit(&env, 0xAA);
     let commit2 = fake_commit(&env, 0xBB);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
 
     let game = client.get_game(&1u32).unwrap();
     assert_eq!(game.player2, p2);
     assert_eq!(game.seed_commit2, commit2);
-    assert_eq!(game.phase, PHASE_JOINED);
+    assert_eq!(game.phase, Phase::Joined);
 }
 
 #[test]
@@ -95,7 +278,7 @@ fn test_join_wrong_phase() {
     let commit1 = fake_commit(&env, 0xAA);
     let commit2 = fake_commit(&env, 0xBB);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
 
     // Joining again should fail
@@ -112,20 +295,20 @@ fn test_reveal_seed() {
     let seed1 = fake_commit(&env, 0x11);
     let seed2 = fake_commit(&env, 0x22);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
 
     // P1 reveals
-    client.reveal_seed(&1u32, &p1, &seed1);
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
     let game = client.get_game(&1u32).unwrap();
     assert_eq!(game.seed1, seed1);
-    assert_eq!(game.phase, PHASE_JOINED); // Still joined, only one revealed
+    assert_eq!(game.phase, Phase::Joined); // Still joined, only one revealed
 
     // P2 reveals
-    client.reveal_seed(&1u32, &p2, &seed2);
+    client.reveal_seed(&1u32, &p2, &seed2, &SeedOpening::Plain);
     let game = client.get_game(&1u32).unwrap();
     assert_eq!(game.seed2, seed2);
-    assert_eq!(game.phase, PHASE_REVEALED); // Now both revealed
+    assert_eq!(game.phase, Phase::Revealed); // Now both revealed
 }
 
 #[test]
@@ -134,10 +317,10 @@ fn test_reveal_before_join() {
     let commit1 = fake_commit(&env, 0xAA);
     let seed1 = fake_commit(&env, 0x11);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
 
     // Reveal before P2 joins should fail
-    let result = client.try_reveal_seed(&1u32, &p1, &seed1);
+    let result = client.try_reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
     assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
 }
 
@@ -148,15 +331,58 @@ fn test_double_reveal_rejected() {
     let commit2 = fake_commit(&env, 0xBB);
     let seed1 = fake_commit(&env, 0x11);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
-    client.reveal_seed(&1u32, &p1, &seed1);
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
 
     // P1 revealing again should fail
-    let result = client.try_reveal_seed(&1u32, &p1, &seed1);
+    let result = client.try_reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
     assert_eq!(result.err().unwrap().unwrap(), Error::AlreadyRevealed);
 }
 
+#[test]
+fn test_zero_commitment_or_seed_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    assert_eq!(
+        client.try_create_game(&1u32, &p1, &zero, &no_options(&env)),
+        Err(Ok(Error::ZeroCommitment))
+    );
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(client.try_join_game(&1u32, &p2, &zero), Err(Ok(Error::ZeroCommitment)));
+    assert_eq!(client.try_join_any(&p2, &zero, &any_match()), Err(Ok(Error::ZeroCommitment)));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    // A zero seed would read back as "not revealed": it could be revealed
+    // again, and the game could never leave Joined on it
+    assert_eq!(
+        client.try_reveal_seed(&1u32, &p1, &zero, &SeedOpening::Plain),
+        Err(Ok(Error::ZeroSeed))
+    );
+    assert_eq!(
+        client.try_reveal_both(&1u32, &fake_commit(&env, 0x11), &zero, &SeedOpening::Plain, &SeedOpening::Plain),
+        Err(Ok(Error::ZeroSeed))
+    );
+    client.reveal_both(&1u32, &fake_commit(&env, 0x11), &fake_commit(&env, 0x22), &SeedOpening::Plain, &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+}
+
+#[test]
+fn test_mirrored_commitment_or_seed_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let commit1 = fake_commit(&env, 0xAA);
+    let seed1 = fake_commit(&env, 0x11);
+
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
+    let result = client.try_join_game(&1u32, &p2, &commit1);
+    assert_eq!(result.err().unwrap().unwrap(), Error::DuplicateCommitment);
+
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
+    let result = client.try_reveal_seed(&1u32, &p2, &seed1, &SeedOpening::Plain);
+    assert_eq!(result.err().unwrap().unwrap(), Error::DuplicateCommitment);
+}
+
 #[test]
 fn test_non_player_reveal_rejected() {
     let (env, _, client, _, _, p1, p2) = setup_env();
@@ -164,10 +390,10 @@ fn test_non_player_reveal_rejected() {
     let commit2 = fake_commit(&env, 0xBB);
     let outsider = Address::generate(&env);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
 
-    let result = client.try_reveal_seed(&1u32, &outsider, &fake_commit(&env, 0x99));
+    let result = client.try_reveal_seed(&1u32, &outsider, &fake_commit(&env, 0x99), &SeedOpening::Plain);
     assert_eq!(result.err().unwrap().unwrap(), Error::NotPlayer);
 }
 
@@ -177,7 +403,7 @@ fn test_settle_before_reveals_rejected() {
     let commit1 = fake_commit(&env, 0xAA);
     let commit2 = fake_commit(&env, 0xBB);
 
-    client.create_game(&1u32, &p1, &commit1);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
     client.join_game(&1u32, &p2, &commit2);
 
     // Try to settle before revealing seeds
@@ -193,9 +419,17 @@ fn test_settle_before_reveals_rejected() {
         seed2: fake_commit(&env, 0x22),
         session_id: fake_commit(&env, 1),
         winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
     };
 
-    let result = client.try_settle_game(&1u32, &fake_proof, &fake_inputs);
+    let result = client.try_settle_game(
+        &1u32,
+        &fake_proof,
+        &fake_inputs,
+        &Address::generate(&env),
+    );
     assert_eq!(result.err().unwrap().unwrap(), Error::SeedsNotRevealed);
 }
 
@@ -217,21 +451,21 @@ fn test_full_game_flow_until_settlement() {
     let seed2 = fake_commit(&env, 0x22);
 
     // 1. Create open game
-    client.create_game(&1u32, &p1, &commit1);
-    assert_eq!(client.get_game(&1u32).unwrap().phase, PHASE_CREATED);
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Created);
 
     // 2. P2 joins
     client.join_game(&1u32, &p2, &commit2);
     let game = client.get_game(&1u32).unwrap();
-    assert_eq!(game.phase, PHASE_JOINED);
+    assert_eq!(game.phase, Phase::Joined);
     assert_eq!(game.player2, p2);
 
     // 3. Both reveal (in either order)
-    client.reveal_seed(&1u32, &p2, &seed2);
-    assert_eq!(client.get_game(&1u32).unwrap().phase, PHASE_JOINED);
+    client.reveal_seed(&1u32, &p2, &seed2, &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Joined);
 
-    client.reveal_seed(&1u32, &p1, &seed1);
-    assert_eq!(client.get_game(&1u32).unwrap().phase, PHASE_REVEALED);
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
 
     // 4. Settlement would happen here with a real ZK proof
     let game = client.get_game(&1u32).unwrap();
@@ -240,3 +474,3689 @@ fn test_full_game_flow_until_settlement() {
     assert_eq!(game.seed_commit1, commit1);
     assert_eq!(game.seed_commit2, commit2);
 }
+
+#[test]
+fn test_expiry_recorded_and_refreshed() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let commit1 = fake_commit(&env, 0xAA);
+    let commit2 = fake_commit(&env, 0xBB);
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 10_000,
+        join_window: 500,
+        reveal_window: 8_000,
+    });
+
+    client.create_game(&1u32, &p1, &commit1, &no_options(&env));
+    let created = client.get_game_summary(&1u32).unwrap();
+    assert_eq!(created.expires_at, env.ledger().sequence() + 10_000);
+    assert!(!client.is_expired(&1u32));
+
+    // Writes leave the expiry alone while over half the TTL remains
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    client.join_game(&1u32, &p2, &commit2);
+    let joined = client.get_game_summary(&1u32).unwrap();
+    assert_eq!(joined.expires_at, created.expires_at);
+    assert_eq!(joined.phase, Phase::Joined);
+    assert_eq!(joined.player2, p2);
+
+    env.ledger().with_mut(|l| l.sequence_number += 5_000);
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    let revealed = client.get_game_summary(&1u32).unwrap();
+    assert_eq!(revealed.expires_at, env.ledger().sequence() + 10_000);
+
+    // Once the entry lapses the session still reads as expired
+    env.ledger().with_mut(|l| l.sequence_number = revealed.expires_at + 1);
+    assert!(client.get_game(&1u32).is_none());
+    assert!(client.is_expired(&1u32));
+}
+
+#[test]
+fn test_time_remaining_counts_down() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig { game_ttl: 10_000, join_window: 500, reveal_window: 8_000 });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    let start = env.ledger().sequence();
+
+    let time = client.get_time_remaining(&1u32);
+    assert_eq!((time.ledger, time.phase), (start, Phase::Created));
+    assert_eq!((time.join_ledgers, time.reveal_ledgers, time.ttl_ledgers), (501, 0, 10_000));
+
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    let time = client.get_time_remaining(&1u32);
+    assert_eq!((time.join_ledgers, time.reveal_ledgers, time.ttl_ledgers), (0, 8_001, 9_900));
+
+    // Once the reveal window has closed nothing is left but the TTL
+    env.ledger().with_mut(|l| l.sequence_number += 8_001);
+    let time = client.get_time_remaining(&1u32);
+    assert_eq!((time.join_ledgers, time.reveal_ledgers, time.ttl_ledgers), (0, 0, 1_899));
+    assert_eq!(client.try_get_time_remaining(&2u32), Err(Ok(Error::GameNotFound)));
+}
+
+#[test]
+fn test_phase_counts_track_transitions() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    assert_eq!(client.get_phase_counts(), PhaseCounts::default());
+
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xDD), &no_options(&env));
+    client.join_game(&3u32, &p2, &fake_commit(&env, 0xEE));
+    let counts = client.get_phase_counts();
+    assert_eq!((counts.created, counts.joined, counts.revealed), (1, 1, 1));
+
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    client.cancel_game(&2u32);
+    let counts = client.get_phase_counts();
+    assert_eq!(counts, PhaseCounts { created: 0, joined: 1, revealed: 0, settled: 1, cancelled: 1 });
+
+    // A game that lapses stays counted until gc prunes it, and only once
+    let expires_at = client.get_game(&3u32).unwrap().expires_at;
+    env.ledger().with_mut(|l| l.sequence_number = expires_at + 1);
+    assert_eq!(client.get_phase_counts().joined, 1);
+    client.gc_indexes(&vec![&env, 1u32, 3]);
+    client.gc_indexes(&vec![&env, 3u32]);
+    assert_eq!(client.get_phase_counts(), PhaseCounts { created: 0, joined: 0, revealed: 0, settled: 1, cancelled: 1 });
+}
+
+#[test]
+fn test_expiry_warning_when_deadline_near() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig { game_ttl: 10_000, join_window: 500, reveal_window: 200 });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    // Off by default
+    env.ledger().with_mut(|l| l.sequence_number += 150);
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().event_seq, 3);
+
+    client.set_expiry_warning(&100);
+    assert_eq!(client.get_config().expiry_warning, 100);
+    client.reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    // Revealed: only the TTL is left, and it is far off
+    assert_eq!(client.get_game(&1u32).unwrap().event_seq, 4);
+
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xDD));
+    env.ledger().with_mut(|l| l.sequence_number += 150);
+    client.reveal_seed(&2u32, &p1, &fake_commit(&env, 0x33), &SeedOpening::Plain);
+    let expected = crate::events::GameExpiringSoon { session_id: 2, seq: 4, phase: Phase::Joined, ledgers_left: 51 };
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert_eq!(published.events().last().unwrap(), &soroban_sdk::Event::to_xdr(&expected, &env, &contract_id));
+}
+
+#[test]
+fn test_is_expired_unknown_game() {
+    let (_, _, client, _, _, _, _) = setup_env();
+
+    let result = client.try_is_expired(&999u32);
+    assert_eq!(result.err().unwrap().unwrap(), Error::GameNotFound);
+    assert!(client.get_game_summary(&999u32).is_none());
+}
+
+#[test]
+fn test_timing_config_update() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let config = TimingConfig {
+        game_ttl: 10_000,
+        join_window: 500,
+        reveal_window: 200,
+    };
+    client.set_timing_config(&config);
+    assert_eq!(client.get_timing_config(), config);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.expires_at, env.ledger().sequence() + 10_000);
+    assert_eq!(game.join_deadline, env.ledger().sequence() + 500);
+}
+
+#[test]
+fn test_invalid_timing_config_rejected() {
+    let (_, _, client, _, _, _, _) = setup_env();
+    let config = TimingConfig {
+        game_ttl: 100,
+        join_window: 500, // longer than the TTL
+        reveal_window: 50,
+    };
+    let result = client.try_set_timing_config(&config);
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidConfig);
+}
+
+#[test]
+fn test_join_after_window_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 10_000,
+        join_window: 50,
+        reveal_window: 50,
+    });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+
+    env.ledger().with_mut(|l| l.sequence_number += 51);
+    let result = client.try_join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(result.err().unwrap().unwrap(), Error::JoinWindowClosed);
+}
+
+#[test]
+fn test_reveal_after_window_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 10_000,
+        join_window: 50,
+        reveal_window: 50,
+    });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    env.ledger().with_mut(|l| l.sequence_number += 50);
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    let result = client.try_reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    assert_eq!(result.err().unwrap().unwrap(), Error::RevealWindowClosed);
+}
+
+#[test]
+fn test_scheduled_game_reveals_from_its_start() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 10_000,
+        join_window: 50,
+        reveal_window: 50,
+    });
+    let start = env.ledger().sequence() + 200;
+    let beyond = GameOptions { starts_at: env.ledger().sequence() + 10_001, ..no_options(&env) };
+    assert_eq!(client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &beyond), Err(Ok(Error::InvalidConfig)));
+    let options = GameOptions { starts_at: start, ..no_options(&env) };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    // Joins are open at once; reveals wait for the start
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    let result = client.try_reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    assert_eq!(result, Err(Ok(Error::NotStarted)));
+    let result = client.try_reveal_both(&1u32, &fake_commit(&env, 0x11), &fake_commit(&env, 0x22), &SeedOpening::Plain, &SeedOpening::Plain);
+    assert_eq!(result, Err(Ok(Error::NotStarted)));
+
+    // The reveal window runs from the start, not the join
+    assert_eq!(client.get_game(&1u32).unwrap().reveal_deadline, start + 50);
+    env.ledger().with_mut(|l| l.sequence_number = start + 50);
+    client.reveal_both(&1u32, &fake_commit(&env, 0x11), &fake_commit(&env, 0x22), &SeedOpening::Plain, &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+}
+
+#[test]
+fn test_verifier_input_count_mismatch() {
+    let env = Env::default();
+    let inputs = [
+        fake_commit(&env, 0xAA),
+        fake_commit(&env, 0xBB),
+        fake_commit(&env, 0x11),
+        fake_commit(&env, 0x22),
+        u32_field(&env, 1),
+        fake_commit(&env, 1),
+    ];
+
+    // 5 IC entries only cover 4 public inputs; we supply 6
+    let result = verifier::verify_groth16(&env, &zero_proof(&env), &dummy_vk(&env, 5), &inputs);
+    assert_eq!(result, Err(Error::InputCountMismatch));
+
+    let result = verifier::verify_groth16(&env, &zero_proof(&env), &dummy_vk(&env, 0), &inputs);
+    assert_eq!(result, Err(Error::InputCountMismatch));
+}
+
+#[test]
+fn test_small_scalar_mul_matches_g1_mul() {
+    let env = Env::default();
+    let g1 = Bn254G1Affine::from_bytes(g1_gen(&env));
+    let mut wide = [0x11u8; 32];
+    wide[0] = 0x01;
+    let scalars = [
+        u32_field(&env, 1),
+        u32_field(&env, 25),
+        u32_field(&env, u32::MAX),
+        hex_bytes(&env, "000000000000000000000000000000000000000000000000ffffffffffffffff"),
+        BytesN::from_array(&env, &wide),
+    ];
+    for scalar in scalars {
+        let expected = env.crypto().bn254().g1_mul(&g1, &Fr::from_bytes(scalar.clone()));
+        assert_eq!(verifier::scalar_mul(&env, &g1, &scalar).unwrap().to_bytes(), expected.to_bytes());
+    }
+    assert!(verifier::scalar_mul(&env, &g1, &fake_commit(&env, 0)).is_none());
+}
+
+/// Host cost of the hot paths, guarding against regressions. Before the
+/// small-scalar input combination and the single key read, settling this
+/// game measured 40.6M CPU instructions; small inputs now skip most of
+/// the nine g1_muls (this test's seeds are small too, so real games save
+/// somewhat less). Game 2 reveals into a pending index game 1 already
+/// populated, as on a live contract; the first game into an empty index
+/// also writes its directory nodes.
+// CPU budgets for the second reveal and the settlement after it: the
+// measured items below plus a little headroom. A reveal is ~305k for the game entry,
+// auth and event, ~95k for the activity log, ~85k for the pending
+// settlement page and ~70k for the phase counts (~552k); a settlement is
+// ~29.6M for the pairing check and ~1.5M for everything else (~31.1M).
+// A change that adds to either must add its own line here, not just
+// raise the total.
+const REVEAL_BUDGET: u64 = 575_000;
+const SETTLE_BUDGET: u64 = 31_500_000;
+
+#[test]
+fn test_reveal_and_settle_budgets() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&2u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+
+    env.cost_estimate().budget().reset_default();
+    client.reveal_seed(&2u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    assert!(env.cost_estimate().budget().cpu_instruction_cost() < REVEAL_BUDGET);
+
+    env.cost_estimate().budget().reset_default();
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert!(env.cost_estimate().budget().cpu_instruction_cost() < SETTLE_BUDGET);
+}
+
+#[test]
+fn test_vk_provenance_follows_the_key() {
+    let (env, _, client, _, _, _, _) = setup_env();
+    let provenance = VkProvenance {
+        circuit: String::from_str(&env, "pirate_cards v3"),
+        ceremony_hash: BytesN::from_array(&env, &[7u8; 32]),
+        toolchain: String::from_str(&env, "snarkjs 0.7.4"),
+    };
+    client.register_vk(&5, &synthetic_vk(&env, false));
+    assert_eq!(client.get_vk_info(&1u32), None); // no such variant
+    assert_eq!(client.try_set_vk_provenance(&6, &provenance), Err(Ok(Error::NoVk)));
+    client.set_vk_provenance(&5, &provenance);
+    client.register_variant(&1u32, &Variant { deck_size: 25, suits: 3, rounds: 12, vk_id: 5 });
+
+    let info = client.get_vk_info(&1u32).unwrap();
+    assert_eq!(info.vk_id, 5);
+    assert_eq!(info.vk_hash, synthetic_vk(&env, false).hash(&env));
+    assert_eq!(client.get_vk_provenance(&5), Some(provenance.clone()));
+
+    // A new key clears what described the old one
+    let hash = BytesN::from_array(&env, &[8u8; 32]);
+    client.register_vk_hash(&5, &hash);
+    let info = client.get_vk_info(&1u32).unwrap();
+    assert_eq!(info.vk_hash, hash);
+    assert_eq!(client.get_vk_provenance(&5), None);
+
+    // Under a council it is queued like the key
+    let signer = Address::generate(&env);
+    client.set_council(&Council { signers: Vec::from_array(&env, [signer.clone()]), threshold: 1 });
+    assert_eq!(client.try_set_vk_provenance(&5, &provenance), Err(Ok(Error::CouncilRequired)));
+    let id = client.propose(&signer, &AdminAction::SetVkProvenance(5, provenance.clone()));
+    client.execute(&id);
+    assert_eq!(client.get_vk_provenance(&5), Some(provenance));
+}
+
+#[test]
+fn test_set_vk_rejects_wrong_ic_length() {
+    let (env, _, client, _, _, _, _) = setup_env();
+
+    let result = client.try_set_vk(&dummy_vk(&env, 5));
+    assert_eq!(result.err().unwrap().unwrap(), Error::VkIcLengthMismatch);
+}
+
+#[test]
+fn test_settle_with_stale_vk_rejected() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+
+    // Simulate a VK written by an older build that skipped validation
+    env.as_contract(&contract_id, || storage::set_vk(&env, 0, &dummy_vk(&env, 5)));
+
+    let result = client.try_settle_game(
+        &1u32,
+        &zero_proof(&env),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(result.err().unwrap().unwrap(), Error::VkIcLengthMismatch);
+}
+
+#[test]
+fn test_game_keeps_the_key_id_its_variant_had_at_creation() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+
+    // Pointing the variant at another key leaves games in flight on theirs
+    client.register_vk_hash(&9, &BytesN::from_array(&env, &[9u8; 32]));
+    client.register_variant(&0u32, &Variant { deck_size: 25, suits: 3, rounds: 12, vk_id: 9 });
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(client.get_game(&2u32).unwrap().vk_id, 9);
+    assert_eq!(client.get_game(&1u32).unwrap().vk_id, 0);
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+}
+
+#[test]
+fn test_settle_with_valid_proof() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    assert_eq!(client.get_winner(&1u32), None);
+    let winner = client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(winner, Some(p1.clone()));
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.phase, Phase::Settled);
+    assert_eq!(game.winner, Winner::Player1);
+    assert_eq!(game.winner_address, Some(p1.clone()));
+    assert_eq!(client.get_winner(&1u32), Some(p1));
+}
+
+#[test]
+fn test_settle_with_other_deck_config_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let mut inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    // A short-deck proof cannot settle a classic game
+    inputs.deck_size = u32_field(&env, 13);
+    let result = client.try_settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(result.err().unwrap().unwrap(), Error::PublicInputMismatch);
+}
+
+#[test]
+fn test_settle_player2_winner_address() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let mut inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    inputs.winner = fake_commit(&env, 2);
+    let winner = client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(winner, Some(p2.clone()));
+    assert_eq!(client.get_winner(&1u32), Some(p2));
+    assert_eq!(client.get_game(&1u32).unwrap().winner, Winner::Player2);
+}
+
+#[test]
+fn test_negated_b_convention() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+
+    // A pre-negated π_B fails against a standard-convention key...
+    client.set_vk(&synthetic_vk(&env, false));
+    let result = client.try_settle_game(
+        &1u32,
+        &synthetic_proof(&env, true),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidProof);
+
+    // ...and verifies once the key declares the negated convention
+    client.set_vk(&synthetic_vk(&env, true));
+    let winner = client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, true),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(winner, Some(p1));
+}
+
+#[test]
+fn test_identity_proof_points_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    let mut proof = synthetic_proof(&env, false);
+    proof.pi_a = BytesN::from_array(&env, &[0u8; 64]);
+    let result = client.try_settle_game(&1u32, &proof, &inputs, &Address::generate(&env));
+    assert_eq!(result.err().unwrap().unwrap(), Error::DegenerateProofPoint);
+
+    let mut proof = synthetic_proof(&env, false);
+    proof.pi_b = BytesN::from_array(&env, &[0u8; 128]);
+    let result = client.try_settle_game(&1u32, &proof, &inputs, &Address::generate(&env));
+    assert_eq!(result.err().unwrap().unwrap(), Error::DegenerateProofPoint);
+
+    let mut proof = synthetic_proof(&env, false);
+    proof.pi_c = BytesN::from_array(&env, &[0u8; 64]);
+    let result = client.try_settle_game(&1u32, &proof, &inputs, &Address::generate(&env));
+    assert_eq!(result.err().unwrap().unwrap(), Error::DegenerateProofPoint);
+}
+
+#[test]
+fn test_off_subgroup_pi_b_rejected_by_host() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    let mut proof = synthetic_proof(&env, false);
+    proof.pi_b = hex_bytes(&env, G2_OFF_SUBGROUP);
+
+    // The host pairing traps on the point; it is never treated as a
+    // well-formed (merely wrong) proof.
+    let result = client.try_settle_game(&1u32, &proof, &inputs, &Address::generate(&env));
+    assert!(matches!(result, Err(Err(_))));
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+}
+
+#[test]
+fn test_winner_field_decoding() {
+    let env = Env::default();
+    assert_eq!(Winner::from_field(&fake_commit(&env, 0)), Some(Winner::None));
+    assert_eq!(Winner::from_field(&fake_commit(&env, 1)), Some(Winner::Player1));
+    assert_eq!(Winner::from_field(&fake_commit(&env, 2)), Some(Winner::Player2));
+    assert_eq!(Winner::from_field(&fake_commit(&env, 3)), Some(Winner::Draw));
+    assert_eq!(Winner::from_field(&fake_commit(&env, 4)), None);
+
+    let mut high = [0u8; 32];
+    high[0] = 1;
+    high[31] = 1;
+    assert_eq!(Winner::from_field(&BytesN::from_array(&env, &high)), None);
+}
+
+#[test]
+fn test_settle_draw_recorded() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let mut inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    // Winner::None is never a valid outcome
+    inputs.winner = fake_commit(&env, 0);
+    let result = client.try_settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidWinner);
+
+    inputs.winner = fake_commit(&env, 3);
+    let winner = client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+    assert_eq!(winner, None);
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.phase, Phase::Settled);
+    assert_eq!(game.winner, Winner::Draw);
+    assert_eq!(client.get_winner(&1u32), None);
+}
+
+#[test]
+fn test_draw_tiebroken_in_no_draws_game() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let options = GameOptions {
+        no_draws: true,
+        ..no_options(&env)
+    };
+    let (commit1, commit2) = (fake_commit(&env, 0xAA), fake_commit(&env, 0xBB));
+    let (seed1, seed2) = (fake_commit(&env, 0x11), fake_commit(&env, 0x22));
+    client.create_game(&1u32, &p1, &commit1, &options);
+    client.join_game(&1u32, &p2, &commit2);
+    client.reveal_both(&1u32, &seed1, &seed2, &SeedOpening::Plain, &SeedOpening::Plain);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    let inputs = PublicInputs {
+        seed_commit1: commit1,
+        seed_commit2: commit2,
+        seed1,
+        seed2,
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 3),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    let winner = client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+
+    let game = client.get_game(&1u32).unwrap();
+    assert!(game.tiebroken);
+    assert_eq!(game.phase, Phase::Settled);
+    assert_eq!(winner, game.winner_address);
+    let expected = if game.winner == Winner::Player1 { p1 } else { p2 };
+    assert_eq!(winner, Some(expected));
+}
+
+#[test]
+fn test_get_phase() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    assert_eq!(client.get_phase(&1u32), None);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Created));
+
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Joined));
+}
+
+#[test]
+fn test_pending_settlement_index() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+
+    // Sessions 5 and 3 reach Phase::Revealed; 4 only joins
+    for sid in [5u32, 4, 3] {
+        client.create_game(&sid, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+        client.join_game(&sid, &p2, &fake_commit(&env, 0xBB));
+        if sid != 4 {
+            client.reveal_seed(&sid, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+            client.reveal_seed(&sid, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+        }
+    }
+
+    let pending = client.list_pending_settlement(&0, &10);
+    assert_eq!(pending, SessionPage { ids: vec![&env, 1u32, 3, 5], next: None });
+    assert_eq!(client.list_pending_settlement(&0, &2), SessionPage { ids: vec![&env, 1u32, 3], next: Some(5) });
+    assert_eq!(client.list_pending_settlement(&4, &10).ids, vec![&env, 5u32]);
+    assert_eq!(client.list_pending_settlement(&6, &10).ids, vec![&env]);
+
+    // Settling removes the game from the index
+    client.set_vk(&synthetic_vk(&env, false));
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(client.list_pending_settlement(&0, &10).ids, vec![&env, 3u32, 5]);
+}
+
+#[test]
+fn test_open_and_player_game_indexes() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let p3 = Address::generate(&env);
+    for sid in [7u32, 2, 9] {
+        client.create_game(&sid, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    }
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env, 2u32, 7, 9]);
+
+    // Joining or cancelling closes a game
+    client.join_game(&7u32, &p2, &fake_commit(&env, 0xBB));
+    client.cancel_game(&9u32);
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env, 2u32]);
+
+    client.create_game(&4u32, &p3, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&4u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(client.list_player_games(&p2, &0, &10).ids, vec![&env, 4u32, 7]);
+    assert_eq!(client.list_player_games(&p3, &5, &10).ids, vec![&env]);
+}
+
+#[test]
+fn test_index_pages_bound_their_reads() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    // Ids far apart each sit under their own branch of the index
+    let ids = [3u32, 0x0100_0000, 0x0200_0000, 0x0300_0000, 0x0400_0000, 0x0400_0001, 0xEFFF_FFFF];
+    for sid in ids {
+        client.create_game(&sid, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    }
+
+    // Every id turns up exactly once across the pages, in order
+    let mut seen = Vec::new(&env);
+    let mut cursor = Some(0);
+    let mut calls = 0;
+    while let Some(from) = cursor {
+        let page = client.list_open_games(&from, &100);
+        seen.append(&page.ids);
+        cursor = page.next;
+        calls += 1;
+    }
+    assert_eq!(seen, Vec::from_array(&env, ids));
+    assert!(calls > 1);
+
+    assert_eq!(client.list_open_games(&0x0400_0001, &1).ids, vec![&env, 0x0400_0001]);
+    client.cancel_game(&0xEFFF_FFFF);
+    assert_eq!(client.list_open_games(&0x0400_0002, &10), SessionPage { ids: vec![&env], next: None });
+}
+
+#[test]
+fn test_indexes_pruned_on_finish_and_by_gc() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 100,
+        join_window: 50,
+        reveal_window: 50,
+    });
+
+    // Settling and cancelling drop a game from every index
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&1u32);
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.cancel_game(&2u32);
+    assert_eq!(client.list_player_games(&p1, &0, &10).ids, vec![&env]);
+    assert_eq!(client.list_player_games(&p2, &0, &10).ids, vec![&env]);
+
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&3u32, &p2, &fake_commit(&env, 0xBB));
+    client.create_game(&4u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+
+    // Past its join deadline an open game leaves the open list
+    env.ledger().with_mut(|l| l.sequence_number += 60);
+    assert_eq!(client.gc_indexes(&vec![&env, 3u32, 4]), 1);
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env]);
+
+    client.create_game(&5u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&5u32, &p2, &fake_commit(&env, 0xBB));
+
+    // Once evicted, games 3 and 4 are pruned from the player lists too
+    env.ledger().with_mut(|l| l.sequence_number += 41);
+    assert!(client.get_game(&3u32).is_none());
+    assert_eq!(client.gc_indexes(&vec![&env, 3u32, 4, 5]), 2);
+    assert_eq!(client.list_player_games(&p1, &0, &10).ids, vec![&env, 5u32]);
+    assert_eq!(client.list_player_games(&p2, &0, &10).ids, vec![&env, 5u32]);
+}
+
+#[test]
+fn test_bounty_paid_to_settler() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1], 1_000);
+    let options = GameOptions {
+        bounty_token: Some(token),
+        bounty_amount: 100,
+        ..no_options(&env)
+    };
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(token_client.balance(&p1), 900);
+    assert_eq!(token_client.balance(&contract_id), 100);
+    assert_eq!(client.get_bounty(&1u32).unwrap().funder, p1);
+
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    client.reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    let keeper = Address::generate(&env);
+    let inputs = PublicInputs {
+        seed_commit1: fake_commit(&env, 0xAA),
+        seed_commit2: fake_commit(&env, 0xBB),
+        seed1: fake_commit(&env, 0x11),
+        seed2: fake_commit(&env, 0x22),
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 2),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &keeper);
+    assert_eq!(token_client.balance(&keeper), 100);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert!(client.get_bounty(&1u32).is_none());
+}
+
+#[test]
+fn test_invalid_bounty_rejected() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let (token, _) = create_token(&env, &[&p1], 1_000);
+    let options = GameOptions {
+        bounty_token: Some(token),
+        bounty_amount: 0,
+        ..no_options(&env)
+    };
+
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidAmount);
+}
+
+#[test]
+fn test_cancel_game_refunds_bounty() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1], 1_000);
+    let options = GameOptions {
+        bounty_token: Some(token),
+        bounty_amount: 100,
+        ..no_options(&env)
+    };
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.cancel_game(&1u32);
+    assert_eq!(token_client.balance(&p1), 1_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Cancelled));
+
+    // A cancelled game can no longer be joined or cancelled again
+    let result = client.try_join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
+    let result = client.try_cancel_game(&1u32);
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
+}
+
+#[test]
+fn test_cancel_after_join_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    let result = client.try_cancel_game(&1u32);
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
+}
+
+#[test]
+fn test_refund_bounty_after_reveal_deadline() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1], 1_000);
+    let options = GameOptions {
+        bounty_token: Some(token),
+        bounty_amount: 100,
+        ..no_options(&env)
+    };
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 10_000,
+        join_window: 50,
+        reveal_window: 50,
+    });
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    // Still revealable: not refundable yet
+    let result = client.try_refund_bounty(&1u32);
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
+
+    env.ledger().with_mut(|l| l.sequence_number += 51);
+    let refunded = client.refund_bounty(&1u32);
+    assert_eq!(refunded.amount, 100);
+    assert_eq!(token_client.balance(&p1), 1_000);
+
+    let result = client.try_refund_bounty(&1u32);
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoBounty);
+}
+
+#[test]
+fn test_reveal_both() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let seed1 = fake_commit(&env, 0x11);
+    let seed2 = fake_commit(&env, 0x22);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_both(&1u32, &seed1, &seed2, &SeedOpening::Plain, &SeedOpening::Plain);
+
+    // Both players had to authorize the single call
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    assert!(auths.iter().any(|(addr, _)| *addr == p1));
+    assert!(auths.iter().any(|(addr, _)| *addr == p2));
+
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.phase, Phase::Revealed);
+    assert_eq!(game.seed1, seed1);
+    assert_eq!(game.seed2, seed2);
+    assert_eq!(client.list_pending_settlement(&0, &10).ids, vec![&env, 1u32]);
+}
+
+#[test]
+fn test_reveal_both_after_single_reveal_rejected() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+
+    let result = client.try_reveal_both(&1u32, &fake_commit(&env, 0x11), &fake_commit(&env, 0x22), &SeedOpening::Plain, &SeedOpening::Plain);
+    assert_eq!(result.err().unwrap().unwrap(), Error::AlreadyRevealed);
+}
+
+#[test]
+fn test_reveal_delegates_reveal_and_nothing_else() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let (d1, d2) = (Address::generate(&env), Address::generate(&env));
+    let (seed1, seed2) = (fake_commit(&env, 0x11), fake_commit(&env, 0x22));
+    let options = GameOptions { reveal_delegate: Some(d1.clone()), ..no_options(&env) };
+
+    // A player cannot be their own or their opponent's delegate
+    let own = GameOptions { reveal_delegate: Some(p1.clone()), ..no_options(&env) };
+    assert_eq!(client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &own), Err(Ok(Error::InvalidConfig)));
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    let join = |delegate: &Address| JoinOptions { reveal_delegate: Some(delegate.clone()), metadata: fake_commit(&env, 0), terms: fake_commit(&env, 0), stake: 0 };
+    let result = client.try_join_game_with(&1u32, &p2, &fake_commit(&env, 0xBB), &join(&p1));
+    assert_eq!(result, Err(Ok(Error::InvalidConfig)));
+    client.join_game_with(&1u32, &p2, &fake_commit(&env, 0xBB), &join(&d2));
+
+    // Only the delegate authorizes, bound to the player and seed
+    client.delegate_reveal(&1u32, &p1, &seed1, &SeedOpening::Plain);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(auth_args(&env, &d1), (1u32, p1.clone(), seed1.clone()).into_val(&env));
+    assert_eq!(client.get_game_log(&1u32).last_unchecked().actor, d1);
+
+    // A delegate is not a player
+    assert_eq!(client.try_forfeit(&1u32, &d2), Err(Ok(Error::NotPlayer)));
+    assert_eq!(client.try_reveal_seed(&1u32, &d2, &seed2, &SeedOpening::Plain), Err(Ok(Error::NotPlayer)));
+    client.delegate_reveal(&1u32, &p2, &seed2, &SeedOpening::Plain);
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+
+    // Players who named no delegate reveal themselves
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xDD));
+    let result = client.try_delegate_reveal(&2u32, &p2, &seed2, &SeedOpening::Plain);
+    assert_eq!(result, Err(Ok(Error::NoDelegate)));
+
+    // Nor can the joiner be the one player 1 named
+    let by_p2 = GameOptions { reveal_delegate: Some(p2.clone()), ..no_options(&env) };
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xEE), &by_p2);
+    assert_eq!(client.try_join_game(&3u32, &p2, &fake_commit(&env, 0xFF)), Err(Ok(Error::InvalidConfig)));
+}
+
+#[test]
+fn test_player_metadata_stored_and_published() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (meta1, meta2) = (fake_commit(&env, 0x71), fake_commit(&env, 0x72));
+    let options = GameOptions { metadata: meta1.clone(), ..no_options(&env) };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    let join = JoinOptions { reveal_delegate: None, metadata: meta2.clone(), terms: fake_commit(&env, 0), stake: 0 };
+    client.join_game_with(&1u32, &p2, &fake_commit(&env, 0xBB), &join);
+    let expected = crate::events::GameJoined { session_id: 1, seq: 2, player2: p2.clone(), metadata: meta2.clone() };
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!((game.metadata1, game.metadata2), (meta1, meta2.clone()));
+
+    // A requester's metadata waits with their request
+    let approval = GameOptions { require_approval: true, ..no_options(&env) };
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &approval);
+    client.join_game_with(&2u32, &p2, &fake_commit(&env, 0xDD), &join);
+    assert_eq!(client.get_game(&2u32).unwrap().metadata2, fake_commit(&env, 0));
+    client.approve_join(&2u32, &p2);
+    assert_eq!(client.get_game(&2u32).unwrap().metadata2, meta2);
+}
+
+#[test]
+fn test_terms_co_signed_at_join() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let terms = fake_commit(&env, 0x7E);
+    let options = GameOptions { terms: terms.clone(), ..no_options(&env) };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    // Joining without signing the same terms fails, and quick play skips the game
+    let commit2 = fake_commit(&env, 0xBB);
+    assert_eq!(client.try_join_game(&1u32, &p2, &commit2), Err(Ok(Error::TermsMismatch)));
+    let mut join = JoinOptions { reveal_delegate: None, metadata: fake_commit(&env, 0), terms: fake_commit(&env, 0x7F), stake: 0 };
+    assert_eq!(client.try_join_game_with(&1u32, &p2, &commit2, &join), Err(Ok(Error::TermsMismatch)));
+    assert_eq!(client.try_join_any(&p2, &commit2, &any_match()), Err(Ok(Error::NoOpenGame)));
+
+    join.terms = terms.clone();
+    client.join_game_with(&1u32, &p2, &commit2, &join);
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert_eq!(auth_args(&env, &p2), (1u32, commit2, join).into_val(&env));
+    let expected = crate::events::TermsAgreed { session_id: 1, seq: 3, terms: terms.clone() };
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+
+    client.forfeit(&1u32, &p2);
+    assert_eq!(client.get_settlement_receipt(&1u32).unwrap().terms, terms);
+}
+
+#[test]
+fn test_followers_counted_once_and_listed_up_to_cap() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(client.try_follow_game(&2u32, &p2), Err(Ok(Error::GameNotFound)));
+
+    let first = Address::generate(&env);
+    assert_eq!(client.follow_game(&1u32, &first), 1);
+    let expected = crate::events::GameFollowed { session_id: 1, seq: 2, follower: first.clone(), followers: 1 };
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+    assert_eq!(client.follow_game(&1u32, &first), 1);
+    assert_eq!(client.get_game(&1u32).unwrap().event_seq, 2);
+    let log = client.get_game_log(&1u32);
+    assert_eq!((log.len(), log.last_unchecked().actor), (2, first.clone()));
+
+    for _ in 0..crate::MAX_LISTED_FOLLOWERS {
+        client.follow_game(&1u32, &Address::generate(&env));
+    }
+    let followers = client.get_followers(&1u32);
+    assert_eq!((followers.count, followers.listed.len()), (21, 20));
+    assert_eq!(followers.listed.get(0), Some(first));
+    assert_eq!(client.get_game_summary(&1u32).unwrap().followers, 21);
+
+    // Finished games take no more followers
+    client.cancel_game(&1u32);
+    assert_eq!(client.try_follow_game(&1u32, &p2), Err(Ok(Error::InvalidState)));
+}
+
+#[test]
+fn test_game_log_records_actions_and_keeps_the_latest() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    env.ledger().with_mut(|l| l.sequence_number += 5);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    client.forfeit(&1u32, &p1);
+
+    let start = env.ledger().sequence() - 5;
+    let entry = |actor: &Address, action: &str, ledger| LogEntry { actor: actor.clone(), action: Symbol::new(&env, action), ledger };
+    assert_eq!(
+        client.get_game_log(&1u32),
+        vec![
+            &env,
+            entry(&p1, "create", start),
+            entry(&p2, "join", start + 5),
+            entry(&p2, "reveal", start + 5),
+            entry(&p1, "forfeit", start + 5),
+        ]
+    );
+    assert!(client.get_game_log(&2u32).is_empty());
+
+    // Past the cap the oldest entries give way
+    let options = GameOptions { require_approval: true, ..no_options(&env) };
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &options);
+    for _ in 0..crate::MAX_LOG_ENTRIES / 2 {
+        client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+        client.withdraw_join(&2u32, &p2);
+    }
+    let log = client.get_game_log(&2u32);
+    assert_eq!(log.len(), crate::MAX_LOG_ENTRIES);
+    assert_eq!(log.get_unchecked(0).action, Symbol::new(&env, "request_join"));
+    assert_eq!(log.last_unchecked().action, Symbol::new(&env, "withdraw_join"));
+}
+
+#[test]
+fn test_session_id_not_reusable_after_eviction() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 100,
+        join_window: 50,
+        reveal_window: 50,
+    });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(client.get_session_creator(&1u32), Some(p1.clone()));
+
+    // Let the temporary game entry lapse
+    env.ledger().with_mut(|l| l.sequence_number += 101);
+    assert!(client.get_game(&1u32).is_none());
+
+    // A different creator cannot silently take over the old id
+    let result = client.try_create_game(&1u32, &p2, &fake_commit(&env, 0xBB), &no_options(&env));
+    assert_eq!(result.err().unwrap().unwrap(), Error::SessionIdConsumed);
+    assert_eq!(client.get_session_creator(&1u32), Some(p1));
+}
+
+#[test]
+fn test_tombstone_survives_eviction() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig {
+        game_ttl: 100,
+        join_window: 50,
+        reveal_window: 50,
+    });
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+    client.settle_game(
+        &1u32,
+        &synthetic_proof(&env, false),
+        &inputs,
+        &Address::generate(&env),
+    );
+
+    let tombstone = client.get_tombstone(&1u32).unwrap();
+    assert_eq!(tombstone.winner, Winner::Player1);
+    assert_eq!(tombstone.winner_address, Some(p1.clone()));
+    assert_eq!(tombstone.settled_at, env.ledger().sequence());
+
+    env.ledger().with_mut(|l| l.sequence_number += 101);
+    assert!(client.get_game(&1u32).is_none());
+    assert_eq!(client.get_winner(&1u32), Some(p1.clone()));
+
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    assert_eq!(result.err().unwrap().unwrap(), Error::GameAlreadySettled);
+}
+
+// ── Salted commitments ──────────────────────────────────────────────────
+
+fn salted_game(
+    env: &Env,
+    contract_id: &Address,
+    client: &PirateCardsContractClient,
+    p1: &Address,
+    p2: &Address,
+) -> (BytesN<32>, BytesN<32>, BytesN<32>, BytesN<32>) {
+    let (seed1, salt1) = (fake_commit(env, 0x11), fake_commit(env, 0xa1));
+    let (seed2, salt2) = (fake_commit(env, 0x22), fake_commit(env, 0xa2));
+    let commit1 = SeedCommitment::compute(env, contract_id, 1, p1, &seed1, &salt1).to_bytes();
+    let commit2 = SeedCommitment::compute(env, contract_id, 1, p2, &seed2, &salt2).to_bytes();
+    let options = GameOptions {
+        commit_scheme: CommitScheme::Salted,
+        ..no_options(env)
+    };
+    client.create_game(&1u32, p1, &commit1, &options);
+    client.join_game(&1u32, p2, &commit2);
+    (seed1, salt1, seed2, salt2)
+}
+
+#[test]
+fn test_salted_reveal_verifies_commitment() {
+    let (env, contract_id, client, _admin, _ohloss, p1, p2) = setup_env();
+    let (seed1, salt1, seed2, salt2) = salted_game(&env, &contract_id, &client, &p1, &p2);
+
+    assert!(client.verify_commitment(
+        &1u32,
+        &p1,
+        &seed1,
+        &salt1,
+        &client.get_game(&1u32).unwrap().seed_commit1,
+    ));
+
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Salted(salt1));
+    client.reveal_seed(&1u32, &p2, &seed2, &SeedOpening::Salted(salt2));
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+}
+
+#[test]
+fn test_salted_reveal_wrong_salt_rejected() {
+    let (env, contract_id, client, _admin, _ohloss, p1, p2) = setup_env();
+    let (seed1, _salt1, _seed2, _salt2) = salted_game(&env, &contract_id, &client, &p1, &p2);
+
+    let result = client.try_reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Salted(fake_commit(&env, 0xff)));
+    assert_eq!(result, Err(Ok(Error::CommitmentMismatch)));
+
+    // A plain opening is not accepted for a salted game
+    let result = client.try_reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
+    assert_eq!(result, Err(Ok(Error::CommitmentMismatch)));
+}
+
+#[test]
+fn test_salted_commitment_bound_to_player_and_session() {
+    let (env, contract_id, client, _admin, _ohloss, p1, p2) = setup_env();
+    let (seed, salt) = (fake_commit(&env, 0x11), fake_commit(&env, 0xa1));
+    let commit = SeedCommitment::compute(&env, &contract_id, 1, &p1, &seed, &salt).to_bytes();
+
+    assert!(client.verify_commitment(&1u32, &p1, &seed, &salt, &commit));
+    assert!(!client.verify_commitment(&1u32, &p2, &seed, &salt, &commit));
+    assert!(!client.verify_commitment(&2u32, &p1, &seed, &salt, &commit));
+}
+
+// ── Dual-hash commitments ───────────────────────────────────────────────
+
+fn keccak_commit(env: &Env, inner: &BytesN<32>) -> BytesN<32> {
+    env.crypto().keccak256(&inner.clone().into()).to_bytes()
+}
+
+#[test]
+fn test_dual_hash_reveal_and_settle() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let (inner1, inner2) = (fake_commit(&env, 0xAA), fake_commit(&env, 0xBB));
+    let options = GameOptions {
+        commit_scheme: CommitScheme::DualHash,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &keccak_commit(&env, &inner1), &options);
+    client.join_game(&1u32, &p2, &keccak_commit(&env, &inner2));
+
+    let (seed1, seed2) = (fake_commit(&env, 0x11), fake_commit(&env, 0x22));
+    client.reveal_both(
+        &1u32,
+        &seed1,
+        &seed2,
+        &SeedOpening::DualHash(inner1.clone()),
+        &SeedOpening::DualHash(inner2.clone()),
+    );
+    client.set_vk(&synthetic_vk(&env, false));
+
+    // The circuit sees the Poseidon layer, not the stored keccak commitment
+    let mut inputs = PublicInputs {
+        seed_commit1: keccak_commit(&env, &inner1),
+        seed_commit2: keccak_commit(&env, &inner2),
+        seed1,
+        seed2,
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    let settler = Address::generate(&env);
+    let result = client.try_settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::PublicInputMismatch)));
+
+    inputs.seed_commit1 = inner1;
+    inputs.seed_commit2 = inner2;
+    assert_eq!(client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler), Some(p1));
+}
+
+#[test]
+fn test_dual_hash_wrong_inner_rejected_at_reveal() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inner1 = fake_commit(&env, 0xAA);
+    let options = GameOptions {
+        commit_scheme: CommitScheme::DualHash,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &keccak_commit(&env, &inner1), &options);
+    client.join_game(&1u32, &p2, &keccak_commit(&env, &fake_commit(&env, 0xBB)));
+
+    let seed1 = fake_commit(&env, 0x11);
+    let result = client.try_reveal_seed(&1u32, &p1, &seed1, &SeedOpening::DualHash(fake_commit(&env, 0xCC)));
+    assert_eq!(result, Err(Ok(Error::CommitmentMismatch)));
+
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::DualHash(inner1.clone()));
+    assert_eq!(client.get_game(&1u32).unwrap().inner_commit1, inner1);
+}
+
+// ── Randomness beacon ───────────────────────────────────────────────────
+
+#[test]
+fn test_create_beacon_game_without_beacon_rejected() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let options = GameOptions {
+        use_beacon: true,
+        ..no_options(&env)
+    };
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::NoBeacon)));
+}
+
+#[test]
+fn test_beacon_value_mixed_into_settlement() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let beacon_id = env.register(mock_beacon::MockBeacon, ());
+    let beacon = mock_beacon::MockBeaconClient::new(&env, &beacon_id);
+    client.set_beacon(&beacon_id);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    let options = GameOptions {
+        use_beacon: true,
+        ..no_options(&env)
+    };
+    let (commit1, commit2) = (fake_commit(&env, 0xAA), fake_commit(&env, 0xBB));
+    let (seed1, seed2) = (fake_commit(&env, 0x11), fake_commit(&env, 0x22));
+    client.create_game(&1u32, &p1, &commit1, &options);
+    client.join_game(&1u32, &p2, &commit2);
+    client.reveal_both(&1u32, &seed1, &seed2, &SeedOpening::Plain, &SeedOpening::Plain);
+
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.beacon, Some(beacon_id.clone()));
+    assert_eq!(game.beacon_round, env.ledger().sequence() + 1);
+
+    let mut inputs = PublicInputs {
+        seed_commit1: commit1,
+        seed_commit2: commit2,
+        seed1,
+        seed2,
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    let settler = Address::generate(&env);
+
+    // The beacon has not published the round yet
+    let result = client.try_settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::BeaconNotReady)));
+
+    beacon.publish(&game.beacon_round, &BytesN::from_array(&env, &[0xff; 32]));
+    let result = client.try_settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::PublicInputMismatch)));
+
+    // The value enters the circuit reduced into the scalar field
+    let mut field = [0xff; 32];
+    field[0] = 0x1f;
+    inputs.beacon = BytesN::from_array(&env, &field);
+    assert_eq!(client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler), Some(p1));
+}
+
+// ── Wager metadata ──────────────────────────────────────────────────────
+
+#[test]
+fn test_wager_metadata_stored_on_game() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let token = Address::generate(&env);
+    let memo = BytesN::from_array(&env, &[7u8; 32]);
+    let options = GameOptions {
+        wager_token: Some(token.clone()),
+        wager_amount: 500,
+        memo: memo.clone(),
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.wager_token, Some(token));
+    assert_eq!(game.wager_amount, 500);
+    assert_eq!(game.memo, memo);
+}
+
+#[test]
+fn test_wager_amount_without_token_rejected() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let options = GameOptions {
+        wager_amount: 500,
+        ..no_options(&env)
+    };
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+}
+
+// ── Variants ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_unknown_variant_rejected() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let options = GameOptions {
+        variant: 7,
+        ..no_options(&env)
+    };
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::UnknownVariant)));
+
+    let bad = Variant {
+        deck_size: 9,
+        suits: 3,
+        rounds: 5,
+        vk_id: 1,
+    };
+    assert_eq!(client.try_register_variant(&7u32, &bad), Err(Ok(Error::InvalidConfig)));
+}
+
+#[test]
+fn test_variant_settles_with_its_own_vk() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let short = Variant {
+        deck_size: 13,
+        suits: 3,
+        rounds: 6,
+        vk_id: 1,
+    };
+    client.register_variant(&1u32, &short);
+    assert_eq!(client.get_variant(&1u32), Some(short));
+
+    let options = GameOptions {
+        variant: 1,
+        ..no_options(&env)
+    };
+    let (commit1, commit2) = (fake_commit(&env, 0xAA), fake_commit(&env, 0xBB));
+    let (seed1, seed2) = (fake_commit(&env, 0x11), fake_commit(&env, 0x22));
+    client.create_game(&1u32, &p1, &commit1, &options);
+    client.join_game(&1u32, &p2, &commit2);
+    client.reveal_both(&1u32, &seed1, &seed2, &SeedOpening::Plain, &SeedOpening::Plain);
+
+    let inputs = PublicInputs {
+        seed_commit1: commit1,
+        seed_commit2: commit2,
+        seed1,
+        seed2,
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 13),
+        suits: u32_field(&env, 3),
+    };
+    let settler = Address::generate(&env);
+
+    // The classic VK does not settle short-deck games
+    client.set_vk(&synthetic_vk(&env, false));
+    let result = client.try_settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::NoVk)));
+
+    client.register_vk(&1u32, &synthetic_vk(&env, false));
+    assert_eq!(client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &settler), Some(p1));
+}
+
+#[test]
+fn test_derive_deck_view_matches_engine() {
+    let (env, _, client, _, _, _, _) = setup_env();
+    let (seed1, seed2) = (BytesN::from_array(&env, &[0x11; 32]), BytesN::from_array(&env, &[0x22; 32]));
+
+    // The classic circuit's Poseidon-weight order, as the prover lays it out
+    assert_eq!(client.get_variant_deck_order(&0u32), DeckOrder::PoseidonWeights);
+    let beacon = BytesN::from_array(&env, &[0x0b; 32]);
+    for (session_id, beacon) in [(1u32, BytesN::from_array(&env, &[0; 32])), (9, beacon.clone())] {
+        let witness =
+            pirate_prover::Witness::new(&seed1.to_array(), &seed2.to_array(), session_id, &beacon.to_array()).unwrap();
+        let expected = Vec::from_array(&env, witness.deck.map(u32::from));
+        assert_eq!(client.derive_deck(&seed1, &seed2, &session_id, &beacon, &0u32), expected);
+    }
+    let unreduced = BytesN::from_array(&env, &[0xff; 32]);
+    assert_eq!(client.try_derive_deck(&unreduced, &seed2, &1u32, &beacon, &0u32), Err(Ok(Error::DeckNotDerivable)));
+    assert_eq!(client.try_derive_deck(&seed1, &seed2, &1u32, &unreduced, &0u32), Err(Ok(Error::DeckNotDerivable)));
+
+    // The engine's vector, so the host's keccak and the off-chain one agree
+    client.set_variant_deck_order(&0u32, &DeckOrder::KeccakShuffle);
+    let classic = [5, 14, 1, 24, 10, 22, 8, 0, 11, 13, 21, 6, 12, 2, 23, 17, 16, 19, 9, 3, 15, 20, 4, 7, 18];
+    assert_eq!(client.derive_deck(&seed1, &seed2, &1u32, &beacon, &0u32), Vec::from_array(&env, classic));
+
+    let mut native = [0u32; 13];
+    let keccak = |data: &[u8]| env.crypto().keccak256(&Bytes::from_slice(&env, data)).to_array();
+    pirate_engine::derive_deck(&seed1.to_array(), &seed2.to_array(), 1, &mut native, keccak);
+    client.register_variant(&1u32, &Variant { deck_size: 13, suits: 3, rounds: 6, vk_id: 1 });
+    // No Poseidon-weight circuit lays out a short deck
+    assert_eq!(client.try_derive_deck(&seed1, &seed2, &1u32, &beacon, &1u32), Err(Ok(Error::DeckNotDerivable)));
+    client.set_variant_deck_order(&1u32, &DeckOrder::KeccakShuffle);
+    assert_eq!(client.derive_deck(&seed1, &seed2, &1u32, &beacon, &1u32), Vec::from_array(&env, native));
+
+    assert_eq!(client.try_derive_deck(&seed1, &seed2, &1u32, &beacon, &7u32), Err(Ok(Error::UnknownVariant)));
+    assert_eq!(
+        client.try_set_variant_deck_order(&7u32, &DeckOrder::KeccakShuffle),
+        Err(Ok(Error::UnknownVariant))
+    );
+}
+
+// ── Stakes ──────────────────────────────────────────────────────────────
+
+fn staked_game(
+    env: &Env,
+    client: &PirateCardsContractClient,
+    p1: &Address,
+    p2: &Address,
+    stake: i128,
+) -> TokenClient<'static> {
+    let (token, token_client) = create_token(env, &[p1, p2], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: stake,
+        escrow_wager: true,
+        ..no_options(env)
+    };
+    client.create_game(&1u32, p1, &fake_commit(env, 0xAA), &options);
+    client.join_game(&1u32, p2, &fake_commit(env, 0xBB));
+    token_client
+}
+
+#[test]
+fn test_staked_winner_takes_pot_minus_rake() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    assert_eq!(token.balance(&contract_id), 2_000);
+    assert_eq!(client.get_stake(&1u32).unwrap().depositors.len(), 2);
+
+    client.reveal_both(&1u32, &fake_commit(&env, 0x11), &fake_commit(&env, 0x22), &SeedOpening::Plain, &SeedOpening::Plain);
+    client.set_vk(&synthetic_vk(&env, false));
+    let inputs = PublicInputs {
+        seed_commit1: fake_commit(&env, 0xAA),
+        seed_commit2: fake_commit(&env, 0xBB),
+        seed1: fake_commit(&env, 0x11),
+        seed2: fake_commit(&env, 0x22),
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+
+    // 2.5% of 2_000 stays with the contract as rake
+    assert_eq!(token.balance(&p1), 9_000 + 1_950);
+    assert_eq!(token.balance(&p2), 9_000);
+    assert_eq!(token.balance(&contract_id), 50);
+    assert!(client.get_stake(&1u32).is_none());
+}
+
+#[test]
+fn test_agreed_draw_splits_stakes_evenly() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 1_021);
+
+    client.agree_draw(&1u32);
+
+    // pot 2_042, rake 51, remainder 1_991 splits as 995 each plus 1 dust
+    assert_eq!(token.balance(&p1), 10_000 - 1_021 + 995);
+    assert_eq!(token.balance(&p2), 10_000 - 1_021 + 995);
+    assert_eq!(token.balance(&contract_id), 52);
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.phase, Phase::Settled);
+    assert_eq!(game.winner, Winner::Draw);
+}
+
+#[test]
+fn test_agreed_draw_splits_large_stakes() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let stake = 10i128.pow(30);
+    let (token, token_client) = create_token(&env, &[&p1, &p2], stake);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: stake,
+        escrow_wager: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    // stake * net alone overflows an i128
+    client.agree_draw(&1u32);
+    assert_eq!(token_client.balance(&p1), stake - stake / 40);
+    assert_eq!(token_client.balance(&p2), stake - stake / 40);
+}
+
+#[test]
+fn test_handicap_stakes_pay_out_at_odds() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1, &p2], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token.clone()),
+        wager_amount: 300,
+        opponent_stake: 100,
+        escrow_wager: true,
+        ..no_options(&env)
+    };
+    let negative = GameOptions { opponent_stake: -1, ..options.clone() };
+    assert_eq!(client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &negative), Err(Ok(Error::InvalidAmount)));
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    // player2 must co-sign the stake the odds put on them
+    let commit2 = fake_commit(&env, 0xBB);
+    assert_eq!(client.try_join_game(&1u32, &p2, &commit2), Err(Ok(Error::OddsMismatch)));
+    let mut join = JoinOptions { reveal_delegate: None, metadata: fake_commit(&env, 0), terms: fake_commit(&env, 0), stake: 300 };
+    assert_eq!(client.try_join_game_with(&1u32, &p2, &commit2, &join), Err(Ok(Error::OddsMismatch)));
+    join.stake = 100;
+    client.join_game_with(&1u32, &p2, &commit2, &join);
+    assert_eq!(token_client.balance(&p2), 9_900);
+    assert_eq!(token_client.balance(&contract_id), 400);
+    assert_eq!(client.get_game(&1u32).unwrap().opponent_stake, 100);
+
+    client.agree_draw(&1u32);
+
+    // net pot 390 goes back 3:1: 292 and 97, the dust joining the rake
+    assert_eq!(token_client.balance(&p1), 10_000 - 300 + 292);
+    assert_eq!(token_client.balance(&p2), 10_000 - 100 + 97);
+    assert_eq!(token_client.balance(&contract_id), 11);
+}
+
+#[test]
+fn test_missed_reveal_forfeits_to_the_player_who_revealed() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig { game_ttl: 10_000, join_window: 50, reveal_window: 50 });
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+
+    env.ledger().with_mut(|l| l.sequence_number += 50);
+    assert_eq!(client.try_claim_timeout(&1u32), Err(Ok(Error::RevealWindowOpen)));
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    assert_eq!(client.claim_timeout(&1u32), Some(p1.clone()));
+
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!((game.phase, game.winner, game.forfeit), (Phase::Settled, Winner::Player1, true));
+    assert_eq!(token.balance(&p1), 10_000 + 1_000 - 50);
+    assert_eq!(token.balance(&p2), 9_000);
+    assert_eq!(token.balance(&contract_id), 50);
+    assert_eq!(client.try_claim_timeout(&1u32), Err(Ok(Error::InvalidState)));
+}
+
+#[test]
+fn test_timeout_with_no_reveals_refunds_both() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_timing_config(&TimingConfig { game_ttl: 10_000, join_window: 50, reveal_window: 50 });
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+
+    env.ledger().with_mut(|l| l.sequence_number += 51);
+    assert_eq!(client.claim_timeout(&1u32), None);
+    let published = env.events().all().filter_by_contract(&contract_id);
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!(game.phase, Phase::Cancelled);
+
+    // The terminal event still carries both commitments
+    let z = BytesN::from_array(&env, &[0u8; 32]);
+    let expected = crate::events::GameCancelled {
+        session_id: 1,
+        seq: game.event_seq,
+        seed_commit1: fake_commit(&env, 0xAA),
+        seed_commit2: fake_commit(&env, 0xBB),
+        seed1: z.clone(),
+        seed2: z,
+    };
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+    assert_eq!(token.balance(&p1), 10_000);
+    assert_eq!(token.balance(&p2), 10_000);
+    assert_eq!(token.balance(&contract_id), 0);
+    assert_eq!(client.get_phase_counts().joined, 0);
+}
+
+#[test]
+fn test_agree_draw_rejected_for_no_draws_game() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let options = GameOptions {
+        no_draws: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(client.try_agree_draw(&1u32), Err(Ok(Error::InvalidState)));
+}
+
+#[test]
+fn test_cancel_refunds_stake() {
+    let (env, contract_id, client, _, _, p1, _) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(token_client.balance(&contract_id), 1_000);
+
+    client.cancel_game(&1u32);
+    assert_eq!(token_client.balance(&p1), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_creator_picks_from_join_requests() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (p3, p4) = (Address::generate(&env), Address::generate(&env));
+    let (token, token_client) = create_token(&env, &[&p1, &p2, &p3, &p4], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        require_approval: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    // Requests escrow their stakes but leave the seat open
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.join_game(&1u32, &p3, &fake_commit(&env, 0xCC));
+    client.join_game(&1u32, &p4, &fake_commit(&env, 0xDD));
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Created);
+    assert_eq!(client.get_join_requests(&1u32).len(), 3);
+    assert_eq!(token_client.balance(&contract_id), 4_000);
+    let result = client.try_join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(result.err().unwrap().unwrap(), Error::InvalidState);
+
+    client.decline_join(&1u32, &p2);
+    assert_eq!(token_client.balance(&p2), 10_000);
+    let result = client.try_approve_join(&1u32, &p2);
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoJoinRequest);
+
+    // Picking one refunds everyone else
+    client.approve_join(&1u32, &p4);
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!((game.phase, game.player2, game.seed_commit2), (Phase::Joined, p4, fake_commit(&env, 0xDD)));
+    assert_eq!(token_client.balance(&p3), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 2_000);
+    assert_eq!(client.get_join_requests(&1u32).len(), 0);
+}
+
+#[test]
+fn test_join_any_takes_oldest_joinable_game() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let p3 = Address::generate(&env);
+    let approval = GameOptions { require_approval: true, ..no_options(&env) };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &approval);
+    client.create_game(&2u32, &p2, &fake_commit(&env, 0xAB), &no_options(&env));
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAC), &no_options(&env));
+
+    // Session 1 needs approval and session 2 is the player's own
+    assert_eq!(client.join_any(&p2, &fake_commit(&env, 0xBB), &any_match()), 3);
+    let game = client.get_game(&3u32).unwrap();
+    assert_eq!((game.phase, game.player2), (Phase::Joined, p2));
+    assert_eq!(client.join_any(&p3, &fake_commit(&env, 0xCC), &any_match()), 2);
+
+    let result = client.try_join_any(&p3, &fake_commit(&env, 0xCD), &any_match());
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoOpenGame);
+
+    // Nor is anyone matched against the creator they reveal for
+    let delegated = GameOptions { reveal_delegate: Some(p3.clone()), ..no_options(&env) };
+    client.create_game(&4u32, &p1, &fake_commit(&env, 0xAD), &delegated);
+    let result = client.try_join_any(&p3, &fake_commit(&env, 0xCD), &any_match());
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoOpenGame);
+    assert_eq!(client.join_any(&Address::generate(&env), &fake_commit(&env, 0xCE), &any_match()), 4);
+}
+
+#[test]
+fn test_join_any_filters_on_stake_and_rating() {
+    let (env, contract_id, client, _, ohloss_id, p1, p2) = setup_env();
+    let (p3, p4) = (Address::generate(&env), Address::generate(&env));
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    ohloss.set_capabilities(&OHLOSS_CAP_RATINGS);
+    ohloss.set_rating(&p1, &1_500);
+    ohloss.set_rating(&p3, &900);
+    let (token, token_client) = create_token(&env, &[&p1, &p2], 10_000);
+    let staked = GameOptions {
+        wager_token: Some(token.clone()),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p3, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAB), &staked);
+
+    let cheap_and_strong = MatchFilter { max_stake: 999, min_rating: 1_000, ..any_match() };
+    let result = client.try_join_any(&p2, &fake_commit(&env, 0xBB), &cheap_and_strong);
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoOpenGame);
+
+    let staked_only = MatchFilter { token: Some(token), min_stake: 500, ..any_match() };
+    assert_eq!(client.join_any(&p2, &fake_commit(&env, 0xBB), &staked_only), 2);
+    assert_eq!(token_client.balance(&contract_id), 2_000);
+
+    // Ratings are read when matching, not when the game was listed
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAC), &no_options(&env));
+    ohloss.set_rating(&p1, &800);
+    let free_and_weak = MatchFilter { max_stake: 0, max_rating: 1_000, ..any_match() };
+    assert_eq!(client.join_any(&p4, &fake_commit(&env, 0xCC), &free_and_weak), 1);
+    assert_eq!(client.join_any(&p2, &fake_commit(&env, 0xCD), &free_and_weak), 3);
+}
+
+#[test]
+fn test_join_any_scans_a_bounded_prefix() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let approval = GameOptions { require_approval: true, ..no_options(&env) };
+    for sid in 0..crate::MAX_QUICK_PLAY_SCAN {
+        client.create_game(&(sid + 1), &p1, &fake_commit(&env, 0xAA), &approval);
+    }
+    let last = crate::MAX_QUICK_PLAY_SCAN + 1;
+    client.create_game(&last, &p1, &fake_commit(&env, 0xAB), &no_options(&env));
+
+    let result = client.try_join_any(&p2, &fake_commit(&env, 0xBB), &any_match());
+    assert_eq!(result.err().unwrap().unwrap(), Error::NoOpenGame);
+    client.cancel_game(&1u32);
+    assert_eq!(client.join_any(&p2, &fake_commit(&env, 0xBB), &any_match()), last);
+}
+
+// ── Insurance ───────────────────────────────────────────────────────────
+
+#[test]
+fn test_rake_funds_insurance_and_compensation_is_timelocked() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 10_000);
+    client.agree_draw(&1u32);
+    // Rake of 500 on the 20_000 pot; a fifth of it is insured
+    assert_eq!(client.get_insurance_balance(&token.address), 100);
+    assert_eq!(token.balance(&contract_id), 500);
+
+    let victim = Address::generate(&env);
+    let pending = client.propose_compensation(&1u32, &victim, &60);
+    assert_eq!(pending.token, token.address);
+
+    let result = client.try_compensate(&1u32, &victim, &60);
+    assert_eq!(result, Err(Ok(Error::TimelockActive)));
+
+    env.ledger().with_mut(|l| l.sequence_number = pending.eta);
+    let result = client.try_compensate(&1u32, &victim, &61);
+    assert_eq!(result, Err(Ok(Error::InvalidState)));
+    client.compensate(&1u32, &victim, &60);
+
+    assert_eq!(token.balance(&victim), 60);
+    assert_eq!(client.get_insurance_balance(&token.address), 40);
+    assert!(client.get_compensation(&1u32).is_none());
+}
+
+#[test]
+fn test_compensation_limited_by_insurance_balance() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 10_000);
+    client.agree_draw(&1u32);
+
+    let victim = Address::generate(&env);
+    let pending = client.propose_compensation(&1u32, &victim, &101);
+    env.ledger().with_mut(|l| l.sequence_number = pending.eta);
+    let result = client.try_compensate(&1u32, &victim, &101);
+    assert_eq!(result, Err(Ok(Error::InsufficientInsurance)));
+    assert_eq!(token.balance(&victim), 0);
+}
+
+#[test]
+fn test_rake_change_does_not_apply_to_games_in_flight() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    assert_eq!(client.try_set_rake_bps(&1_001), Err(Ok(Error::InvalidConfig)));
+
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.set_rake_bps(&1_000);
+    assert_eq!(client.get_rake_bps(), 1_000);
+    assert_eq!(client.get_game(&1u32).unwrap().rake_bps, 250);
+
+    client.agree_draw(&1u32);
+    assert_eq!(token.balance(&contract_id), 50);
+}
+
+// ── Treasury ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_treasury_withdrawal_accounting() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 10_000);
+    client.agree_draw(&1u32);
+
+    // 500 rake, 100 of it insured
+    let treasury = Address::generate(&env);
+    client.set_treasury(&treasury);
+    assert_eq!(client.get_treasury_account(&token.address).accrued, 400);
+
+    let to = Address::generate(&env);
+    let result = client.try_withdraw_treasury(&token.address, &to, &401);
+    assert_eq!(result, Err(Ok(Error::InsufficientTreasury)));
+
+    let account = client.withdraw_treasury(&token.address, &to, &300);
+    assert_eq!(account.withdrawn, 300);
+    assert_eq!(token.balance(&to), 300);
+    let result = client.try_withdraw_treasury(&token.address, &to, &101);
+    assert_eq!(result, Err(Ok(Error::InsufficientTreasury)));
+}
+
+// ── Sponsorship ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_sponsored_pot_paid_to_winner() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let sponsor = Address::generate(&env);
+    let (token, token_client) = create_token(&env, &[&sponsor], 1_000);
+    client.allow_sponsor_token(&token, &100);
+    let inputs = {
+        client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+        client.sponsor_pot(&1u32, &sponsor, &token, &300);
+        client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+        client.sponsor_pot(&1u32, &sponsor, &token, &200);
+        client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+        client.reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+        PublicInputs {
+            seed_commit1: fake_commit(&env, 0xAA),
+            seed_commit2: fake_commit(&env, 0xBB),
+            seed1: fake_commit(&env, 0x11),
+            seed2: fake_commit(&env, 0x22),
+            session_id: u32_field(&env, 1),
+            winner: fake_commit(&env, 2),
+            beacon: fake_commit(&env, 0),
+            deck_size: u32_field(&env, 25),
+            suits: u32_field(&env, 3),
+        }
+    };
+    assert_eq!(token_client.balance(&contract_id), 500);
+
+    // Sponsorship closes once both seeds are revealed
+    let result = client.try_sponsor_pot(&1u32, &sponsor, &token, &100);
+    assert_eq!(result, Err(Ok(Error::InvalidState)));
+
+    client.set_vk(&synthetic_vk(&env, false));
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(token_client.balance(&p2), 500);
+    assert!(client.get_sponsorships(&1u32).is_empty());
+}
+
+#[test]
+fn test_sponsorships_refunded_on_cancel_and_split_on_draw() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let sponsor = Address::generate(&env);
+    let (token, token_client) = create_token(&env, &[&sponsor], 1_000);
+    client.allow_sponsor_token(&token, &100);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.sponsor_pot(&1u32, &sponsor, &token, &300);
+    client.cancel_game(&1u32);
+    assert_eq!(token_client.balance(&sponsor), 1_000);
+
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.sponsor_pot(&2u32, &sponsor, &token, &301);
+    client.agree_draw(&2u32);
+    assert_eq!(token_client.balance(&p1), 150);
+    assert_eq!(token_client.balance(&p2), 150);
+    assert_eq!(token_client.balance(&sponsor), 700);
+}
+
+#[test]
+fn test_sponsorships_limited_to_accepted_tokens() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let sponsor = Address::generate(&env);
+    let (token, _) = create_token(&env, &[&sponsor], 1_000);
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+
+    // Only allowlisted tokens, and never below their minimum
+    let result = client.try_sponsor_pot(&2u32, &sponsor, &token, &300);
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+    client.allow_sponsor_token(&token, &100);
+    let result = client.try_sponsor_pot(&2u32, &sponsor, &token, &99);
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+    client.sponsor_pot(&2u32, &sponsor, &token, &100);
+
+    // A staked game only takes its wager token
+    let wager = staked_game(&env, &client, &p1, &p2, 200);
+    let result = client.try_sponsor_pot(&1u32, &sponsor, &token, &100);
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+    client.allow_sponsor_token(&wager.address, &100);
+    client.sponsor_pot(&1u32, &p1, &wager.address, &100);
+
+    client.remove_sponsor_token(&token);
+    assert_eq!(client.get_sponsor_token(&token), None);
+    let result = client.try_sponsor_pot(&2u32, &sponsor, &token, &100);
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+}
+
+#[test]
+fn test_freeroll_prize_pulled_by_allowance() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let sponsor = Address::generate(&env);
+    let (token, token_client) = create_token(&env, &[&sponsor], 1_000);
+    let options = GameOptions {
+        sponsor: Some(sponsor.clone()),
+        sponsor_token: Some(token.clone()),
+        sponsor_amount: 400,
+        ..no_options(&env)
+    };
+
+    // Without an allowance the deposit cannot be pulled
+    assert!(client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options).is_err());
+
+    // A freeroll cannot also escrow player stakes
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &GameOptions {
+        wager_token: Some(token.clone()),
+        wager_amount: 10,
+        escrow_wager: true,
+        ..options.clone()
+    });
+    assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+    token_client.approve(&sponsor, &contract_id, &800, &env.ledger().sequence());
+
+    // An unrelated creator cannot spend the allowance without the sponsor
+    let stranger = Address::generate(&env);
+    let args = (3u32, fake_commit(&env, 0xCC), options.clone()).into_val(&env);
+    env.mock_auths(&[MockAuth {
+        address: &stranger,
+        invoke: &MockAuthInvoke { contract: &contract_id, fn_name: "create_game", args, sub_invokes: &[] },
+    }]);
+    assert!(client.try_create_game(&3u32, &stranger, &fake_commit(&env, 0xCC), &options).is_err());
+    assert_eq!(token_client.balance(&sponsor), 1_000);
+    env.mock_all_auths();
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(auth_args(&env, &sponsor), (1u32, token.clone(), 400i128).into_val(&env));
+    assert!(client.get_game(&1u32).unwrap().freeroll);
+    assert_eq!(token_client.balance(&contract_id), 400);
+    client.cancel_game(&1u32);
+    assert_eq!(token_client.balance(&sponsor), 1_000);
+
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&2u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    client.reveal_seed(&2u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    let inputs = PublicInputs {
+        seed_commit1: fake_commit(&env, 0xAA),
+        seed_commit2: fake_commit(&env, 0xBB),
+        seed1: fake_commit(&env, 0x11),
+        seed2: fake_commit(&env, 0x22),
+        session_id: u32_field(&env, 2),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    client.set_vk(&synthetic_vk(&env, false));
+    client.settle_game(&2u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(token_client.balance(&p1), 400);
+    assert_eq!(token_client.balance(&sponsor), 600);
+}
+
+// ── Affiliates ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_affiliate_earns_share_of_rake() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let code = BytesN::from_array(&env, &[7u8; 32]);
+    let affiliate = Address::generate(&env);
+    let (token, token_client) = create_token(&env, &[&p1, &p2], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token.clone()),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        referrer: Some(code.clone()),
+        ..no_options(&env)
+    };
+
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::UnknownAffiliate)));
+    assert_eq!(
+        client.try_register_affiliate(&code, &affiliate, &10_001),
+        Err(Ok(Error::InvalidConfig))
+    );
+    client.register_affiliate(&code, &affiliate, &5_000);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&1u32);
+
+    // Pot 2000, rake 50, insurance 10, affiliate half of the remaining 40
+    assert_eq!(client.get_affiliate_balance(&code, &token), 20);
+    assert_eq!(client.get_treasury_account(&token).accrued, 20);
+
+    // Rotating the payout address moves future claims, not past earnings
+    let rotated = Address::generate(&env);
+    client.register_affiliate(&code, &rotated, &5_000);
+    assert_eq!(client.claim_affiliate(&code, &token), 20);
+    assert_eq!(token_client.balance(&rotated), 20);
+    assert_eq!(client.try_claim_affiliate(&code, &token), Err(Ok(Error::NothingToClaim)));
+
+    // A retired code can no longer be referenced
+    client.remove_affiliate(&code);
+    let result = client.try_create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::UnknownAffiliate)));
+    assert_eq!(token_client.balance(&contract_id), 30);
+}
+
+// ── Observer ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_observer_receives_game_callbacks() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let observer_id = env.register(mock_observer::MockObserver, ());
+    let observer = mock_observer::MockObserverClient::new(&env, &observer_id);
+    let options = GameOptions {
+        observer: Some(observer_id.clone()),
+        ..no_options(&env)
+    };
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.reveal_seed(&1u32, &p1, &fake_commit(&env, 0x11), &SeedOpening::Plain);
+    assert_eq!(observer.calls(&1u32).len(), 1);
+    client.reveal_seed(&1u32, &p2, &fake_commit(&env, 0x22), &SeedOpening::Plain);
+    client.agree_draw(&1u32);
+
+    let calls = observer.calls(&1u32);
+    assert_eq!(calls.len(), 3);
+    assert_eq!(calls.get(0).unwrap(), Symbol::new(&env, "joined"));
+    assert_eq!(calls.get(1).unwrap(), Symbol::new(&env, "revealed"));
+    assert_eq!(calls.get(2).unwrap(), Symbol::new(&env, "settled"));
+
+    // Games without an observer are unaffected
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    assert!(observer.calls(&2u32).is_empty());
+}
+
+#[test]
+fn test_failing_observer_does_not_block_game() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let options = GameOptions {
+        observer: Some(Address::generate(&env)),
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&1u32);
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Settled));
+}
+
+// ── Ohloss reporting ────────────────────────────────────────────────────
+
+#[test]
+fn test_legacy_ohloss_gets_win_lose_only() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&1u32);
+    assert_eq!(ohloss.last_report(&1u32), None);
+
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(client.forfeit(&2u32, &p2), p1);
+    assert_eq!(ohloss.last_report(&2u32), Some(Symbol::new(&env, "end_game")));
+}
+
+#[test]
+fn test_extended_ohloss_gets_draws_forfeits_and_scores() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    ohloss.set_capabilities(&(OHLOSS_CAP_DRAWS | OHLOSS_CAP_FORFEITS | OHLOSS_CAP_SCORES));
+
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(ohloss.last_report(&1u32), Some(Symbol::new(&env, "end_game_scored")));
+
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&2u32);
+    assert_eq!(ohloss.last_report(&2u32), Some(Symbol::new(&env, "report_draw")));
+
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&3u32, &p2, &fake_commit(&env, 0xBB));
+    client.forfeit(&3u32, &p1);
+    assert_eq!(ohloss.last_report(&3u32), Some(Symbol::new(&env, "report_forfeit")));
+    assert!(client.get_game(&3u32).unwrap().forfeit);
+}
+
+#[test]
+fn test_forfeit_pays_opponent() {
+    let (env, client_id, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_forfeit(&1u32, &outsider), Err(Ok(Error::NotPlayer)));
+
+    client.forfeit(&1u32, &p1);
+    let published = env.events().all().filter_by_contract(&client_id);
+    assert_eq!(token.balance(&p2), 10_950);
+    assert_eq!(token.balance(&client_id), 50);
+    assert_eq!(client.get_winner(&1u32), Some(p2.clone()));
+
+    // The settlement event carries the commitments; neither seed was revealed
+    let game = client.get_game(&1u32).unwrap();
+    let expected = crate::events::GameSettled {
+        session_id: 1,
+        seq: game.event_seq,
+        winner: Some(p2),
+        outcome: Winner::Player2,
+        memo: game.memo,
+        seed_commit1: game.seed_commit1,
+        seed_commit2: game.seed_commit2,
+        seed1: BytesN::from_array(&env, &[0u8; 32]),
+        seed2: BytesN::from_array(&env, &[0u8; 32]),
+    };
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &client_id)));
+}
+
+#[test]
+fn test_acknowledged_result_unlocks_discounted_rematch() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_dispute_window(&100);
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    let outsider = Address::generate(&env);
+    let rematch = GameOptions {
+        wager_token: Some(token.address.clone()),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        rematch_of: Some(1),
+        ..no_options(&env)
+    };
+    assert_eq!(client.try_acknowledge_result(&1u32, &p1), Err(Ok(Error::InvalidState)));
+    client.forfeit(&1u32, &p1);
+
+    // Only the loser acknowledges, once, and then cannot contest
+    assert_eq!(client.try_acknowledge_result(&1u32, &p2), Err(Ok(Error::NotPlayer)));
+    assert_eq!(client.try_create_game(&2u32, &p2, &fake_commit(&env, 0xCC), &rematch), Err(Ok(Error::RematchUnavailable)));
+    client.acknowledge_result(&1u32, &p1);
+    let published = env.events().all().filter_by_contract(&contract_id);
+    let expected = crate::events::ResultAcknowledged { session_id: 1, seq: client.get_game(&1u32).unwrap().event_seq, player: p1.clone() };
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+    assert!(client.get_game(&1u32).unwrap().result_acknowledged);
+    assert_eq!(client.try_acknowledge_result(&1u32, &p1), Err(Ok(Error::InvalidState)));
+    assert_eq!(client.try_flag_dispute(&1u32, &p1, &30), Err(Ok(Error::InvalidState)));
+
+    // Either player may open the rematch, at half the rake, for the other
+    assert_eq!(client.try_create_game(&2u32, &outsider, &fake_commit(&env, 0xCC), &rematch), Err(Ok(Error::RematchUnavailable)));
+    client.create_game(&2u32, &p2, &fake_commit(&env, 0xCC), &rematch);
+    assert_eq!(client.get_game(&2u32).unwrap().rake_bps, 125);
+    assert_eq!(client.try_join_game(&2u32, &outsider, &fake_commit(&env, 0xDD)), Err(Ok(Error::NotPlayer)));
+    client.join_game(&2u32, &p1, &fake_commit(&env, 0xDD));
+
+    // One rematch per acknowledgment
+    assert_eq!(client.try_create_game(&3u32, &p1, &fake_commit(&env, 0xEE), &rematch), Err(Ok(Error::RematchUnavailable)));
+
+    // ...unless it is cancelled, by timeout or by its creator, or lapses
+    let timing = client.get_timing_config();
+    env.ledger().with_mut(|l| l.sequence_number += timing.reveal_window + 1);
+    assert_eq!(client.claim_timeout(&2u32), None);
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xEE), &rematch);
+    assert_eq!(client.try_create_game(&4u32, &p1, &fake_commit(&env, 0xEE), &rematch), Err(Ok(Error::RematchUnavailable)));
+    client.cancel_game(&3u32);
+    client.create_game(&4u32, &p2, &fake_commit(&env, 0xEE), &rematch);
+    env.ledger().with_mut(|l| l.sequence_number += timing.join_window + 1);
+    client.create_game(&5u32, &p1, &fake_commit(&env, 0xEE), &rematch);
+}
+
+#[test]
+fn test_set_ohloss_negotiates_protocol_version() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    assert_eq!(client.get_ohloss_version(), 2);
+
+    let unsupported = env.register(mock_ohloss::MockOhloss, ());
+    mock_ohloss::MockOhlossClient::new(&env, &unsupported).set_protocol_version(&99);
+    assert_eq!(client.try_set_ohloss(&unsupported), Err(Ok(Error::IncompatibleOhloss)));
+
+    // A build from before `protocol_version` existed speaks version 1
+    let unversioned = env.register(mock_ohloss::MockOhloss, ());
+    mock_ohloss::MockOhlossClient::new(&env, &unversioned).set_protocol_version(&0);
+    assert_eq!(client.set_ohloss(&unversioned), 1);
+
+    // A version 1 Ohloss is never asked for capabilities, even if it has them
+    let legacy_id = env.register(mock_ohloss::MockOhloss, ());
+    let legacy = mock_ohloss::MockOhlossClient::new(&env, &legacy_id);
+    legacy.set_protocol_version(&1);
+    legacy.set_capabilities(&OHLOSS_CAP_FORFEITS);
+    assert_eq!(client.set_ohloss(&legacy_id), 1);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.forfeit(&1u32, &p2);
+    assert_eq!(legacy.last_report(&1u32), Some(Symbol::new(&env, "end_game")));
+    assert_eq!(mock_ohloss::MockOhlossClient::new(&env, &ohloss_id).last_report(&1u32), None);
+}
+
+#[test]
+fn test_failed_report_does_not_block_settlement_and_can_be_retried() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    ohloss.set_failing(&true);
+    client.forfeit(&1u32, &p2);
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Settled));
+    assert!(!client.get_game(&1u32).unwrap().result_reported);
+
+    assert_eq!(client.try_report_settlement(&1u32), Err(Ok(Error::ReportFailed)));
+    ohloss.set_failing(&false);
+    client.report_settlement(&1u32);
+    assert!(client.get_game(&1u32).unwrap().result_reported);
+    assert_eq!(ohloss.last_report(&1u32), Some(Symbol::new(&env, "end_game")));
+    assert_eq!(client.try_report_settlement(&1u32), Err(Ok(Error::InvalidState)));
+}
+
+#[test]
+fn test_join_reverts_when_ohloss_rejects_start_game() {
+    let (env, contract_id, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    let (token, token_client) = create_token(&env, &[&p1, &p2], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: 1_000,
+        escrow_wager: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+
+    ohloss.set_failing(&true);
+    let result = client.try_join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(result, Err(Ok(Error::RegisterFailed)));
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!((game.phase, game.seed_commit2), (Phase::Created, fake_commit(&env, 0)));
+    assert_eq!(token_client.balance(&p2), 10_000);
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env, 1u32]);
+
+    ohloss.set_failing(&false);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(token_client.balance(&contract_id), 2_000);
+}
+
+#[test]
+fn test_game_reports_to_the_ohloss_it_registered_with() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(client.get_game(&1u32).unwrap().ohloss, Some(ohloss_id.clone()));
+
+    let replacement = env.register(mock_ohloss::MockOhloss, ());
+    client.set_ohloss(&replacement);
+    client.forfeit(&1u32, &p2);
+    let original = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    assert_eq!(original.last_report(&1u32), Some(Symbol::new(&env, "end_game")));
+    assert_eq!(mock_ohloss::MockOhlossClient::new(&env, &replacement).last_report(&1u32), None);
+}
+
+#[test]
+fn test_game_reports_to_its_result_sink() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    let league_id = env.register(mock_ohloss::MockOhloss, ());
+    let league = mock_ohloss::MockOhlossClient::new(&env, &league_id);
+    let options = GameOptions {
+        result_sink: Some(league_id.clone()),
+        ..no_options(&env)
+    };
+
+    let result = client.try_create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    assert_eq!(result, Err(Ok(Error::UnknownResultSink)));
+    assert_eq!(client.allow_result_sink(&league_id), 2);
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.forfeit(&1u32, &p2);
+    assert_eq!(league.last_report(&1u32), Some(Symbol::new(&env, "end_game")));
+    assert_eq!(mock_ohloss::MockOhlossClient::new(&env, &ohloss_id).last_report(&1u32), None);
+
+    // Games on a removed sink cannot start
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.remove_result_sink(&league_id);
+    let result = client.try_join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(result, Err(Ok(Error::UnknownResultSink)));
+}
+
+#[test]
+fn test_settle_or_get_is_idempotent() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+    let proof = synthetic_proof(&env, false);
+    let settler = Address::generate(&env);
+
+    assert_eq!(client.settle_or_get(&1u32, &proof, &inputs, &settler), Some(p1.clone()));
+    assert_eq!(client.settle_or_get(&1u32, &proof, &inputs, &settler), Some(p1));
+    let result = client.try_settle_game(&1u32, &proof, &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::GameAlreadySettled)));
+
+    // Unsettled sessions still surface the usual errors
+    let result = client.try_settle_or_get(&2u32, &proof, &inputs, &settler);
+    assert_eq!(result, Err(Ok(Error::GameNotFound)));
+}
+
+#[test]
+fn test_get_games_batch() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.create_game(&3u32, &p2, &fake_commit(&env, 0xBB), &no_options(&env));
+
+    let games = client.get_games(&vec![&env, 3u32, 2, 1]);
+    assert_eq!(games.len(), 3);
+    assert_eq!(games.get(0).unwrap().unwrap().player1, p2);
+    assert!(games.get(1).unwrap().is_none());
+    assert_eq!(games.get(2).unwrap().unwrap().player1, p1);
+
+    let mut too_many = Vec::new(&env);
+    for id in 0..=storage::MAX_PAGE_SIZE {
+        too_many.push_back(id);
+    }
+    assert_eq!(client.try_get_games(&too_many), Err(Ok(Error::BatchTooLarge)));
+}
+
+// ── Statistics ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_stats_by_variant_and_token() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.forfeit(&1u32, &p2);
+
+    client.register_variant(&1u32, &Variant { deck_size: 25, suits: 3, rounds: 12, vk_id: 0 });
+    let options = GameOptions { variant: 1, ..no_options(&env) };
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.agree_draw(&2u32);
+
+    let global = client.get_stats();
+    assert_eq!((global.settled, global.draws, global.forfeits), (2, 1, 1));
+    let classic = client.get_variant_stats(&0u32);
+    assert_eq!((classic.settled, classic.draws, classic.forfeits), (1, 0, 1));
+    let variant = client.get_variant_stats(&1u32);
+    assert_eq!((variant.settled, variant.draws, variant.forfeits), (1, 1, 0));
+
+    let volume = client.get_token_volume(&token.address);
+    assert_eq!((volume.games, volume.staked, volume.rake), (1, 2_000, 50));
+}
+
+#[test]
+fn test_settlement_receipt_recorded() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+    let proof = synthetic_proof(&env, false);
+    assert!(client.get_settlement_receipt(&1u32).is_none());
+    client.settle_game(&1u32, &proof, &inputs, &Address::generate(&env));
+
+    let receipt = client.get_settlement_receipt(&1u32).unwrap();
+    let mut preimage = Bytes::from_array(&env, &proof.pi_a.to_array());
+    preimage.extend_from_array(&proof.pi_b.to_array());
+    preimage.extend_from_array(&proof.pi_c.to_array());
+    assert_eq!(receipt.proof_hash, env.crypto().sha256(&preimage).to_bytes());
+    assert_eq!(receipt.winner_address, Some(p1.clone()));
+    assert_eq!(receipt.seed1, inputs.seed1);
+    assert_eq!(receipt.seed_commit2, inputs.seed_commit2);
+    assert_eq!(receipt.settled_at, env.ledger().sequence());
+
+    // Settled without a proof: zero proof hash
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    client.forfeit(&2u32, &p1);
+    let receipt = client.get_settlement_receipt(&2u32).unwrap();
+    assert_eq!(receipt.proof_hash, BytesN::from_array(&env, &[0u8; 32]));
+    assert_eq!(receipt.winner, Winner::Player2);
+}
+
+#[test]
+fn test_attestation_layout() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.create_game(&9u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&9u32, &p2, &fake_commit(&env, 0xBB));
+    assert!(client.get_attestation(&9u32).is_none());
+    client.forfeit(&9u32, &p2);
+
+    // Pinned byte layout: relayers on other chains parse it as version 1
+    let mut expected = Bytes::from_slice(&env, b"dead-mans-draw/attestation");
+    expected.extend_from_array(&1u32.to_be_bytes());
+    expected.append(&contract_id.clone().to_xdr(&env));
+    expected.extend_from_array(&9u32.to_be_bytes());
+    expected.append(&p1.clone().to_xdr(&env));
+    expected.append(&p2.clone().to_xdr(&env));
+    expected.extend_from_array(&1u32.to_be_bytes()); // Winner::Player1
+    expected.extend_from_array(&env.ledger().sequence().to_be_bytes());
+    assert_eq!(client.get_attestation(&9u32), Some(expected));
+}
+
+// ── Admin lock ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_freeze_vk_then_renounce_admin() {
+    let (env, _, client, _, _, _, _) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    client.freeze_vk();
+    assert!(client.is_vk_frozen());
+    assert!(!client.is_admin_renounced());
+    assert_eq!(client.try_set_vk(&synthetic_vk(&env, false)), Err(Ok(Error::VkFrozen)));
+    let variant = Variant { deck_size: 25, suits: 3, rounds: 12, vk_id: 0 };
+    assert_eq!(client.try_register_variant(&1u32, &variant), Err(Ok(Error::VkFrozen)));
+
+    // The rest of the admin role survives a VK freeze
+    client.set_rake_bps(&100);
+
+    client.renounce_admin();
+    assert!(client.is_admin_renounced());
+    assert_eq!(client.try_set_rake_bps(&200), Err(Ok(Error::AdminRenounced)));
+    assert_eq!(
+        client.try_set_ohloss(&Address::generate(&env)),
+        Err(Ok(Error::AdminRenounced))
+    );
+    assert_eq!(client.try_renounce_admin(), Err(Ok(Error::AdminRenounced)));
+    assert_eq!(client.get_rake_bps(), 100);
+}
+
+#[test]
+fn test_renounce_closes_the_council_queue() {
+    let (env, _, client, _, _, _, _) = setup_env();
+    let signers = [Address::generate(&env), Address::generate(&env)];
+    client.set_council(&Council { signers: Vec::from_array(&env, signers.clone()), threshold: 1 });
+    let queued = client.propose(&signers[0], &AdminAction::SetRakeBps(500));
+
+    client.renounce_admin();
+    assert!(client.get_config().vk_frozen);
+    assert_eq!(client.try_execute(&queued), Err(Ok(Error::AdminRenounced)));
+    let result = client.try_propose(&signers[1], &AdminAction::SetOhloss(Address::generate(&env)));
+    assert_eq!(result, Err(Ok(Error::AdminRenounced)));
+    assert_eq!(client.get_rake_bps(), 250);
+
+    // Treasury withdrawals stay open
+    let action = AdminAction::WithdrawTreasury(Address::generate(&env), Address::generate(&env), 0);
+    client.propose(&signers[0], &action);
+}
+
+#[test]
+fn test_council_gates_sensitive_operations() {
+    let (env, contract_id, client, _, _, _, _) = setup_env();
+    let signers = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+    let council = Council {
+        signers: Vec::from_array(&env, signers.clone()),
+        threshold: 2,
+    };
+    let bad = Council { threshold: 4, ..council.clone() };
+    assert_eq!(client.try_set_council(&bad), Err(Ok(Error::InvalidConfig)));
+    client.set_council(&council);
+    assert_eq!(client.try_set_council(&council), Err(Ok(Error::CouncilRequired)));
+
+    // Direct calls are closed once the council exists
+    assert_eq!(client.try_register_vk(&3u32, &synthetic_vk(&env, false)), Err(Ok(Error::CouncilRequired)));
+    let token = Address::generate(&env);
+    let to = Address::generate(&env);
+    assert_eq!(client.try_withdraw_treasury(&token, &to, &1), Err(Ok(Error::CouncilRequired)));
+    assert_eq!(client.try_set_rake_bps(&100), Err(Ok(Error::CouncilRequired)));
+    assert_eq!(client.try_set_ohloss(&Address::generate(&env)), Err(Ok(Error::CouncilRequired)));
+    let registry = Address::generate(&env);
+    assert_eq!(client.try_set_name_registry(&registry), Err(Ok(Error::CouncilRequired)));
+
+    let action = AdminAction::RegisterVk(3, synthetic_vk(&env, false));
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_propose(&outsider, &action), Err(Ok(Error::NotCouncilMember)));
+    let id = client.propose(&signers[0], &action);
+    assert_eq!(client.try_execute(&id), Err(Ok(Error::NotEnoughApprovals)));
+    assert_eq!(client.try_approve(&signers[0], &id), Err(Ok(Error::AlreadyApproved)));
+    assert_eq!(client.approve(&signers[2], &id), 2);
+    client.execute(&id);
+    assert!(env.as_contract(&contract_id, || storage::get_vk(&env, 3).is_some()));
+    assert!(client.get_proposal(&id).is_none());
+    assert_eq!(client.try_execute(&id), Err(Ok(Error::ProposalNotFound)));
+
+    // The council can reshape itself
+    let smaller = Council {
+        signers: Vec::from_array(&env, [signers[1].clone()]),
+        threshold: 1,
+    };
+    let id = client.propose(&signers[1], &AdminAction::SetCouncil(smaller.clone()));
+    client.approve(&signers[2], &id);
+    client.execute(&id);
+    assert_eq!(client.get_council(), Some(smaller));
+
+    let id = client.propose(&signers[1], &AdminAction::SetNameRegistry(registry.clone()));
+    client.execute(&id);
+    assert_eq!(client.get_config().name_registry, Some(registry));
+}
+
+#[test]
+fn test_timelock_queues_admin_operations() {
+    let (env, contract_id, client, admin, _, _, _) = setup_env();
+    client.set_timelock_delay(&100);
+    assert_eq!(client.get_timelock_delay(), 100);
+    assert_eq!(client.try_set_timelock_delay(&0), Err(Ok(Error::TimelockRequired)));
+
+    // Direct calls must now go through the queue
+    assert_eq!(client.try_set_rake_bps(&250), Err(Ok(Error::TimelockRequired)));
+    assert_eq!(client.try_register_vk(&3u32, &synthetic_vk(&env, false)), Err(Ok(Error::TimelockRequired)));
+
+    let outsider = Address::generate(&env);
+    let action = AdminAction::SetRakeBps(250);
+    assert_eq!(client.try_propose(&outsider, &action), Err(Ok(Error::NotAdmin)));
+    let id = client.propose(&admin, &action);
+    assert_eq!(client.get_proposal(&id).unwrap().eta, env.ledger().sequence() + 100);
+    assert_eq!(client.try_execute(&id), Err(Ok(Error::TimelockActive)));
+
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    client.execute(&id);
+    assert_eq!(env.as_contract(&contract_id, || storage::get_rake_bps(&env)), 250);
+
+    // A queued operation can be dropped before it runs
+    let id = client.propose(&admin, &AdminAction::RegisterVk(3, synthetic_vk(&env, false)));
+    assert_eq!(client.try_cancel_proposal(&outsider, &id), Err(Ok(Error::NotAdmin)));
+    client.cancel_proposal(&admin, &id);
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    assert_eq!(client.try_execute(&id), Err(Ok(Error::ProposalNotFound)));
+    assert!(!env.as_contract(&contract_id, || storage::get_vk(&env, 3).is_some()));
+}
+
+#[test]
+fn test_pause_flags_are_independent() {
+    let (env, _, client, _, _, player1, player2) = setup_env();
+    client.create_game(&1u32, &player1, &fake_commit(&env, 1), &no_options(&env));
+
+    client.set_paused(&Operation::Create, &true);
+    assert!(client.is_paused(&Operation::Create));
+    assert!(!client.is_paused(&Operation::Join));
+    assert_eq!(
+        client.try_create_game(&2u32, &player1, &fake_commit(&env, 2), &no_options(&env)),
+        Err(Ok(Error::Paused))
+    );
+
+    // Existing games can still be played out
+    client.join_game(&1u32, &player2, &fake_commit(&env, 3));
+
+    client.set_paused(&Operation::Settle, &true);
+    assert_eq!(client.try_forfeit(&1u32, &player1), Err(Ok(Error::Paused)));
+    client.set_paused(&Operation::Settle, &false);
+    client.forfeit(&1u32, &player1);
+
+    client.set_paused(&Operation::Create, &false);
+    client.create_game(&2u32, &player1, &fake_commit(&env, 2), &no_options(&env));
+}
+
+#[test]
+fn test_breaker_halts_variant_after_invalid_proofs() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+    client.set_breaker_threshold(&2);
+    let reporter = Address::generate(&env);
+    let (token, token_client) = create_token(&env, &[&reporter], 1_000);
+
+    let bad = synthetic_proof(&env, true);
+    let other_bad = Groth16Proof { pi_c: bad.pi_a.clone(), ..bad.clone() };
+    let good = synthetic_proof(&env, false);
+
+    // Reports are closed until the admin sets a bond
+    let result = client.try_report_invalid_proof(&1u32, &reporter, &bad, &inputs, &None);
+    assert_eq!(result, Err(Ok(Error::InvalidConfig)));
+    client.set_report_bond(&BondTerms { token: token.clone(), amount: 50 });
+
+    let result = client.try_report_invalid_proof(&1u32, &reporter, &good, &inputs, &None);
+    assert_eq!(result, Err(Ok(Error::InvalidState)));
+    assert_eq!(client.report_invalid_proof(&1u32, &reporter, &bad, &inputs, &None), 1);
+    let result = client.try_report_invalid_proof(&1u32, &reporter, &bad, &inputs, &None);
+    assert_eq!(result, Err(Ok(Error::AlreadyReported)));
+    assert_eq!(client.report_invalid_proof(&1u32, &reporter, &other_bad, &inputs, &None), 2);
+    assert_eq!(client.get_breaker(&0u32), (2, true));
+    assert_eq!(token_client.balance(&contract_id), 100);
+
+    let settler = Address::generate(&env);
+    assert_eq!(
+        client.try_settle_game(&1u32, &good, &inputs, &settler),
+        Err(Ok(Error::BreakerTripped))
+    );
+
+    // Resetting a tripped breaker returns the bonds behind it
+    client.reset_breaker(&0u32);
+    assert_eq!(client.get_breaker(&0u32), (0, false));
+    assert_eq!(token_client.balance(&reporter), 1_000);
+
+    // A valid proof clearing the count forfeits the bonds to the treasury
+    let third_bad = Groth16Proof { pi_a: bad.pi_c.clone(), ..bad.clone() };
+    assert_eq!(client.report_invalid_proof(&1u32, &reporter, &third_bad, &inputs, &None), 1);
+    assert_eq!(client.settle_game(&1u32, &good, &inputs, &settler), Some(p1));
+    assert_eq!(client.get_breaker(&0u32), (0, false));
+    assert_eq!(client.get_treasury_account(&token).accrued, 50);
+    assert_eq!(token_client.balance(&reporter), 950);
+}
+
+#[test]
+fn test_privileged_calls_advance_audit_seq() {
+    let (_, _, client, admin, _, _, _) = setup_env();
+    assert_eq!(client.get_audit_seq(), 0);
+    client.set_rake_bps(&100);
+    client.set_paused(&Operation::Join, &true);
+    assert_eq!(client.get_audit_seq(), 2);
+
+    // Queued operations log the proposal and its execution
+    let id = client.propose(&admin, &AdminAction::SetRakeBps(200));
+    client.execute(&id);
+    assert_eq!(client.get_audit_seq(), 4);
+
+    // Failed calls roll back with the rest of the invocation
+    assert_eq!(client.try_set_rake_bps(&100_000), Err(Ok(Error::InvalidConfig)));
+    assert_eq!(client.get_audit_seq(), 4);
+}
+
+fn auth_args(env: &Env, who: &Address) -> Vec<Val> {
+    let auths = env.auths();
+    let (_, invocation) = auths.iter().find(|(addr, _)| addr == who).unwrap();
+    match &invocation.function {
+        AuthorizedFunction::Contract((_, _, args)) => args.clone(),
+        _ => panic!("expected a contract call"),
+    }
+}
+
+#[test]
+fn test_player_auth_is_bound_to_session_args() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let options = no_options(&env);
+    let commit1 = fake_commit(&env, 0xAA);
+    let commit2 = fake_commit(&env, 0xBB);
+    let seed1 = fake_commit(&env, 0x11);
+
+    client.create_game(&7u32, &p1, &commit1, &options);
+    assert_eq!(auth_args(&env, &p1), (7u32, commit1.clone(), options).into_val(&env));
+
+    client.join_game(&7u32, &p2, &commit2);
+    assert_eq!(auth_args(&env, &p2), (7u32, commit2).into_val(&env));
+
+    client.reveal_seed(&7u32, &p1, &seed1, &SeedOpening::Plain);
+    assert_eq!(auth_args(&env, &p1), (7u32, seed1).into_val(&env));
+
+    client.forfeit(&7u32, &p2);
+    assert_eq!(auth_args(&env, &p2), (7u32,).into_val(&env));
+}
+
+/// Authorize the next call for a smart-wallet player with a real signature,
+/// so the host runs the wallet's `__check_auth` rather than a mock.
+fn wallet_auth(
+    env: &Env,
+    client: &PirateCardsContractClient,
+    wallet: &Address,
+    key: &BytesN<32>,
+    fn_name: &str,
+    args: Vec<Val>,
+) {
+    let invoke = MockAuthInvoke {
+        contract: &client.address,
+        fn_name,
+        args,
+        sub_invokes: &[],
+    };
+    let mut entry: xdr::SorobanAuthorizationEntry = MockAuth { address: wallet, invoke: &invoke }.into();
+    if let xdr::SorobanCredentials::Address(credentials) = &mut entry.credentials {
+        credentials.signature = xdr::ScVal::Bytes(xdr::ScBytes(key.to_array().try_into().unwrap()));
+    }
+    env.set_auths(&[entry]);
+}
+
+#[test]
+fn test_smart_wallet_players() {
+    let (env, _, client, _, _, _, _) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    let key1 = fake_commit(&env, 0x51);
+    let key2 = fake_commit(&env, 0x52);
+    let wallet1 = env.register(mock_wallet::MockWallet, (&key1,));
+    let wallet2 = env.register(mock_wallet::MockWallet, (&key2,));
+
+    let options = no_options(&env);
+    let commit1 = fake_commit(&env, 0xAA);
+    let commit2 = fake_commit(&env, 0xBB);
+    let seed1 = fake_commit(&env, 0x11);
+    let seed2 = fake_commit(&env, 0x22);
+
+    // A signature from the wrong key is rejected by the wallet
+    wallet_auth(&env, &client, &wallet1, &key2, "create_game", (1u32, commit1.clone(), options.clone()).into_val(&env));
+    assert!(client.try_create_game(&1u32, &wallet1, &commit1, &options).is_err());
+
+    wallet_auth(&env, &client, &wallet1, &key1, "create_game", (1u32, commit1.clone(), options.clone()).into_val(&env));
+    client.create_game(&1u32, &wallet1, &commit1, &options);
+    wallet_auth(&env, &client, &wallet2, &key2, "join_game", (1u32, commit2.clone()).into_val(&env));
+    client.join_game(&1u32, &wallet2, &commit2);
+    wallet_auth(&env, &client, &wallet1, &key1, "reveal_seed", (1u32, seed1.clone()).into_val(&env));
+    client.reveal_seed(&1u32, &wallet1, &seed1, &SeedOpening::Plain);
+    wallet_auth(&env, &client, &wallet2, &key2, "reveal_seed", (1u32, seed2.clone()).into_val(&env));
+    client.reveal_seed(&1u32, &wallet2, &seed2, &SeedOpening::Plain);
+
+    let inputs = PublicInputs {
+        seed_commit1: commit1,
+        seed_commit2: commit2,
+        seed1,
+        seed2,
+        session_id: u32_field(&env, 1),
+        winner: fake_commit(&env, 1),
+        beacon: fake_commit(&env, 0),
+        deck_size: u32_field(&env, 25),
+        suits: u32_field(&env, 3),
+    };
+    let winner = client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(winner, Some(wallet1.clone()));
+    assert_eq!(client.get_game(&1u32).unwrap().player2, wallet2);
+}
+
+/// Sign an outcome digest with a secp256k1 key, returning the signature and
+/// recovery id in the form `settle_signed_secp256k1` takes.
+fn secp256k1_sign(env: &Env, key: &k256::ecdsa::SigningKey, digest: &[u8; 32]) -> (BytesN<64>, u32) {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(digest).unwrap();
+    (BytesN::from_array(env, &signature.to_bytes().into()), recovery_id.to_byte() as u32)
+}
+
+fn secp256k1_public(env: &Env, key: &k256::ecdsa::SigningKey) -> BytesN<65> {
+    let point = key.verifying_key().to_encoded_point(false);
+    BytesN::from_array(env, point.as_bytes().try_into().unwrap())
+}
+
+#[test]
+fn test_settle_signed_secp256k1() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let key1 = k256::ecdsa::SigningKey::from_bytes(&[0x11; 32].into()).unwrap();
+    let key2 = k256::ecdsa::SigningKey::from_bytes(&[0x22; 32].into()).unwrap();
+    client.bind_secp256k1_key(&p1, &secp256k1_public(&env, &key1));
+    client.bind_secp256k1_key(&p2, &secp256k1_public(&env, &key2));
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    let payload = OutcomePayload {
+        contract: contract_id.clone(),
+        session_id: 1,
+        outcome: Winner::Player2,
+    };
+    let digest = env.as_contract(&contract_id, || payload.digest(&env)).to_array();
+    let (sig1, rec1) = secp256k1_sign(&env, &key1, &digest);
+    let (sig2, rec2) = secp256k1_sign(&env, &key2, &digest);
+
+    // Both signatures must cover the submitted outcome
+    assert_eq!(
+        client.try_settle_signed_secp256k1(&1u32, &Winner::Player1, &sig1, &rec1, &sig2, &rec2),
+        Err(Ok(Error::InvalidSignature))
+    );
+    assert_eq!(
+        client.try_settle_signed_secp256k1(&1u32, &Winner::Player2, &sig1, &rec1, &sig1, &rec1),
+        Err(Ok(Error::InvalidSignature))
+    );
+
+    let winner = client.settle_signed_secp256k1(&1u32, &Winner::Player2, &sig1, &rec1, &sig2, &rec2);
+    assert_eq!(winner, Some(p2));
+    assert_eq!(client.get_phase(&1u32), Some(Phase::Settled));
+    let receipt = client.get_settlement_receipt(&1u32).unwrap();
+    assert_eq!(receipt.proof_hash, BytesN::from_array(&env, &[0u8; 32]));
+}
+
+#[test]
+fn test_settle_signed_ed25519() {
+    use ed25519_dalek::Signer;
+
+    let (env, contract_id, client, _, _, _, _) = setup_env();
+    let key1 = ed25519_dalek::SigningKey::from_bytes(&[0x11; 32]);
+    let key2 = ed25519_dalek::SigningKey::from_bytes(&[0x22; 32]);
+    let account = |key: &ed25519_dalek::SigningKey| {
+        let public = BytesN::from_array(&env, key.verifying_key().as_bytes());
+        Address::from_payload(&env, AddressPayload::AccountIdPublicKeyEd25519(public))
+    };
+    let (p1, p2) = (account(&key1), account(&key2));
+
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+
+    let payload = OutcomePayload {
+        contract: contract_id.clone(),
+        session_id: 1,
+        outcome: Winner::Player1,
+    };
+    let digest = env.as_contract(&contract_id, || payload.digest(&env)).to_array();
+    let sign = |key: &ed25519_dalek::SigningKey| BytesN::from_array(&env, &key.sign(&digest).to_bytes());
+    let (sig1, sig2) = (sign(&key1), sign(&key2));
+
+    // A signature by the wrong account does not verify
+    assert!(client.try_settle_signed(&1u32, &Winner::Player1, &sig2, &sig1).is_err());
+
+    let winner = client.settle_signed(&1u32, &Winner::Player1, &sig1, &sig2);
+    assert_eq!(winner, Some(p1));
+    let receipt = client.get_settlement_receipt(&1u32).unwrap();
+    assert_eq!(receipt.winner, Winner::Player1);
+    assert_eq!(receipt.proof_hash, BytesN::from_array(&env, &[0u8; 32]));
+
+    // Contract addresses have no account key to sign with
+    client.create_game(&2u32, &Address::generate(&env), &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&2u32, &p2, &fake_commit(&env, 0xBB));
+    assert_eq!(
+        client.try_settle_signed(&2u32, &Winner::Player1, &sig1, &sig2),
+        Err(Ok(Error::InvalidSignature))
+    );
+}
+
+#[test]
+fn test_dispute_freezes_payout_until_arbitrated() {
+    let (env, contract_id, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    ohloss.set_capabilities(&OHLOSS_CAP_CORRECTIONS);
+    let arbiter = Address::generate(&env);
+    client.set_dispute_window(&100);
+    client.set_arbiter(&arbiter);
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.forfeit(&1u32, &p1);
+
+    // The pot stays escrowed while the result can be challenged
+    assert_eq!(token.balance(&contract_id), 2_000);
+    assert_eq!(client.get_pending_payout(&1u32).unwrap().winner, Winner::Player2);
+    let outsider = Address::generate(&env);
+    assert_eq!(client.try_flag_dispute(&1u32, &outsider, &100), Err(Ok(Error::NotPlayer)));
+    assert_eq!(client.try_flag_dispute(&1u32, &p1, &99), Err(Ok(Error::InvalidAmount)));
+    client.flag_dispute(&1u32, &p1, &100);
+    assert_eq!(client.try_flag_dispute(&1u32, &p2, &100), Err(Ok(Error::DisputeOpen)));
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    assert_eq!(client.try_release_payout(&1u32), Err(Ok(Error::DisputeOpen)));
+
+    // Overturned: the challenger gets the bond back and the pot less rake
+    client.arbitrate(&1u32, &Winner::Player1);
+    assert_eq!(client.get_game_log(&1u32).last_unchecked().actor, arbiter);
+    assert_eq!(token.balance(&p1), 9_000 - 100 + 100 + 1_950);
+    assert_eq!(token.balance(&p2), 9_000);
+    assert_eq!(client.get_winner(&1u32), Some(p1.clone()));
+    assert_eq!(client.get_settlement_receipt(&1u32).unwrap().winner, Winner::Player1);
+    assert!(client.get_dispute(&1u32).is_none());
+    assert_eq!(client.try_flag_dispute(&1u32, &p2, &100), Err(Ok(Error::DisputeWindowClosed)));
+
+    // Ohloss hears about the overturned result
+    assert_eq!(ohloss.last_report(&1u32), Some(Symbol::new(&env, "correct_result")));
+    assert!(client.get_game(&1u32).unwrap().result_reported);
+}
+
+#[test]
+fn test_failed_correction_is_retried() {
+    let (env, _, client, _, ohloss_id, p1, p2) = setup_env();
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss_id);
+    ohloss.set_capabilities(&OHLOSS_CAP_CORRECTIONS);
+    client.set_dispute_window(&100);
+    staked_game(&env, &client, &p1, &p2, 1_000);
+    client.forfeit(&1u32, &p1);
+    client.flag_dispute(&1u32, &p1, &100);
+
+    ohloss.set_failing(&true);
+    client.settle_dispute(&1u32, &Winner::Player1);
+    let log = client.get_game_log(&1u32);
+    assert_eq!(log.get_unchecked(log.len() - 2).action, Symbol::new(&env, "settle_dispute"));
+    assert_eq!(log.last_unchecked().actor, p2);
+    assert!(!client.get_game(&1u32).unwrap().result_reported);
+    assert_eq!(ohloss.last_report(&1u32), Some(Symbol::new(&env, "end_game")));
+
+    ohloss.set_failing(&false);
+    client.report_settlement(&1u32);
+    assert_eq!(ohloss.last_report(&1u32), Some(Symbol::new(&env, "correct_result")));
+    assert_eq!(client.try_report_settlement(&1u32), Err(Ok(Error::InvalidState)));
+}
+
+#[test]
+fn test_unruled_dispute_releases_the_original_result() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_dispute_window(&100);
+    client.set_arbiter(&Address::generate(&env));
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.forfeit(&1u32, &p1);
+    client.flag_dispute(&1u32, &p1, &100);
+
+    env.ledger().with_mut(|l| l.sequence_number += 100 + crate::ARBITRATION_WINDOW - 1);
+    assert_eq!(client.try_release_payout(&1u32), Err(Ok(Error::DisputeOpen)));
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    client.release_payout(&1u32);
+    assert_eq!(token.balance(&p2), 9_000 + 1_950);
+    assert_eq!(token.balance(&p1), 9_000);
+    assert!(client.get_dispute(&1u32).is_none());
+    assert_eq!(client.get_winner(&1u32), Some(p2));
+}
+
+#[test]
+fn test_unchallenged_payout_released_after_window() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_dispute_window(&100);
+    let token = staked_game(&env, &client, &p1, &p2, 1_000);
+    client.forfeit(&1u32, &p1);
+
+    assert_eq!(client.try_release_payout(&1u32), Err(Ok(Error::TimelockActive)));
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    assert_eq!(client.try_flag_dispute(&1u32, &p1, &100), Err(Ok(Error::DisputeWindowClosed)));
+    client.release_payout(&1u32);
+    assert_eq!(token.balance(&p2), 9_000 + 1_950);
+    assert_eq!(token.balance(&contract_id), 50);
+}
+
+#[test]
+fn test_creation_rate_limit() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.set_rate_limit(&RateLimit { window: 10, max_creations: 2 });
+    client.create_game(&1u32, &p1, &fake_commit(&env, 1), &no_options(&env));
+    client.create_game(&2u32, &p1, &fake_commit(&env, 2), &no_options(&env));
+    assert_eq!(
+        client.try_create_game(&3u32, &p1, &fake_commit(&env, 3), &no_options(&env)),
+        Err(Ok(Error::RateLimited))
+    );
+
+    // Limits are per address and reset with the window
+    client.create_game(&3u32, &p2, &fake_commit(&env, 3), &no_options(&env));
+    env.ledger().with_mut(|l| l.sequence_number += 10);
+    client.create_game(&4u32, &p1, &fake_commit(&env, 4), &no_options(&env));
+}
+
+#[test]
+fn test_pair_cooldown_applies_to_rated_games_only() {
+    let (env, _, client, _, ohloss, p1, p2) = setup_env();
+    client.set_pair_cooldown(&50);
+    client.create_game(&1u32, &p1, &fake_commit(&env, 1), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 2));
+
+    // Same pair, either seat, inside the cooldown
+    client.create_game(&2u32, &p2, &fake_commit(&env, 3), &no_options(&env));
+    assert_eq!(client.try_join_game(&2u32, &p1, &fake_commit(&env, 4)), Err(Ok(Error::PairCooldown)));
+
+    // Casual games are not limited and never reach Ohloss
+    let casual = GameOptions { unrated: true, ..no_options(&env) };
+    client.create_game(&3u32, &p2, &fake_commit(&env, 5), &casual);
+    client.join_game(&3u32, &p1, &fake_commit(&env, 6));
+    client.forfeit(&3u32, &p1);
+    let ohloss = mock_ohloss::MockOhlossClient::new(&env, &ohloss);
+    assert_eq!(ohloss.last_report(&3u32), None);
+    assert!(client.get_game(&3u32).unwrap().result_reported);
+
+    env.ledger().with_mut(|l| l.sequence_number += 50);
+    client.join_game(&2u32, &p1, &fake_commit(&env, 4));
+}
+
+#[test]
+fn test_vk_kept_out_of_instance_storage() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let key = storage::DataKey::Vk(0);
+    client.set_vk(&synthetic_vk(&env, false));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&key));
+        assert!(env.storage().persistent().has(&key));
+
+        // A key left in instance storage by an older build still settles
+        env.storage().persistent().remove(&key);
+        env.storage().instance().set(&key, &synthetic_vk(&env, false));
+    });
+
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&key));
+        assert!(env.storage().persistent().has(&key));
+    });
+}
+
+#[test]
+fn test_hash_only_vk_supplied_at_settlement() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    let vk = synthetic_vk(&env, false);
+    client.register_vk_hash(&0u32, &vk.hash(&env));
+    assert!(!env.as_contract(&contract_id, || storage::get_vk(&env, 0).is_some()));
+
+    let proof = synthetic_proof(&env, false);
+    let settler = Address::generate(&env);
+    assert_eq!(client.try_settle_game(&1u32, &proof, &inputs, &settler), Err(Ok(Error::VkRequired)));
+    assert_eq!(
+        client.try_settle_game_with_vk(&1u32, &proof, &inputs, &settler, &synthetic_vk(&env, true)),
+        Err(Ok(Error::VkHashMismatch))
+    );
+    assert_eq!(client.settle_game_with_vk(&1u32, &proof, &inputs, &settler, &vk), Some(p1));
+}
+
+#[test]
+fn test_settle_batch_skips_bad_proofs() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs1 = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    client.create_game(&2u32, &p1, &inputs1.seed_commit1, &no_options(&env));
+    client.join_game(&2u32, &p2, &inputs1.seed_commit2);
+    client.reveal_seed(&2u32, &p1, &inputs1.seed1, &SeedOpening::Plain);
+    client.reveal_seed(&2u32, &p2, &inputs1.seed2, &SeedOpening::Plain);
+    let mut inputs2 = inputs1.clone();
+    inputs2.session_id = u32_field(&env, 2);
+    client.create_game(&3u32, &p1, &inputs1.seed_commit1, &no_options(&env));
+    client.join_game(&3u32, &p2, &inputs1.seed_commit2);
+    client.reveal_seed(&3u32, &p1, &inputs1.seed1, &SeedOpening::Plain);
+    client.reveal_seed(&3u32, &p2, &inputs1.seed2, &SeedOpening::Plain);
+    let mut inputs3 = inputs1.clone();
+    inputs3.session_id = u32_field(&env, 3);
+
+    let entry = |session_id: u32, negated: bool, pub_inputs: &PublicInputs| BatchSettlement {
+        session_id,
+        proof: synthetic_proof(&env, negated),
+        pub_inputs: pub_inputs.clone(),
+    };
+    let settler = Address::generate(&env);
+
+    // A malformed proof is dropped before grouping, so it neither sinks
+    // the good one nor counts toward the breaker
+    let degenerate = BatchSettlement {
+        proof: Groth16Proof { pi_a: BytesN::from_array(&env, &[0u8; 64]), ..synthetic_proof(&env, false) },
+        ..entry(1, false, &inputs1)
+    };
+    let results = client.settle_batch(&vec![&env, entry(2, false, &inputs2), degenerate], &settler);
+    assert_eq!(results, vec![&env, true, false]);
+    assert_eq!(client.get_game(&2u32).unwrap().phase, Phase::Settled);
+    assert_eq!(client.get_breaker(&0u32), (0, false));
+
+    // One bad proof fails the aggregate; the good one still settles and
+    // only the bad one is counted. The per-proof fallback does not fit the
+    // default budget alongside it.
+    env.cost_estimate().budget().reset_unlimited();
+    let results = client.settle_batch(&vec![&env, entry(3, false, &inputs3), entry(1, true, &inputs1)], &settler);
+    assert_eq!(results, vec![&env, true, false]);
+    assert_eq!(client.get_game(&3u32).unwrap().phase, Phase::Settled);
+    assert_eq!(client.get_breaker(&0u32), (1, false));
+
+    // Repeats and already-settled sessions are skipped
+    let results = client.settle_batch(&vec![&env, entry(1, false, &inputs1), entry(1, false, &inputs1), entry(2, false, &inputs2)], &settler);
+    assert_eq!(results, vec![&env, true, false, false]);
+    assert_eq!(client.get_game(&1u32).unwrap().winner, Winner::Player1);
+    assert_eq!(client.get_breaker(&0u32), (0, false));
+
+    let mut too_many = Vec::new(&env);
+    for _ in 0..5 {
+        too_many.push_back(entry(1, false, &inputs1));
+    }
+    assert_eq!(client.try_settle_batch(&too_many, &settler), Err(Ok(Error::BatchTooLarge)));
+}
+
+#[test]
+fn test_settle_aggregate_applies_one_proof_to_many_sessions() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let inputs1 = revealed_game(&env, &client, &p1, &p2);
+    client.set_vk(&synthetic_vk(&env, false));
+
+    // Session 2 is created after the variant moves to another key
+    client.register_vk(&5u32, &synthetic_vk(&env, false));
+    client.register_variant(&0u32, &Variant { deck_size: 25, suits: 3, rounds: 12, vk_id: 5 });
+    client.create_game(&2u32, &p1, &inputs1.seed_commit1, &no_options(&env));
+    client.join_game(&2u32, &p2, &inputs1.seed_commit2);
+    client.reveal_seed(&2u32, &p1, &inputs1.seed1, &SeedOpening::Plain);
+    client.reveal_seed(&2u32, &p2, &inputs1.seed2, &SeedOpening::Plain);
+    let mut inputs2 = inputs1.clone();
+    inputs2.session_id = u32_field(&env, 2);
+    inputs2.winner = fake_commit(&env, 2);
+
+    let entries = vec![
+        &env,
+        AggregateEntry { session_id: 1, pub_inputs: inputs1.clone() },
+        AggregateEntry { session_id: 2, pub_inputs: inputs2 },
+    ];
+    let settler = Address::generate(&env);
+    assert_eq!(
+        client.try_settle_aggregate(&entries, &synthetic_proof(&env, false), &settler),
+        Err(Ok(Error::NoVk))
+    );
+
+    // The aggregation circuit has a single input: the batch commitment
+    let no_keys = Vec::new(&env);
+    let result = client.try_set_aggregate_vk(&synthetic_vk(&env, false), &no_keys);
+    assert_eq!(result, Err(Ok(Error::VkIcLengthMismatch)));
+    let mut vk = synthetic_vk(&env, false);
+    vk.ic = Vec::from_array(&env, [BytesN::from_array(&env, &[0u8; 64]), BytesN::from_array(&env, &[0u8; 64])]);
+
+    // Mixing keys needs an aggregate key that covers both
+    client.set_aggregate_vk(&vk, &no_keys);
+    assert_eq!(client.try_get_batch_commitment(&entries), Err(Ok(Error::KeysNotCovered)));
+    assert_eq!(
+        client.try_settle_aggregate(&entries, &synthetic_proof(&env, false), &settler),
+        Err(Ok(Error::KeysNotCovered))
+    );
+    client.set_aggregate_vk(&vk, &Vec::from_array(&env, [0u32, 5]));
+
+    let commitment = client.get_batch_commitment(&entries);
+    assert!(commitment.get(0).unwrap() < 0x20);
+    let reversed = vec![&env, entries.get(1).unwrap(), entries.get(0).unwrap()];
+    assert_ne!(client.get_batch_commitment(&reversed), commitment);
+
+    // The commitment binds each entry's key
+    client.register_vk(&5u32, &synthetic_vk(&env, true));
+    assert_ne!(client.get_batch_commitment(&entries), commitment);
+    client.register_vk(&5u32, &synthetic_vk(&env, false));
+    assert_eq!(client.get_batch_commitment(&entries), commitment);
+
+    // Sessions are checked individually and the whole batch fails together
+    let mut bad = entries.clone();
+    bad.push_back(AggregateEntry { session_id: 1, pub_inputs: inputs1 });
+    assert_eq!(client.try_settle_aggregate(&bad, &synthetic_proof(&env, false), &settler), Err(Ok(Error::InvalidState)));
+    assert_eq!(
+        client.try_settle_aggregate(&entries, &synthetic_proof(&env, true), &settler),
+        Err(Ok(Error::InvalidProof))
+    );
+    assert_eq!(client.get_game(&1u32).unwrap().phase, Phase::Revealed);
+
+    let winners = client.settle_aggregate(&entries, &synthetic_proof(&env, false), &settler);
+    assert_eq!(winners, vec![&env, Some(p1), Some(p2)]);
+    assert_eq!(client.get_game(&2u32).unwrap().phase, Phase::Settled);
+}
+
+#[test]
+fn test_game_summary_resolves_registered_names() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    let summary = client.get_game_summary(&1u32).unwrap();
+    assert_eq!((summary.player1_name, summary.player2_name), (None, None));
+
+    let registry_id = env.register(name_registry::NameRegistry, ());
+    let registry = name_registry::NameRegistryClient::new(&env, &registry_id);
+    registry.register(&p1, &String::from_str(&env, "blackbeard"), &Bytes::new(&env));
+    client.set_name_registry(&registry_id);
+
+    let summary = client.get_game_summary(&1u32).unwrap();
+    assert_eq!(summary.player1_name, Some(String::from_str(&env, "blackbeard")));
+    assert_eq!(summary.player2_name, None);
+}
+
+#[test]
+fn test_get_config_reflects_settings() {
+    let (env, _, client, admin, ohloss, _, _) = setup_env();
+    let config = client.get_config();
+    assert_eq!((config.admin, config.ohloss, config.treasury), (admin.clone(), ohloss, admin));
+    assert_eq!(config.rake_bps, 250);
+    assert!(config.paused.is_empty() && config.council.is_empty());
+    assert_eq!(config.variants.len(), 1);
+    assert_eq!(config.variants.get(0).unwrap().vk_hash, None);
+
+    let vk = synthetic_vk(&env, false);
+    client.set_vk(&vk);
+    client.register_variant(&2u32, &Variant { deck_size: 30, suits: 5, rounds: 12, vk_id: 7 });
+    client.register_vk_hash(&7u32, &vk.hash(&env));
+    client.set_paused(&Operation::Join, &true);
+    client.set_rake_bps(&100);
+
+    let config = client.get_config();
+    assert_eq!(config.rake_bps, 100);
+    assert_eq!(config.paused, vec![&env, Operation::Join]);
+    assert_eq!(config.variants.len(), 2);
+    assert_eq!(config.variants.get(0).unwrap().vk_hash, Some(vk.hash(&env)));
+    let custom = config.variants.get(1).unwrap();
+    assert_eq!((custom.variant_id, custom.vk_hash), (2, Some(vk.hash(&env))));
+}
+
+#[test]
+fn test_constructor_sets_initialized_flag() {
+    let (_, _, client, _, _, _, _) = setup_env();
+    assert!(client.is_initialized());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")]
+fn test_constructor_rejects_admin_as_ohloss() {
+    let env = Env::default();
+    let ohloss_id = env.register(mock_ohloss::MockOhloss, ());
+    env.register(PirateCardsContract, (&ohloss_id, &ohloss_id, &None::<VerificationKey>));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #57)")]
+fn test_constructor_rejects_account_ohloss() {
+    let env = Env::default();
+    env.register(PirateCardsContract, (&Address::generate(&env), &Address::generate(&env), &None::<VerificationKey>));
+}
+
+#[test]
+fn test_instance_ttl_extended_on_activity() {
+    use soroban_sdk::testutils::storage::Instance as _;
+
+    let (env, contract_id, client, _, _, p1, _) = setup_env();
+    let ttl = env.storage().max_ttl();
+    assert_eq!(client.get_instance_expiry(), env.ledger().sequence() + ttl);
+
+    // Past half the TTL a new game extends it again
+    env.ledger().with_mut(|l| l.sequence_number += ttl / 2 + 10);
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    let expiry = env.ledger().sequence() + ttl;
+    assert_eq!(client.get_instance_expiry(), expiry);
+    assert_eq!(env.as_contract(&contract_id, || env.storage().instance().get_ttl()), ttl);
+
+    env.ledger().with_mut(|l| l.sequence_number += 10);
+    assert_eq!(client.extend_instance(), expiry + 10);
+}
+
+#[test]
+fn test_uninitialized_instance_returns_errors() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&storage::DataKey::Admin);
+        env.storage().instance().remove(&storage::DataKey::OhlossAddress);
+    });
+
+    assert_eq!(client.try_set_rake_bps(&100), Err(Ok(Error::NotInitialized)));
+    assert_eq!(client.try_get_config(), Err(Ok(Error::NotInitialized)));
+    assert_eq!(client.try_join_game(&1u32, &p2, &fake_commit(&env, 0xBB)), Err(Ok(Error::NotInitialized)));
+}
+
+#[test]
+fn test_constructor_installs_initial_vk() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let ohloss_id = env.register(mock_ohloss::MockOhloss, ());
+    let contract_id = env.register(
+        PirateCardsContract,
+        (&Address::generate(&env), &ohloss_id, &Some(synthetic_vk(&env, false))),
+    );
+    let client = PirateCardsContractClient::new(&env, &contract_id);
+    let (p1, p2) = (Address::generate(&env), Address::generate(&env));
+
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    let settled = client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    assert_eq!(settled, Some(p1));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #17)")]
+fn test_constructor_rejects_malformed_vk() {
+    let env = Env::default();
+    let ohloss_id = env.register(mock_ohloss::MockOhloss, ());
+    env.register(PirateCardsContract, (&Address::generate(&env), &ohloss_id, &Some(dummy_vk(&env, 3))));
+}
+
+#[test]
+fn test_migrate_rewrites_storage_left_by_older_build() {
+    let (env, contract_id, client, _, _, _, _) = setup_env();
+    assert_eq!(client.get_schema_version(), crate::SCHEMA_VERSION);
+    assert_eq!(client.try_migrate(&crate::SCHEMA_VERSION), Err(Ok(Error::InvalidState)));
+
+    // Roll storage back to what a pre-versioning build left behind before
+    // its upgrade: no schema version, no initialized flag, key in instance
+    let key = storage::DataKey::Vk(0);
+    env.as_contract(&contract_id, || {
+        env.storage().instance().remove(&storage::DataKey::SchemaVersion);
+        env.storage().persistent().remove(&storage::DataKey::Initialized);
+        env.storage().instance().set(&key, &synthetic_vk(&env, false));
+    });
+    assert_eq!(client.get_schema_version(), 0);
+    assert!(!client.is_initialized());
+
+    assert_eq!(client.try_migrate(&3), Err(Ok(Error::SchemaMismatch)));
+    assert_eq!(client.migrate(&0), crate::SCHEMA_VERSION);
+    assert!(client.is_initialized());
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&key));
+        assert!(env.storage().persistent().has(&key));
+    });
+    assert_eq!(client.try_migrate(&0), Err(Ok(Error::SchemaMismatch)));
+}
+
+#[test]
+fn test_migrate_rebuilds_open_games_as_lobby() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+    client.create_game(&5u32, &p1, &fake_commit(&env, 0xAB), &no_options(&env));
+
+    // Schema 1 kept open games as a plain Vec of ids
+    env.as_contract(&contract_id, || {
+        env.storage().instance().set(&storage::DataKey::SchemaVersion, &1u32);
+        env.storage().persistent().set(&storage::DataKey::OpenGames, &vec![&env, 3u32, 5, 8]);
+    });
+
+    assert_eq!(client.migrate(&1), crate::SCHEMA_VERSION);
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env, 3u32, 5]);
+    assert_eq!(client.join_any(&p2, &fake_commit(&env, 0xBB), &any_match()), 3);
+}
+
+/// `val` re-encoded without `fields`, as a build predating them stored it.
+fn without_fields(env: &Env, val: Val, fields: &[&str]) -> Val {
+    let mut map: soroban_sdk::Map<Symbol, Val> = val.into_val(env);
+    for field in fields {
+        map.remove(Symbol::new(env, field));
+    }
+    map.into_val(env)
+}
+
+#[test]
+fn test_schema_2_records_upgrade_on_read_and_migrate() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let (token, token_client) = create_token(&env, &[&p1, &p2], 10_000);
+    let options = GameOptions {
+        wager_token: Some(token),
+        wager_amount: 500,
+        escrow_wager: true,
+        require_approval: true,
+        ..no_options(&env)
+    };
+    client.create_game(&1u32, &p1, &fake_commit(&env, 0xAA), &options);
+    client.join_game(&1u32, &p2, &fake_commit(&env, 0xBB));
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAC), &no_options(&env));
+    client.join_game(&3u32, &p2, &fake_commit(&env, 0xBC));
+    client.forfeit(&3u32, &p2);
+
+    // Roll the records back to the fields schema 2 knew
+    let game_fields = [
+        "opponent_stake", "ohloss", "ohloss_version", "event_seq", "metadata1", "metadata2",
+        "result_acknowledged", "rematch_of", "terms", "starts_at", "vk_id",
+    ];
+    env.as_contract(&contract_id, || {
+        let temp = env.storage().temporary();
+        let persistent = env.storage().persistent();
+        let key = storage::DataKey::Game(1);
+        temp.set(&key, &without_fields(&env, temp.get(&key).unwrap(), &game_fields));
+        let key = storage::DataKey::JoinRequests(1);
+        let requests: Vec<Val> = persistent.get(&key).unwrap();
+        let legacy = Vec::from_array(&env, [without_fields(&env, requests.get_unchecked(0), &["metadata"])]);
+        persistent.set(&key, &legacy);
+        let key = storage::DataKey::Stake(1);
+        let stake = without_fields(&env, persistent.get(&key).unwrap(), &["amounts"]);
+        let mut stake: soroban_sdk::Map<Symbol, Val> = stake.into_val(&env);
+        stake.set(Symbol::new(&env, "amount"), 500i128.into_val(&env));
+        persistent.set(&key, &stake);
+        let key = storage::DataKey::Receipt(3);
+        persistent.set(&key, &without_fields(&env, persistent.get(&key).unwrap(), &["terms"]));
+        env.storage().instance().set(&storage::DataKey::SchemaVersion, &5u32);
+    });
+
+    let game = client.get_game(&1u32).unwrap();
+    assert_eq!((game.opponent_stake, game.event_seq, game.starts_at), (500, 0, 0));
+    assert_eq!(client.get_settlement_receipt(&3u32).unwrap().terms, fake_commit(&env, 0));
+
+    assert_eq!(client.migrate(&5), crate::SCHEMA_VERSION);
+    env.as_contract(&contract_id, || {
+        let persistent = env.storage().persistent();
+        assert!(env.storage().temporary().get::<_, Game>(&storage::DataKey::Game(1)).is_some());
+        let stake: crate::types::EscrowedStake = persistent.get(&storage::DataKey::Stake(1)).unwrap();
+        assert_eq!(stake.amounts, vec![&env, 500i128, 500]);
+        let requests: Vec<crate::types::JoinRequest> = persistent.get(&storage::DataKey::JoinRequests(1)).unwrap();
+        assert_eq!(requests.get_unchecked(0).metadata, fake_commit(&env, 0));
+    });
+
+    client.approve_join(&1u32, &p2);
+    client.forfeit(&1u32, &p2);
+    assert_eq!(token_client.balance(&p1), 10_000 + 500 - 25);
+}
+
+#[test]
+fn test_migrate_moves_schema_2_indexes_to_pages() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    revealed_game(&env, &client, &p1, &p2);
+    client.create_game(&3u32, &p1, &fake_commit(&env, 0xAA), &no_options(&env));
+
+    // Schema 2 kept the lobby as one map and the other indexes as Vecs
+    let player_key = storage::DataKey::PlayerGames(p2.clone());
+    env.as_contract(&contract_id, || {
+        let entry = storage::get_lobby_entry(&env, 3).unwrap();
+        storage::remove_open_game(&env, 3);
+        storage::remove_pending_settlement(&env, 1);
+        storage::remove_player_game(&env, &p2, 1);
+        let lobby = soroban_sdk::map![&env, (3u32, entry)];
+        env.storage().instance().set(&storage::DataKey::SchemaVersion, &2u32);
+        env.storage().persistent().set(&storage::DataKey::OpenGames, &lobby);
+        env.storage().persistent().set(&storage::DataKey::PendingSettlement, &vec![&env, 1u32]);
+        env.storage().persistent().set(&player_key, &vec![&env, 1u32]);
+    });
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env]);
+
+    assert_eq!(client.migrate(&2), crate::SCHEMA_VERSION);
+    assert_eq!(client.list_open_games(&0, &10).ids, vec![&env, 3u32]);
+    assert_eq!(client.list_pending_settlement(&0, &10).ids, vec![&env, 1u32]);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&storage::DataKey::OpenGames));
+        assert!(!env.storage().persistent().has(&storage::DataKey::PendingSettlement));
+        assert!(storage::is_player_game(&env, &p2, 1));
+    });
+
+    // A player's old list folds in the first time it is touched
+    client.create_game(&4u32, &p2, &fake_commit(&env, 0xAB), &no_options(&env));
+    assert_eq!(client.list_player_games(&p2, &0, &10).ids, vec![&env, 1u32, 4]);
+    env.as_contract(&contract_id, || assert!(!env.storage().persistent().has(&player_key)));
+}
+
+/// One line per ScVal, e.g. `{amount: i128(5), to: address(G…)}`.
+fn render_scval(val: &xdr::ScVal) -> std::string::String {
+    use std::{format, string::String, vec::Vec};
+    use xdr::ScVal;
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    match val {
+        ScVal::Void => "void".into(),
+        ScVal::Bool(b) => format!("bool({})", b),
+        ScVal::U32(n) => format!("u32({})", n),
+        ScVal::I32(n) => format!("i32({})", n),
+        ScVal::U64(n) => format!("u64({})", n),
+        ScVal::I64(n) => format!("i64({})", n),
+        ScVal::I128(parts) => format!("i128({})", (i128::from(parts.hi) << 64) | i128::from(parts.lo)),
+        ScVal::Symbol(s) => format!("sym({})", text(s.as_slice())),
+        ScVal::String(s) => format!("string({})", text(s.as_slice())),
+        ScVal::Bytes(b) => {
+            let hex: String = b.iter().map(|x| format!("{:02x}", x)).collect();
+            format!("bytes{}({})", b.len(), hex)
+        }
+        ScVal::Address(a) => format!("address({})", a),
+        ScVal::Vec(Some(items)) => {
+            let items: Vec<String> = items.iter().map(render_scval).collect();
+            format!("vec[{}]", items.join(", "))
+        }
+        ScVal::Map(Some(entries)) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|e| match &e.key {
+                    ScVal::Symbol(k) => format!("{}: {}", text(k.as_slice()), render_scval(&e.val)),
+                    k => format!("{}: {}", render_scval(k), render_scval(&e.val)),
+                })
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Publishes one sample of every event type and compares the topics and
+/// data against the snapshot recorded for `EVENT_SCHEMA_VERSION`. A shape
+/// change must come with a version bump and a new snapshot, recorded by
+/// running this test with `RECORD_EVENT_SCHEMA=1`; the old ones stay as
+/// history for indexers. A missing snapshot fails the test.
+#[test]
+fn test_event_schema_matches_snapshot() {
+    use crate::events::*;
+    use std::{format, string::String};
+
+    let env = Env::default();
+    let contract = env.register(mock_ohloss::MockOhloss, ()); // any contract frame will do
+    let a = Address::generate(&env);
+    let b = Address::generate(&env);
+    let token = Address::generate(&env);
+    let memo = BytesN::from_array(&env, &[9u8; 32]);
+    let code = BytesN::from_array(&env, &[3u8; 32]);
+
+    env.as_contract(&contract, || {
+        let e = &env;
+        GameCreated { session_id: 7, seq: 1, player1: a.clone(), wager_token: Some(token.clone()), wager_amount: 100, opponent_stake: 40, memo: memo.clone(), metadata: code.clone(), terms: code.clone(), starts_at: 40 }.publish(e);
+        GameJoined { session_id: 7, seq: 2, player2: b.clone(), metadata: code.clone() }.publish(e);
+        JoinRequested { session_id: 7, seq: 3, joiner: b.clone() }.publish(e);
+        JoinDeclined { session_id: 7, seq: 4, joiner: b.clone() }.publish(e);
+        SeedRevealed { session_id: 7, seq: 5, player: a.clone() }.publish(e);
+        let (commit, seed) = (BytesN::from_array(e, &[1u8; 32]), BytesN::from_array(e, &[2u8; 32]));
+        GameSettled { session_id: 7, seq: 6, winner: Some(a.clone()), outcome: Winner::Player1, memo: memo.clone(), seed_commit1: commit.clone(), seed_commit2: commit.clone(), seed1: seed.clone(), seed2: seed.clone() }.publish(e);
+        GameSettled { session_id: 8, seq: 7, winner: None, outcome: Winner::Draw, memo: memo.clone(), seed_commit1: commit.clone(), seed_commit2: commit.clone(), seed1: seed.clone(), seed2: BytesN::from_array(e, &[0u8; 32]) }.publish(e);
+        GameFollowed { session_id: 7, seq: 27, follower: b.clone(), followers: 3 }.publish(e);
+        TermsAgreed { session_id: 7, seq: 26, terms: code.clone() }.publish(e);
+        ResultAcknowledged { session_id: 7, seq: 25, player: b.clone() }.publish(e);
+        GameCancelled { session_id: 7, seq: 22, seed_commit1: commit.clone(), seed_commit2: commit.clone(), seed1: BytesN::from_array(e, &[0u8; 32]), seed2: BytesN::from_array(e, &[0u8; 32]) }.publish(e);
+        GameExpiringSoon { session_id: 7, seq: 24, phase: Phase::Joined, ledgers_left: 51 }.publish(e);
+        BountyPaid { session_id: 7, seq: 8, to: a.clone(), token: token.clone(), amount: 5 }.publish(e);
+        BountyRefunded { session_id: 7, seq: 9, to: a.clone(), token: token.clone(), amount: 5 }.publish(e);
+        StakeDeposited { session_id: 7, seq: 10, from: a.clone(), token: token.clone(), amount: 100 }.publish(e);
+        StakePaid { session_id: 7, seq: 11, to: a.clone(), token: token.clone(), amount: 190 }.publish(e);
+        StakeRefunded { session_id: 7, seq: 12, to: b.clone(), token: token.clone(), amount: 100 }.publish(e);
+        PotSponsored { session_id: 7, seq: 13, sponsor: b.clone(), token: token.clone(), amount: 50 }.publish(e);
+        SponsorshipPaid { session_id: 7, seq: 14, to: a.clone(), token: token.clone(), amount: 50 }.publish(e);
+        SponsorshipRefunded { session_id: 7, seq: 15, to: b.clone(), token: token.clone(), amount: 50 }.publish(e);
+        RakeCollected { session_id: 7, seq: 16, token: token.clone(), amount: 10, insurance: 2 }.publish(e);
+        CompensationProposed { session_id: 7, seq: 17, to: a.clone(), token: token.clone(), amount: 20, eta: 500 }.publish(e);
+        CompensationPaid { session_id: 7, seq: 18, to: a.clone(), token: token.clone(), amount: 20 }.publish(e);
+        TreasuryWithdrawal { token: token.clone(), to: a.clone(), amount: 4, accrued: 10, withdrawn: 4 }.publish(e);
+        AffiliateRegistered { code: code.clone(), payout: b.clone(), share_bps: 2_000 }.publish(e);
+        AffiliateRemoved { code: code.clone() }.publish(e);
+        AffiliateCredited { session_id: 7, seq: 19, code: code.clone(), token: token.clone(), amount: 2 }.publish(e);
+        AffiliateClaimed { code: code.clone(), payout: b.clone(), token: token.clone(), amount: 2 }.publish(e);
+        OhlossUpdated { ohloss: b.clone(), version: 2 }.publish(e);
+        ResultSinkAllowed { sink: b.clone(), version: 2 }.publish(e);
+        ResultSinkRemoved { sink: b.clone() }.publish(e);
+        AdminRenounced { admin: a.clone() }.publish(e);
+        VkFrozen {}.publish(e);
+        DisputeFlagged { session_id: 7, seq: 20, challenger: b.clone(), token: token.clone(), bond: 30 }.publish(e);
+        DisputeResolved { session_id: 7, seq: 21, ruling: Winner::Player2, upheld: false, bond_to: b.clone() }.publish(e);
+        PayoutReleased { session_id: 7, seq: 23 }.publish(e);
+        ArbiterUpdated { arbiter: b.clone() }.publish(e);
+        NameRegistryUpdated { registry: b.clone() }.publish(e);
+        Secp256k1KeyBound { player: a.clone(), key: BytesN::from_array(e, &[4u8; 65]) }.publish(e);
+        AdminAudit { seq: 1, kind: Symbol::new(e, "set_vk"), actor: a.clone(), details_hash: code.clone() }.publish(e);
+        BreakerTripped { variant: 0, failures: 3 }.publish(e);
+        BreakerReset { variant: 0 }.publish(e);
+        PauseUpdated { op: Operation::Settle, paused: true }.publish(e);
+        CouncilUpdated { signers: vec![e, a.clone(), b.clone()], threshold: 2 }.publish(e);
+        ProposalCreated { proposal_id: 1, proposer: a.clone(), eta: 500 }.publish(e);
+        ProposalApproved { proposal_id: 1, signer: b.clone() }.publish(e);
+        ProposalExecuted { proposal_id: 1 }.publish(e);
+        ProposalCancelled { proposal_id: 1, canceller: a.clone() }.publish(e);
+        TimelockUpdated { delay: 500 }.publish(e);
+        SchemaMigrated { from_version: 1, to_version: 2 }.publish(e);
+        TreasuryUpdated { treasury: b.clone() }.publish(e);
+        RakeUpdated { old_bps: 0, new_bps: 250 }.publish(e);
+        TimingConfigUpdated { game_ttl: 10_000, join_window: 500, reveal_window: 8_000 }.publish(e);
+        VariantRegistered { variant_id: 1, deck_size: 25, suits: 3, rounds: 12, vk_id: 1 }.publish(e);
+        SessionRangeAssigned { orchestrator: b.clone(), start: 0xF000_0000, end: 0xF000_FFFF }.publish(e);
+        SessionRangeRemoved { orchestrator: b.clone() }.publish(e);
+        BeaconUpdated { beacon: b.clone() }.publish(e);
+        soroban_pvp_game::PvpResult { session_id: 7, player1: a.clone(), player2: b.clone(), winner: Some(a.clone()), draw: false }.publish(e);
+    });
+
+    let mut rendered = String::new();
+    for event in env.events().all().events() {
+        let xdr::ContractEventBody::V0(body) = &event.body;
+        let topics: std::vec::Vec<String> = body.topics.iter().map(render_scval).collect();
+        rendered += &format!("[{}] {}\n", topics.join(", "), render_scval(&body.data));
+    }
+
+    let path = format!(
+        "{}/test_snapshots/events/v{}.txt",
+        env!("CARGO_MANIFEST_DIR"),
+        crate::EVENT_SCHEMA_VERSION
+    );
+    match std::fs::read_to_string(&path) {
+        Ok(recorded) => assert!(
+            recorded == rendered,
+            "event schema v{} changed; bump EVENT_SCHEMA_VERSION and record the new shape.\n\
+             recorded:\n{}\nnow:\n{}",
+            crate::EVENT_SCHEMA_VERSION,
+            recorded,
+            rendered
+        ),
+        Err(_) if std::env::var_os("RECORD_EVENT_SCHEMA").is_some() => {
+            std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+            std::fs::write(&path, &rendered).unwrap();
+        }
+        Err(_) => panic!(
+            "no snapshot at {} for event schema v{}; run with RECORD_EVENT_SCHEMA=1 to record it",
+            path,
+            crate::EVENT_SCHEMA_VERSION
+        ),
+    }
+}
+
+/// `circuit`'s verification key in the contract's encoding.
+pub(crate) fn circuit_vk(env: &Env, circuit: &pirate_circuit::Circuit) -> VerificationKey {
+    let key = circuit.verification_key();
+    let mut ic = Vec::new(env);
+    for point in &key.ic {
+        ic.push_back(BytesN::from_array(env, point));
+    }
+    VerificationKey {
+        alpha_g1: BytesN::from_array(env, &key.alpha_g1),
+        beta_g2: BytesN::from_array(env, &key.beta_g2),
+        gamma_g2: BytesN::from_array(env, &key.gamma_g2),
+        delta_g2: BytesN::from_array(env, &key.delta_g2),
+        ic,
+        negated_b: key.negated_b,
+    }
+}
+
+/// `witness`'s public inputs and a proof of them from `circuit`, in the
+/// contract's encoding.
+pub(crate) fn circuit_proof(
+    env: &Env,
+    circuit: &pirate_circuit::Circuit,
+    witness: &pirate_prover::Witness,
+) -> (PublicInputs, Groth16Proof) {
+    let field = |x: &[u8; 32]| BytesN::from_array(env, x);
+    let public = &witness.public_inputs;
+    let inputs = PublicInputs {
+        seed_commit1: field(&public.seed_commit1),
+        seed_commit2: field(&public.seed_commit2),
+        seed1: field(&public.seed1),
+        seed2: field(&public.seed2),
+        session_id: field(&public.session_id),
+        winner: field(&public.winner),
+        beacon: field(&public.beacon),
+        deck_size: field(&public.deck_size),
+        suits: field(&public.suits),
+    };
+    let proof = circuit.prove(&pirate_circuit::Assignment::from(witness), &mut ark_std::test_rng()).unwrap();
+    let proof = Groth16Proof {
+        pi_a: BytesN::from_array(env, &proof.pi_a),
+        pi_b: BytesN::from_array(env, &proof.pi_b),
+        pi_c: BytesN::from_array(env, &proof.pi_c),
+    };
+    (inputs, proof)
+}
+
+#[test]
+fn test_settle_with_in_repo_circuit() {
+    let (env, _, client, _, _, p1, p2) = setup_env();
+    let circuit = pirate_circuit::Circuit::setup(7);
+    let vk = circuit_vk(&env, &circuit);
+    client.set_vk(&vk);
+
+    // A real game: Poseidon commitments, the weight-sorted deck, the rules
+    let (seed1, seed2) = (u32_field(&env, 123456789), u32_field(&env, 987654321));
+    let witness = pirate_prover::Witness::new(&seed1.to_array(), &seed2.to_array(), 1, &[0; 32]).unwrap();
+    let (inputs, proof) = circuit_proof(&env, &circuit, &witness);
+    client.create_game(&1u32, &p1, &inputs.seed_commit1, &no_options(&env));
+    client.join_game(&1u32, &p2, &inputs.seed_commit2);
+    client.reveal_seed(&1u32, &p1, &seed1, &SeedOpening::Plain);
+    client.reveal_seed(&1u32, &p2, &seed2, &SeedOpening::Plain);
+
+    assert_eq!(verifier::verify_groth16(&env, &proof, &vk, &inputs.to_array()), Ok(true));
+    let flipped = PublicInputs { seed1: inputs.seed2.clone(), seed2: inputs.seed1.clone(), ..inputs.clone() };
+    assert_eq!(verifier::verify_groth16(&env, &proof, &vk, &flipped.to_array()), Ok(false));
+
+    // The proof is bound to its inputs: claiming the other winner fails
+    let settler = Address::generate(&env);
+    let other = u32_field(&env, 3 - witness.public_inputs.winner[31] as u32);
+    let claimed = PublicInputs { winner: other, ..inputs.clone() };
+    assert_eq!(client.try_settle_game(&1u32, &proof, &claimed, &settler), Err(Ok(Error::InvalidProof)));
+
+    let expected = match witness.outcome.winner {
+        pirate_engine::Player::One => p1,
+        pirate_engine::Player::Two => p2,
+    };
+    assert_eq!(client.settle_game(&1u32, &proof, &inputs, &settler), Some(expected));
+}
+
+#[test]
+fn test_events_numbered_per_session() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    client.set_vk(&synthetic_vk(&env, false));
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+    let expected = crate::events::GameCreated {
+        session_id: 2,
+        seq: 1,
+        player1: p1.clone(),
+        wager_token: None,
+        wager_amount: 0,
+        opponent_stake: 0,
+        memo: BytesN::from_array(&env, &[0u8; 32]),
+        metadata: BytesN::from_array(&env, &[0u8; 32]),
+        terms: BytesN::from_array(&env, &[0u8; 32]),
+        starts_at: 0,
+    };
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert_eq!(published.events().last().unwrap(), &soroban_sdk::Event::to_xdr(&expected, &env, &contract_id));
+    client.cancel_game(&2u32);
+    assert_eq!(client.get_game(&2u32).unwrap().event_seq, 2);
+
+    // Created, joined and one seed revealed per player
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    assert_eq!(client.get_game(&1u32).unwrap().event_seq, 4);
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    let expected = crate::events::GameSettled {
+        session_id: 1,
+        seq: 5,
+        winner: Some(p1.clone()),
+        outcome: Winner::Player1,
+        memo: BytesN::from_array(&env, &[0u8; 32]),
+        seed_commit1: inputs.seed_commit1.clone(),
+        seed_commit2: inputs.seed_commit2.clone(),
+        seed1: inputs.seed1.clone(),
+        seed2: inputs.seed2.clone(),
+    };
+    let published = env.events().all().filter_by_contract(&contract_id);
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+    assert_eq!(client.get_game(&1u32).unwrap().event_seq, 5);
+}
+
+#[test]
+fn test_pvp_game_interface() {
+    let (env, contract_id, client, _, _, p1, p2) = setup_env();
+    let pvp = PvpGameClient::new(&env, &contract_id);
+    client.set_vk(&synthetic_vk(&env, false));
+    client.create_game(&2u32, &p1, &fake_commit(&env, 0xCC), &no_options(&env));
+    assert_eq!(
+        pvp.game_info(&2u32),
+        Some(GameInfo { player1: p1.clone(), player2: None, status: GameStatus::Open })
+    );
+    assert_eq!(pvp.game_info(&9u32), None);
+
+    let inputs = revealed_game(&env, &client, &p1, &p2);
+    assert_eq!(pvp.game_info(&1u32).unwrap().status, GameStatus::InProgress);
+    assert_eq!(pvp.get_result(&1u32), None);
+
+    client.settle_game(&1u32, &synthetic_proof(&env, false), &inputs, &Address::generate(&env));
+    let published = env.events().all().filter_by_contract(&contract_id);
+    let expected = PvpResult { session_id: 1, player1: p1.clone(), player2: p2.clone(), winner: Some(p1.clone()), draw: false };
+    assert!(published.events().iter().any(|e| *e == soroban_sdk::Event::to_xdr(&expected, &env, &contract_id)));
+
+    let info = pvp.game_info(&1u32).unwrap();
+    assert_eq!((info.player2, info.status), (Some(p2.clone()), GameStatus::Finished));
+    assert_eq!(
+        pvp.get_result(&1u32),
+        Some(GameResult { winner: Some(p1), draw: false, finished_at: env.ledger().sequence() })
+    );
+}
+
+#[test]
+fn test_reserved_session_ranges() {
+    let (env, _, client, _, _, p1, _) = setup_env();
+    let orchestrator = Address::generate(&env);
+    let other = Address::generate(&env);
+    let commit = fake_commit(&env, 0xAA);
+    let start = crate::RESERVED_SESSION_START;
+
+    // Unassigned reserved ids are off limits to everyone
+    assert_eq!(
+        client.try_create_game(&start, &p1, &commit, &no_options(&env)),
+        Err(Ok(Error::ReservedSessionId))
+    );
+    client.create_game(&(start - 1), &p1, &commit, &no_options(&env));
+
+    assert_eq!(client.try_assign_session_range(&orchestrator, &(start - 1), &start), Err(Ok(Error::InvalidConfig)));
+    client.assign_session_range(&orchestrator, &start, &(start + 99));
+    assert_eq!(
+        client.try_assign_session_range(&other, &(start + 99), &(start + 200)),
+        Err(Ok(Error::InvalidConfig))
+    );
+    client.assign_session_range(&other, &(start + 100), &(start + 200));
+    assert_eq!(client.get_session_ranges().ranges.len(), 2);
+
+    // Games in a range need its orchestrator's authorization
+    client.create_game(&(start + 5), &p1, &commit, &no_options(&env));
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == orchestrator));
+    assert_eq!(
+        client.try_create_game(&(start + 300), &p1, &commit, &no_options(&env)),
+        Err(Ok(Error::ReservedSessionId))
+    );
+
+    client.remove_session_range(&orchestrator);
+    assert_eq!(client.try_remove_session_range(&orchestrator), Err(Ok(Error::InvalidState)));
+    let ranges = client.get_session_ranges();
+    assert_eq!((ranges.reserved_start, ranges.ranges.len()), (start, 1));
+    assert_eq!(
+        client.try_create_game(&(start + 6), &p1, &commit, &no_options(&env)),
+        Err(Ok(Error::ReservedSessionId))
+    );
+}
@@ -0,0 +1,134 @@
+#![cfg(test)]
+//! Host cost of the verification configurations we could adopt, printed
+//! as a table. Costs come from the test host's budget, the same model the
+//! network meters with; guest Wasm is not metered in native tests, so only
+//! rows built from host calls are comparable. Run with `bun run bench`.
+//!
+//! BN254 is what the circuit and verifier use today. Protocol 25 exposes
+//! no BN254 MSM and neither curve has a host decompression call, so those
+//! rows report "n/a" and proof sizes instead of costs.
+
+extern crate std;
+
+use soroban_sdk::{
+    crypto::{
+        bls12_381::{Fr as BlsFr, G1Affine as BlsG1, G2Affine as BlsG2},
+        bn254::{Bn254G1Affine, Bn254G2Affine, Fr},
+    },
+    Bytes, Env, Vec,
+};
+use std::{format, println, string::String, vec::Vec as StdVec};
+
+use crate::{
+    test::{circuit_proof, circuit_vk},
+    verifier,
+};
+
+struct Row {
+    config: &'static str,
+    cpu: Option<u64>,
+    mem: Option<u64>,
+    note: String,
+}
+
+/// CPU instructions and memory bytes `f` charges to the budget.
+fn measure<T>(env: &Env, f: impl FnOnce() -> T) -> (u64, u64) {
+    env.cost_estimate().budget().reset_default();
+    f();
+    let budget = env.cost_estimate().budget();
+    (budget.cpu_instruction_cost(), budget.memory_bytes_cost())
+}
+
+fn row(config: &'static str, (cpu, mem): (u64, u64), note: &str) -> Row {
+    Row { config, cpu: Some(cpu), mem: Some(mem), note: note.into() }
+}
+
+fn na(config: &'static str, note: String) -> Row {
+    Row { config, cpu: None, mem: None, note }
+}
+
+#[test]
+#[ignore = "benchmark: run with `bun run bench`"]
+fn bench_verification_configurations() {
+    let env = Env::default();
+    // A real game: four full-width field elements (commitments and seeds)
+    // and five small ones
+    let circuit = pirate_circuit::Circuit::setup(7);
+    let vk = circuit_vk(&env, &circuit);
+    let witness = pirate_prover::Witness::new(&[0x0a; 32], &[0x0b; 32], 1, &[0; 32]).unwrap();
+    let (inputs, proof) = circuit_proof(&env, &circuit, &witness);
+    let fields = inputs.to_array();
+    let n = fields.len() as u32;
+    let mut rows = StdVec::new();
+
+    // BN254, as deployed
+    let cost = measure(&env, || assert!(verifier::verify_groth16(&env, &proof, &vk, &fields).unwrap()));
+    rows.push(row("BN254 Groth16 verify (current)", cost, "9 inputs, small ones via g1_add"));
+
+    let bn254 = env.crypto().bn254();
+    let ic: StdVec<Bn254G1Affine> = (0..=n).map(|i| Bn254G1Affine::from_bytes(vk.ic.get_unchecked(i))).collect();
+    let cost = measure(&env, || {
+        let mut acc = ic[0].clone();
+        for (point, x) in ic[1..].iter().zip(&fields) {
+            acc = bn254.g1_add(&acc, &bn254.g1_mul(point, &Fr::from_bytes(x.clone())));
+        }
+        acc
+    });
+    rows.push(row("BN254 vk_x: g1_mul loop", cost, "every input through g1_mul"));
+    let cost = measure(&env, || {
+        let mut acc = ic[0].clone();
+        for (point, x) in ic[1..].iter().zip(&fields) {
+            if let Some(term) = verifier::scalar_mul(&env, point, x) {
+                acc = bn254.g1_add(&acc, &term);
+            }
+        }
+        acc
+    });
+    rows.push(row("BN254 vk_x: scalar_mul loop", cost, "current; g1_add chain below 2^64"));
+    rows.push(na("BN254 vk_x: MSM", "no bn254 g1_msm host call".into()));
+
+    let g1 = Bn254G1Affine::from_bytes(vk.alpha_g1.clone());
+    let g2 = Bn254G2Affine::from_bytes(vk.beta_g2.clone());
+    let cost = measure(&env, || {
+        bn254.pairing_check(Vec::from_array(&env, [g1.clone(), g1.clone(), g1.clone(), g1.clone()]),
+            Vec::from_array(&env, [g2.clone(), g2.clone(), g2.clone(), g2.clone()]))
+    });
+    rows.push(row("BN254 4-pair pairing check", cost, "includes G2 subgroup checks"));
+
+    // BLS12-381, on hashed points; only the shape of the work matters
+    let bls = env.crypto().bls12_381();
+    let dst = Bytes::from_slice(&env, b"dead-mans-draw/bench");
+    let bls_ic: StdVec<BlsG1> = (0..=n).map(|i| bls.hash_to_g1(&Bytes::from_array(&env, &[i as u8]), &dst)).collect();
+    let bls_g2: BlsG2 = bls.hash_to_g2(&Bytes::from_slice(&env, b"g2"), &dst);
+    let scalars: StdVec<BlsFr> = fields.iter().map(|x| BlsFr::from_bytes(x.clone())).collect();
+    let cost = measure(&env, || {
+        let mut acc = bls_ic[0].clone();
+        for (point, x) in bls_ic[1..].iter().zip(&scalars) {
+            acc = bls.g1_add(&acc, &bls.g1_mul(point, x));
+        }
+        acc
+    });
+    rows.push(row("BLS12-381 vk_x: g1_mul loop", cost, ""));
+    let cost = measure(&env, || {
+        let msm = bls.g1_msm(Vec::from_slice(&env, &bls_ic[1..]), Vec::from_slice(&env, &scalars));
+        bls.g1_add(&bls_ic[0], &msm)
+    });
+    rows.push(row("BLS12-381 vk_x: g1_msm", cost, ""));
+    let p = bls_ic[0].clone();
+    let cost = measure(&env, || {
+        bls.pairing_check(Vec::from_array(&env, [p.clone(), p.clone(), p.clone(), p.clone()]),
+            Vec::from_array(&env, [bls_g2.clone(), bls_g2.clone(), bls_g2.clone(), bls_g2.clone()]))
+    });
+    rows.push(row("BLS12-381 4-pair pairing check", cost, ""));
+
+    // π_A, π_B, π_C: two G1 points and one G2 point
+    rows.push(na("BN254 proof encoding", "256 B uncompressed, 128 B compressed; no host decompression".into()));
+    rows.push(na("BLS12-381 proof encoding", "384 B uncompressed, 192 B compressed; no host decompression".into()));
+
+    println!("| configuration | CPU insns | memory bytes | notes |");
+    println!("|---|---:|---:|---|");
+    let cell = |v: Option<u64>| v.map_or(String::from("n/a"), |v| format!("{}", v));
+    for r in &rows {
+        println!("| {} | {} | {} | {} |", r.config, cell(r.cpu), cell(r.mem), r.note);
+    }
+}
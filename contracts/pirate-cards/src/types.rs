@@ -1,4 +1,176 @@
-use soroban_sdk::{contracttype, Address, BytesN, Vec};
+use soroban_sdk::{contracttype, crypto::Hash, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+
+pub use pirate_vk::VerificationKey;
+
+/// Game lifecycle phase, encoded as a u32 on the wire. Phases only move
+/// forward, so ordering comparisons express "at least joined" etc.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Phase {
+    Created = 0,
+    Joined = 1,
+    Revealed = 2,
+    Settled = 3,
+    Cancelled = 4, // creator withdrew the game before anyone joined
+}
+
+/// How seed commitments are formed and checked.
+///   Poseidon: commit = Poseidon(seed); checked only in-circuit at settlement.
+///   Salted:   commit = SeedCommitment; checked on-chain at reveal.
+///   DualHash: commit = keccak256(Poseidon(seed)); the keccak layer is
+///             checked at reveal, the Poseidon layer in-circuit.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum CommitScheme {
+    Poseidon = 0,
+    Salted = 1,
+    DualHash = 2,
+}
+
+/// Data a player supplies alongside their seed to open their commitment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SeedOpening {
+    Plain,                 // CommitScheme::Poseidon
+    Salted(BytesN<32>),    // CommitScheme::Salted: the salt
+    DualHash(BytesN<32>),  // CommitScheme::DualHash: the inner Poseidon commitment
+}
+
+/// Domain tag prefixed to every salted seed commitment.
+pub const SEED_COMMITMENT_DOMAIN: &[u8] = b"dead-mans-draw/seed-commitment/v1";
+
+/// Salted, domain-separated seed commitment:
+///
+///   sha256(domain_tag || contract || session_id || player || seed || salt)
+///
+/// where `domain_tag` is SEED_COMMITMENT_DOMAIN, `contract` and `player` are
+/// the XDR encodings of their `ScVal::Address`, `session_id` is 4 bytes
+/// big-endian, and `seed` and `salt` are 32 bytes each. Binding the contract,
+/// session and player stops a commitment from being replayed in another
+/// game or copied by the opponent.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SeedCommitment(BytesN<32>);
+
+impl SeedCommitment {
+    pub fn compute(
+        env: &Env,
+        contract: &Address,
+        session_id: u32,
+        player: &Address,
+        seed: &BytesN<32>,
+        salt: &BytesN<32>,
+    ) -> SeedCommitment {
+        let mut preimage = Bytes::from_slice(env, SEED_COMMITMENT_DOMAIN);
+        preimage.append(&contract.clone().to_xdr(env));
+        preimage.extend_from_array(&session_id.to_be_bytes());
+        preimage.append(&player.clone().to_xdr(env));
+        preimage.append(&seed.clone().into());
+        preimage.append(&salt.clone().into());
+        SeedCommitment(env.crypto().sha256(&preimage).to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> BytesN<32> {
+        self.0.clone()
+    }
+}
+
+/// Domain tag and format version that open every result attestation.
+/// Bump the version whenever the layout below changes.
+pub const ATTESTATION_DOMAIN: &[u8] = b"dead-mans-draw/attestation";
+pub const ATTESTATION_VERSION: u32 = 1;
+
+/// Canonical settlement message for relaying results to other chains:
+///
+///   domain_tag || version || contract || session_id || player1 || player2
+///     || winner || ledger
+///
+/// where `domain_tag` is ATTESTATION_DOMAIN, `version` is
+/// ATTESTATION_VERSION, addresses are the XDR encodings of their
+/// `ScVal::Address`, and `version`, `session_id`, `winner` (the `Winner`
+/// wire value) and `ledger` (the settlement ledger) are 4 bytes big-endian.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation(Bytes);
+
+impl Attestation {
+    pub fn compute(
+        env: &Env,
+        contract: &Address,
+        session_id: u32,
+        player1: &Address,
+        player2: &Address,
+        winner: Winner,
+        ledger: u32,
+    ) -> Attestation {
+        let mut message = Bytes::from_slice(env, ATTESTATION_DOMAIN);
+        message.extend_from_array(&ATTESTATION_VERSION.to_be_bytes());
+        message.append(&contract.clone().to_xdr(env));
+        message.extend_from_array(&session_id.to_be_bytes());
+        message.append(&player1.clone().to_xdr(env));
+        message.append(&player2.clone().to_xdr(env));
+        message.extend_from_array(&(winner as u32).to_be_bytes());
+        message.extend_from_array(&ledger.to_be_bytes());
+        Attestation(message)
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        self.0.clone()
+    }
+}
+
+/// Game outcome. Encoded as a u32 on the wire and as the circuit's
+/// `winner` public input (a field element holding the same value).
+/// `Draw` is reserved for draw-capable circuits; the current circuit
+/// always resolves ties with a coin flip.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Winner {
+    None = 0,
+    Player1 = 1,
+    Player2 = 2,
+    Draw = 3,
+}
+
+impl Winner {
+    /// Decode the circuit's 32-byte big-endian `winner` public input.
+    pub fn from_field(field: &BytesN<32>) -> Option<Winner> {
+        let bytes = field.to_array();
+        if bytes[..31] != [0u8; 31] {
+            return None;
+        }
+        match bytes[31] {
+            0 => Some(Winner::None),
+            1 => Some(Winner::Player1),
+            2 => Some(Winner::Player2),
+            3 => Some(Winner::Draw),
+            _ => None,
+        }
+    }
+}
+
+/// How a variant's circuit lays out the deck, and so what `derive_deck`
+/// computes for it. Every circuit in this tree sorts by Poseidon weights
+/// of the combined seed.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum DeckOrder {
+    PoseidonWeights = 0, // pirate_engine::order_by_keys over Poseidon(combined, card)
+    KeccakShuffle = 1,   // pirate_engine::derive_deck
+}
+
+/// Admin-registered ruleset. Each variant names the circuit VK that
+/// settles it, so several modes can share one deployment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Variant {
+    pub deck_size: u32,
+    pub suits: u32,
+    pub rounds: u32,
+    pub vk_id: u32,
+}
 
 /// Game state stored in temporary storage (30-day TTL).
 #[contracttype]
@@ -10,8 +182,565 @@ pub struct Game {
     pub seed_commit2: BytesN<32>,
     pub seed1: BytesN<32>,
     pub seed2: BytesN<32>,
-    pub phase: u32, // 0=created, 1=joined, 2=p1_revealed, 3=p2_revealed, 4=both_revealed, 5=settled
-    pub winner: u32, // 0=none, 1=player1, 2=player2
+    pub inner_commit1: BytesN<32>,    // DualHash only: Poseidon commit opened at reveal
+    pub inner_commit2: BytesN<32>,
+    pub commit_scheme: CommitScheme,
+    pub phase: Phase,
+    pub winner: Winner,
+    pub winner_address: Option<Address>, // set at settlement
+    pub expires_at: u32,              // ledger at which the temporary entry lapses
+    pub join_deadline: u32,           // last ledger player2 may join
+    pub reveal_deadline: u32,         // last ledger seeds may be revealed (0 until joined)
+    pub beacon: Option<Address>,      // beacon pinned at creation, if any
+    pub beacon_round: u32,            // round whose value seeds the deck; set on final reveal
+    pub no_draws: bool,
+    pub tiebroken: bool,              // winner was picked by tiebreak from a draw
+    pub wager_token: Option<Address>,
+    pub wager_amount: i128,
+    pub opponent_stake: i128,         // player2's wager; equals wager_amount unless the game is at odds
+    pub memo: BytesN<32>,
+    pub variant: u32,
+    pub vk_id: u32,                   // the variant's key id at creation; re-registering the variant does not move it
+    pub escrow_wager: bool,
+    pub rake_bps: u32,                // rake in force at creation; later changes do not apply
+    pub freeroll: bool,               // prize funded by a sponsor at creation; players stake nothing
+    pub referrer: Option<BytesN<32>>, // affiliate code credited with a share of the rake
+    pub observer: Option<Address>,    // contract notified of this game's progress
+    pub forfeit: bool,                // the loser conceded rather than being proven beaten
+    pub result_reported: bool,        // Ohloss accepted the result; see report_settlement
+    pub result_sink: Option<Address>, // allowlisted Ohloss this game reports to instead of the default
+    pub unrated: bool,                // casual: never reported to Ohloss, exempt from the pair cooldown
+    pub require_approval: bool,       // the creator picks player2 from the join requests
+    pub ohloss: Option<Address>,      // Ohloss start_game went to; rated games report here too
+    pub ohloss_version: u32,          // its protocol version at join
+    pub event_seq: u32,               // `seq` of the latest event about this session
+    // Opaque hashes each player attached when they took their seat, e.g. of
+    // their client build or the ruleset they acknowledge; zero for none
+    pub metadata1: BytesN<32>,
+    pub metadata2: BytesN<32>,
+    pub result_acknowledged: bool,    // the loser accepted the result; see acknowledge_result
+    pub rematch_of: Option<u32>,      // session this rematches; only its other player may join
+    // Hash of off-chain side-conditions both players signed (player1 at
+    // creation, player2 at join), e.g. "winner streams the replay". Not
+    // enforced; kept as the reference for arbitration. Zero for none
+    pub terms: BytesN<32>,
+    pub starts_at: u32,               // scheduled game: no reveals before this ledger; zero for none
+}
+
+impl Game {
+    /// Number the next event about this game. The caller must store the
+    /// game afterwards.
+    pub fn next_event_seq(&mut self) -> u32 {
+        bump_seq(&mut self.event_seq)
+    }
+}
+
+/// Advance a session's event counter and return the new `seq`.
+pub fn bump_seq(seq: &mut u32) -> u32 {
+    *seq += 1;
+    *seq
+}
+
+/// Persistent record of a settled session, kept after the temporary Game
+/// entry is evicted.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tombstone {
+    pub winner: Winner,
+    pub winner_address: Option<Address>, // None for a draw
+    pub settled_at: u32, // ledger sequence of settlement
+}
+
+/// Outcome both players sign to settle a game by agreement. Bound to this
+/// contract and session so a signature cannot be reused elsewhere.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutcomePayload {
+    pub contract: Address,
+    pub session_id: u32,
+    pub outcome: Winner,
+}
+
+impl OutcomePayload {
+    /// The 32-byte digest players sign: keccak256 of the payload's XDR.
+    pub fn digest(&self, env: &Env) -> Hash<32> {
+        env.crypto().keccak256(&self.clone().to_xdr(env))
+    }
+}
+
+/// Result detail sent to Ohloss deployments that accept scored results.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameScore {
+    pub outcome: Winner,
+    pub tiebroken: bool,
+    pub variant: u32,
+}
+
+/// Canonical, durable record of a concluded game for archival and
+/// off-chain attestation. Fields are only ever appended.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementReceipt {
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Winner,
+    pub winner_address: Option<Address>, // None for a draw
+    pub seed_commit1: BytesN<32>,
+    pub seed_commit2: BytesN<32>,
+    pub seed1: BytesN<32>, // zero if never revealed (agreed draw, forfeit)
+    pub seed2: BytesN<32>,
+    pub proof_hash: BytesN<32>, // sha256(pi_a || pi_b || pi_c); zero when settled without a proof
+    pub settled_at: u32,
+    pub variant: u32,
+    pub terms: BytesN<32>, // the game's co-signed side-conditions; zero for none
+}
+
+/// One registered variant as seen by `get_config`. `vk_hash` is the hash
+/// of the stored key, or the registered hash of a hash-only key.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VariantConfig {
+    pub variant_id: u32,
+    pub variant: Variant,
+    pub vk_hash: Option<BytesN<32>>,
+    pub breaker_tripped: bool,
+}
+
+/// Deployment configuration in one view, for admin dashboards.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub admin: Address,
+    pub admin_renounced: bool,
+    pub treasury: Address,
+    pub ohloss: Address,
+    pub ohloss_version: u32,
+    pub beacon: Option<Address>,
+    pub arbiter: Option<Address>,
+    pub name_registry: Option<Address>,
+    pub council: Vec<Address>, // empty without a council
+    pub council_threshold: u32,
+    pub timelock_delay: u32,
+    pub rake_bps: u32,
+    pub timing: TimingConfig,
+    pub rate_limit: RateLimit,
+    pub pair_cooldown: u32,
+    pub expiry_warning: u32,
+    pub dispute_window: u32,
+    pub breaker_threshold: u32,
+    pub paused: Vec<Operation>,
+    pub vk_frozen: bool,
+    pub variants: Vec<VariantConfig>,
+}
+
+/// Ledgers left on a game's clocks as of `ledger`, for countdown timers.
+/// A window's count includes the current ledger, so 1 means this is the
+/// last ledger to act in; it is 0 once the window has closed or does not
+/// apply to the game's phase (e.g. joining after someone has joined).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeInfo {
+    pub ledger: u32,
+    pub phase: Phase,
+    pub join_ledgers: u32,
+    pub reveal_ledgers: u32,
+    pub ttl_ledgers: u32, // until the game entry lapses unless written again
+}
+
+/// Compact view of a game for lobby/polling clients (no seeds or commits).
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GameSummary {
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_name: Option<String>, // from the name registry, if any
+    pub player2_name: Option<String>,
+    pub phase: Phase,
+    pub winner: Winner,
+    pub expires_at: u32,
+    pub followers: u32, // spectators who called follow_game
+}
+
+/// Spectators following a game: how many, and the first
+/// `MAX_LISTED_FOLLOWERS` of them in follow order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Followers {
+    pub count: u32,
+    pub listed: Vec<Address>,
+}
+
+/// One line of a game's activity log: who did what, and when. `action` is
+/// the entrypoint's short name, e.g. `reveal` or `reveal_by_delegate`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogEntry {
+    pub actor: Address,
+    pub action: Symbol,
+    pub ledger: u32,
+}
+
+/// One call's worth of a session index in ascending order. `next` is the
+/// cursor to pass for more, or None once the index is exhausted; a call
+/// that reached its read cap may return fewer ids than asked, even none,
+/// and still have a `next`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionPage {
+    pub ids: Vec<u32>,
+    pub next: Option<u32>,
+}
+
+/// What quick play knows about an open game without loading it: the
+/// creator, the stake a joiner must post (zero and no token unless the
+/// wager is escrowed), the variant and whether joins need approval. The
+/// creator's rating moves while the game waits, so it is read live.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LobbyEntry {
+    pub creator: Address,
+    pub token: Option<Address>,
+    pub stake: i128,
+    pub variant: u32,
+    pub approval: bool,
+}
+
+/// Which open games `join_any` may pick. Bounds are inclusive.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchFilter {
+    pub token: Option<Address>, // only games staked in this token; None for any
+    pub min_stake: i128,
+    pub max_stake: i128,
+    pub variant: Option<u32>,   // None for any variant
+    pub min_rating: u32,        // band on the creator's current rating
+    pub max_rating: u32,
+}
+
+impl MatchFilter {
+    /// Everything but the rating band, which needs a call to Ohloss.
+    pub fn matches(&self, entry: &LobbyEntry) -> bool {
+        (self.token.is_none() || entry.token == self.token)
+            && (self.min_stake..=self.max_stake).contains(&entry.stake)
+            && self.variant.is_none_or(|variant| variant == entry.variant)
+    }
+
+    /// Whether the filter bounds the rating at all, so unrated searches
+    /// skip the Ohloss call.
+    pub fn bands_rating(&self) -> bool {
+        self.min_rating > 0 || self.max_rating < u32::MAX
+    }
+
+    pub fn rating_matches(&self, rating: u32) -> bool {
+        (self.min_rating..=self.max_rating).contains(&rating)
+    }
+}
+
+/// A prospective player2 waiting for the creator of a `require_approval`
+/// game to pick them. Any stake is escrowed when the request is lodged.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JoinRequest {
+    pub joiner: Address,
+    pub seed_commit: BytesN<32>,
+    pub metadata: BytesN<32>,
+}
+
+/// Player stakes held in escrow, keyed by session id in persistent storage.
+/// `amounts[i]` is what `depositors[i]` paid; they differ only at odds.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowedStake {
+    pub token: Address,
+    pub amounts: Vec<i128>,
+    pub depositors: Vec<Address>,
+}
+
+/// Session ids `start..=end`, inside the reserved space, that only
+/// `orchestrator` (e.g. a tournament or factory contract) may create games
+/// under.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionRange {
+    pub orchestrator: Address,
+    pub start: u32,
+    pub end: u32, // inclusive
+}
+
+/// How the session-id space is split: ids below `reserved_start` are for
+/// anyone; the rest only within an assigned range, by its orchestrator.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionRanges {
+    pub reserved_start: u32,
+    pub ranges: Vec<SessionRange>,
+}
+
+/// Most games one address may create per window of ledgers. A zero
+/// `max_creations` disables the limit.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RateLimit {
+    pub window: u32, // ledgers
+    pub max_creations: u32,
+}
+
+/// An address's game creations in its current rate-limit window.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreationWindow {
+    pub start: u32,
+    pub count: u32,
+}
+
+/// Stakes and sponsorships held back after settlement while the result can
+/// still be disputed. Self-contained so it outlives the game entry.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingPayout {
+    pub release_at: u32, // first ledger `release_payout` may run
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Winner,
+    pub rake_bps: u32,
+    pub referrer: Option<BytesN<32>>,
+}
+
+/// A player's bonded challenge to a settled result.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    pub challenger: Address,
+    pub token: Address, // the session's stake token
+    pub bond: i128,
+}
+
+/// A third-party contribution to a session's prize.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sponsorship {
+    pub sponsor: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// What an invalid-proof report must post: `amount` of `token`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondTerms {
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// A bond posted with an invalid-proof report, held against its variant
+/// until the breaker is reset or a valid proof clears the count.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReportBond {
+    pub reporter: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// Registered affiliate. Codes are looked up at payout, so the admin can
+/// rotate the payout address or rate without touching open games.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Affiliate {
+    pub payout: Address,
+    pub share_bps: u32, // share of the treasury's rake cut, in basis points
+}
+
+/// Settlement counters, kept for the whole deployment and per variant.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GameStats {
+    pub settled: u32,   // games concluded by proof, agreement or forfeit
+    pub draws: u32,
+    pub forfeits: u32,
+    pub tiebroken: u32,
+}
+
+/// Roughly how many games sit in each phase. Settled and cancelled only
+/// grow; the others rise and fall as games move on. The live phases are
+/// approximate: a game whose entry expires stays counted in the phase it
+/// lapsed in until `gc_indexes` prunes it, and games already in flight
+/// when counting began are missing until they reach a terminal phase.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PhaseCounts {
+    pub created: u32,
+    pub joined: u32,
+    pub revealed: u32,
+    pub settled: u32,
+    pub cancelled: u32,
+}
+
+impl PhaseCounts {
+    pub fn add(&mut self, other: &PhaseCounts) {
+        self.created += other.created;
+        self.joined += other.joined;
+        self.revealed += other.revealed;
+        self.settled += other.settled;
+        self.cancelled += other.cancelled;
+    }
+
+    pub fn slot(&mut self, phase: Phase) -> &mut u32 {
+        match phase {
+            Phase::Created => &mut self.created,
+            Phase::Joined => &mut self.joined,
+            Phase::Revealed => &mut self.revealed,
+            Phase::Settled => &mut self.settled,
+            Phase::Cancelled => &mut self.cancelled,
+        }
+    }
+}
+
+/// Escrowed stake volume settled in one token.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TokenVolume {
+    pub games: u32,
+    pub staked: i128, // sum of settled pots
+    pub rake: i128,
+}
+
+/// Operations that can be paused independently.
+///   Withdraw covers treasury, affiliate and compensation payouts; player
+///   refunds stay open so funds can always leave a stranded game.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Operation {
+    Create = 0,
+    Join = 1,
+    Reveal = 2,
+    Settle = 3,   // proofs, agreed draws and forfeits
+    Withdraw = 4,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 5] = [
+        Operation::Create,
+        Operation::Join,
+        Operation::Reveal,
+        Operation::Settle,
+        Operation::Withdraw,
+    ];
+}
+
+/// M-of-N signer set guarding sensitive operations once configured.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Council {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// A sensitive operation a proposal executes once approved and its
+/// timelock has passed.
+#[contracttype]
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)] // wire type; no allocator to box the VK
+pub enum AdminAction {
+    RegisterVk(u32, VerificationKey),          // vk_id, key
+    RegisterVkHash(u32, BytesN<32>),           // vk_id, VerificationKey::hash
+    Upgrade(BytesN<32>),                       // new wasm hash
+    WithdrawTreasury(Address, Address, i128),  // token, to, amount
+    SetCouncil(Council),
+    SetOhloss(Address),
+    SetRakeBps(u32),
+    SetTimelockDelay(u32),                     // ledgers
+    SetAggregateVk(VerificationKey, Vec<u32>), // key, vk_ids it may mix
+    SetVkProvenance(u32, VkProvenance),        // vk_id, provenance
+    SetNameRegistry(Address),
+}
+
+/// Queued admin operation awaiting approvals and its timelock.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AdminProposal {
+    pub action: AdminAction,
+    pub approvals: Vec<Address>,
+    pub eta: u32, // first ledger at which `execute` may run it
+}
+
+/// Lifetime rake accounting for one token. The withdrawable balance is
+/// `accrued - withdrawn`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreasuryAccount {
+    pub accrued: i128,
+    pub withdrawn: i128,
+}
+
+/// Insurance payout queued by the admin, executable once `eta` is reached.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingCompensation {
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub eta: u32, // first ledger at which `compensate` may execute
+}
+
+/// A bounty held in escrow, keyed by session id in persistent storage.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowedBounty {
+    pub funder: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// Optional parameters supplied at `create_game`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GameOptions {
+    pub bounty_token: Option<Address>, // token paid to whoever settles
+    pub bounty_amount: i128,           // must be 0 when no bounty token is set
+    pub commit_scheme: CommitScheme,
+    pub use_beacon: bool,              // mix the admin-configured beacon into the deck
+    pub no_draws: bool,                // resolve a proven draw by seeded tiebreak
+    pub wager_token: Option<Address>,  // informational unless escrow_wager is set
+    pub wager_amount: i128,            // must be 0 when no wager token is set
+    pub opponent_stake: i128,          // player2's wager at handicap odds; zero to match wager_amount
+    pub escrow_wager: bool,            // pull each player's wager as a stake
+    pub memo: BytesN<32>,              // free-form tag for frontends and controllers
+    pub variant: u32,                  // registered variant id; 0 is classic
+    pub sponsor: Option<Address>,      // freeroll funder; must have approved this contract
+    pub sponsor_token: Option<Address>,
+    pub sponsor_amount: i128,          // pulled from the sponsor via allowance at creation
+    pub referrer: Option<BytesN<32>>,  // registered affiliate code, if any
+    pub observer: Option<Address>,     // contract implementing `Observer` for this game
+    pub result_sink: Option<Address>,  // allowlisted Ohloss to report to; None for the default
+    pub unrated: bool,                 // casual game that does not count toward standings
+    pub require_approval: bool,        // joiners wait for the creator's approve_join
+    pub reveal_delegate: Option<Address>, // may reveal player1's seed via delegate_reveal
+    pub metadata: BytesN<32>,          // player1's metadata commitment; see Game::metadata1
+    pub rematch_of: Option<u32>,       // acknowledged game against the same opponent; see acknowledge_result
+    pub terms: BytesN<32>,             // side-conditions player2 must co-sign; zero for none
+    pub starts_at: u32,                // ledger before which seeds cannot be revealed; zero for none
+}
+
+/// What player2 attaches to `join_game_with`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JoinOptions {
+    pub reveal_delegate: Option<Address>, // may reveal player2's seed via delegate_reveal
+    pub metadata: BytesN<32>,             // player2's metadata commitment; see Game::metadata2
+    pub terms: BytesN<32>,                // must equal the game's terms, co-signing them
+    pub stake: i128,                      // must equal a handicap game's opponent_stake; zero for even stakes
+}
+
+/// Admin-configurable timing parameters, all measured in ledgers (~5s each).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimingConfig {
+    pub game_ttl: u32,      // TTL applied to game entries on every write
+    pub join_window: u32,   // ledgers after creation during which player2 may join
+    pub reveal_window: u32, // ledgers after joining during which seeds may be revealed
 }
 
 /// Groth16 proof over BN254 (Protocol 25).
@@ -25,21 +754,35 @@ pub struct Groth16Proof {
     pub pi_c: BytesN<64>,
 }
 
-/// Groth16 verification key stored on-chain.
-/// IC length = nPublic + 1 (7 entries for 6 public inputs).
+/// Where a verification key came from, so auditors can tie it to a
+/// published trusted-setup ceremony. Dropped whenever the key it
+/// describes is replaced.
 #[contracttype]
-#[derive(Clone, Debug)]
-pub struct VerificationKey {
-    pub alpha_g1: BytesN<64>,
-    pub beta_g2: BytesN<128>,
-    pub gamma_g2: BytesN<128>,
-    pub delta_g2: BytesN<128>,
-    pub ic: Vec<BytesN<64>>,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VkProvenance {
+    pub circuit: String,           // circuit name and version, e.g. "pirate_cards v3"
+    pub ceremony_hash: BytesN<32>, // hash of the ceremony transcript
+    pub toolchain: String,         // e.g. "snarkjs 0.7.4"
+}
+
+/// A variant's key as `get_vk_info` reports it. Its provenance, if any
+/// has been recorded, comes from `get_vk_provenance`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VkInfo {
+    pub vk_id: u32,
+    pub vk_hash: BytesN<32>, // sha256 of the key's XDR, stored or hash-only alike
 }
 
+/// Number of public inputs the pirate cards circuit exposes.
+pub const PUBLIC_INPUT_COUNT: u32 = 9;
+
 /// Public inputs for the pirate cards circuit.
-/// 6 field elements, each 32 bytes big-endian:
-///   seed_commit1, seed_commit2, seed1, seed2, session_id, winner
+/// 9 field elements, each 32 bytes big-endian:
+///   seed_commit1, seed_commit2, seed1, seed2, session_id, winner, beacon,
+///   deck_size, suits
+/// `beacon` is zero for games that do not use the randomness beacon.
+/// `deck_size` and `suits` must match the game's variant.
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct PublicInputs {
@@ -49,4 +792,212 @@ pub struct PublicInputs {
     pub seed2: BytesN<32>,
     pub session_id: BytesN<32>,
     pub winner: BytesN<32>,
+    pub beacon: BytesN<32>,
+    pub deck_size: BytesN<32>,
+    pub suits: BytesN<32>,
+}
+
+impl PublicInputs {
+    /// Field elements in circuit order, as consumed by the verifier.
+    pub fn to_array(&self) -> [BytesN<32>; 9] {
+        [
+            self.seed_commit1.clone(),
+            self.seed_commit2.clone(),
+            self.seed1.clone(),
+            self.seed2.clone(),
+            self.session_id.clone(),
+            self.winner.clone(),
+            self.beacon.clone(),
+            self.deck_size.clone(),
+            self.suits.clone(),
+        ]
+    }
+}
+
+/// One session attested to by an aggregated proof: the inputs its own
+/// settlement proof would have carried.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AggregateEntry {
+    pub session_id: u32,
+    pub pub_inputs: PublicInputs,
+}
+
+/// One entry of a `settle_batch` call.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct BatchSettlement {
+    pub session_id: u32,
+    pub proof: Groth16Proof,
+    pub pub_inputs: PublicInputs,
+}
+
+// --- Schema 2 layouts ---
+// Entries written before a type gained fields decode as these and are
+// upgraded on read, so games in flight across an upgrade keep working
+// before and after `migrate` rewrites the ones it can reach.
+
+/// Game as schema 2 stored it.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct GameV2 {
+    pub player1: Address,
+    pub player2: Address,
+    pub seed_commit1: BytesN<32>,
+    pub seed_commit2: BytesN<32>,
+    pub seed1: BytesN<32>,
+    pub seed2: BytesN<32>,
+    pub inner_commit1: BytesN<32>,
+    pub inner_commit2: BytesN<32>,
+    pub commit_scheme: CommitScheme,
+    pub phase: Phase,
+    pub winner: Winner,
+    pub winner_address: Option<Address>,
+    pub expires_at: u32,
+    pub join_deadline: u32,
+    pub reveal_deadline: u32,
+    pub beacon: Option<Address>,
+    pub beacon_round: u32,
+    pub no_draws: bool,
+    pub tiebroken: bool,
+    pub wager_token: Option<Address>,
+    pub wager_amount: i128,
+    pub memo: BytesN<32>,
+    pub variant: u32,
+    pub escrow_wager: bool,
+    pub rake_bps: u32,
+    pub freeroll: bool,
+    pub referrer: Option<BytesN<32>>,
+    pub observer: Option<Address>,
+    pub forfeit: bool,
+    pub result_reported: bool,
+    pub result_sink: Option<Address>,
+    pub unrated: bool,
+    pub require_approval: bool,
+}
+
+impl GameV2 {
+    /// Even stakes, no metadata, terms or schedule, and reports to the
+    /// default Ohloss, as every game was before those existed. `vk_id` is
+    /// the variant's key id now, the best record of it left.
+    pub fn upgrade(self, vk_id: u32) -> Game {
+        let zero = BytesN::from_array(self.player1.env(), &[0u8; 32]);
+        Game {
+            player1: self.player1,
+            player2: self.player2,
+            seed_commit1: self.seed_commit1,
+            seed_commit2: self.seed_commit2,
+            seed1: self.seed1,
+            seed2: self.seed2,
+            inner_commit1: self.inner_commit1,
+            inner_commit2: self.inner_commit2,
+            commit_scheme: self.commit_scheme,
+            phase: self.phase,
+            winner: self.winner,
+            winner_address: self.winner_address,
+            expires_at: self.expires_at,
+            join_deadline: self.join_deadline,
+            reveal_deadline: self.reveal_deadline,
+            beacon: self.beacon,
+            beacon_round: self.beacon_round,
+            no_draws: self.no_draws,
+            tiebroken: self.tiebroken,
+            wager_token: self.wager_token,
+            wager_amount: self.wager_amount,
+            opponent_stake: self.wager_amount,
+            memo: self.memo,
+            variant: self.variant,
+            vk_id,
+            escrow_wager: self.escrow_wager,
+            rake_bps: self.rake_bps,
+            freeroll: self.freeroll,
+            referrer: self.referrer,
+            observer: self.observer,
+            forfeit: self.forfeit,
+            result_reported: self.result_reported,
+            result_sink: self.result_sink,
+            unrated: self.unrated,
+            require_approval: self.require_approval,
+            ohloss: None,
+            ohloss_version: 0,
+            event_seq: 0,
+            metadata1: zero.clone(),
+            metadata2: zero.clone(),
+            result_acknowledged: false,
+            rematch_of: None,
+            terms: zero,
+            starts_at: 0,
+        }
+    }
+}
+
+/// JoinRequest as schema 2 stored it.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct JoinRequestV2 {
+    pub joiner: Address,
+    pub seed_commit: BytesN<32>,
+}
+
+impl JoinRequestV2 {
+    pub fn upgrade(self) -> JoinRequest {
+        let metadata = BytesN::from_array(self.joiner.env(), &[0u8; 32]);
+        JoinRequest { joiner: self.joiner, seed_commit: self.seed_commit, metadata }
+    }
+}
+
+/// EscrowedStake as schema 2 stored it: every depositor paid `amount`.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct EscrowedStakeV2 {
+    pub token: Address,
+    pub amount: i128,
+    pub depositors: Vec<Address>,
+}
+
+impl EscrowedStakeV2 {
+    pub fn upgrade(self) -> EscrowedStake {
+        let mut amounts = Vec::new(self.token.env());
+        for _ in self.depositors.iter() {
+            amounts.push_back(self.amount);
+        }
+        EscrowedStake { token: self.token, amounts, depositors: self.depositors }
+    }
+}
+
+/// SettlementReceipt as schema 2 stored it.
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub struct SettlementReceiptV2 {
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Winner,
+    pub winner_address: Option<Address>,
+    pub seed_commit1: BytesN<32>,
+    pub seed_commit2: BytesN<32>,
+    pub seed1: BytesN<32>,
+    pub seed2: BytesN<32>,
+    pub proof_hash: BytesN<32>,
+    pub settled_at: u32,
+    pub variant: u32,
+}
+
+impl SettlementReceiptV2 {
+    pub fn upgrade(self) -> SettlementReceipt {
+        let terms = BytesN::from_array(self.player1.env(), &[0u8; 32]);
+        SettlementReceipt {
+            player1: self.player1,
+            player2: self.player2,
+            winner: self.winner,
+            winner_address: self.winner_address,
+            seed_commit1: self.seed_commit1,
+            seed_commit2: self.seed_commit2,
+            seed1: self.seed1,
+            seed2: self.seed2,
+            proof_hash: self.proof_hash,
+            settled_at: self.settled_at,
+            variant: self.variant,
+            terms,
+        }
+    }
 }
@@ -1,7 +1,10 @@
-use soroban_sdk::{vec, Env, Vec};
+use soroban_sdk::{vec, BytesN, Env, Vec};
 use soroban_sdk::crypto::bn254::{Bn254G1Affine, Bn254G2Affine, Fr};
 
+use soroban_sdk::{xdr::ToXdr, Bytes, U256};
+
 use crate::types::{Groth16Proof, PublicInputs, VerificationKey};
+use crate::Error;
 
 /// BN254 base field modulus p (big-endian, 32 bytes).
 /// Used to negate G1 points: neg(x, y) = (x, p - y).
@@ -14,45 +17,47 @@ const BN254_P: [u8; 32] = [
 
 /// Verify a Groth16 proof over BN254 using Protocol 25 host functions.
 ///
-/// 6 public inputs: seed_commit1, seed_commit2, seed1, seed2, session_id, winner
-/// IC vector has 7 entries (nPublic + 1).
+/// The number of public inputs is taken from the VK: IC has nPublic + 1
+/// entries, and `inputs` must supply exactly nPublic 32-byte big-endian
+/// field elements. A mismatch returns `Error::InputCountMismatch` instead
+/// of trapping on a missing IC entry.
 ///
 /// Verification equation (multi-pairing):
 ///   e(A, B) * e(-alpha, beta) * e(-vk_x, gamma) * e(-C, delta) == 1
+///
+/// When `vk.negated_b` is set the proof carries -B, so A is negated too:
+/// e(-A, -B) == e(A, B) and the equation is otherwise unchanged.
+///
+/// Point validation: G1 has cofactor 1, so on-curve is sufficient. G2 points
+/// (π_B and the VK's beta/gamma/delta) are checked by the host's
+/// `bn254_multi_pairing_check`, which rejects any point that is off the
+/// twist or outside the prime-order subgroup. That rejection is a host trap
+/// rather than an `Error`: the contract has no G2 scalar multiplication with
+/// which to test membership itself, so a rogue-subgroup π_B aborts the
+/// invocation instead of being accepted.
 pub fn verify_groth16(
     env: &Env,
     proof: &Groth16Proof,
     vk: &VerificationKey,
-    pub_inputs: &PublicInputs,
-) -> bool {
-    let bn254 = env.crypto().bn254();
-
-    // 6 public inputs as Fr scalars
-    let scalars: [Fr; 6] = [
-        Fr::from_bytes(pub_inputs.seed_commit1.clone()),
-        Fr::from_bytes(pub_inputs.seed_commit2.clone()),
-        Fr::from_bytes(pub_inputs.seed1.clone()),
-        Fr::from_bytes(pub_inputs.seed2.clone()),
-        Fr::from_bytes(pub_inputs.session_id.clone()),
-        Fr::from_bytes(pub_inputs.winner.clone()),
-    ];
-
-    // Compute vk_x = IC[0] + sum(IC[i+1] * scalars[i])
-    let mut vk_x = Bn254G1Affine::from_bytes(vk.ic.get(0).unwrap());
-    for i in 0u32..6 {
-        let ic_point = Bn254G1Affine::from_bytes(vk.ic.get(i + 1).unwrap());
-        let term = bn254.g1_mul(&ic_point, &scalars[i as usize]);
-        vk_x = bn254.g1_add(&vk_x, &term);
-    }
+    inputs: &[BytesN<32>],
+) -> Result<bool, Error> {
+    check_proof(proof, vk, inputs)?;
+    let vk_x = input_combination(env, vk, inputs);
+    Ok(pairing(env, proof, vk, &vk_x))
+}
 
+/// The single-proof pairing check, given the proof's vk_x.
+fn pairing(env: &Env, proof: &Groth16Proof, vk: &VerificationKey, vk_x: &Bn254G1Affine) -> bool {
     // Negate G1 points for the pairing equation
+    let pi_a = Bn254G1Affine::from_bytes(proof.pi_a.clone());
+    let pi_a = if vk.negated_b { negate_g1(env, &pi_a) } else { pi_a };
     let neg_alpha = negate_g1(env, &Bn254G1Affine::from_bytes(vk.alpha_g1.clone()));
-    let neg_vk_x = negate_g1(env, &vk_x);
+    let neg_vk_x = negate_g1(env, vk_x);
     let neg_c = negate_g1(env, &Bn254G1Affine::from_bytes(proof.pi_c.clone()));
 
     let g1_points: Vec<Bn254G1Affine> = vec![
         env,
-        Bn254G1Affine::from_bytes(proof.pi_a.clone()),
+        pi_a,
         neg_alpha,
         neg_vk_x,
         neg_c,
@@ -66,10 +71,151 @@ pub fn verify_groth16(
         Bn254G2Affine::from_bytes(vk.delta_g2.clone()),
     ];
 
-    bn254.pairing_check(g1_points, g2_points)
+    env.crypto().bn254().pairing_check(g1_points, g2_points)
+}
+
+/// Verify several Groth16 proofs under one key, returning each proof's
+/// validity.
+///
+/// All proofs are first checked together in a single multi-pairing. Each
+/// proof's equation is raised to a random r_i and the results multiplied,
+/// so the alpha, gamma and delta terms collapse into one pairing each
+/// (K + 3 pairings and one final exponentiation instead of 4K and K):
+///   prod e(r_i*A_i, B_i) * e(-(sum r_i)*alpha, beta)
+///     * e(-sum r_i*vk_x_i, gamma) * e(-sum r_i*C_i, delta) == 1
+///
+/// r_0 is 1 and the rest are 128-bit Fiat-Shamir challenges over every
+/// proof and input in the batch, so a submitter cannot choose bad proofs
+/// whose errors cancel. Only if the aggregate fails is each proof checked
+/// alone, reusing its vk_x, to find the bad ones; that fallback costs a
+/// full pairing check per proof.
+pub fn verify_groth16_batch(
+    env: &Env,
+    vk: &VerificationKey,
+    proofs: &Vec<Groth16Proof>,
+    inputs: &Vec<PublicInputs>,
+) -> Result<Vec<bool>, Error> {
+    let bn254 = env.crypto().bn254();
+    if proofs.is_empty() || proofs.len() != inputs.len() {
+        return Err(Error::InputCountMismatch);
+    }
+
+    let transcript = env.crypto().sha256(&(proofs.clone(), inputs.clone()).to_xdr(env)).to_bytes();
+    let mut vk_xs: Vec<Bn254G1Affine> = Vec::new(env);
+    let mut g1_points: Vec<Bn254G1Affine> = Vec::new(env);
+    let mut g2_points: Vec<Bn254G2Affine> = Vec::new(env);
+    let mut r_sum = U256::from_u32(env, 1);
+    let mut acc_vk_x = Bn254G1Affine::from_array(env, &[0u8; 64]);
+    let mut acc_c = Bn254G1Affine::from_array(env, &[0u8; 64]);
+    for (i, proof) in proofs.iter().enumerate() {
+        let inputs = inputs.get_unchecked(i as u32).to_array();
+        check_proof(&proof, vk, &inputs)?;
+        let vk_x = input_combination(env, vk, &inputs);
+        vk_xs.push_back(vk_x.clone());
+
+        let pi_a = Bn254G1Affine::from_bytes(proof.pi_a.clone());
+        let pi_a = if vk.negated_b { negate_g1(env, &pi_a) } else { pi_a };
+        let pi_c = Bn254G1Affine::from_bytes(proof.pi_c.clone());
+        let (a, vk_x, c) = if i == 0 {
+            (pi_a, vk_x, pi_c)
+        } else {
+            let r = challenge(env, &transcript, i as u32);
+            r_sum = r_sum.add(r.as_u256());
+            (bn254.g1_mul(&pi_a, &r), bn254.g1_mul(&vk_x, &r), bn254.g1_mul(&pi_c, &r))
+        };
+        g1_points.push_back(a);
+        g2_points.push_back(Bn254G2Affine::from_bytes(proof.pi_b.clone()));
+        acc_vk_x = bn254.g1_add(&acc_vk_x, &vk_x);
+        acc_c = bn254.g1_add(&acc_c, &c);
+    }
+
+    let alpha = Bn254G1Affine::from_bytes(vk.alpha_g1.clone());
+    let alpha = if proofs.len() == 1 { alpha } else { bn254.g1_mul(&alpha, &Fr::from_u256(r_sum)) };
+    g1_points.push_back(negate_g1(env, &alpha));
+    g2_points.push_back(Bn254G2Affine::from_bytes(vk.beta_g2.clone()));
+    g1_points.push_back(negate_g1(env, &acc_vk_x));
+    g2_points.push_back(Bn254G2Affine::from_bytes(vk.gamma_g2.clone()));
+    g1_points.push_back(negate_g1(env, &acc_c));
+    g2_points.push_back(Bn254G2Affine::from_bytes(vk.delta_g2.clone()));
+
+    let all_valid = bn254.pairing_check(g1_points, g2_points);
+    let mut valid = Vec::new(env);
+    for (i, proof) in proofs.iter().enumerate() {
+        valid.push_back(all_valid || pairing(env, &proof, vk, &vk_xs.get_unchecked(i as u32)));
+    }
+    Ok(valid)
+}
+
+/// Shape checks shared by single and batch verification.
+pub fn check_proof(proof: &Groth16Proof, vk: &VerificationKey, inputs: &[BytesN<32>]) -> Result<(), Error> {
+    if vk.ic.is_empty() || (vk.ic.len() - 1) as usize != inputs.len() {
+        return Err(Error::InputCountMismatch);
+    }
+
+    // The point at infinity (all-zero encoding) is never a valid proof
+    // element; reject it up front rather than letting the pairing fail.
+    if proof.pi_a.to_array() == [0u8; 64]
+        || proof.pi_b.to_array() == [0u8; 128]
+        || proof.pi_c.to_array() == [0u8; 64]
+    {
+        return Err(Error::DegenerateProofPoint);
+    }
+    Ok(())
+}
+
+/// vk_x = IC[0] + sum(IC[i+1] * inputs[i]). Input count already checked.
+fn input_combination(env: &Env, vk: &VerificationKey, inputs: &[BytesN<32>]) -> Bn254G1Affine {
+    let bn254 = env.crypto().bn254();
+    let mut vk_x = Bn254G1Affine::from_bytes(vk.ic.get_unchecked(0));
+    for (i, input) in inputs.iter().enumerate() {
+        let ic_point = Bn254G1Affine::from_bytes(vk.ic.get_unchecked(i as u32 + 1));
+        if let Some(term) = scalar_mul(env, &ic_point, input) {
+            vk_x = bn254.g1_add(&vk_x, &term);
+        }
+    }
+    vk_x
+}
+
+/// point * scalar, or None when the scalar is zero. Most public inputs
+/// (session id, winner, deck size, suits, an unused beacon) fit in 64 bits,
+/// and double-and-add over g1_add (a few thousand instructions each) costs
+/// a fraction of one g1_mul (over a million).
+pub(crate) fn scalar_mul(env: &Env, point: &Bn254G1Affine, scalar: &BytesN<32>) -> Option<Bn254G1Affine> {
+    let bn254 = env.crypto().bn254();
+    let bytes = scalar.to_array();
+    if bytes[..24].iter().any(|b| *b != 0) {
+        return Some(bn254.g1_mul(point, &Fr::from_bytes(scalar.clone())));
+    }
+    let mut small = [0u8; 8];
+    small.copy_from_slice(&bytes[24..]);
+    let small = u64::from_be_bytes(small);
+    if small == 0 {
+        return None;
+    }
+    let mut acc = point.clone();
+    for bit in (0..63 - small.leading_zeros()).rev() {
+        acc = bn254.g1_add(&acc, &acc);
+        if (small >> bit) & 1 == 1 {
+            acc = bn254.g1_add(&acc, point);
+        }
+    }
+    Some(acc)
+}
+
+/// The i-th batch challenge: the first 16 bytes of sha256(transcript || i),
+/// as a 128-bit scalar.
+fn challenge(env: &Env, transcript: &BytesN<32>, i: u32) -> Fr {
+    let mut preimage = Bytes::from_array(env, &transcript.to_array());
+    preimage.extend_from_array(&i.to_be_bytes());
+    let digest = env.crypto().sha256(&preimage).to_array();
+    let mut scalar = [0u8; 32];
+    scalar[16..].copy_from_slice(&digest[..16]);
+    Fr::from_bytes(BytesN::from_array(env, &scalar))
 }
 
 /// Negate a BN254 G1 affine point: (x, y) -> (x, p - y).
+/// The identity maps to itself; proof points are screened for it before
+/// this is called, so only VK-derived points (e.g. vk_x) can hit that case.
 fn negate_g1(env: &Env, point: &Bn254G1Affine) -> Bn254G1Affine {
     let bytes = point.to_array();
 
@@ -0,0 +1,29 @@
+#![no_std]
+
+//! The Groth16 verification key pirate-cards stores and verifies against,
+//! in its wire encoding. Kept in its own crate so contracts that hand a key
+//! to pirate-cards, such as the factory, encode it with the same type.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, BytesN, Env, Vec};
+
+/// Groth16 verification key stored on-chain.
+/// IC length = nPublic + 1 (7 entries for 6 public inputs).
+/// `negated_b` marks keys whose prover emits π_B already negated
+/// (snarkjs does not; some gnark configurations do).
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct VerificationKey {
+    pub alpha_g1: BytesN<64>,
+    pub beta_g2: BytesN<128>,
+    pub gamma_g2: BytesN<128>,
+    pub delta_g2: BytesN<128>,
+    pub ic: Vec<BytesN<64>>,
+    pub negated_b: bool,
+}
+
+impl VerificationKey {
+    /// sha256 of the key's XDR, as stored for hash-only keys.
+    pub fn hash(&self, env: &Env) -> BytesN<32> {
+        env.crypto().sha256(&self.clone().to_xdr(env)).to_bytes()
+    }
+}
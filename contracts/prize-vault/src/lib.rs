@@ -0,0 +1,502 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Env, Vec,
+};
+
+/// Most operators a pool may name.
+pub const MAX_OPERATORS: u32 = 10;
+/// Most paid places a pool may have.
+pub const MAX_PLACES: u32 = 16;
+/// Most game contracts the admin may allow pools to name.
+pub const MAX_GAMES: u32 = 10;
+/// Prize shares are in basis points of the pool and must sum to this.
+pub const TOTAL_BPS: u32 = 10_000;
+/// The result-sink protocol version this vault speaks: plain win/lose
+/// reports, no optional calls.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[contracttype]
+enum DataKey {
+    Admin,
+    Pool(u32),
+    Contribution(u32, Address), // pool, funder -> amount still held
+    Games,                      // game contracts the admin allows pools to name
+    Session(u32),               // held by the first allowed game to start it
+}
+
+/// What `open_pool` sets up; fixed for the pool's lifetime.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolConfig {
+    pub token: Address,
+    pub bracket: Address,          // reports the final standings
+    pub game: Address,             // game contract whose results this vault hears as a sink
+    pub operators: Vec<Address>,
+    pub threshold: u32,            // operator approvals needed to release
+    pub shares_bps: Vec<u32>,      // prize share per place, champion first
+    pub expires_at: u32,           // ledger after which an unreleased pool refunds its funders
+}
+
+/// A tournament's prize pool and what must happen before it pays out.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub token: Address,
+    pub bracket: Address,          // reports the final standings
+    pub game: Address,             // game contract whose results this vault hears as a sink
+    pub operators: Vec<Address>,
+    pub threshold: u32,            // operator approvals needed to release
+    pub shares_bps: Vec<u32>,      // prize share per place, champion first
+    pub expires_at: u32,           // ledger after which an unreleased pool refunds its funders
+    pub balance: i128,
+    pub final_session: u32,        // the final's session id; set with the standings
+    pub standings: Vec<Address>,   // paid places, champion first; empty until reported
+    pub approvals: Vec<Address>,   // operators who signed off on the standings
+    pub released: bool,
+}
+
+/// A game reported to this vault through the result-sink interface.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionResult {
+    pub game: Address,
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Option<Address>, // None until the game ends
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum VaultError {
+    PoolExists = 1,
+    NoPool = 2,
+    InvalidConfig = 3,
+    InvalidAmount = 4,
+    NotOperator = 5,
+    AlreadyApproved = 6,
+    NoStandings = 7,
+    StandingsReported = 8,
+    StandingsMismatch = 9,
+    Released = 10,
+    UnknownSession = 11,
+    UnknownGame = 12,
+    SessionTaken = 13,
+    Expired = 14,
+    NotExpired = 15,
+    NothingToRefund = 16,
+}
+
+#[contractevent]
+pub struct PoolOpened {
+    #[topic]
+    pub pool_id: u32,
+    pub token: Address,
+    pub bracket: Address,
+    pub threshold: u32,
+}
+
+#[contractevent]
+pub struct PoolFunded {
+    #[topic]
+    pub pool_id: u32,
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct StandingsReported {
+    #[topic]
+    pub pool_id: u32,
+    pub final_session: u32,
+    pub standings: Vec<Address>,
+}
+
+#[contractevent]
+pub struct PayoutApproved {
+    #[topic]
+    pub pool_id: u32,
+    pub operator: Address,
+    pub approvals: u32,
+}
+
+#[contractevent]
+pub struct PoolRefunded {
+    #[topic]
+    pub pool_id: u32,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct PrizePaid {
+    #[topic]
+    pub pool_id: u32,
+    pub place: u32,
+    pub to: Address,
+    pub amount: i128,
+}
+
+/// Holds tournament prize pools outside the game contract. A pool pays its
+/// places only after its bracket contract has reported the final
+/// standings and `threshold` of its operators have approved them.
+///
+/// A pool not released by its `expires_at` ledger is closed to standings
+/// and approvals, and each funder can take back what they put in.
+///
+/// The vault is also a result sink (protocol version 1): a game contract
+/// that allowlists it and routes a tournament's games here reports each
+/// start and result, and the bracket's champion and runner-up must match
+/// the players and winner of the final it names. Only game contracts the
+/// admin allows are heard, and a session id belongs to the first of them
+/// to start it, so a result always lands on the game that reported the
+/// start.
+#[contract]
+pub struct PrizeVault;
+
+#[contractimpl]
+impl PrizeVault {
+    /// Deploy: the admin who decides which game contracts pools may name.
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+    }
+
+    /// Admin: let pools name `game` and hear its starts and results.
+    pub fn allow_game(env: Env, game: Address) -> Result<(), VaultError> {
+        require_admin(&env);
+        let mut games = get_games(&env);
+        if !games.contains(&game) {
+            if games.len() >= MAX_GAMES {
+                return Err(VaultError::InvalidConfig);
+            }
+            games.push_back(game);
+        }
+        set_games(&env, &games);
+        Ok(())
+    }
+
+    /// Admin: stop hearing new games from `game` and opening pools on it.
+    /// Its sessions already started still take their results.
+    pub fn disallow_game(env: Env, game: Address) {
+        require_admin(&env);
+        let mut games = get_games(&env);
+        if let Some(i) = games.first_index_of(&game) {
+            games.remove(i);
+            set_games(&env, &games);
+        }
+    }
+
+    /// The game contracts pools may name.
+    pub fn get_games(env: Env) -> Vec<Address> {
+        get_games(&env)
+    }
+
+    /// Open pool `pool_id`. `config.shares_bps` lists each paid place's cut,
+    /// champion first, and must sum to `TOTAL_BPS`; `config.threshold` of
+    /// the distinct operators must approve the standings before
+    /// `config.expires_at`.
+    pub fn open_pool(env: Env, pool_id: u32, creator: Address, config: PoolConfig) -> Result<(), VaultError> {
+        creator.require_auth();
+        let PoolConfig { token, bracket, game, operators, threshold, shares_bps, expires_at } = config;
+        if env.storage().persistent().has(&DataKey::Pool(pool_id)) {
+            return Err(VaultError::PoolExists);
+        }
+        let places = shares_bps.len();
+        if operators.len() > MAX_OPERATORS
+            || threshold == 0
+            || threshold > operators.len()
+            || has_duplicates(&operators)
+            || places == 0
+            || places > MAX_PLACES
+            || shares_bps.iter().sum::<u32>() != TOTAL_BPS
+            || expires_at <= env.ledger().sequence()
+        {
+            return Err(VaultError::InvalidConfig);
+        }
+        if !get_games(&env).contains(&game) {
+            return Err(VaultError::UnknownGame);
+        }
+
+        let pool = Pool {
+            token: token.clone(),
+            bracket: bracket.clone(),
+            game,
+            operators,
+            threshold,
+            shares_bps,
+            expires_at,
+            balance: 0,
+            final_session: 0,
+            standings: Vec::new(&env),
+            approvals: Vec::new(&env),
+            released: false,
+        };
+        set_pool(&env, pool_id, &pool);
+        PoolOpened { pool_id, token, bracket, threshold }.publish(&env);
+        Ok(())
+    }
+
+    /// Add `amount` from `from` to the pool. Anyone may fund a pool until it
+    /// pays out or expires.
+    pub fn fund(env: Env, pool_id: u32, from: Address, amount: i128) -> Result<i128, VaultError> {
+        from.require_auth();
+        let mut pool = get_pool(&env, pool_id)?;
+        require_open(&env, &pool)?;
+        if amount <= 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+        token::Client::new(&env, &pool.token).transfer(&from, env.current_contract_address(), &amount);
+        pool.balance += amount;
+        set_pool(&env, pool_id, &pool);
+        let contributed = get_contribution(&env, pool_id, &from);
+        set_contribution(&env, pool_id, &from, contributed + amount);
+        PoolFunded { pool_id, from, amount }.publish(&env);
+        Ok(pool.balance)
+    }
+
+    /// Bracket: report the final standings, one address per paid place,
+    /// and the session the final was played under. Only once per pool.
+    pub fn report_standings(
+        env: Env,
+        pool_id: u32,
+        final_session: u32,
+        standings: Vec<Address>,
+    ) -> Result<(), VaultError> {
+        let mut pool = get_pool(&env, pool_id)?;
+        pool.bracket.require_auth();
+        require_open(&env, &pool)?;
+        if !pool.standings.is_empty() {
+            return Err(VaultError::StandingsReported);
+        }
+        if standings.len() != pool.shares_bps.len() || has_duplicates(&standings) {
+            return Err(VaultError::InvalidConfig);
+        }
+
+        let result = get_session(&env, final_session)
+            .filter(|result| result.game == pool.game)
+            .ok_or(VaultError::UnknownSession)?;
+        let Some(champion) = &result.winner else {
+            return Err(VaultError::StandingsMismatch);
+        };
+        let runner_up = if *champion == result.player1 { &result.player2 } else { &result.player1 };
+        if standings.get_unchecked(0) != *champion
+            || standings.get(1).is_some_and(|second| second != *runner_up)
+        {
+            return Err(VaultError::StandingsMismatch);
+        }
+
+        pool.final_session = final_session;
+        pool.standings = standings.clone();
+        set_pool(&env, pool_id, &pool);
+        StandingsReported { pool_id, final_session, standings }.publish(&env);
+        Ok(())
+    }
+
+    /// Operator: sign off on the reported standings. The approval that
+    /// reaches the threshold pays every place. Returns the approvals so far.
+    pub fn approve_payout(env: Env, pool_id: u32, operator: Address) -> Result<u32, VaultError> {
+        operator.require_auth();
+        let mut pool = get_pool(&env, pool_id)?;
+        require_open(&env, &pool)?;
+        if !pool.operators.contains(&operator) {
+            return Err(VaultError::NotOperator);
+        }
+        if pool.standings.is_empty() {
+            return Err(VaultError::NoStandings);
+        }
+        if pool.approvals.contains(&operator) {
+            return Err(VaultError::AlreadyApproved);
+        }
+
+        pool.approvals.push_back(operator.clone());
+        let approvals = pool.approvals.len();
+        PayoutApproved { pool_id, operator, approvals }.publish(&env);
+        if approvals >= pool.threshold {
+            pay_places(&env, pool_id, &mut pool);
+        }
+        set_pool(&env, pool_id, &pool);
+        Ok(approvals)
+    }
+
+    /// Anyone can return `funder`'s contributions to a pool that expired
+    /// without paying out. Returns the amount refunded.
+    pub fn refund(env: Env, pool_id: u32, funder: Address) -> Result<i128, VaultError> {
+        let mut pool = get_pool(&env, pool_id)?;
+        if pool.released {
+            return Err(VaultError::Released);
+        }
+        if env.ledger().sequence() <= pool.expires_at {
+            return Err(VaultError::NotExpired);
+        }
+        let amount = get_contribution(&env, pool_id, &funder);
+        if amount == 0 {
+            return Err(VaultError::NothingToRefund);
+        }
+
+        set_contribution(&env, pool_id, &funder, 0);
+        pool.balance -= amount;
+        set_pool(&env, pool_id, &pool);
+        token::Client::new(&env, &pool.token).transfer(&env.current_contract_address(), &funder, &amount);
+        PoolRefunded { pool_id, to: funder, amount }.publish(&env);
+        Ok(amount)
+    }
+
+    /// The pool, if it exists.
+    pub fn get_pool(env: Env, pool_id: u32) -> Option<Pool> {
+        get_pool(&env, pool_id).ok()
+    }
+
+    /// What `funder` has in pool `pool_id` and would get back on expiry.
+    pub fn get_contribution(env: Env, pool_id: u32, funder: Address) -> i128 {
+        get_contribution(&env, pool_id, &funder)
+    }
+
+    /// What this vault heard about `session_id` as a result sink, and from
+    /// which game.
+    pub fn get_session(env: Env, session_id: u32) -> Option<SessionResult> {
+        get_session(&env, session_id)
+    }
+
+    /// Result sink: a game contract registering a started game. Only
+    /// allowed game contracts are heard, and an id another game already
+    /// started is refused.
+    pub fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+    ) -> Result<(), VaultError> {
+        game_id.require_auth();
+        if !get_games(&env).contains(&game_id) {
+            return Err(VaultError::UnknownGame);
+        }
+        if get_session(&env, session_id).is_some_and(|result| result.game != game_id) {
+            return Err(VaultError::SessionTaken);
+        }
+        set_session(&env, session_id, &SessionResult { game: game_id.clone(), player1, player2, winner: None });
+        Ok(())
+    }
+
+    /// Result sink: the game contract that started `session_id` reporting
+    /// its winner. The interface does not name the caller; the session's
+    /// own game must authorize it.
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) -> Result<(), VaultError> {
+        let mut result = get_session(&env, session_id)
+            .filter(|result| result.winner.is_none())
+            .ok_or(VaultError::UnknownSession)?;
+        result.game.require_auth();
+        let winner = if player1_won { &result.player1 } else { &result.player2 };
+        result.winner = Some(winner.clone());
+        set_session(&env, session_id, &result);
+        Ok(())
+    }
+
+    /// Result sink: the protocol version game contracts negotiate.
+    pub fn protocol_version(_env: Env) -> u32 {
+        PROTOCOL_VERSION
+    }
+}
+
+/// Pay each place its share of the balance; rounding dust goes to the
+/// champion.
+fn pay_places(env: &Env, pool_id: u32, pool: &mut Pool) {
+    let client = token::Client::new(env, &pool.token);
+    let share = |bps: u32| pool.balance * bps as i128 / TOTAL_BPS as i128;
+    let dust = pool.balance - pool.shares_bps.iter().map(share).sum::<i128>();
+    for (place, (to, bps)) in pool.standings.iter().zip(pool.shares_bps.iter()).enumerate() {
+        let amount = if place == 0 { share(bps) + dust } else { share(bps) };
+        if amount > 0 {
+            client.transfer(&env.current_contract_address(), &to, &amount);
+        }
+        PrizePaid { pool_id, place: place as u32, to, amount }.publish(env);
+    }
+    pool.balance = 0;
+    pool.released = true;
+}
+
+/// Standings, approvals and funds are only taken before a pool pays out
+/// or expires.
+fn require_open(env: &Env, pool: &Pool) -> Result<(), VaultError> {
+    if pool.released {
+        return Err(VaultError::Released);
+    }
+    if env.ledger().sequence() > pool.expires_at {
+        return Err(VaultError::Expired);
+    }
+    Ok(())
+}
+
+fn require_admin(env: &Env) {
+    let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+    admin.require_auth();
+}
+
+fn has_duplicates(addresses: &Vec<Address>) -> bool {
+    addresses
+        .iter()
+        .enumerate()
+        .any(|(i, address)| addresses.first_index_of(&address) != Some(i as u32))
+}
+
+fn get_pool(env: &Env, pool_id: u32) -> Result<Pool, VaultError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Pool(pool_id))
+        .ok_or(VaultError::NoPool)
+}
+
+fn set_pool(env: &Env, pool_id: u32, pool: &Pool) {
+    let key = DataKey::Pool(pool_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, pool);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+fn get_contribution(env: &Env, pool_id: u32, funder: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Contribution(pool_id, funder.clone()))
+        .unwrap_or(0)
+}
+
+fn set_contribution(env: &Env, pool_id: u32, funder: &Address, amount: i128) {
+    let key = DataKey::Contribution(pool_id, funder.clone());
+    if amount == 0 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, &amount);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+fn get_games(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Games)
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_games(env: &Env, games: &Vec<Address>) {
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&DataKey::Games, games);
+    env.storage().persistent().extend_ttl(&DataKey::Games, ttl, ttl);
+}
+
+fn get_session(env: &Env, session_id: u32) -> Option<SessionResult> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Session(session_id))
+}
+
+fn set_session(env: &Env, session_id: u32, result: &SessionResult) {
+    let key = DataKey::Session(session_id);
+    let ttl = env.storage().max_ttl();
+    env.storage().persistent().set(&key, result);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
@@ -0,0 +1,212 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, Vec,
+};
+
+use crate::{PoolConfig, PrizeVault, PrizeVaultClient, VaultError};
+
+struct Setup {
+    env: Env,
+    client: PrizeVaultClient<'static>,
+    admin: Address,
+    token: TokenClient<'static>,
+    bracket: Address,
+    game: Address,
+    operators: Vec<Address>,
+}
+
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PrizeVault, (admin.clone(),));
+    let client = PrizeVaultClient::new(&env, &contract_id);
+    let game = Address::generate(&env);
+    client.allow_game(&game);
+    let token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let operators = vec![&env, Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+    Setup {
+        client,
+        admin,
+        token: TokenClient::new(&env, &token),
+        bracket: Address::generate(&env),
+        game,
+        operators,
+        env,
+    }
+}
+
+fn config(s: &Setup, threshold: u32, operators: &Vec<Address>, shares_bps: Vec<u32>) -> PoolConfig {
+    PoolConfig {
+        token: s.token.address.clone(),
+        bracket: s.bracket.clone(),
+        game: s.game.clone(),
+        operators: operators.clone(),
+        threshold,
+        shares_bps,
+        expires_at: 1_000,
+    }
+}
+
+/// Pool 1 paying 70/30 on two of three operators, funded with `amount`.
+fn funded_pool(s: &Setup, amount: i128) {
+    let creator = Address::generate(&s.env);
+    s.client.open_pool(&1u32, &creator, &config(s, 2, &s.operators, vec![&s.env, 7_000u32, 3_000]));
+    let sponsor = Address::generate(&s.env);
+    StellarAssetClient::new(&s.env, &s.token.address).mint(&sponsor, &amount);
+    s.client.fund(&1u32, &sponsor, &amount);
+}
+
+#[test]
+fn test_prizes_released_on_standings_and_threshold() {
+    let s = setup();
+    funded_pool(&s, 1_001);
+    let (alice, bob) = (Address::generate(&s.env), Address::generate(&s.env));
+
+    // The final, reported by the game contract through the result sink
+    s.client.start_game(&s.game, &9u32, &alice, &bob);
+    s.client.end_game(&9u32, &false);
+    assert_eq!(s.client.get_session(&9u32).unwrap().winner, Some(bob.clone()));
+
+    let op = |i| s.operators.get_unchecked(i);
+    assert_eq!(s.client.try_approve_payout(&1u32, &op(0)), Err(Ok(VaultError::NoStandings)));
+    s.client.report_standings(&1u32, &9u32, &vec![&s.env, bob.clone(), alice.clone()]);
+    assert_eq!(
+        s.client.try_report_standings(&1u32, &9u32, &vec![&s.env, bob.clone(), alice.clone()]),
+        Err(Ok(VaultError::StandingsReported))
+    );
+
+    assert_eq!(s.client.approve_payout(&1u32, &op(0)), 1);
+    assert_eq!(s.client.try_approve_payout(&1u32, &op(0)), Err(Ok(VaultError::AlreadyApproved)));
+    assert_eq!(
+        s.client.try_approve_payout(&1u32, &Address::generate(&s.env)),
+        Err(Ok(VaultError::NotOperator))
+    );
+    assert_eq!(s.token.balance(&bob), 0);
+
+    // The second approval reaches the threshold; the dust goes to the champion
+    assert_eq!(s.client.approve_payout(&1u32, &op(2)), 2);
+    assert_eq!(s.token.balance(&bob), 701);
+    assert_eq!(s.token.balance(&alice), 300);
+    let pool = s.client.get_pool(&1u32).unwrap();
+    assert!(pool.released);
+    assert_eq!(pool.balance, 0);
+    assert_eq!(s.client.try_approve_payout(&1u32, &op(1)), Err(Ok(VaultError::Released)));
+}
+
+#[test]
+fn test_standings_must_match_the_final() {
+    let s = setup();
+    funded_pool(&s, 1_000);
+    let (alice, bob) = (Address::generate(&s.env), Address::generate(&s.env));
+    let standings = vec![&s.env, alice.clone(), bob.clone()];
+
+    assert_eq!(s.client.try_report_standings(&1u32, &9u32, &standings), Err(Ok(VaultError::UnknownSession)));
+    s.client.start_game(&s.game, &9u32, &alice, &bob);
+    assert_eq!(s.client.try_report_standings(&1u32, &9u32, &standings), Err(Ok(VaultError::StandingsMismatch)));
+
+    // Bob won, so Alice cannot be reported champion
+    s.client.end_game(&9u32, &false);
+    assert_eq!(s.client.try_report_standings(&1u32, &9u32, &standings), Err(Ok(VaultError::StandingsMismatch)));
+    assert_eq!(
+        s.client.try_report_standings(&1u32, &9u32, &vec![&s.env, bob.clone()]),
+        Err(Ok(VaultError::InvalidConfig))
+    );
+
+    s.client.report_standings(&1u32, &9u32, &vec![&s.env, bob.clone(), alice.clone()]);
+}
+
+#[test]
+fn test_only_allowed_games_are_heard() {
+    let s = setup();
+    funded_pool(&s, 1_000);
+    let (alice, bob) = (Address::generate(&s.env), Address::generate(&s.env));
+
+    // A game the admin has not allowed can neither open a pool nor report
+    let other = Address::generate(&s.env);
+    let mut elsewhere = config(&s, 2, &s.operators, vec![&s.env, 10_000u32]);
+    elsewhere.game = other.clone();
+    assert_eq!(
+        s.client.try_open_pool(&2u32, &Address::generate(&s.env), &elsewhere),
+        Err(Ok(VaultError::UnknownGame))
+    );
+    assert_eq!(s.client.try_start_game(&other, &9u32, &alice, &bob), Err(Ok(VaultError::UnknownGame)));
+
+    // Once allowed, it cannot take an id another game started, and a
+    // pool only accepts a final its own game played
+    s.client.allow_game(&other);
+    assert_eq!(s.env.auths()[0].0, s.admin);
+    assert_eq!(s.client.get_games(), vec![&s.env, s.game.clone(), other.clone()]);
+    s.client.start_game(&s.game, &9u32, &alice, &bob);
+    assert_eq!(s.client.try_start_game(&other, &9u32, &bob, &alice), Err(Ok(VaultError::SessionTaken)));
+    s.client.start_game(&other, &10u32, &bob, &alice);
+    s.client.end_game(&10u32, &true);
+    assert_eq!(
+        s.client.try_report_standings(&1u32, &10u32, &vec![&s.env, bob.clone(), alice.clone()]),
+        Err(Ok(VaultError::UnknownSession))
+    );
+    s.client.end_game(&9u32, &false);
+    assert_eq!(s.client.try_end_game(&9u32, &true), Err(Ok(VaultError::UnknownSession)));
+    assert_eq!(s.client.get_session(&9u32).unwrap().game, s.game);
+
+    // Disallowing stops new games but not the finals already in play
+    s.client.start_game(&s.game, &11u32, &alice, &bob);
+    s.client.disallow_game(&s.game);
+    assert_eq!(s.client.try_start_game(&s.game, &12u32, &alice, &bob), Err(Ok(VaultError::UnknownGame)));
+    s.client.end_game(&11u32, &true);
+    assert_eq!(s.client.get_session(&11u32).unwrap().winner, Some(alice.clone()));
+    assert_eq!(s.client.get_games(), vec![&s.env, other]);
+}
+
+#[test]
+fn test_expired_pool_refunds_its_funders() {
+    let s = setup();
+    funded_pool(&s, 600);
+    let late = Address::generate(&s.env);
+    StellarAssetClient::new(&s.env, &s.token.address).mint(&late, &400);
+    s.client.fund(&1u32, &late, &150);
+    s.client.fund(&1u32, &late, &250);
+    assert_eq!(s.client.get_contribution(&1u32, &late), 400);
+    assert_eq!(s.client.try_refund(&1u32, &late), Err(Ok(VaultError::NotExpired)));
+
+    // Past expiry nothing more is taken, and each funder gets back their own
+    s.env.ledger().with_mut(|l| l.sequence_number = 1_001);
+    let (alice, bob) = (Address::generate(&s.env), Address::generate(&s.env));
+    s.client.start_game(&s.game, &9u32, &alice, &bob);
+    s.client.end_game(&9u32, &true);
+    let standings = vec![&s.env, alice, bob];
+    assert_eq!(s.client.try_report_standings(&1u32, &9u32, &standings), Err(Ok(VaultError::Expired)));
+    assert_eq!(s.client.try_approve_payout(&1u32, &s.operators.get_unchecked(0)), Err(Ok(VaultError::Expired)));
+    assert_eq!(s.client.try_fund(&1u32, &late, &1), Err(Ok(VaultError::Expired)));
+
+    assert_eq!(s.client.refund(&1u32, &late), 400);
+    assert_eq!(s.token.balance(&late), 400);
+    assert_eq!(s.client.try_refund(&1u32, &late), Err(Ok(VaultError::NothingToRefund)));
+    assert_eq!(s.client.get_pool(&1u32).unwrap().balance, 600);
+}
+
+#[test]
+fn test_pool_config_rules() {
+    let s = setup();
+    let creator = Address::generate(&s.env);
+    let open = |threshold: u32, operators: &Vec<Address>, shares: Vec<u32>| {
+        s.client.try_open_pool(&1u32, &creator, &config(&s, threshold, operators, shares))
+    };
+    let even = vec![&s.env, 5_000u32, 5_000];
+    let mut stale = config(&s, 2, &s.operators, even.clone());
+    stale.expires_at = s.env.ledger().sequence();
+    assert_eq!(s.client.try_open_pool(&1u32, &creator, &stale), Err(Ok(VaultError::InvalidConfig)));
+    assert_eq!(open(0, &s.operators, even.clone()), Err(Ok(VaultError::InvalidConfig)));
+    assert_eq!(open(4, &s.operators, even.clone()), Err(Ok(VaultError::InvalidConfig)));
+    assert_eq!(open(2, &s.operators, vec![&s.env, 5_000u32, 4_000]), Err(Ok(VaultError::InvalidConfig)));
+    let twice = vec![&s.env, s.operators.get_unchecked(0), s.operators.get_unchecked(0)];
+    assert_eq!(open(2, &twice, even.clone()), Err(Ok(VaultError::InvalidConfig)));
+
+    assert_eq!(open(3, &s.operators, even.clone()), Ok(Ok(())));
+    assert_eq!(open(3, &s.operators, even), Err(Ok(VaultError::PoolExists)));
+    assert_eq!(s.client.try_fund(&1u32, &creator, &0), Err(Ok(VaultError::InvalidAmount)));
+    assert_eq!(s.client.try_fund(&2u32, &creator, &10), Err(Ok(VaultError::NoPool)));
+}
@@ -0,0 +1,61 @@
+#![no_std]
+
+//! A small interface for two-player games on Soroban, so leaderboards,
+//! tournaments and the like can integrate any game that implements it
+//! without knowing its rules. A game implements `PvpGame` on its contract
+//! and publishes `PvpResult` whenever a session's result is decided or
+//! changes; consumers call through `PvpGameClient`.
+
+use soroban_sdk::{contractclient, contractevent, contracttype, Address, Env};
+
+/// Where a session stands.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum GameStatus {
+    Open = 0,       // waiting for a second player
+    InProgress = 1,
+    Finished = 2,   // a result is available from `get_result`
+    Cancelled = 3,  // closed without being played
+}
+
+/// A session's players and status.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameInfo {
+    pub player1: Address,
+    pub player2: Option<Address>, // None until someone joins
+    pub status: GameStatus,
+}
+
+/// A finished session's result.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameResult {
+    pub winner: Option<Address>, // None for a draw
+    pub draw: bool,
+    pub finished_at: u32,        // ledger sequence the result was decided at
+}
+
+#[contractclient(name = "PvpGameClient")]
+pub trait PvpGame {
+    /// The session's players and status, while the game keeps it.
+    fn game_info(env: Env, session_id: u32) -> Option<GameInfo>;
+
+    /// The session's result once finished. Games should keep results
+    /// longer than sessions, so this can outlive `game_info`.
+    fn get_result(env: Env, session_id: u32) -> Option<GameResult>;
+}
+
+/// Published with each decided result, and again if it is later changed
+/// (e.g. overturned on appeal); the latest event is authoritative.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PvpResult {
+    #[topic]
+    pub session_id: u32,
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Option<Address>, // None for a draw
+    pub draw: bool,
+}
@@ -0,0 +1,187 @@
+//! `circuits/pirate_cards.circom` as an R1CS, template for template:
+//! `synthesize` follows PirateCards, `deck_shuffle` DeckShuffle and
+//! `game_sim` GameSim/GameRound, with the same public inputs in the same
+//! order.
+//!
+//! A card weight's high half must lie below the modulus' high half, and
+//! the coin is decomposed canonically, as in the circom source. Without
+//! them a prover could satisfy the bit decompositions with the bits of
+//! w + p instead of w, reordering the deck or flipping the coin. An honest
+//! weight fails the first check only if its top 126 bits equal the
+//! modulus', which has probability 2^-126.
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use pirate_engine::DECK_SIZE;
+
+use crate::r1cs::{Cs, Lc};
+use crate::Assignment;
+
+const SEED_COMMIT1: usize = 0;
+const SEED_COMMIT2: usize = 1;
+const SEED1: usize = 2;
+const SEED2: usize = 3;
+const SESSION_ID: usize = 4;
+const WINNER: usize = 5;
+const BEACON: usize = 6;
+const DECK_SIZE_INPUT: usize = 7;
+const SUITS: usize = 8;
+
+/// The constraints for `assignment`, satisfied only if it proves a real
+/// game.
+pub fn synthesize(assignment: &Assignment) -> Cs {
+    let mut cs = Cs::new(&assignment.public);
+    let input = |i| cs.public(i);
+    let (commit1, commit2, seed1, seed2) = (input(SEED_COMMIT1), input(SEED_COMMIT2), input(SEED1), input(SEED2));
+    let (session_id, winner, beacon) = (input(SESSION_ID), input(WINNER), input(BEACON));
+    let (deck_size, suits) = (input(DECK_SIZE_INPUT), input(SUITS));
+
+    // 0. The ruleset this circuit implements
+    cs.enforce_equal(&deck_size, &Lc::constant(DECK_SIZE as u64));
+    cs.enforce_equal(&suits, &Lc::constant(pirate_engine::SUITS as u64));
+
+    // 1. Seed commitments
+    let hash1 = cs.poseidon(std::slice::from_ref(&seed1));
+    cs.enforce_equal(&hash1, &commit1);
+    let hash2 = cs.poseidon(std::slice::from_ref(&seed2));
+    cs.enforce_equal(&hash2, &commit2);
+
+    // 2. Combined seed, 3. shuffle, 4. play, 5. winner
+    let combined = cs.poseidon(&[seed1, seed2, session_id, beacon]);
+    let deck = deck_shuffle(&mut cs, &combined, assignment);
+    let proven = game_sim(&mut cs, &deck, &combined);
+    cs.enforce_equal(&proven, &winner);
+    cs
+}
+
+/// `deck` is a permutation of 0..25 sorted by the low 128 bits of
+/// Poseidon(combined, card).
+fn deck_shuffle(cs: &mut Cs, combined: &Lc, assignment: &Assignment) -> Vec<Lc> {
+    let deck: Vec<Lc> = assignment.deck.iter().map(|card| cs.alloc(Fr::from(*card))).collect();
+    for card in &deck {
+        let in_range = cs.less_than(card, &Lc::constant(DECK_SIZE as u64), 5);
+        cs.enforce_equal(&in_range, &Lc::constant(1u64));
+    }
+    for i in 0..DECK_SIZE {
+        for j in i + 1..DECK_SIZE {
+            let diff = deck[i].sub(&deck[j]);
+            let inv = cs.alloc(cs.eval(&diff).inverse().unwrap_or_default());
+            cs.enforce(inv, diff, Lc::constant(1u64));
+        }
+    }
+
+    let mut modulus_high = Fr::MODULUS;
+    modulus_high.divn(128);
+    let modulus_high = modulus_high.to_bits_le();
+    let mut truncs = Vec::with_capacity(DECK_SIZE);
+    for (i, card) in deck.iter().enumerate() {
+        let weight = cs.poseidon(&[combined.clone(), card.clone()]);
+        let trunc = cs.alloc(Fr::from(assignment.trunc_weights[i]));
+        let high = cs.alloc(Fr::from(assignment.high_weights[i]));
+        cs.enforce_equal(&weight, &trunc.add_scaled(&high, Fr::from(2u64).pow([128])));
+        cs.bits(&trunc, 128);
+        let high_bits = cs.bits(&high, 126);
+        let canonical = cs.bits_below(&high_bits, &modulus_high);
+        cs.enforce_equal(&canonical, &Lc::constant(1u64));
+        truncs.push(trunc);
+    }
+    for pair in truncs.windows(2) {
+        let sorted = cs.less_eq(&pair[0], &pair[1], 128);
+        cs.enforce_equal(&sorted, &Lc::constant(1u64));
+    }
+    deck
+}
+
+/// CardType: 0 Triangle, 1 Square, 2 Line, 3 black spot.
+fn card_type(cs: &mut Cs, card: &Lc) -> Lc {
+    let mut card_type = Lc::constant(3u64);
+    for bound in [8u64, 16, 24] {
+        let below = cs.less_than(card, &Lc::constant(bound), 5);
+        card_type = card_type.sub(&below);
+    }
+    card_type
+}
+
+struct RoundState {
+    score1: Lc,
+    score2: Lc,
+    active: Lc,
+    winner: Lc,
+}
+
+/// GameRound
+fn game_round(cs: &mut Cs, card1: &Lc, card2: &Lc, state: RoundState) -> RoundState {
+    let one = Lc::constant(1u64);
+    let (type1, type2) = (card_type(cs, card1), card_type(cs, card2));
+    let bs1 = cs.is_equal(&type1, &Lc::constant(3u64));
+    let bs2 = cs.is_equal(&type2, &Lc::constant(3u64));
+    let bs_product = cs.mul(&bs1, &bs2);
+    let any_bs = bs1.add(&bs2).sub(&bs_product);
+
+    // combined = 3·type1 + type2: {1, 5, 6} player 1, {0, 4, 8} a tie
+    let combined = type1.scale(3u64).add(&type2);
+    let hits = |values: [u64; 3], cs: &mut Cs| {
+        values.iter().fold(Lc::default(), |acc, v| acc.add(&cs.is_equal(&combined, &Lc::constant(*v))))
+    };
+    let p1_wins = hits([1, 5, 6], cs);
+    let is_tie = hits([0, 4, 8], cs);
+
+    let no_bs = one.sub(&any_bs);
+    let active_no_bs = cs.mul(&state.active, &no_bs);
+    let delta1 = cs.mul(&p1_wins, &active_no_bs);
+    let p2_wins = one.sub(&p1_wins).sub(&is_tie);
+    let delta2 = cs.mul(&p2_wins, &active_no_bs);
+    let score1 = state.score1.add(&delta1);
+    let score2 = state.score2.add(&delta2);
+
+    let target = Lc::constant(pirate_engine::WIN_TARGET as u64);
+    let ge3_p1 = cs.greater_eq(&score1, &target, 4);
+    let ge3_p2 = cs.greater_eq(&score2, &target, 4);
+
+    let winner_unset = cs.is_equal(&state.winner, &Lc::default());
+    let can_set = cs.mul(&state.active, &winner_unset);
+    // Black spot first: drawn by player 1 → 2, by player 2 → 1
+    let bs_code = bs1.scale(2u64).add(&bs2).sub(&bs_product.scale(3u64));
+    let bs_sets = cs.mul(&can_set, &any_bs);
+    let bs_contribution = cs.mul(&bs_sets, &bs_code);
+
+    let no_bs_can_set = cs.mul(&can_set, &no_bs);
+    let score3_p1 = cs.mul(&ge3_p1, &no_bs_can_set);
+    let score3_p2 = cs.mul(&ge3_p2, &no_bs_can_set);
+    let score3_p2 = cs.mul(&score3_p2, &one.sub(&ge3_p1));
+    let score_contribution = score3_p1.add(&score3_p2.scale(2u64));
+
+    let decided = bs_contribution.add(&score_contribution);
+    let winner = state.winner.add(&decided);
+    let still_active = cs.is_zero(&decided);
+    let active = cs.mul(&state.active, &still_active);
+    RoundState { score1, score2, active, winner }
+}
+
+/// GameSim: twelve rounds, then most wins, then the coin. Returns the
+/// winner, 1 or 2.
+fn game_sim(cs: &mut Cs, deck: &[Lc], combined: &Lc) -> Lc {
+    let mut state = RoundState {
+        score1: Lc::default(),
+        score2: Lc::default(),
+        active: Lc::constant(1u64),
+        winner: Lc::default(),
+    };
+    for round in 0..pirate_engine::ROUNDS {
+        state = game_round(cs, &deck[2 * round], &deck[2 * round + 1], state);
+    }
+
+    let s1_ahead = cs.greater_than(&state.score1, &state.score2, 4);
+    let s2_ahead = cs.greater_than(&state.score2, &state.score1, 4);
+    let level = cs.is_equal(&state.score1, &state.score2);
+
+    let coin = cs.poseidon(&[combined.clone(), Lc::constant(DECK_SIZE as u64)]);
+    let coin_bits = cs.bits_strict(&coin);
+    let coin_winner = coin_bits[0].add_constant(1u64);
+
+    let tb_p1 = cs.mul(&s1_ahead, &state.active);
+    let tb_p2 = cs.mul(&s2_ahead, &state.active);
+    let tb_tie = cs.mul(&level, &state.active);
+    let tb_coin = cs.mul(&tb_tie, &coin_winner);
+    state.winner.add(&tb_p1).add(&tb_p2.scale(2u64)).add(&tb_coin)
+}
@@ -0,0 +1,160 @@
+//! The pirate-cards circuit in Rust: an R1CS of
+//! `circuits/pirate_cards.circom`, set up and proved with arkworks'
+//! Groth16 and encoded as the contract expects. It lives next to the verifier so the two
+//! are tested together; `contracts/pirate-cards` settles games with
+//! proofs from it rather than from a circuit built elsewhere.
+//!
+//! Keys from [`Circuit::setup`] are not the deployed ones: the deployed
+//! verification key comes from the circom build and its ceremony, and
+//! this circuit's key only verifies this circuit's proofs. It proves the
+//! same statement over the same public inputs, so it doubles as a
+//! [`pirate_prover::Backend`] wherever a self-generated key will do
+//! (tests, local networks).
+
+#[cfg(test)]
+mod test;
+
+pub mod circuit;
+pub mod r1cs;
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Groth16, ProvingKey};
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+use pirate_engine::DECK_SIZE;
+use pirate_prover::snarkjs::decimal;
+use pirate_prover::{Backend, Error, Groth16Proof, Witness, PUBLIC_INPUT_NAMES};
+use serde_json::Value;
+
+/// Every input signal of the circuit, public ones in circuit order.
+#[derive(Clone, Debug)]
+pub struct Assignment {
+    pub public: [Fr; 9],
+    pub deck: [u8; DECK_SIZE],
+    pub trunc_weights: [u128; DECK_SIZE],
+    pub high_weights: [u128; DECK_SIZE],
+}
+
+impl From<&Witness> for Assignment {
+    fn from(witness: &Witness) -> Self {
+        Assignment {
+            public: witness.public_inputs.to_array().map(|x| Fr::from_be_bytes_mod_order(&x)),
+            deck: witness.deck,
+            trunc_weights: witness.trunc_weights,
+            high_weights: witness.high_weights,
+        }
+    }
+}
+
+impl Assignment {
+    /// From the circuit's `input.json`, as `Witness::circuit_input` writes it.
+    pub fn from_json(input: &Value) -> Result<Self, Error> {
+        let field = |v: &Value| decimal(v).map(|x| Fr::from_be_bytes_mod_order(&x));
+        let array = |name: &str| -> Result<Vec<u128>, Error> {
+            let values = input[name].as_array().filter(|a| a.len() == DECK_SIZE);
+            let values = values.ok_or_else(|| Error::Malformed(format!("{} needs {} entries", name, DECK_SIZE)))?;
+            values
+                .iter()
+                .map(|v| v.as_str().and_then(|s| s.parse().ok()).ok_or_else(|| Error::Malformed(format!("bad {}", name))))
+                .collect()
+        };
+        let mut public = [Fr::from(0u64); 9];
+        for (x, name) in public.iter_mut().zip(PUBLIC_INPUT_NAMES) {
+            *x = field(&input[name])?;
+        }
+        let deck = array("deck")?;
+        if deck.iter().any(|card| *card > u8::MAX as u128) {
+            return Err(Error::Malformed("bad deck".into()));
+        }
+        Ok(Assignment {
+            public,
+            deck: core::array::from_fn(|i| deck[i] as u8),
+            trunc_weights: array("trunc_weights")?.try_into().unwrap(),
+            high_weights: array("high_weights")?.try_into().unwrap(),
+        })
+    }
+}
+
+/// Mirrors pirate-cards' `VerificationKey`, in its encoding.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    pub ic: Vec<[u8; 64]>,
+    pub negated_b: bool,
+}
+
+pub struct Circuit {
+    pub pk: ProvingKey<Bn254>,
+}
+
+impl Circuit {
+    /// A setup with randomness from `seed`: deterministic, and so only fit
+    /// for tests and local networks.
+    pub fn setup(seed: u64) -> Self {
+        // Any game gives the same constraints; this one is just valid
+        let witness = Witness::new(&[0; 32], &[0; 32], 0, &[0; 32]).unwrap();
+        let cs = circuit::synthesize(&Assignment::from(&witness));
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(cs, &mut StdRng::seed_from_u64(seed));
+        Circuit { pk: pk.expect("the circuit synthesizes") }
+    }
+
+    pub fn verification_key(&self) -> VerificationKey {
+        let vk = &self.pk.vk;
+        VerificationKey {
+            alpha_g1: g1_bytes(&vk.alpha_g1),
+            beta_g2: g2_bytes(&vk.beta_g2),
+            gamma_g2: g2_bytes(&vk.gamma_g2),
+            delta_g2: g2_bytes(&vk.delta_g2),
+            ic: vk.gamma_abc_g1.iter().map(g1_bytes).collect(),
+            negated_b: false,
+        }
+    }
+
+    /// Prove `assignment` with blinding from `rng`, or None if it is not a
+    /// real game.
+    pub fn prove<R: Rng>(&self, assignment: &Assignment, rng: &mut R) -> Option<Groth16Proof> {
+        let cs = circuit::synthesize(assignment);
+        if cs.unsatisfied().is_some() {
+            return None;
+        }
+        let proof = Groth16::<Bn254>::create_random_proof_with_reduction(cs, &self.pk, rng).ok()?;
+        Some(Groth16Proof { pi_a: g1_bytes(&proof.a), pi_b: g2_bytes(&proof.b), pi_c: g1_bytes(&proof.c) })
+    }
+}
+
+impl Backend for Circuit {
+    fn prove(&self, input: &Value) -> Result<(Groth16Proof, Vec<[u8; 32]>), Error> {
+        let assignment = Assignment::from_json(input)?;
+        let proof = Circuit::prove(self, &assignment, &mut rand::thread_rng())
+            .ok_or_else(|| Error::Backend("the input does not satisfy the circuit".into()))?;
+        Ok((proof, assignment.public.iter().map(fr_bytes).collect()))
+    }
+}
+
+fn fr_bytes(x: &Fr) -> [u8; 32] {
+    x.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+fn fq_bytes(x: &Fq) -> [u8; 32] {
+    x.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+fn g1_bytes(p: &G1Affine) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out[..32].copy_from_slice(&fq_bytes(&p.x));
+    out[32..].copy_from_slice(&fq_bytes(&p.y));
+    out
+}
+
+/// be(X_c1) || be(X_c0) || be(Y_c1) || be(Y_c0), as the host expects.
+fn g2_bytes(p: &G2Affine) -> [u8; 128] {
+    let mut out = [0u8; 128];
+    for (k, coord) in [&p.x, &p.y].iter().enumerate() {
+        out[64 * k..64 * k + 32].copy_from_slice(&fq_bytes(&coord.c1));
+        out[64 * k + 32..64 * k + 64].copy_from_slice(&fq_bytes(&coord.c0));
+    }
+    out
+}
@@ -0,0 +1,239 @@
+//! A minimal rank-1 constraint system and the circomlib gadgets the
+//! circuit is built from. Synthesis always carries values: `Cs` records
+//! each constraint and the assignment together, and no gadget branches on
+//! a value, so every assignment yields the same constraints. A finished
+//! `Cs` replays into arkworks as a `ConstraintSynthesizer` for Groth16.
+
+use std::collections::BTreeMap;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use pirate_prover::poseidon;
+
+/// A linear combination of variables; variable 0 is the constant one.
+#[derive(Clone, Debug, Default)]
+pub struct Lc(pub BTreeMap<usize, Fr>);
+
+impl Lc {
+    pub fn var(index: usize) -> Self {
+        Lc(BTreeMap::from([(index, Fr::one())]))
+    }
+
+    pub fn constant(c: impl Into<Fr>) -> Self {
+        Lc(BTreeMap::from([(0, c.into())]))
+    }
+
+    pub fn add(&self, other: &Lc) -> Lc {
+        self.add_scaled(other, Fr::one())
+    }
+
+    pub fn sub(&self, other: &Lc) -> Lc {
+        self.add_scaled(other, -Fr::one())
+    }
+
+    pub fn scale(&self, k: impl Into<Fr>) -> Lc {
+        let k = k.into();
+        Lc(self.0.iter().map(|(i, c)| (*i, *c * k)).collect())
+    }
+
+    pub fn add_constant(&self, c: impl Into<Fr>) -> Lc {
+        self.add(&Lc::constant(c))
+    }
+
+    /// self + k·other
+    pub fn add_scaled(&self, other: &Lc, k: Fr) -> Lc {
+        let mut sum = self.0.clone();
+        for (i, c) in &other.0 {
+            let entry = sum.entry(*i).or_insert_with(Fr::zero);
+            *entry += *c * k;
+            if entry.is_zero() {
+                sum.remove(i);
+            }
+        }
+        Lc(sum)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Cs {
+    /// The assignment, starting with the constant one.
+    pub values: Vec<Fr>,
+    /// Variables 1..=num_public are the public inputs.
+    pub num_public: usize,
+    /// Rows of A·z ∘ B·z = C·z.
+    pub constraints: Vec<(Lc, Lc, Lc)>,
+}
+
+impl Cs {
+    /// A system whose first variables are `public`, in order.
+    pub fn new(public: &[Fr]) -> Self {
+        let mut values = vec![Fr::one()];
+        values.extend_from_slice(public);
+        Cs { values, num_public: public.len(), constraints: Vec::new() }
+    }
+
+    pub fn public(&self, i: usize) -> Lc {
+        assert!(i < self.num_public);
+        Lc::var(i + 1)
+    }
+
+    pub fn eval(&self, lc: &Lc) -> Fr {
+        lc.0.iter().map(|(i, c)| self.values[*i] * c).sum()
+    }
+
+    pub fn alloc(&mut self, value: Fr) -> Lc {
+        self.values.push(value);
+        Lc::var(self.values.len() - 1)
+    }
+
+    pub fn enforce(&mut self, a: Lc, b: Lc, c: Lc) {
+        self.constraints.push((a, b, c));
+    }
+
+    pub fn enforce_equal(&mut self, a: &Lc, b: &Lc) {
+        self.enforce(a.sub(b), Lc::constant(1u64), Lc::default());
+    }
+
+    /// The row that fails first, if the assignment does not satisfy every
+    /// constraint.
+    pub fn unsatisfied(&self) -> Option<usize> {
+        self.constraints.iter().position(|(a, b, c)| self.eval(a) * self.eval(b) != self.eval(c))
+    }
+
+    pub fn mul(&mut self, a: &Lc, b: &Lc) -> Lc {
+        let out = self.alloc(self.eval(a) * self.eval(b));
+        self.enforce(a.clone(), b.clone(), out.clone());
+        out
+    }
+
+    /// Num2Bits(n): the low `n` bits of `x`, least significant first, each
+    /// boolean, summing to `x`.
+    pub fn bits(&mut self, x: &Lc, n: usize) -> Vec<Lc> {
+        let value = self.eval(x).into_bigint().to_bits_le();
+        let mut sum = Lc::default();
+        let mut bits = Vec::with_capacity(n);
+        let mut weight = Fr::one();
+        for &bit in value.iter().take(n) {
+            let b = self.alloc(Fr::from(bit));
+            self.enforce(b.clone(), b.add_constant(-Fr::one()), Lc::default());
+            sum = sum.add_scaled(&b, weight);
+            weight.double_in_place();
+            bits.push(b);
+        }
+        self.enforce_equal(&sum, x);
+        bits
+    }
+
+    /// Whether little-endian `bits` encode an integer below `bound`, as a
+    /// boolean Lc. Walking from the top bit, `eq` tracks "equal so far" and
+    /// the result collects the first position where bits drop below
+    /// `bound`: one constraint per set bit of `bound`.
+    pub fn bits_below(&mut self, bits: &[Lc], bound: &[bool]) -> Lc {
+        let mut eq = Lc::constant(1u64);
+        let mut below = Lc::default();
+        for (bit, &set) in bits.iter().zip(bound).rev() {
+            if set {
+                let still_eq = self.mul(&eq, bit);
+                below = below.add(&eq).sub(&still_eq);
+                eq = still_eq;
+            } else {
+                eq = self.mul(&eq, &Lc::constant(1u64).sub(bit));
+            }
+        }
+        below
+    }
+
+    /// Num2Bits(254) restricted to the canonical encoding, so a prover
+    /// cannot pick the bits of x + p instead of x.
+    pub fn bits_strict(&mut self, x: &Lc) -> Vec<Lc> {
+        let bits = self.bits(x, Fr::MODULUS_BIT_SIZE as usize);
+        let below = self.bits_below(&bits, &Fr::MODULUS.to_bits_le());
+        self.enforce_equal(&below, &Lc::constant(1u64));
+        bits
+    }
+
+    /// IsZero
+    pub fn is_zero(&mut self, x: &Lc) -> Lc {
+        let value = self.eval(x);
+        let inv = self.alloc(value.inverse().unwrap_or_default());
+        let out = self.alloc(Fr::from(value.is_zero()));
+        self.enforce(x.clone(), inv, Lc::constant(1u64).sub(&out));
+        self.enforce(x.clone(), out.clone(), Lc::default());
+        out
+    }
+
+    /// IsEqual
+    pub fn is_equal(&mut self, a: &Lc, b: &Lc) -> Lc {
+        self.is_zero(&a.sub(b))
+    }
+
+    /// LessThan(n): a < b for a, b below 2^n.
+    pub fn less_than(&mut self, a: &Lc, b: &Lc, n: usize) -> Lc {
+        let shifted = a.add_constant(Fr::from(2u64).pow([n as u64])).sub(b);
+        let bits = self.bits(&shifted, n + 1);
+        Lc::constant(1u64).sub(&bits[n])
+    }
+
+    /// LessEqThan(n)
+    pub fn less_eq(&mut self, a: &Lc, b: &Lc, n: usize) -> Lc {
+        self.less_than(a, &b.add_constant(1u64), n)
+    }
+
+    /// GreaterThan(n)
+    pub fn greater_than(&mut self, a: &Lc, b: &Lc, n: usize) -> Lc {
+        self.less_than(b, a, n)
+    }
+
+    /// GreaterEqThan(n)
+    pub fn greater_eq(&mut self, a: &Lc, b: &Lc, n: usize) -> Lc {
+        self.less_than(b, &a.add_constant(1u64), n)
+    }
+
+    /// circomlib's Poseidon(inputs.len()): three constraints per S-box.
+    pub fn poseidon(&mut self, inputs: &[Lc]) -> Lc {
+        let t = inputs.len() + 1;
+        let params = poseidon::params(t);
+        let full = poseidon::FULL_ROUNDS;
+        let mut state = vec![Lc::default()];
+        state.extend_from_slice(inputs);
+        for round in 0..full + params.partial_rounds {
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = x.add_constant(params.constants[round * t + i]);
+            }
+            let sboxes = if round < full / 2 || round >= full / 2 + params.partial_rounds { t } else { 1 };
+            for x in state.iter_mut().take(sboxes) {
+                let x2 = self.mul(x, x);
+                let x4 = self.mul(&x2, &x2);
+                *x = self.mul(&x4, x);
+            }
+            state = params
+                .mds
+                .iter()
+                .map(|row| row.iter().zip(&state).fold(Lc::default(), |acc, (m, x)| acc.add_scaled(x, *m)))
+                .collect();
+        }
+        state.swap_remove(0)
+    }
+}
+
+/// The public inputs as instance variables, in order, then the rest of the
+/// assignment as witnesses, then every row.
+impl ConstraintSynthesizer<Fr> for Cs {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let mut vars = vec![Variable::One];
+        for (i, value) in self.values.iter().enumerate().skip(1) {
+            let var = if i <= self.num_public {
+                cs.new_input_variable(|| Ok(*value))?
+            } else {
+                cs.new_witness_variable(|| Ok(*value))?
+            };
+            vars.push(var);
+        }
+        let lc = |lc: &Lc| LinearCombination(lc.0.iter().map(|(i, c)| (*c, vars[*i])).collect());
+        for (a, b, c) in &self.constraints {
+            cs.enforce_constraint(lc(a), lc(b), lc(c))?;
+        }
+        Ok(())
+    }
+}
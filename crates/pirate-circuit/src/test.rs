@@ -0,0 +1,81 @@
+#![cfg(test)]
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{prepare_verifying_key, Groth16};
+use pirate_prover::witness::bytes;
+use pirate_prover::Witness;
+
+use crate::r1cs::{Cs, Lc};
+use crate::*;
+
+fn witness(seed1: u64, seed2: u64, session_id: u32) -> Witness {
+    Witness::new(&bytes(&Fr::from(seed1)), &bytes(&Fr::from(seed2)), session_id, &[0; 32]).unwrap()
+}
+
+#[test]
+fn test_constraints_accept_only_real_games() {
+    for (seed1, seed2, session_id) in [(123456789, 987654321, 1), (1, 2, 3), (42, 42, 7)] {
+        let assignment = Assignment::from(&witness(seed1, seed2, session_id));
+        assert_eq!(circuit::synthesize(&assignment).unsatisfied(), None);
+
+        // The other winner
+        let mut wrong = assignment.clone();
+        wrong.public[5] = Fr::from(3u64) - wrong.public[5];
+        assert!(circuit::synthesize(&wrong).unsatisfied().is_some());
+
+        // Any other order of the same cards
+        let mut wrong = assignment.clone();
+        wrong.deck.swap(0, 1);
+        wrong.trunc_weights.swap(0, 1);
+        wrong.high_weights.swap(0, 1);
+        assert!(circuit::synthesize(&wrong).unsatisfied().is_some());
+    }
+
+    // Same constraints whatever the game
+    let a = circuit::synthesize(&Assignment::from(&witness(1, 2, 3)));
+    let b = circuit::synthesize(&Assignment::from(&witness(5, 6, 7)));
+    assert_eq!((a.constraints.len(), a.values.len()), (b.constraints.len(), b.values.len()));
+}
+
+#[test]
+fn test_strict_bits_reject_the_aliased_encoding() {
+    let mut cs = Cs::new(&[]);
+    let x = cs.alloc(Fr::from(5u64));
+    cs.bits_strict(&x);
+    assert_eq!(cs.unsatisfied(), None);
+
+    // 5 + p still sums to 5 in the field, but is not below p
+    let mut alias = Fr::MODULUS;
+    alias.add_with_carry(&5u64.into());
+    let bits: Vec<Lc> = alias.to_bits_le()[..254].iter().map(|b| cs.alloc(Fr::from(*b))).collect();
+    let below = cs.bits_below(&bits, &Fr::MODULUS.to_bits_le());
+    assert_eq!(cs.eval(&below), Fr::from(0u64));
+}
+
+#[test]
+fn test_proof_verifies_and_backs_the_prover() {
+    let circuit = Circuit::setup(1);
+    let witness = witness(123456789, 987654321, 1);
+    let assignment = Assignment::from(&witness);
+    let cs = circuit::synthesize(&assignment);
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(cs, &circuit.pk, &mut ark_std::test_rng()).unwrap();
+
+    // Natively, and only for the inputs it was made for
+    let pvk = prepare_verifying_key(&circuit.pk.vk);
+    assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &assignment.public).unwrap());
+    let mut flipped = assignment.public;
+    flipped[5] = Fr::from(3u64) - flipped[5];
+    assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &flipped).unwrap());
+
+    // Nothing proves a game that does not satisfy the constraints
+    let mut wrong = assignment.clone();
+    wrong.public = flipped;
+    assert!(circuit.prove(&wrong, &mut ark_std::test_rng()).is_none());
+
+    // As a prover backend, including the signal-order check
+    let public = &witness.public_inputs;
+    let proved = pirate_prover::prove(&circuit, &public.seed1, &public.seed2, 1, &[0; 32]).unwrap();
+    assert_eq!(&proved.public_inputs, public);
+    assert_eq!(circuit.verification_key().ic.len(), 10);
+}
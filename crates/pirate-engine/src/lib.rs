@@ -0,0 +1,184 @@
+//! The Dead Man's Draw rules, exactly as `circuits/game_sim.circom`
+//! proves them: who wins a given deck order. Anything that predicts or
+//! checks a result off-chain (the prover, tools, tests) plays through
+//! here rather than keeping its own copy of the rules.
+//!
+//! Cards are numbered 0..25: 0-7 Triangle, 8-15 Square, 16-23 Line and 24
+//! the black spot. Each round the players draw the next two cards (player
+//! 1 first); drawing the black spot loses at once, otherwise Triangle
+//! beats Square beats Line beats Triangle. First to three round wins takes
+//! the game; if the deck runs out, most wins, then a coin flip.
+
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+mod prng;
+mod shuffle;
+
+pub use prng::{game_stream, keccak_stream, Prng};
+pub use shuffle::{derive_deck, shuffle_seed, MAX_DERIVED_DECK, SHUFFLE_DOMAIN};
+
+/// Cards in the classic deck.
+pub const DECK_SIZE: usize = 25;
+/// Suits other than the black spot.
+pub const SUITS: u32 = 3;
+/// The one card that loses on sight.
+pub const BLACK_SPOT: u8 = 24;
+/// Round wins that end the game early.
+pub const WIN_TARGET: u32 = 3;
+/// Rounds a full deck allows; the last card is never drawn.
+pub const ROUNDS: usize = (DECK_SIZE - 1) / 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Suit {
+    Triangle,
+    Square,
+    Line,
+    BlackSpot,
+}
+
+/// The suit of card `card` (0..25).
+pub fn suit(card: u8) -> Suit {
+    match card {
+        0..=7 => Suit::Triangle,
+        8..=15 => Suit::Square,
+        16..=23 => Suit::Line,
+        _ => Suit::BlackSpot,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Player {
+    One = 1,
+    Two = 2,
+}
+
+/// How a game was decided.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ending {
+    /// The loser drew the black spot.
+    BlackSpot,
+    /// The winner reached `WIN_TARGET` round wins.
+    Target,
+    /// The deck ran out and the winner had more round wins.
+    MostWins,
+    /// The deck ran out level and the coin decided.
+    CoinFlip,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    pub winner: Player,
+    pub ending: Ending,
+    pub score1: u32,
+    pub score2: u32,
+    /// Rounds drawn, including the deciding one.
+    pub rounds: u32,
+}
+
+/// Who takes a round between two non-black-spot suits, or None on a tie.
+pub fn round_winner(a: Suit, b: Suit) -> Option<Player> {
+    let (a, b) = (a as u8, b as u8);
+    if a == b {
+        None
+    } else if (a + 1) % 3 == b {
+        Some(Player::One)
+    } else {
+        Some(Player::Two)
+    }
+}
+
+/// Play `deck` out. `coin` is the tiebreak bit ([`Deal::coin`], the low
+/// bit of Poseidon(combined_seed, 25) in the circuit); false gives player 1
+/// a tied game, true player 2.
+pub fn play(deck: &[u8; DECK_SIZE], coin: bool) -> Outcome {
+    let (mut score1, mut score2) = (0, 0);
+    for round in 0..ROUNDS {
+        let (a, b) = (suit(deck[2 * round]), suit(deck[2 * round + 1]));
+        let rounds = round as u32 + 1;
+        let decided = |winner, ending, score1, score2| Outcome { winner, ending, score1, score2, rounds };
+        if a == Suit::BlackSpot {
+            return decided(Player::Two, Ending::BlackSpot, score1, score2);
+        }
+        if b == Suit::BlackSpot {
+            return decided(Player::One, Ending::BlackSpot, score1, score2);
+        }
+        match round_winner(a, b) {
+            Some(Player::One) => score1 += 1,
+            Some(Player::Two) => score2 += 1,
+            None => {}
+        }
+        if score1 >= WIN_TARGET {
+            return decided(Player::One, Ending::Target, score1, score2);
+        }
+        if score2 >= WIN_TARGET {
+            return decided(Player::Two, Ending::Target, score1, score2);
+        }
+    }
+    let (winner, ending) = match score1.cmp(&score2) {
+        core::cmp::Ordering::Greater => (Player::One, Ending::MostWins),
+        core::cmp::Ordering::Less => (Player::Two, Ending::MostWins),
+        core::cmp::Ordering::Equal if coin => (Player::Two, Ending::CoinFlip),
+        core::cmp::Ordering::Equal => (Player::One, Ending::CoinFlip),
+    };
+    Outcome { winner, ending, score1, score2, rounds: ROUNDS as u32 }
+}
+
+/// The deck the circuit accepts for these per-card sort keys (the low 128
+/// bits of Poseidon(combined_seed, card)): cards in ascending key order,
+/// equal keys keeping card order.
+pub fn order_by_keys(keys: &[u128; DECK_SIZE]) -> [u8; DECK_SIZE] {
+    let mut deck = [0u8; DECK_SIZE];
+    for (i, card) in deck.iter_mut().enumerate() {
+        *card = i as u8;
+    }
+    // Insertion sort is stable and plenty for 25 cards
+    for i in 1..DECK_SIZE {
+        let mut j = i;
+        while j > 0 && keys[deck[j - 1] as usize] > keys[deck[j] as usize] {
+            deck.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+    deck
+}
+
+/// A classic game's draw from its stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Deal {
+    pub deck: [u8; DECK_SIZE],
+    /// The tiebreak bit for [`play`].
+    pub coin: bool,
+    /// Block `card` of the stream, the big-endian weight the deck sorts on.
+    pub weights: [[u8; 32]; DECK_SIZE],
+}
+
+/// Deal a classic game the way the circuit does: the first `DECK_SIZE`
+/// blocks of `stream` weigh each card, the deck is [`order_by_keys`] of
+/// their low 128 bits, and the coin is the low bit of the next block. The
+/// circuit's stream is Poseidon(combined_seed, k).
+pub fn deal<B: FnMut(u32) -> [u8; 32]>(mut stream: Prng<B>) -> Deal {
+    let weights: [[u8; 32]; DECK_SIZE] = core::array::from_fn(|_| stream.next_block());
+    let low = |block: &[u8; 32]| {
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&block[16..]);
+        u128::from_be_bytes(bytes)
+    };
+    let keys = weights.map(|weight| low(&weight));
+    let coin = low(&stream.next_block()) & 1 == 1;
+    Deal { deck: order_by_keys(&keys), coin, weights }
+}
+
+/// Whether `deck` holds every card exactly once.
+pub fn is_permutation(deck: &[u8; DECK_SIZE]) -> bool {
+    let mut seen = [false; DECK_SIZE];
+    for &card in deck {
+        match seen.get_mut(card as usize) {
+            Some(slot) if !*slot => *slot = true,
+            _ => return false,
+        }
+    }
+    true
+}
@@ -0,0 +1,89 @@
+//! The games' randomness: a counter-mode stream of 32-byte blocks, block k
+//! being some hash of the game's seed material and k. Two block functions
+//! are in use:
+//!
+//! - Poseidon(combined_seed, k), which the classic circuit proves: [`deal`]
+//!   sorts the deck by blocks 0..25 and flips the tiebreak coin with block
+//!   25, and the prover and the contract's `derive_deck` view both deal
+//!   through it.
+//! - keccak256(seed || be32(k)) ([`keccak_stream`]), behind the keccak
+//!   shuffle of [`crate::derive_deck`].
+//!
+//! The engine has no hash of its own: callers pass the block function (the
+//! host's hashes on-chain, any matching crate off-chain), and nothing here
+//! depends on the target (no floats, only u32 words and byte compares), so
+//! no_std and std builds agree and the prover and the contract draw the
+//! same stream.
+//!
+//! [`deal`]: crate::deal
+
+/// A counter-mode stream over `block`, which returns block k for k = 0, 1, ..
+pub struct Prng<B> {
+    counter: u32,
+    block: [u8; 32],
+    used: usize,
+    block_fn: B,
+}
+
+impl<B: FnMut(u32) -> [u8; 32]> Prng<B> {
+    /// The stream of `block(0)`, `block(1)`, ...
+    pub fn new(block_fn: B) -> Self {
+        Prng { counter: 0, block: [0; 32], used: 32, block_fn }
+    }
+
+    /// The next whole block, dropping any words left in the current one.
+    pub fn next_block(&mut self) -> [u8; 32] {
+        self.block = (self.block_fn)(self.counter);
+        self.counter += 1;
+        self.used = self.block.len();
+        self.block
+    }
+
+    /// The next big-endian word of the stream, eight to a block.
+    pub fn next_u32(&mut self) -> u32 {
+        if self.used == self.block.len() {
+            self.next_block();
+            self.used = 0;
+        }
+        let word = &self.block[self.used..self.used + 4];
+        self.used += 4;
+        u32::from_be_bytes([word[0], word[1], word[2], word[3]])
+    }
+
+    /// Uniform in 0..bound (bound > 0), rejecting words from the
+    /// incomplete top range rather than taking a biased remainder.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        let limit = u32::MAX - u32::MAX % bound;
+        loop {
+            let word = self.next_u32();
+            if word < limit {
+                return word % bound;
+            }
+        }
+    }
+}
+
+/// The keccak256 stream for `seed`: block k is keccak256(seed || be32(k)).
+pub fn keccak_stream(
+    seed: [u8; 32],
+    mut keccak: impl FnMut(&[u8]) -> [u8; 32],
+) -> Prng<impl FnMut(u32) -> [u8; 32]> {
+    Prng::new(move |counter: u32| {
+        let mut preimage = [0u8; 36];
+        preimage[..32].copy_from_slice(&seed);
+        preimage[32..].copy_from_slice(&counter.to_be_bytes());
+        keccak(&preimage)
+    })
+}
+
+/// The keccak stream a keccak-shuffle game draws from: seeded with
+/// `shuffle_seed(seed1, seed2, variant)`.
+pub fn game_stream(
+    seed1: &[u8; 32],
+    seed2: &[u8; 32],
+    variant: u32,
+    mut keccak: impl FnMut(&[u8]) -> [u8; 32],
+) -> Prng<impl FnMut(u32) -> [u8; 32]> {
+    let seed = crate::shuffle_seed(seed1, seed2, variant, &mut keccak);
+    keccak_stream(seed, keccak)
+}
@@ -0,0 +1,51 @@
+//! The keccak shuffle: a Fisher–Yates pass driven by the game's keccak
+//! stream ([`game_stream`]) over both revealed seeds, so that anyone can
+//! lay out a game's deck from its seeds alone.
+//!
+//! The classic circuit (`circuits/pirate_cards.circom`) predates this
+//! shuffle and deals from its Poseidon stream instead ([`crate::deal`]);
+//! circuits built against `derive_deck` use a new verification key.
+
+use crate::game_stream;
+
+/// Prefix of the shuffle seed preimage.
+pub const SHUFFLE_DOMAIN: &[u8] = b"dead-mans-draw/shuffle/v1";
+/// Largest deck `derive_deck` lays out, so callers can size a buffer.
+pub const MAX_DERIVED_DECK: usize = 256;
+
+/// keccak256(SHUFFLE_DOMAIN || seed1 || seed2 || be32(variant)).
+pub fn shuffle_seed(
+    seed1: &[u8; 32],
+    seed2: &[u8; 32],
+    variant: u32,
+    keccak: &mut impl FnMut(&[u8]) -> [u8; 32],
+) -> [u8; 32] {
+    let mut preimage = [0u8; SHUFFLE_DOMAIN.len() + 68];
+    let (domain, rest) = preimage.split_at_mut(SHUFFLE_DOMAIN.len());
+    domain.copy_from_slice(SHUFFLE_DOMAIN);
+    rest[..32].copy_from_slice(seed1);
+    rest[32..64].copy_from_slice(seed2);
+    rest[64..].copy_from_slice(&variant.to_be_bytes());
+    keccak(&preimage)
+}
+
+/// Lay out `deck.len()` cards (at most `MAX_DERIVED_DECK`) for `variant`:
+/// fill 0..n, then for i from n-1 down to 1 swap card i with card j,
+/// j = `below(i + 1)` from [`game_stream`].
+pub fn derive_deck(
+    seed1: &[u8; 32],
+    seed2: &[u8; 32],
+    variant: u32,
+    deck: &mut [u32],
+    keccak: impl FnMut(&[u8]) -> [u8; 32],
+) {
+    assert!(deck.len() <= MAX_DERIVED_DECK);
+    let mut prng = game_stream(seed1, seed2, variant, keccak);
+    for (i, card) in deck.iter_mut().enumerate() {
+        *card = i as u32;
+    }
+    for i in (1..deck.len()).rev() {
+        let j = prng.below(i as u32 + 1) as usize;
+        deck.swap(i, j);
+    }
+}
@@ -0,0 +1,149 @@
+#![cfg(test)]
+
+use crate::*;
+
+/// A deck whose first rounds are `pairs`, then the untouched cards in
+/// numeric order.
+fn deck(pairs: &[(u8, u8)]) -> [u8; DECK_SIZE] {
+    let mut deck = [0u8; DECK_SIZE];
+    let mut used = [false; DECK_SIZE];
+    let mut n = 0;
+    for &(a, b) in pairs {
+        for card in [a, b] {
+            deck[n] = card;
+            used[card as usize] = true;
+            n += 1;
+        }
+    }
+    for card in 0..DECK_SIZE as u8 {
+        if !used[card as usize] {
+            deck[n] = card;
+            n += 1;
+        }
+    }
+    deck
+}
+
+#[test]
+fn test_rps_and_black_spot() {
+    assert_eq!(round_winner(Suit::Triangle, Suit::Square), Some(Player::One));
+    assert_eq!(round_winner(Suit::Square, Suit::Line), Some(Player::One));
+    assert_eq!(round_winner(Suit::Line, Suit::Triangle), Some(Player::One));
+    assert_eq!(round_winner(Suit::Square, Suit::Triangle), Some(Player::Two));
+    assert_eq!(round_winner(Suit::Line, Suit::Line), None);
+
+    // Triangle over Square, then player 1 draws the black spot
+    let outcome = play(&deck(&[(0, 8), (BLACK_SPOT, 1)]), false);
+    assert_eq!((outcome.winner, outcome.ending), (Player::Two, Ending::BlackSpot));
+    assert_eq!((outcome.score1, outcome.score2, outcome.rounds), (1, 0, 2));
+}
+
+#[test]
+fn test_first_to_three_and_exhaustion() {
+    let outcome = play(&deck(&[(0, 8), (9, 1), (10, 2), (16, 3), (11, 4)]), false);
+    assert_eq!((outcome.winner, outcome.ending), (Player::Two, Ending::Target));
+    assert_eq!((outcome.score1, outcome.score2, outcome.rounds), (2, 3, 5));
+
+    // Every round a tie, black spot last: the coin decides
+    let tied: [(u8, u8); 12] = core::array::from_fn(|i| (i as u8 * 2, i as u8 * 2 + 1));
+    assert_eq!(play(&deck(&tied), false).winner, Player::One);
+    let outcome = play(&deck(&tied), true);
+    assert_eq!((outcome.winner, outcome.ending, outcome.rounds), (Player::Two, Ending::CoinFlip, 12));
+}
+
+#[test]
+fn test_order_by_keys_is_stable() {
+    let mut keys = [0u128; DECK_SIZE];
+    for (card, key) in keys.iter_mut().enumerate() {
+        *key = (DECK_SIZE - card) as u128 % 7;
+    }
+    let deck = order_by_keys(&keys);
+    assert!(is_permutation(&deck));
+    assert_eq!(&deck[..4], &[4, 11, 18, 3]); // keys 0, 0, 0, 1
+    for pair in deck.windows(2) {
+        let (a, b) = (pair[0] as usize, pair[1] as usize);
+        assert!(keys[a] < keys[b] || (keys[a] == keys[b] && a < b));
+    }
+    assert!(!is_permutation(&[0; DECK_SIZE]));
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+    Keccak256::digest(data).into()
+}
+
+/// The classic deck for seeds 0x11.. and 0x22.., shared with the
+/// contract's `derive_deck` test.
+const CLASSIC_DECK: [u32; DECK_SIZE] =
+    [5, 14, 1, 24, 10, 22, 8, 0, 11, 13, 21, 6, 12, 2, 23, 17, 16, 19, 9, 3, 15, 20, 4, 7, 18];
+
+#[test]
+fn test_derive_deck_is_a_seeded_permutation() {
+    let (seed1, seed2) = ([0x11u8; 32], [0x22u8; 32]);
+    let mut deck = [0u32; DECK_SIZE];
+    derive_deck(&seed1, &seed2, 0, &mut deck, keccak);
+    assert_eq!(deck, CLASSIC_DECK);
+
+    // Seed order and variant both matter
+    let mut other = [0u32; DECK_SIZE];
+    derive_deck(&seed2, &seed1, 0, &mut other, keccak);
+    assert_ne!(other, deck);
+    derive_deck(&seed1, &seed2, 1, &mut other, keccak);
+    assert_ne!(other, deck);
+
+    let mut big = [0u32; MAX_DERIVED_DECK];
+    derive_deck(&seed1, &seed2, 0, &mut big, keccak);
+    let mut seen = [false; MAX_DERIVED_DECK];
+    big.iter().for_each(|card| seen[*card as usize] = true);
+    assert!(seen.iter().all(|s| *s));
+}
+
+#[test]
+fn test_prng_reads_counter_blocks_in_order() {
+    let seed = [0x33u8; 32];
+    let block = |k: u32| {
+        let mut preimage = [0u8; 36];
+        preimage[..32].copy_from_slice(&seed);
+        preimage[32..].copy_from_slice(&k.to_be_bytes());
+        keccak(&preimage)
+    };
+    let word = |block: [u8; 32], i: usize| u32::from_be_bytes(block[4 * i..4 * i + 4].try_into().unwrap());
+
+    let mut prng = keccak_stream(seed, keccak);
+    for i in 0..8 {
+        assert_eq!(prng.next_u32(), word(block(0), i));
+    }
+    assert_eq!(prng.next_u32(), word(block(1), 0));
+
+    // The game stream is the one derive_deck shuffles with
+    let (seed1, seed2) = ([0x11u8; 32], [0x22u8; 32]);
+    let mut prng = game_stream(&seed1, &seed2, 0, keccak);
+    let mut deck: [u32; DECK_SIZE] = core::array::from_fn(|i| i as u32);
+    for i in (1..DECK_SIZE).rev() {
+        let j = prng.below(i as u32 + 1);
+        assert!(j <= i as u32);
+        deck.swap(i, j as usize);
+    }
+    assert_eq!(deck, CLASSIC_DECK);
+    assert_eq!(prng.below(1), 0);
+}
+
+#[test]
+fn test_deal_sorts_on_low_weight_bits() {
+    // Block k's high half is noise; its low half sorts card k to position
+    // 24 - k, and block 25 carries the coin
+    let block = |k: u32| {
+        let mut block = [0xa5u8; 32];
+        block[16..].copy_from_slice(&(DECK_SIZE as u128 - k as u128).to_be_bytes());
+        block
+    };
+    let dealt = deal(Prng::new(block));
+    let reversed: [u8; DECK_SIZE] = core::array::from_fn(|i| (DECK_SIZE - 1 - i) as u8);
+    assert_eq!(dealt.deck, reversed);
+    assert_eq!(dealt.weights[3], block(3));
+    assert!(!dealt.coin); // block 25 ends in 0
+    let odd = |k: u32| if k == DECK_SIZE as u32 { [1; 32] } else { block(k) };
+    let odd_deal = deal(Prng::new(odd));
+    assert!(odd_deal.coin);
+    assert_eq!(odd_deal.deck, dealt.deck);
+}
@@ -0,0 +1,169 @@
+//! Off-chain proving for pirate-cards: from both revealed seeds and the
+//! session id to a `Groth16Proof` and `PublicInputs` the contract's
+//! `settle_game` accepts, in the same encoding `verifier.rs` reads.
+//!
+//! The pipeline is the one `prover/scripts/prove.js` runs, in Rust:
+//! Poseidon commitments and combined seed, the weight-sorted deck, the
+//! game played through `pirate-engine`, then the circuit input handed to
+//! a proving [`Backend`]. The only backend so far wraps the snarkjs CLI
+//! with the repo's compiled circuit and zkey (`bun run circuit:build`,
+//! `bun run circuit:keys`).
+
+#[cfg(test)]
+mod test;
+
+pub mod poseidon;
+pub mod snarkjs;
+pub mod witness;
+
+use std::fmt;
+
+pub use snarkjs::Snarkjs;
+pub use witness::Witness;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The named input is not a canonical BN254 scalar (big-endian, below
+    /// the modulus), so the contract would reject it too.
+    NotInField(&'static str),
+    /// The backend could not be run or failed.
+    Backend(String),
+    /// The backend's output could not be parsed.
+    Malformed(String),
+    /// The backend proved public signals other than the witness's, e.g. a
+    /// circuit build with a different input order.
+    InputMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotInField(name) => write!(f, "{} is not a BN254 scalar", name),
+            Error::Backend(msg) => write!(f, "proving backend failed: {}", msg),
+            Error::Malformed(msg) => write!(f, "unexpected backend output: {}", msg),
+            Error::InputMismatch => write!(f, "proved public signals differ from the witness"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Mirrors pirate-cards' `Groth16Proof`: G1 as be(x) || be(y), G2 as
+/// be(x_c1) || be(x_c0) || be(y_c1) || be(y_c0).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Groth16Proof {
+    pub pi_a: [u8; 64],
+    pub pi_b: [u8; 128],
+    pub pi_c: [u8; 64],
+}
+
+/// Mirrors pirate-cards' `PublicInputs`: big-endian field elements.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicInputs {
+    pub seed_commit1: [u8; 32],
+    pub seed_commit2: [u8; 32],
+    pub seed1: [u8; 32],
+    pub seed2: [u8; 32],
+    pub session_id: [u8; 32],
+    pub winner: [u8; 32],
+    pub beacon: [u8; 32],
+    pub deck_size: [u8; 32],
+    pub suits: [u8; 32],
+}
+
+/// Field names in circuit order, as in `PublicInputs::to_array`.
+pub const PUBLIC_INPUT_NAMES: [&str; 9] = [
+    "seed_commit1", "seed_commit2", "seed1", "seed2", "session_id", "winner", "beacon", "deck_size", "suits",
+];
+
+impl PublicInputs {
+    /// Field elements in circuit order, as consumed by the verifier.
+    pub fn to_array(&self) -> [[u8; 32]; 9] {
+        [
+            self.seed_commit1,
+            self.seed_commit2,
+            self.seed1,
+            self.seed2,
+            self.session_id,
+            self.winner,
+            self.beacon,
+            self.deck_size,
+            self.suits,
+        ]
+    }
+
+    /// The Stellar CLI's JSON argument form (hex per field), as prove.js
+    /// writes it.
+    pub fn to_json(&self) -> serde_json::Value {
+        let fields = PUBLIC_INPUT_NAMES.iter().zip(self.to_array()).map(|(name, x)| (name.to_string(), hex(&x).into()));
+        serde_json::Value::Object(fields.collect())
+    }
+}
+
+impl Groth16Proof {
+    /// The Stellar CLI's JSON argument form.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "pi_a": hex(&self.pi_a), "pi_b": hex(&self.pi_b), "pi_c": hex(&self.pi_c) })
+    }
+}
+
+/// Everything `settle_game` needs, plus the game as the engine played it.
+#[derive(Clone, Debug)]
+pub struct Proved {
+    pub proof: Groth16Proof,
+    pub public_inputs: PublicInputs,
+    pub outcome: pirate_engine::Outcome,
+}
+
+impl Proved {
+    /// `{"proof": .., "public_inputs": ..}`, the shape of prove.js'
+    /// `proof_payload.json`.
+    pub fn payload(&self) -> serde_json::Value {
+        serde_json::json!({ "proof": self.proof.to_json(), "public_inputs": self.public_inputs.to_json() })
+    }
+}
+
+/// Something that turns the circuit input into a proof and the public
+/// signals it proved, in circuit order.
+pub trait Backend {
+    fn prove(&self, input: &serde_json::Value) -> Result<(Groth16Proof, Vec<[u8; 32]>), Error>;
+}
+
+/// Build the witness for a game and prove it with `backend`. `beacon` is
+/// the beacon value the contract reduced to the field, or zero for games
+/// without one.
+pub fn prove(
+    backend: &impl Backend,
+    seed1: &[u8; 32],
+    seed2: &[u8; 32],
+    session_id: u32,
+    beacon: &[u8; 32],
+) -> Result<Proved, Error> {
+    let witness = Witness::new(seed1, seed2, session_id, beacon)?;
+    let (proof, signals) = backend.prove(&witness.circuit_input())?;
+    if signals != witness.public_inputs.to_array() {
+        return Err(Error::InputMismatch);
+    }
+    Ok(Proved { proof, public_inputs: witness.public_inputs, outcome: witness.outcome })
+}
+
+/// keccak256 as the Soroban host computes it, for
+/// [`pirate_engine::keccak_stream`] and the shuffle off-chain.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+    Keccak256::digest(data).into()
+}
+
+/// The keccak-shuffle `deck_size`-card deck for `variant`, as the contract's
+/// `derive_deck` view returns it for `DeckOrder::KeccakShuffle` variants;
+/// [`Witness::deck`] is the Poseidon-weight one.
+pub fn derive_deck(seed1: &[u8; 32], seed2: &[u8; 32], variant: u32, deck_size: usize) -> Vec<u32> {
+    let mut deck = vec![0; deck_size];
+    pirate_engine::derive_deck(seed1, seed2, variant, &mut deck, keccak256);
+    deck
+}
+
+/// Lowercase hex, no prefix.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,59 @@
+//! `pirate-prover <seed1> <seed2> [session_id] [beacon]`: prove a game and
+//! print the `settle_game` payload as JSON, like `prover/scripts/prove.js`.
+//! Seeds and beacon are decimal or 0x-prefixed hex. The circuit artifacts
+//! are read from `PIRATE_PROVER_DIR` (default: the repo's `prover/`) and
+//! `SNARKJS` overrides the snarkjs executable.
+
+use std::path::PathBuf;
+use std::process::exit;
+
+use pirate_prover::{snarkjs, Snarkjs};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 || args.len() > 4 {
+        eprintln!("Usage: pirate-prover <seed1> <seed2> [session_id] [beacon]");
+        exit(2);
+    }
+    let field = |arg: &str| parse_field(arg).unwrap_or_else(|| fail(&format!("not a 32-byte number: {}", arg)));
+    let seed1 = field(&args[0]);
+    let seed2 = field(&args[1]);
+    let session_id = match args.get(2) {
+        Some(arg) => arg.parse().unwrap_or_else(|_| fail(&format!("not a session id: {}", arg))),
+        None => 1,
+    };
+    let beacon = args.get(3).map(|arg| field(arg)).unwrap_or([0; 32]);
+
+    let dir = std::env::var_os("PIRATE_PROVER_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../prover"));
+    let mut backend = Snarkjs::from_prover_dir(&dir);
+    if let Some(command) = std::env::var_os("SNARKJS") {
+        backend.command = command.into();
+    }
+
+    match pirate_prover::prove(&backend, &seed1, &seed2, session_id, &beacon) {
+        Ok(proved) => println!("{}", serde_json::to_string_pretty(&proved.payload()).unwrap()),
+        Err(e) => fail(&e.to_string()),
+    }
+}
+
+fn parse_field(arg: &str) -> Option<[u8; 32]> {
+    match arg.strip_prefix("0x") {
+        Some(hex) if hex.len() <= 64 && hex.bytes().all(|c| c.is_ascii_hexdigit()) => {
+            let padded = format!("{:0>64}", hex);
+            let mut out = [0u8; 32];
+            for (i, byte) in out.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&padded[2 * i..2 * i + 2], 16).ok()?;
+            }
+            Some(out)
+        }
+        Some(_) => None,
+        None => snarkjs::decimal(&arg.into()).ok(),
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("pirate-prover: {}", msg);
+    exit(1);
+}
@@ -0,0 +1,149 @@
+//! Poseidon over the BN254 scalar field, bit-for-bit the circomlib
+//! `Poseidon(n)` the circuit uses (x^5 S-box, 8 full rounds, the
+//! reference partial round counts).
+//!
+//! Rather than vendoring circomlib's constant tables, the round constants
+//! and MDS matrix are regenerated the way the reference script
+//! (`generate_parameters_grain.sage`) produced them: from the Grain LFSR
+//! seeded with the field and round parameters.
+
+use std::sync::OnceLock;
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+
+pub const FULL_ROUNDS: usize = 8;
+/// circomlib's partial rounds for widths t = 2..=17.
+const PARTIAL_ROUNDS: [usize; 16] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68];
+/// Widths the circuit needs: Poseidon(1), Poseidon(2) and Poseidon(4).
+const WIDTHS: [usize; 3] = [2, 3, 5];
+
+/// One width's parameters: `constants[round * t + i]` is added to state
+/// element i before `round`'s S-boxes, and `mds` mixes the state after.
+pub struct Params {
+    pub partial_rounds: usize,
+    pub constants: Vec<Fr>,
+    pub mds: Vec<Vec<Fr>>,
+}
+
+/// The LFSR behind the reference parameters, after the 160 warm-up
+/// clocks, with its self-shrinking output.
+struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    fn new(t: usize, partial_rounds: usize) -> Self {
+        // field = 1 (prime), sbox = 0 (x^alpha), n = 254, then t, R_F, R_P
+        let fields = [(1, 2), (0, 4), (Fr::MODULUS_BIT_SIZE as usize, 12), (t, 12), (FULL_ROUNDS, 10), (partial_rounds, 10)];
+        let mut state = [true; 80];
+        let mut n = 0;
+        for (value, bits) in fields {
+            for i in (0..bits).rev() {
+                state[n] = (value >> i) & 1 == 1;
+                n += 1;
+            }
+        }
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+
+    fn clock(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.copy_within(1.., 0);
+        self.state[79] = bit;
+        bit
+    }
+
+    /// Bits come in pairs; a pair starting with 1 yields its second bit.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// `Fr::MODULUS_BIT_SIZE` bits, most significant first.
+    fn next_bits(&mut self) -> Vec<bool> {
+        (0..Fr::MODULUS_BIT_SIZE).map(|_| self.next_bit()).collect()
+    }
+
+    /// A round constant: out-of-range draws are rejected, not reduced.
+    fn next_constant(&mut self) -> Fr {
+        loop {
+            let bits = self.next_bits();
+            if let Some(x) = Fr::from_bigint(BigInteger::from_bits_be(&bits)) {
+                return x;
+            }
+        }
+    }
+
+    /// An MDS sample: reduced modulo the field, as `F(...)` does in Sage.
+    fn next_reduced(&mut self) -> Fr {
+        let mut bytes = [0u8; 32];
+        for (i, bit) in self.next_bits().into_iter().rev().enumerate() {
+            bytes[31 - i / 8] |= (bit as u8) << (i % 8);
+        }
+        Fr::from_be_bytes_mod_order(&bytes)
+    }
+}
+
+impl Params {
+    fn generate(t: usize) -> Self {
+        let partial_rounds = PARTIAL_ROUNDS[t - 2];
+        let mut grain = Grain::new(t, partial_rounds);
+        let constants = (0..(FULL_ROUNDS + partial_rounds) * t).map(|_| grain.next_constant()).collect();
+        // A Cauchy matrix 1/(x_i + y_j) over 2t distinct samples
+        let mds = loop {
+            let samples: Vec<Fr> = (0..2 * t).map(|_| grain.next_reduced()).collect();
+            let distinct = samples.iter().enumerate().all(|(i, x)| !samples[..i].contains(x));
+            let (xs, ys) = samples.split_at(t);
+            let rows: Option<Vec<Vec<Fr>>> =
+                xs.iter().map(|x| ys.iter().map(|y| (*x + y).inverse()).collect()).collect();
+            if let (true, Some(rows)) = (distinct, rows) {
+                break rows;
+            }
+        };
+        Params { partial_rounds, constants, mds }
+    }
+}
+
+/// The parameters for state width `t` (inputs + 1): 2, 3 or 5.
+pub fn params(t: usize) -> &'static Params {
+    static PARAMS: [OnceLock<Params>; WIDTHS.len()] = [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+    let slot = WIDTHS.iter().position(|w| *w == t).expect("no Poseidon width for this arity");
+    PARAMS[slot].get_or_init(|| Params::generate(t))
+}
+
+fn pow5(x: Fr) -> Fr {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// circomlib's `Poseidon(inputs.len())` for 1, 2 or 4 inputs.
+pub fn hash(inputs: &[Fr]) -> Fr {
+    let t = inputs.len() + 1;
+    let Params { partial_rounds, constants, mds } = params(t);
+    let mut state = vec![Fr::zero()];
+    state.extend_from_slice(inputs);
+    for round in 0..FULL_ROUNDS + *partial_rounds {
+        for (x, c) in state.iter_mut().zip(&constants[round * t..]) {
+            *x += c;
+        }
+        let full = round < FULL_ROUNDS / 2 || round >= FULL_ROUNDS / 2 + *partial_rounds;
+        if full {
+            state.iter_mut().for_each(|x| *x = pow5(*x));
+        } else {
+            state[0] = pow5(state[0]);
+        }
+        state = mds.iter().map(|row| row.iter().zip(&state).map(|(m, x)| *m * x).sum()).collect();
+    }
+    state[0]
+}
@@ -0,0 +1,108 @@
+//! A [`Backend`] that shells out to `snarkjs groth16 fullprove` with the
+//! circuit Wasm and zkey the `prover/` scripts build.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde_json::Value;
+
+use crate::{Backend, Error, Groth16Proof};
+
+#[derive(Clone, Debug)]
+pub struct Snarkjs {
+    /// The snarkjs executable, `snarkjs` on PATH by default.
+    pub command: PathBuf,
+    pub wasm: PathBuf,
+    pub zkey: PathBuf,
+}
+
+impl Snarkjs {
+    /// The artifacts under a `prover/` directory after `bun run
+    /// circuit:build` and `bun run circuit:keys`.
+    pub fn from_prover_dir(dir: &Path) -> Self {
+        Snarkjs {
+            command: "snarkjs".into(),
+            wasm: dir.join("build/pirate_cards_js/pirate_cards.wasm"),
+            zkey: dir.join("keys/pirate_cards_final.zkey"),
+        }
+    }
+}
+
+impl Backend for Snarkjs {
+    fn prove(&self, input: &Value) -> Result<(Groth16Proof, Vec<[u8; 32]>), Error> {
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        let run = RUNS.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("pirate-prover-{}-{}", std::process::id(), run));
+        std::fs::create_dir_all(&dir).map_err(|e| Error::Backend(e.to_string()))?;
+        let result = fullprove(self, input, &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+}
+
+fn fullprove(snarkjs: &Snarkjs, input: &Value, dir: &Path) -> Result<(Groth16Proof, Vec<[u8; 32]>), Error> {
+    let (input_path, proof_path, public_path) = (dir.join("input.json"), dir.join("proof.json"), dir.join("public.json"));
+    std::fs::write(&input_path, input.to_string()).map_err(|e| Error::Backend(e.to_string()))?;
+    let output = Command::new(&snarkjs.command)
+        .args(["groth16", "fullprove"])
+        .args([&input_path, &snarkjs.wasm, &snarkjs.zkey, &proof_path, &public_path])
+        .output()
+        .map_err(|e| Error::Backend(format!("cannot run {}: {}", snarkjs.command.display(), e)))?;
+    if !output.status.success() {
+        return Err(Error::Backend(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    let proof = Groth16Proof::from_snarkjs(&read_json(&proof_path)?)?;
+    let signals = read_json(&public_path)?
+        .as_array()
+        .ok_or_else(|| Error::Malformed("public.json is not an array".into()))?
+        .iter()
+        .map(decimal)
+        .collect::<Result<_, _>>()?;
+    Ok((proof, signals))
+}
+
+fn read_json(path: &Path) -> Result<Value, Error> {
+    let text = std::fs::read_to_string(path).map_err(|e| Error::Backend(e.to_string()))?;
+    serde_json::from_str(&text).map_err(|e| Error::Malformed(e.to_string()))
+}
+
+impl Groth16Proof {
+    /// From snarkjs' `proof.json`, swapping each G2 coordinate's c0/c1 into
+    /// the order the host expects.
+    pub fn from_snarkjs(proof: &Value) -> Result<Self, Error> {
+        let g1 = |p: &Value| -> Result<[u8; 64], Error> {
+            let mut out = [0u8; 64];
+            out[..32].copy_from_slice(&decimal(&p[0])?);
+            out[32..].copy_from_slice(&decimal(&p[1])?);
+            Ok(out)
+        };
+        let b = &proof["pi_b"];
+        let mut pi_b = [0u8; 128];
+        for (k, coord) in [&b[0][1], &b[0][0], &b[1][1], &b[1][0]].into_iter().enumerate() {
+            pi_b[32 * k..32 * (k + 1)].copy_from_slice(&decimal(coord)?);
+        }
+        Ok(Groth16Proof { pi_a: g1(&proof["pi_a"])?, pi_b, pi_c: g1(&proof["pi_c"])? })
+    }
+}
+
+/// A JSON decimal string below 2^256 as 32 big-endian bytes.
+pub fn decimal(value: &Value) -> Result<[u8; 32], Error> {
+    let text = value.as_str().ok_or_else(|| Error::Malformed(format!("expected a decimal string, got {}", value)))?;
+    let mut bytes = [0u8; 32];
+    for digit in text.bytes() {
+        if !digit.is_ascii_digit() {
+            return Err(Error::Malformed(format!("not a decimal number: {}", text)));
+        }
+        let mut carry = u32::from(digit - b'0');
+        for byte in bytes.iter_mut().rev() {
+            let v = u32::from(*byte) * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return Err(Error::Malformed(format!("{} does not fit in 32 bytes", text)));
+        }
+    }
+    Ok(bytes)
+}
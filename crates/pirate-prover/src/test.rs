@@ -0,0 +1,111 @@
+#![cfg(test)]
+
+use std::str::FromStr;
+
+use ark_bn254::Fr;
+use serde_json::{json, Value};
+
+use crate::snarkjs::decimal;
+use crate::witness::{bytes, field};
+use crate::*;
+
+fn seed(n: u64) -> [u8; 32] {
+    bytes(&Fr::from(n))
+}
+
+/// Echoes the circuit input's public signals, in `order`, with a dummy proof.
+struct Echo {
+    order: [&'static str; 9],
+}
+
+impl Backend for Echo {
+    fn prove(&self, input: &Value) -> Result<(Groth16Proof, Vec<[u8; 32]>), Error> {
+        let signals = self.order.iter().map(|name| decimal(&input[*name])).collect::<Result<_, _>>()?;
+        Ok((Groth16Proof { pi_a: [1; 64], pi_b: [2; 128], pi_c: [3; 64] }, signals))
+    }
+}
+
+#[test]
+fn test_poseidon_matches_circomlib() {
+    let f = |s: &str| Fr::from_str(s).unwrap();
+    let cases: [(&[u64], &str); 3] = [
+        (&[1], "18586133768512220936620570745912940619677854269274689475585506675881198879027"),
+        (&[1, 2], "7853200120776062878684798364095072458815029376092732009249414926327459813530"),
+        (&[1, 2, 3, 4], "18821383157269793795438455681495246036402687001665670618754263018637548127333"),
+    ];
+    for (inputs, expected) in cases {
+        let inputs: Vec<Fr> = inputs.iter().map(|x| Fr::from(*x)).collect();
+        assert_eq!(poseidon::hash(&inputs), f(expected));
+    }
+}
+
+#[test]
+fn test_witness_satisfies_the_circuit_checks() {
+    let witness = Witness::new(&seed(123456789), &seed(987654321), 1, &[0; 32]).unwrap();
+    let public = &witness.public_inputs;
+    assert_eq!(public.seed_commit1, bytes(&poseidon::hash(&[Fr::from(123456789u64)])));
+    assert_eq!((public.deck_size, public.suits), (seed(25), seed(3)));
+
+    // The deck is a permutation sorted by truncated weight, and each
+    // weight splits Poseidon(combined_seed, card)
+    assert!(pirate_engine::is_permutation(&witness.deck));
+    assert!(witness.trunc_weights.windows(2).all(|w| w[0] <= w[1]));
+    for i in 0..pirate_engine::DECK_SIZE {
+        let weight = poseidon::hash(&[witness.combined_seed, Fr::from(witness.deck[i] as u64)]);
+        let mut split = [0u8; 32];
+        split[..16].copy_from_slice(&witness.high_weights[i].to_be_bytes());
+        split[16..].copy_from_slice(&witness.trunc_weights[i].to_be_bytes());
+        assert_eq!(split, bytes(&weight));
+    }
+
+    let winner = witness.outcome.winner as u64;
+    assert_eq!(public.winner, seed(winner));
+    let input = witness.circuit_input();
+    assert_eq!(input["winner"], json!(winner.to_string()));
+    assert_eq!(input["deck"].as_array().unwrap().len(), 25);
+    assert_eq!(input["session_id"], json!("1"));
+}
+
+#[test]
+fn test_prove_checks_signal_order() {
+    let (seed1, seed2) = (seed(7), seed(8));
+    let proved = prove(&Echo { order: PUBLIC_INPUT_NAMES }, &seed1, &seed2, 3, &[0; 32]).unwrap();
+    assert_eq!(proved.public_inputs.seed1, seed1);
+    let payload = proved.payload();
+    assert_eq!(payload["proof"]["pi_a"], json!("01".repeat(64)));
+    assert_eq!(payload["public_inputs"]["suits"], json!(format!("{:064x}", 3)));
+
+    // A build that swapped two public inputs is caught before settlement
+    let mut swapped = PUBLIC_INPUT_NAMES;
+    swapped.swap(2, 3);
+    assert_eq!(prove(&Echo { order: swapped }, &seed1, &seed2, 3, &[0; 32]).unwrap_err(), Error::InputMismatch);
+
+    let mut too_big = [0xff; 32];
+    assert_eq!(Witness::new(&too_big, &seed2, 3, &[0; 32]).unwrap_err(), Error::NotInField("seed1"));
+    too_big[0] = 0;
+    assert!(field(&too_big, "beacon").is_ok());
+}
+
+#[test]
+fn test_snarkjs_proof_encoding() {
+    let proof = json!({
+        "pi_a": ["1", "2", "1"],
+        "pi_b": [["3", "4"], ["5", "6"], ["1", "0"]],
+        "pi_c": ["7", "8", "1"],
+        "protocol": "groth16",
+    });
+    let proof = Groth16Proof::from_snarkjs(&proof).unwrap();
+    assert_eq!((proof.pi_a[31], proof.pi_a[63], proof.pi_c[63]), (1, 2, 8));
+    // x_c1 || x_c0 || y_c1 || y_c0
+    assert_eq!([proof.pi_b[31], proof.pi_b[63], proof.pi_b[95], proof.pi_b[127]], [4, 3, 6, 5]);
+    assert!(Groth16Proof::from_snarkjs(&json!({ "pi_a": ["x", "2"] })).is_err());
+}
+
+#[test]
+fn test_off_chain_shuffle_matches_the_contract() {
+    assert_eq!(hex(&keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+    // The vector pirate-cards' derive_deck view returns for these seeds
+    let classic = [5, 14, 1, 24, 10, 22, 8, 0, 11, 13, 21, 6, 12, 2, 23, 17, 16, 19, 9, 3, 15, 20, 4, 7, 18];
+    assert_eq!(derive_deck(&[0x11; 32], &[0x22; 32], 0, 25), classic);
+    assert_eq!(derive_deck(&[0x11; 32], &[0x22; 32], 0, 0), Vec::<u32>::new());
+}
@@ -0,0 +1,92 @@
+//! The circuit's inputs for one game, public and private, computed as
+//! `circuits/pirate_cards.circom` checks them.
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use pirate_engine::{Deal, Outcome, Player, Prng, DECK_SIZE, SUITS};
+use serde_json::{json, Value};
+
+use crate::{poseidon, Error, PublicInputs};
+
+#[derive(Clone, Debug)]
+pub struct Witness {
+    pub public_inputs: PublicInputs,
+    /// Poseidon(seed1, seed2, session_id, beacon).
+    pub combined_seed: Fr,
+    pub deck: [u8; DECK_SIZE],
+    /// Low and high 128 bits of Poseidon(combined_seed, deck[i]).
+    pub trunc_weights: [u128; DECK_SIZE],
+    pub high_weights: [u128; DECK_SIZE],
+    pub outcome: Outcome,
+}
+
+impl Witness {
+    pub fn new(seed1: &[u8; 32], seed2: &[u8; 32], session_id: u32, beacon: &[u8; 32]) -> Result<Self, Error> {
+        let seed1_fr = field(seed1, "seed1")?;
+        let seed2_fr = field(seed2, "seed2")?;
+        let beacon_fr = field(beacon, "beacon")?;
+        let combined_seed = poseidon::hash(&[seed1_fr, seed2_fr, Fr::from(session_id), beacon_fr]);
+
+        let stream = Prng::new(|k: u32| bytes(&poseidon::hash(&[combined_seed, Fr::from(k)])));
+        let Deal { deck, coin, weights } = pirate_engine::deal(stream);
+        let outcome = pirate_engine::play(&deck, coin);
+
+        let winner = match outcome.winner {
+            Player::One => 1u64,
+            Player::Two => 2,
+        };
+        let public_inputs = PublicInputs {
+            seed_commit1: bytes(&poseidon::hash(&[seed1_fr])),
+            seed_commit2: bytes(&poseidon::hash(&[seed2_fr])),
+            seed1: *seed1,
+            seed2: *seed2,
+            session_id: bytes(&Fr::from(session_id)),
+            winner: bytes(&Fr::from(winner)),
+            beacon: *beacon,
+            deck_size: bytes(&Fr::from(DECK_SIZE as u64)),
+            suits: bytes(&Fr::from(SUITS)),
+        };
+        Ok(Witness {
+            public_inputs,
+            combined_seed,
+            deck,
+            trunc_weights: core::array::from_fn(|i| split(&weights[deck[i] as usize]).0),
+            high_weights: core::array::from_fn(|i| split(&weights[deck[i] as usize]).1),
+            outcome,
+        })
+    }
+
+    /// The circuit's `input.json`: every signal as a decimal string.
+    pub fn circuit_input(&self) -> Value {
+        let decimal = |x: &[u8; 32]| Fr::from_be_bytes_mod_order(x).to_string();
+        let mut input = serde_json::Map::new();
+        for (name, x) in crate::PUBLIC_INPUT_NAMES.iter().zip(self.public_inputs.to_array()) {
+            input.insert(name.to_string(), decimal(&x).into());
+        }
+        let strings = |xs: &[u128]| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        input.insert("deck".into(), json!(self.deck.iter().map(|c| c.to_string()).collect::<Vec<_>>()));
+        input.insert("trunc_weights".into(), json!(strings(&self.trunc_weights)));
+        input.insert("high_weights".into(), json!(strings(&self.high_weights)));
+        Value::Object(input)
+    }
+}
+
+/// A canonical big-endian scalar, or `NotInField(name)`.
+pub fn field(bytes: &[u8; 32], name: &'static str) -> Result<Fr, Error> {
+    let x = Fr::from_be_bytes_mod_order(bytes);
+    if &self::bytes(&x) == bytes {
+        Ok(x)
+    } else {
+        Err(Error::NotInField(name))
+    }
+}
+
+/// `x` as 32 big-endian bytes.
+pub fn bytes(x: &Fr) -> [u8; 32] {
+    x.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+/// (low 128 bits, the rest) of big-endian `be`.
+fn split(be: &[u8; 32]) -> (u128, u128) {
+    (u128::from_be_bytes(be[16..].try_into().unwrap()), u128::from_be_bytes(be[..16].try_into().unwrap()))
+}
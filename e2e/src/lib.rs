@@ -0,0 +1,198 @@
+//! Helpers for the end-to-end tests in `tests/`, which run the contracts on
+//! a real node through the Stellar CLI rather than the SDK's test host.
+//! Nothing here touches the network until a test calls it.
+
+#[cfg(test)]
+mod test;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{json, Value};
+
+/// Defaults match `stellar container start local` (the quickstart image).
+pub const DEFAULT_RPC_URL: &str = "http://localhost:8000/rpc";
+pub const DEFAULT_PASSPHRASE: &str = "Standalone Network ; February 2017";
+
+/// The repository root, one level above this crate.
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+/// Where `stellar contract build` leaves a contract's Wasm.
+pub fn wasm_path(crate_name: &str) -> PathBuf {
+    repo_root()
+        .join("target/wasm32-unknown-unknown/release")
+        .join(format!("{}.wasm", crate_name.replace('-', "_")))
+}
+
+/// The node under test, read from `E2E_RPC_URL` and
+/// `E2E_NETWORK_PASSPHRASE`. Every CLI call names it explicitly, so no
+/// `stellar network add` is needed.
+#[derive(Clone, Debug)]
+pub struct Network {
+    pub rpc_url: String,
+    pub passphrase: String,
+}
+
+impl Network {
+    pub fn from_env() -> Self {
+        Network {
+            rpc_url: std::env::var("E2E_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.into()),
+            passphrase: std::env::var("E2E_NETWORK_PASSPHRASE")
+                .unwrap_or_else(|_| DEFAULT_PASSPHRASE.into()),
+        }
+    }
+
+    /// Run `stellar <args> --rpc-url .. --network-passphrase ..` and return
+    /// its trimmed stdout, panicking with stderr if it fails.
+    pub fn stellar(&self, args: &[&str]) -> String {
+        let (head, tail) = match args.iter().position(|a| *a == "--") {
+            Some(i) => args.split_at(i),
+            None => (args, &[][..]),
+        };
+        let output = Command::new("stellar")
+            .args(head)
+            .args(["--rpc-url", &self.rpc_url, "--network-passphrase", &self.passphrase])
+            .args(tail)
+            .output()
+            .expect("the stellar CLI must be on PATH");
+        if !output.status.success() {
+            panic!(
+                "stellar {} failed:\n{}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    /// Create (or reuse) the CLI identity `name`, fund it from the node's
+    /// friendbot and return its address.
+    pub fn funded_identity(&self, name: &str) -> String {
+        self.stellar(&["keys", "generate", name, "--fund", "--overwrite"]);
+        let output = Command::new("stellar").args(["keys", "address", name]).output().unwrap();
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    /// Deploy `crate_name`'s built Wasm from `source`, passing
+    /// `constructor_args` (`--name value` pairs) to `__constructor`.
+    pub fn deploy(&self, crate_name: &str, source: &str, constructor_args: &[&str]) -> String {
+        let wasm = wasm_path(crate_name);
+        assert!(wasm.exists(), "{} is missing; run `bun run build` first", wasm.display());
+        let mut args = vec!["contract", "deploy", "--wasm", wasm.to_str().unwrap(), "--source-account", source];
+        if !constructor_args.is_empty() {
+            args.push("--");
+            args.extend_from_slice(constructor_args);
+        }
+        self.stellar(&args)
+    }
+
+    /// Invoke `function` on `contract` as `source` and parse the JSON it
+    /// returns (`null` for functions returning nothing).
+    pub fn invoke(&self, contract: &str, source: &str, function: &str, args: &[&str]) -> Value {
+        let mut all = vec!["contract", "invoke", "--id", contract, "--source-account", source, "--", function];
+        all.extend_from_slice(args);
+        let out = self.stellar(&all);
+        if out.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&out).unwrap_or(Value::String(out))
+        }
+    }
+
+    /// The latest closed ledger, from RPC `getLatestLedger`.
+    pub fn latest_ledger(&self) -> u32 {
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"getLatestLedger"}"#;
+        let output = Command::new("curl")
+            .args(["-s", "-X", "POST", "-H", "Content-Type: application/json", "-d", body, &self.rpc_url])
+            .output()
+            .expect("curl must be on PATH");
+        let response: Value = serde_json::from_slice(&output.stdout).expect("RPC did not return JSON");
+        response["result"]["sequence"].as_u64().expect("no ledger sequence in response") as u32
+    }
+
+    /// Events `contract` emitted from `start_ledger` on, as the CLI's JSON
+    /// output (pretty-printed, one object per event).
+    pub fn events(&self, contract: &str, start_ledger: u32) -> String {
+        let start = start_ledger.to_string();
+        self.stellar(&["events", "--id", contract, "--start-ledger", &start, "--output", "json"])
+    }
+}
+
+/// Run `prover/scripts/prove.js` for the given decimal seeds and return
+/// the `proof_payload.json` it writes. Needs the circuit built and keys
+/// generated (`bun run circuit:build`, `bun run circuit:keys`).
+pub fn prove(seed1: &str, seed2: &str, session_id: u32) -> Value {
+    let prover = repo_root().join("prover");
+    let status = Command::new("node")
+        .current_dir(&prover)
+        .args(["scripts/prove.js", seed1, seed2, &session_id.to_string()])
+        .status()
+        .expect("node must be on PATH");
+    assert!(status.success(), "prove.js failed");
+    read_json(&prover.join("build/proof_payload.json"))
+}
+
+/// The snarkjs-exported verification key from `bun run circuit:keys`.
+pub fn snarkjs_vk() -> Value {
+    read_json(&repo_root().join("prover/keys/verification_key.json"))
+}
+
+fn read_json(path: &Path) -> Value {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    serde_json::from_str(&text).unwrap()
+}
+
+/// A snarkjs verification key as the contract's `VerificationKey`, in the
+/// CLI's JSON argument form. Points are encoded as prove.js encodes the
+/// proof, so the two always agree.
+pub fn contract_vk(vk: &Value) -> Value {
+    let ic: Vec<Value> = vk["IC"].as_array().unwrap().iter().map(|p| json!(g1_hex(p))).collect();
+    json!({
+        "alpha_g1": g1_hex(&vk["vk_alpha_1"]),
+        "beta_g2": g2_hex(&vk["vk_beta_2"]),
+        "gamma_g2": g2_hex(&vk["vk_gamma_2"]),
+        "delta_g2": g2_hex(&vk["vk_delta_2"]),
+        "ic": ic,
+        "negated_b": false,
+    })
+}
+
+/// be(x) || be(y) from snarkjs' `[x, y, "1"]`.
+pub fn g1_hex(point: &Value) -> String {
+    format!("{}{}", field_hex(&point[0]), field_hex(&point[1]))
+}
+
+/// be(X_c1) || be(X_c0) || be(Y_c1) || be(Y_c0) from snarkjs'
+/// `[[x_c0, x_c1], [y_c0, y_c1], ..]`, the order the host expects.
+pub fn g2_hex(point: &Value) -> String {
+    format!(
+        "{}{}{}{}",
+        field_hex(&point[0][1]),
+        field_hex(&point[0][0]),
+        field_hex(&point[1][1]),
+        field_hex(&point[1][0])
+    )
+}
+
+fn field_hex(value: &Value) -> String {
+    decimal_to_hex32(value.as_str().expect("field elements are decimal strings"))
+}
+
+/// A decimal string below 2^256 as 64 big-endian hex digits.
+pub fn decimal_to_hex32(decimal: &str) -> String {
+    let mut bytes = [0u8; 32];
+    for digit in decimal.bytes() {
+        assert!(digit.is_ascii_digit(), "not a decimal number: {}", decimal);
+        let mut carry = u32::from(digit - b'0');
+        for byte in bytes.iter_mut().rev() {
+            let v = u32::from(*byte) * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        assert!(carry == 0, "{} does not fit in 32 bytes", decimal);
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,20 @@
+#![cfg(test)]
+
+use serde_json::json;
+
+use crate::{decimal_to_hex32, g1_hex, g2_hex};
+
+#[test]
+fn test_snarkjs_points_encode_as_prove_js_does() {
+    assert_eq!(decimal_to_hex32("0"), "00".repeat(32));
+    assert_eq!(decimal_to_hex32("258"), format!("{}0102", "00".repeat(30)));
+    // BN254 base field modulus
+    let p = "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+    assert_eq!(decimal_to_hex32(p), "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47");
+
+    let word = |n: u8| format!("{}{:02x}", "00".repeat(31), n);
+    assert_eq!(g1_hex(&json!(["1", "2", "1"])), word(1) + &word(2));
+    // Each G2 coordinate is written c1 then c0
+    let g2 = json!([["1", "2"], ["3", "4"], ["1", "0"]]);
+    assert_eq!(g2_hex(&g2), word(2) + &word(1) + &word(4) + &word(3));
+}
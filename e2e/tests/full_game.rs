@@ -0,0 +1,116 @@
+//! A full game on a live node: deploy mock-ohloss and pirate-cards, create,
+//! join, reveal, prove with the real circuit and settle, then check the
+//! final state and the events the contract emitted.
+//!
+//! Run with `cargo test -p e2e --features e2e` once a node is up (see the
+//! README); it is skipped entirely without the feature.
+
+#![cfg(feature = "e2e")]
+
+use e2e::{contract_vk, prove, snarkjs_vk, Network};
+use serde_json::json;
+
+const SEED1: &str = "123456789";
+const SEED2: &str = "987654321";
+const SESSION_ID: u32 = 1;
+
+#[test]
+fn test_full_game_with_real_proof() {
+    let net = Network::from_env();
+    let admin = net.funded_identity("e2e-admin");
+    let p1 = net.funded_identity("e2e-player1");
+    let p2 = net.funded_identity("e2e-player2");
+
+    // Fresh instances every run, so SESSION_ID is never taken
+    let ohloss = net.deploy("mock-ohloss", "e2e-admin", &[]);
+    let vk = contract_vk(&snarkjs_vk()).to_string();
+    let contract = net.deploy(
+        "pirate-cards",
+        "e2e-admin",
+        &["--admin", &admin, "--ohloss", &ohloss, "--vk", &vk],
+    );
+    let start = net.latest_ledger();
+
+    // Proving first gives us the Poseidon commitments to open the game with
+    let payload = prove(SEED1, SEED2, SESSION_ID);
+    let inputs = &payload["public_inputs"];
+    let hex = |field: &str| inputs[field].as_str().unwrap().to_string();
+    let session = SESSION_ID.to_string();
+
+    let options = json!({
+        "bounty_token": null,
+        "bounty_amount": "0",
+        "commit_scheme": "Poseidon",
+        "use_beacon": false,
+        "no_draws": false,
+        "wager_token": null,
+        "wager_amount": "0",
+        "opponent_stake": "0",
+        "escrow_wager": false,
+        "memo": "00".repeat(32),
+        "variant": 0,
+        "sponsor": null,
+        "sponsor_token": null,
+        "sponsor_amount": "0",
+        "referrer": null,
+        "observer": null,
+        "result_sink": null,
+        "unrated": false,
+        "require_approval": false,
+        "reveal_delegate": null,
+        "metadata": "00".repeat(32),
+        "rematch_of": null,
+        "terms": "00".repeat(32),
+        "starts_at": 0,
+    })
+    .to_string();
+    net.invoke(&contract, "e2e-player1", "create_game", &[
+        "--session_id", &session,
+        "--player1", &p1,
+        "--seed_commit1", &hex("seed_commit1"),
+        "--options", &options,
+    ]);
+    net.invoke(&contract, "e2e-player2", "join_game", &[
+        "--session_id", &session,
+        "--player2", &p2,
+        "--seed_commit2", &hex("seed_commit2"),
+    ]);
+    for (source, player, seed) in [("e2e-player1", &p1, "seed1"), ("e2e-player2", &p2, "seed2")] {
+        net.invoke(&contract, source, "reveal_seed", &[
+            "--session_id", &session,
+            "--player", player,
+            "--seed", &hex(seed),
+            "--opening", "\"Plain\"",
+        ]);
+    }
+
+    let proof = payload["proof"].to_string();
+    let pub_inputs = inputs.to_string();
+    let winner = net.invoke(&contract, "e2e-admin", "settle_game", &[
+        "--session_id", &session,
+        "--proof", &proof,
+        "--pub_inputs", &pub_inputs,
+        "--settler", &admin,
+    ]);
+
+    // The proven winner field is 1 or 2; the circuit never proves a draw
+    let expected = match hex("winner").as_str() {
+        w if w.ends_with("01") => &p1,
+        w if w.ends_with("02") => &p2,
+        w => panic!("unexpected winner field {}", w),
+    };
+    assert_eq!(winner, json!(expected));
+
+    let game = net.invoke(&contract, "e2e-admin", "get_game", &["--session_id", &session]);
+    assert_eq!(game["phase"], json!(3)); // Settled
+    assert_eq!(game["winner_address"], json!(expected));
+    assert_eq!(game["ohloss"], json!(ohloss));
+    assert_eq!(game["result_reported"], json!(true));
+    let report = net.invoke(&ohloss, "e2e-admin", "last_report", &["--session_id", &session]);
+    assert!(report.is_string(), "Ohloss recorded no result: {}", report);
+
+    let events = net.events(&contract, start);
+    for topic in ["game_created", "game_joined", "seed_revealed", "game_settled"] {
+        assert!(events.contains(topic), "no {} event in:\n{}", topic, events);
+    }
+}
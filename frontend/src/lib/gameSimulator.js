@@ -41,13 +41,14 @@ function rpsWinner(type1, type2) {
  * @param {BigInt} seed1 - Player 1's seed
  * @param {BigInt} seed2 - Player 2's seed
  * @param {BigInt} sessionId - Game session ID
+ * @param {BigInt} beacon - Beacon field element (0 for games without a beacon)
  * @returns {{ deck: number[], rounds: object[], winner: number, endReason: string }}
  */
-export async function simulateFullGame(seed1, seed2, sessionId) {
+export async function simulateFullGame(seed1, seed2, sessionId, beacon = 0n) {
   const poseidon = await getPoseidon();
   const F = poseidon.F;
 
-  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId]));
+  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId, beacon]));
 
   // Compute card weights, sort by truncated 128-bit values (matches circuit)
   const mask128 = (1n << 128n) - 1n;
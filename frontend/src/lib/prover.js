@@ -12,6 +12,7 @@ const WASM_URL = "/circuits/pirate_cards.wasm";
 const ZKEY_URL = "/keys/pirate_cards_final.zkey";
 
 const N_CARDS = 25;
+const N_SUITS = 3;
 
 let poseidonInstance = null;
 
@@ -60,9 +61,10 @@ export function generateRandomSeed() {
  * @param {BigInt} seed1 - Player 1's seed
  * @param {BigInt} seed2 - Player 2's seed
  * @param {BigInt} sessionId - Game session ID
+ * @param {BigInt} beacon - Beacon field element (0 for games without a beacon)
  * @returns {{ proof, publicInputs, gameLog }} Hex-encoded proof + game simulation log
  */
-export async function generateProof(seed1, seed2, sessionId) {
+export async function generateProof(seed1, seed2, sessionId, beacon = 0n) {
   const poseidon = await getPoseidon();
   const F = poseidon.F;
 
@@ -71,7 +73,7 @@ export async function generateProof(seed1, seed2, sessionId) {
   const seedCommit2 = F.toObject(poseidon([seed2]));
 
   // Combined seed
-  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId]));
+  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId, beacon]));
 
   // Compute card weights with truncated values
   const mask128 = (1n << 128n) - 1n;
@@ -145,6 +147,9 @@ export async function generateProof(seed1, seed2, sessionId) {
     seed2: seed2.toString(),
     session_id: sessionId.toString(),
     winner: winner.toString(),
+    beacon: beacon.toString(),
+    deck_size: N_CARDS.toString(),
+    suits: N_SUITS.toString(),
     deck: deck.map(c => c.toString()),
     trunc_weights: truncWeights.map(w => w.toString()),
     high_weights: highWeights.map(w => w.toString()),
@@ -171,6 +176,9 @@ export async function generateProof(seed1, seed2, sessionId) {
       seed2: bigintToHex32(publicSignals[3]),
       session_id: bigintToHex32(publicSignals[4]),
       winner: bigintToHex32(publicSignals[5]),
+      beacon: bigintToHex32(publicSignals[6]),
+      deck_size: bigintToHex32(publicSignals[7]),
+      suits: bigintToHex32(publicSignals[8]),
     },
     gameLog,
     winner,
@@ -86,14 +86,78 @@ async function submitTx(contractId, method, args, publicKey) {
   return { success: false, txHash, error: `Send failed: ${sendResponse.status}` };
 }
 
+/**
+ * Encode a contracttype struct as ScvMap. Soroban requires the map keys to
+ * be sorted, so entries are sorted by field name here.
+ */
+function structToScVal(fields) {
+  return xdr.ScVal.scvMap(
+    Object.keys(fields)
+      .sort()
+      .map((key) => new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(key), val: fields[key] }))
+  );
+}
+
+/**
+ * Encode GameOptions. All options are optional; omitted ones take the
+ * contract's "not set" value.
+ */
+function gameOptionsToScVal(options = {}) {
+  return structToScVal({
+    bounty_token: options.bountyToken
+      ? new Address(options.bountyToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    bounty_amount: nativeToScVal(BigInt(options.bountyAmount ?? 0), { type: "i128" }),
+    commit_scheme: nativeToScVal(options.commitScheme ?? 0, { type: "u32" }),
+    use_beacon: xdr.ScVal.scvBool(Boolean(options.useBeacon)),
+    no_draws: xdr.ScVal.scvBool(Boolean(options.noDraws)),
+    wager_token: options.wagerToken
+      ? new Address(options.wagerToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    wager_amount: nativeToScVal(BigInt(options.wagerAmount ?? 0), { type: "i128" }),
+    opponent_stake: nativeToScVal(BigInt(options.opponentStake ?? 0), { type: "i128" }),
+    escrow_wager: xdr.ScVal.scvBool(Boolean(options.escrowWager)),
+    memo: nativeToScVal(Buffer.from(options.memoHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    variant: nativeToScVal(options.variant ?? 0, { type: "u32" }),
+    sponsor: options.sponsor
+      ? new Address(options.sponsor).toScVal()
+      : xdr.ScVal.scvVoid(),
+    sponsor_token: options.sponsorToken
+      ? new Address(options.sponsorToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    sponsor_amount: nativeToScVal(BigInt(options.sponsorAmount ?? 0), { type: "i128" }),
+    referrer: options.referrerHex
+      ? nativeToScVal(Buffer.from(options.referrerHex, "hex"), { type: "bytes" })
+      : xdr.ScVal.scvVoid(),
+    observer: options.observer
+      ? new Address(options.observer).toScVal()
+      : xdr.ScVal.scvVoid(),
+    result_sink: options.resultSink
+      ? new Address(options.resultSink).toScVal()
+      : xdr.ScVal.scvVoid(),
+    unrated: xdr.ScVal.scvBool(Boolean(options.unrated)),
+    require_approval: xdr.ScVal.scvBool(Boolean(options.requireApproval)),
+    reveal_delegate: options.revealDelegate
+      ? new Address(options.revealDelegate).toScVal()
+      : xdr.ScVal.scvVoid(),
+    metadata: nativeToScVal(Buffer.from(options.metadataHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    rematch_of: options.rematchOf != null
+      ? nativeToScVal(options.rematchOf, { type: "u32" })
+      : xdr.ScVal.scvVoid(),
+    terms: nativeToScVal(Buffer.from(options.termsHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    starts_at: nativeToScVal(options.startsAt ?? 0, { type: "u32" }),
+  });
+}
+
 /**
  * Create an open game: commit P1's seed. Anyone can join with the session ID.
  */
-export async function createGame(contractId, sessionId, player1, seedCommitHex, publicKey) {
+export async function createGame(contractId, sessionId, player1, seedCommitHex, publicKey, options = {}) {
   const args = [
     nativeToScVal(sessionId, { type: "u32" }),
     new Address(player1).toScVal(),
     nativeToScVal(Buffer.from(seedCommitHex, "hex"), { type: "bytes" }),
+    gameOptionsToScVal(options),
   ];
   return submitTx(contractId, "create_game", args, publicKey);
 }
@@ -110,20 +174,34 @@ export async function joinGame(contractId, sessionId, seedCommitHex, publicKey)
   return submitTx(contractId, "join_game", args, publicKey);
 }
 
+/**
+ * Encode a SeedOpening: Plain for Poseidon games, Salted(salt) for salted
+ * games, DualHash(innerCommit) for dual-hash games.
+ */
+function seedOpeningToScVal(openingHex, kind = "Salted") {
+  if (!openingHex) return xdr.ScVal.scvVec([xdr.ScVal.scvSymbol("Plain")]);
+  return xdr.ScVal.scvVec([
+    xdr.ScVal.scvSymbol(kind),
+    nativeToScVal(Buffer.from(openingHex, "hex"), { type: "bytes" }),
+  ]);
+}
+
 /**
  * Reveal a seed on-chain.
  */
-export async function revealSeed(contractId, sessionId, seedHex, publicKey) {
+export async function revealSeed(contractId, sessionId, seedHex, publicKey, openingHex = null, openingKind = "Salted") {
   const args = [
     nativeToScVal(sessionId, { type: "u32" }),
     new Address(publicKey).toScVal(),
     nativeToScVal(Buffer.from(seedHex, "hex"), { type: "bytes" }),
+    seedOpeningToScVal(openingHex, openingKind),
   ];
   return submitTx(contractId, "reveal_seed", args, publicKey);
 }
 
 /**
- * Settle a game: submit ZK proof.
+ * Settle a game: submit ZK proof. Any settlement bounty is paid to the
+ * submitting account.
  */
 export async function settleGame(contractId, sessionId, proof, pubInputs, publicKey) {
   // Encode Groth16Proof as ScvMap (alphabetically sorted keys)
@@ -144,6 +222,14 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, public
 
   // Encode PublicInputs as ScvMap (alphabetically sorted keys)
   const pubInputsVal = xdr.ScVal.scvMap([
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("beacon"),
+      val: nativeToScVal(Buffer.from(pubInputs.beacon ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("deck_size"),
+      val: nativeToScVal(Buffer.from(pubInputs.deck_size, "hex"), { type: "bytes" }),
+    }),
     new xdr.ScMapEntry({
       key: xdr.ScVal.scvSymbol("seed1"),
       val: nativeToScVal(Buffer.from(pubInputs.seed1, "hex"), { type: "bytes" }),
@@ -164,6 +250,10 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, public
       key: xdr.ScVal.scvSymbol("session_id"),
       val: nativeToScVal(Buffer.from(pubInputs.session_id, "hex"), { type: "bytes" }),
     }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("suits"),
+      val: nativeToScVal(Buffer.from(pubInputs.suits, "hex"), { type: "bytes" }),
+    }),
     new xdr.ScMapEntry({
       key: xdr.ScVal.scvSymbol("winner"),
       val: nativeToScVal(Buffer.from(pubInputs.winner, "hex"), { type: "bytes" }),
@@ -174,6 +264,7 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, public
     nativeToScVal(sessionId, { type: "u32" }),
     proofVal,
     pubInputsVal,
+    new Address(publicKey).toScVal(),
   ];
   return submitTx(contractId, "settle_game", args, publicKey);
 }
@@ -201,6 +292,31 @@ export async function getGame(contractId, sessionId, publicKey) {
   return null;
 }
 
+/**
+ * Query several games in one simulation. Returns an array aligned with
+ * `sessionIds`, holding the raw ScVal game or null for unknown sessions.
+ */
+export async function getGames(contractId, sessionIds, publicKey) {
+  const server = new rpc.Server(RPC_URL);
+  const contract = new Contract(contractId);
+  const account = await server.getAccount(publicKey);
+
+  const ids = xdr.ScVal.scvVec(sessionIds.map((id) => nativeToScVal(id, { type: "u32" })));
+  const tx = new TransactionBuilder(account, {
+    fee: BASE_FEE,
+    networkPassphrase: NETWORK_PASSPHRASE,
+  })
+    .addOperation(contract.call("get_games", ids))
+    .setTimeout(30)
+    .build();
+
+  const simResponse = await server.simulateTransaction(tx);
+  if (!simResponse.result) return sessionIds.map(() => null);
+  return simResponse.result.retval
+    .vec()
+    .map((entry) => (entry.switch() === xdr.ScValType.scvVoid() ? null : entry));
+}
+
 /**
  * Query game state and return a parsed JS object.
  * Returns null if game not found.
@@ -223,6 +339,8 @@ export async function getGameParsed(contractId, sessionId, publicKey) {
     player2: native.player2,
     phase: Number(native.phase),
     winner: Number(native.winner),
+    winnerAddress: native.winner_address ?? null,
+    expiresAt: Number(native.expires_at),
     seed1Hex: toHex(native.seed1),
     seed2Hex: toHex(native.seed2),
     seed1Revealed: !isZero(native.seed1),
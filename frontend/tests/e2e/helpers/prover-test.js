@@ -16,6 +16,7 @@ const WASM_PATH = path.join(PROJECT_ROOT, "public", "circuits", "pirate_cards.wa
 const ZKEY_PATH = path.join(PROJECT_ROOT, "public", "keys", "pirate_cards_final.zkey");
 
 const N_CARDS = 25;
+const N_SUITS = 3;
 
 let poseidonInstance = null;
 
@@ -62,7 +63,7 @@ export async function computeSeedCommitment(seedBigint) {
  * Generate a Groth16 proof for the pirate cards game.
  * Uses filesystem paths for WASM and zkey (Node.js compatible).
  */
-export async function generateProof(seed1, seed2, sessionId) {
+export async function generateProof(seed1, seed2, sessionId, beacon = 0n) {
   const poseidon = await getPoseidon();
   const F = poseidon.F;
 
@@ -71,7 +72,7 @@ export async function generateProof(seed1, seed2, sessionId) {
   const seedCommit2 = F.toObject(poseidon([seed2]));
 
   // Combined seed
-  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId]));
+  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId, beacon]));
 
   // Compute card weights with truncated values
   const mask128 = (1n << 128n) - 1n;
@@ -145,6 +146,9 @@ export async function generateProof(seed1, seed2, sessionId) {
     seed2: seed2.toString(),
     session_id: sessionId.toString(),
     winner: winner.toString(),
+    beacon: beacon.toString(),
+    deck_size: N_CARDS.toString(),
+    suits: N_SUITS.toString(),
     deck: deck.map(c => c.toString()),
     trunc_weights: truncWeights.map(w => w.toString()),
     high_weights: highWeights.map(w => w.toString()),
@@ -171,6 +175,9 @@ export async function generateProof(seed1, seed2, sessionId) {
       seed2: bigintToHex32(publicSignals[3]),
       session_id: bigintToHex32(publicSignals[4]),
       winner: bigintToHex32(publicSignals[5]),
+      beacon: bigintToHex32(publicSignals[6]),
+      deck_size: bigintToHex32(publicSignals[7]),
+      suits: bigintToHex32(publicSignals[8]),
     },
     gameLog,
     winner,
@@ -181,11 +188,11 @@ export async function generateProof(seed1, seed2, sessionId) {
  * Simulate the full game without generating a proof.
  * Used for cross-verification.
  */
-export async function simulateFullGame(seed1, seed2, sessionId) {
+export async function simulateFullGame(seed1, seed2, sessionId, beacon = 0n) {
   const poseidon = await getPoseidon();
   const F = poseidon.F;
 
-  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId]));
+  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId, beacon]));
 
   const mask128 = (1n << 128n) - 1n;
   const weights = [];
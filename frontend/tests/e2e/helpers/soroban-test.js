@@ -97,14 +97,76 @@ export async function fundAccount(publicKey) {
   return { funded: true };
 }
 
+/**
+ * Encode a contracttype struct as ScvMap with sorted keys.
+ */
+function structToScVal(fields) {
+  return xdr.ScVal.scvMap(
+    Object.keys(fields)
+      .sort()
+      .map((key) => new xdr.ScMapEntry({ key: xdr.ScVal.scvSymbol(key), val: fields[key] }))
+  );
+}
+
+/**
+ * Encode GameOptions (mirrors src/lib/soroban.js).
+ */
+function gameOptionsToScVal(options = {}) {
+  return structToScVal({
+    bounty_token: options.bountyToken
+      ? new Address(options.bountyToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    bounty_amount: nativeToScVal(BigInt(options.bountyAmount ?? 0), { type: "i128" }),
+    commit_scheme: nativeToScVal(options.commitScheme ?? 0, { type: "u32" }),
+    use_beacon: xdr.ScVal.scvBool(Boolean(options.useBeacon)),
+    no_draws: xdr.ScVal.scvBool(Boolean(options.noDraws)),
+    wager_token: options.wagerToken
+      ? new Address(options.wagerToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    wager_amount: nativeToScVal(BigInt(options.wagerAmount ?? 0), { type: "i128" }),
+    opponent_stake: nativeToScVal(BigInt(options.opponentStake ?? 0), { type: "i128" }),
+    escrow_wager: xdr.ScVal.scvBool(Boolean(options.escrowWager)),
+    memo: nativeToScVal(Buffer.from(options.memoHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    variant: nativeToScVal(options.variant ?? 0, { type: "u32" }),
+    sponsor: options.sponsor
+      ? new Address(options.sponsor).toScVal()
+      : xdr.ScVal.scvVoid(),
+    sponsor_token: options.sponsorToken
+      ? new Address(options.sponsorToken).toScVal()
+      : xdr.ScVal.scvVoid(),
+    sponsor_amount: nativeToScVal(BigInt(options.sponsorAmount ?? 0), { type: "i128" }),
+    referrer: options.referrerHex
+      ? nativeToScVal(Buffer.from(options.referrerHex, "hex"), { type: "bytes" })
+      : xdr.ScVal.scvVoid(),
+    observer: options.observer
+      ? new Address(options.observer).toScVal()
+      : xdr.ScVal.scvVoid(),
+    result_sink: options.resultSink
+      ? new Address(options.resultSink).toScVal()
+      : xdr.ScVal.scvVoid(),
+    unrated: xdr.ScVal.scvBool(Boolean(options.unrated)),
+    require_approval: xdr.ScVal.scvBool(Boolean(options.requireApproval)),
+    reveal_delegate: options.revealDelegate
+      ? new Address(options.revealDelegate).toScVal()
+      : xdr.ScVal.scvVoid(),
+    metadata: nativeToScVal(Buffer.from(options.metadataHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    rematch_of: options.rematchOf != null
+      ? nativeToScVal(options.rematchOf, { type: "u32" })
+      : xdr.ScVal.scvVoid(),
+    terms: nativeToScVal(Buffer.from(options.termsHex ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    starts_at: nativeToScVal(options.startsAt ?? 0, { type: "u32" }),
+  });
+}
+
 /**
  * Create an open game: commit P1's seed.
  */
-export async function createGame(contractId, sessionId, player1PublicKey, seedCommitHex, keypair) {
+export async function createGame(contractId, sessionId, player1PublicKey, seedCommitHex, keypair, options = {}) {
   const args = [
     nativeToScVal(sessionId, { type: "u32" }),
     new Address(player1PublicKey).toScVal(),
     nativeToScVal(Buffer.from(seedCommitHex, "hex"), { type: "bytes" }),
+    gameOptionsToScVal(options),
   ];
   return submitTx(contractId, "create_game", args, keypair);
 }
@@ -121,14 +183,27 @@ export async function joinGame(contractId, sessionId, seedCommitHex, keypair) {
   return submitTx(contractId, "join_game", args, keypair);
 }
 
+/**
+ * Encode a SeedOpening: Plain for Poseidon games, Salted(salt) for salted
+ * games, DualHash(innerCommit) for dual-hash games.
+ */
+function seedOpeningToScVal(openingHex, kind = "Salted") {
+  if (!openingHex) return xdr.ScVal.scvVec([xdr.ScVal.scvSymbol("Plain")]);
+  return xdr.ScVal.scvVec([
+    xdr.ScVal.scvSymbol(kind),
+    nativeToScVal(Buffer.from(openingHex, "hex"), { type: "bytes" }),
+  ]);
+}
+
 /**
  * Reveal a seed on-chain.
  */
-export async function revealSeed(contractId, sessionId, seedHex, keypair) {
+export async function revealSeed(contractId, sessionId, seedHex, keypair, openingHex = null, openingKind = "Salted") {
   const args = [
     nativeToScVal(sessionId, { type: "u32" }),
     new Address(keypair.publicKey()).toScVal(),
     nativeToScVal(Buffer.from(seedHex, "hex"), { type: "bytes" }),
+    seedOpeningToScVal(openingHex, openingKind),
   ];
   return submitTx(contractId, "reveal_seed", args, keypair);
 }
@@ -153,6 +228,14 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, keypai
   ]);
 
   const pubInputsVal = xdr.ScVal.scvMap([
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("beacon"),
+      val: nativeToScVal(Buffer.from(pubInputs.beacon ?? "00".repeat(32), "hex"), { type: "bytes" }),
+    }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("deck_size"),
+      val: nativeToScVal(Buffer.from(pubInputs.deck_size, "hex"), { type: "bytes" }),
+    }),
     new xdr.ScMapEntry({
       key: xdr.ScVal.scvSymbol("seed1"),
       val: nativeToScVal(Buffer.from(pubInputs.seed1, "hex"), { type: "bytes" }),
@@ -173,6 +256,10 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, keypai
       key: xdr.ScVal.scvSymbol("session_id"),
       val: nativeToScVal(Buffer.from(pubInputs.session_id, "hex"), { type: "bytes" }),
     }),
+    new xdr.ScMapEntry({
+      key: xdr.ScVal.scvSymbol("suits"),
+      val: nativeToScVal(Buffer.from(pubInputs.suits, "hex"), { type: "bytes" }),
+    }),
     new xdr.ScMapEntry({
       key: xdr.ScVal.scvSymbol("winner"),
       val: nativeToScVal(Buffer.from(pubInputs.winner, "hex"), { type: "bytes" }),
@@ -183,6 +270,7 @@ export async function settleGame(contractId, sessionId, proof, pubInputs, keypai
     nativeToScVal(sessionId, { type: "u32" }),
     proofVal,
     pubInputsVal,
+    new Address(keypair.publicKey()).toScVal(),
   ];
   return submitTx(contractId, "settle_game", args, keypair);
 }
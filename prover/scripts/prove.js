@@ -5,7 +5,7 @@
  * Computes the deterministic deck shuffle, simulates the game,
  * and produces a proof of the winner.
  *
- * Usage: node scripts/prove.js <seed1> <seed2> <session_id>
+ * Usage: node scripts/prove.js <seed1> <seed2> <session_id> [beacon]
  *
  * Output: build/proof_payload.json
  */
@@ -20,6 +20,7 @@ const __dirname = dirname(fileURLToPath(import.meta.url));
 const PROVER_DIR = join(__dirname, "..");
 
 const N_CARDS = 25;
+const N_SUITS = 3;
 
 // Card types: 0=Triangle(0-7), 1=Square(8-15), 2=Line(16-23), 3=BlackSpot(24)
 function cardType(c) {
@@ -45,17 +46,20 @@ async function main() {
   const seed1Str = process.argv[2];
   const seed2Str = process.argv[3];
   const sessionIdStr = process.argv[4] || "1";
+  const beaconStr = process.argv[5] || "0";
 
   if (!seed1Str || !seed2Str) {
-    console.error("Usage: node scripts/prove.js <seed1> <seed2> [session_id]");
+    console.error("Usage: node scripts/prove.js <seed1> <seed2> [session_id] [beacon]");
     console.error("  seed1, seed2: decimal bigint strings (player seeds)");
     console.error("  session_id: game identifier (default: 1)");
+  console.error("  beacon: beacon field element (default: 0, no beacon)");
     process.exit(1);
   }
 
   const seed1 = BigInt(seed1Str);
   const seed2 = BigInt(seed2Str);
   const sessionId = BigInt(sessionIdStr);
+  const beacon = BigInt(beaconStr);
 
   const poseidon = await buildPoseidon();
   const F = poseidon.F;
@@ -67,11 +71,12 @@ async function main() {
   console.log(`seed1:        ${seed1}`);
   console.log(`seed2:        ${seed2}`);
   console.log(`session_id:   ${sessionId}`);
+  console.log(`beacon:       ${beacon}`);
   console.log(`seed_commit1: ${seedCommit1}`);
   console.log(`seed_commit2: ${seedCommit2}`);
 
   // Compute combined seed
-  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId]));
+  const combinedSeed = F.toObject(poseidon([seed1, seed2, sessionId, beacon]));
 
   // Compute card weights with truncated values for sorting
   const mask128 = (1n << 128n) - 1n;
@@ -181,6 +186,9 @@ async function main() {
     seed2: seed2.toString(),
     session_id: sessionId.toString(),
     winner: winner.toString(),
+    beacon: beacon.toString(),
+    deck_size: N_CARDS.toString(),
+    suits: N_SUITS.toString(),
     deck: deck.map((c) => c.toString()),
     trunc_weights: truncWeights.map((w) => w.toString()),
     high_weights: highWeights.map((w) => w.toString()),
@@ -219,6 +227,9 @@ async function main() {
       seed2: bigintToHex(publicSignals[3], 32),
       session_id: bigintToHex(publicSignals[4], 32),
       winner: bigintToHex(publicSignals[5], 32),
+      beacon: bigintToHex(publicSignals[6], 32),
+      deck_size: bigintToHex(publicSignals[7], 32),
+      suits: bigintToHex(publicSignals[8], 32),
     },
     _debug: { publicSignals, proof },
   };
@@ -234,6 +245,7 @@ async function main() {
   console.log(`  seed2:        0x${payload.public_inputs.seed2}`);
   console.log(`  session_id:   0x${payload.public_inputs.session_id}`);
   console.log(`  winner:       0x${payload.public_inputs.winner}`);
+  console.log(`  beacon:       0x${payload.public_inputs.beacon}`);
 }
 
 function bigintToHex(s, byteLen) {